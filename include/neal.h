@@ -0,0 +1,59 @@
+/* The C API of the NEAL interpreter. Build the library with `cargo build --release --lib --features ffi`. See `src/ffi.rs`. */
+
+#ifndef NEAL_H
+#define NEAL_H
+
+#include <stdbool.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+/* Returned by `neal_run()`. */
+#define NEAL_OK 0
+#define NEAL_INVALID_ARGUMENT 64  /* A pointer was null or the source code was not valid UTF-8. */
+#define NEAL_TOKENIZE_ERROR 65  /* The source code could not be tokenized. */
+#define NEAL_PARSE_ERROR 66  /* The token sequence could not be parsed. */
+#define NEAL_RUNTIME_ERROR 70  /* An error occurred while executing the program. */
+
+typedef struct NealInterpreter NealInterpreter;
+
+typedef enum NealValueType {
+    NEAL_NULL = 0,
+    NEAL_NUMBER = 1,
+    NEAL_STRING = 2,
+    NEAL_BOOL = 3,
+    NEAL_ARRAY = 4,
+    NEAL_DICTIONARY = 5,
+    NEAL_FUNCTION = 6,
+} NealValueType;
+
+/* Which fields are meaningful depends on `value_type`: `number` for numbers, `boolean` for Booleans, and `string` for strings and,
+ * as `print` would show them, arrays, dictionaries and functions. `string` is null otherwise, and is freed by `neal_value_free()`. */
+typedef struct NealValue {
+    NealValueType value_type;
+    double number;
+    bool boolean;
+    char *string;
+} NealValue;
+
+/* Creates an interpreter, which must be destroyed with `neal_free()`. */
+NealInterpreter *neal_new(void);
+
+/* Runs the NUL-terminated source code, keeping variables between runs. Returns `NEAL_OK` or the kind of error which stopped it. */
+int neal_run(NealInterpreter *interpreter, const char *source);
+
+/* Reads the variable into `value`, returning whether it exists. If it does, `value` must be released with `neal_value_free()`. */
+bool neal_get_global(const NealInterpreter *interpreter, const char *name, NealValue *value);
+
+/* Releases the string owned by the value, if any. */
+void neal_value_free(NealValue *value);
+
+/* Destroys the interpreter. Freeing a null pointer does nothing. */
+void neal_free(NealInterpreter *interpreter);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
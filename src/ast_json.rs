@@ -0,0 +1,291 @@
+//! Converts the abstract syntax tree to JSON, so that tools written in other languages can use it without reimplementing the parser.
+//!
+//! The output is an object of the form `{"version": 1, "statements": [...]}`. Every statement and expression is an object with
+//! a `"kind"`, which is the name of its `StmtType` or `ExprType` variant, e.g., `"Print"`, along with its `"line"` and `"column"`.
+//! The other fields of a node are named after the fields of its variant, e.g., `"condition"` and `"body"` for `While`.
+//! Optional parts which were not given, e.g., the `else` body of an `If`, are `null`. Operators are tokens, which are objects with
+//! a `"kind"` (the `TokenType` variant), `"lexeme"`, `"line"` and `"column"`. Literals are JSON values, e.g., `"value": 2.5`.
+//!
+//! The schema only changes when `version` does, so tools can check it before reading the rest.
+
+use crate::expr::{Expr, ExprType};
+use crate::stmt::{Stmt, StmtType};
+use crate::token::{Literal, Token};
+
+/// The version of the schema, which is increased whenever the output changes in a way tools could notice.
+pub const SCHEMA_VERSION: usize = 1;
+
+/// A JSON value.
+#[derive(Clone, Debug, PartialEq)]
+enum Json {
+    Null,
+    Bool(bool),
+    Number(f64),
+    String_(String),
+    Array(Vec<Json>),
+    Object(Vec<(&'static str, Json)>),  // The fields are kept in the order they were given, so the output is always the same.
+}
+
+impl Json {
+    /// Appends the value to `output`, indented to the given depth. Arrays and objects with elements are spread over several lines.
+    fn write(&self, output: &mut String, depth: usize) {
+        match self {
+            Json::Null => output.push_str("null"),
+            Json::Bool(bool) => output.push_str(&bool.to_string()),
+            // Numbers in source code are always finite, and Rust writes them without an exponent, so they are valid JSON.
+            Json::Number(number) => output.push_str(&number.to_string()),
+            Json::String_(string) => write_string(string, output),
+            Json::Array(elements) if elements.is_empty() => output.push_str("[]"),
+            Json::Array(elements) => {
+                output.push_str("[\n");
+                for (i, element) in elements.iter().enumerate() {
+                    output.push_str(&"  ".repeat(depth + 1));
+                    element.write(output, depth + 1);
+                    output.push_str(if i + 1 < elements.len() { ",\n" } else { "\n" });
+                }
+                output.push_str(&"  ".repeat(depth));
+                output.push(']');
+            },
+            Json::Object(fields) if fields.is_empty() => output.push_str("{}"),
+            Json::Object(fields) => {
+                output.push_str("{\n");
+                for (i, (name, value)) in fields.iter().enumerate() {
+                    output.push_str(&"  ".repeat(depth + 1));
+                    write_string(name, output);
+                    output.push_str(": ");
+                    value.write(output, depth + 1);
+                    output.push_str(if i + 1 < fields.len() { ",\n" } else { "\n" });
+                }
+                output.push_str(&"  ".repeat(depth));
+                output.push('}');
+            },
+        }
+    }
+}
+
+/// Appends `string` to `output` as a JSON string, escaping the characters which cannot appear in one as they are.
+fn write_string(string: &str, output: &mut String) {
+    output.push('"');
+    for c in string.chars() {
+        match c {
+            '"' => output.push_str("\\\""),
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            '\t' => output.push_str("\\t"),
+            c if (c as u32) < 0x20 => output.push_str(&format!("\\u{:04x}", c as u32)),
+            c => output.push(c),
+        }
+    }
+    output.push('"');
+}
+
+/// Returns the abstract syntax tree as a JSON document, following the schema described at the top of this module.
+pub fn to_json(ast: &[Stmt]) -> String {
+    let document = Json::Object(vec![
+        ("version", Json::Number(SCHEMA_VERSION as f64)),
+        ("statements", Json::Array(ast.iter().map(stmt).collect())),
+    ]);
+    let mut output = String::new();
+    document.write(&mut output, 0);
+    output.push('\n');
+    output
+}
+
+/// Returns a node with the given kind and position, followed by the given fields.
+fn node(kind: &'static str, line: usize, column: usize, fields: Vec<(&'static str, Json)>) -> Json {
+    let mut all_fields = vec![
+        ("kind", Json::String_(String::from(kind))),
+        ("line", Json::Number(line as f64)),
+        ("column", Json::Number(column as f64)),
+    ];
+    all_fields.extend(fields);
+    Json::Object(all_fields)
+}
+
+/// Returns the optional node, or `null` if it is not given.
+fn optional<T>(value: &Option<T>, convert: fn(&T) -> Json) -> Json {
+    value.as_ref().map_or(Json::Null, convert)
+}
+
+/// Returns the statement as a JSON node.
+fn stmt(stmt_: &Stmt) -> Json {
+    let (kind, fields) = match &stmt_.stmt_type {
+        StmtType::Block { body } => ("Block", vec![("body", Json::Array(body.iter().map(stmt).collect()))]),
+        StmtType::Break => ("Break", vec![]),
+        StmtType::Expression { expression } => ("Expression", vec![("expression", expr(expression))]),
+        StmtType::For { initialiser, condition, increment, body } => ("For", vec![
+            ("initialiser", optional(initialiser, |initialiser| stmt(initialiser))),
+            ("condition", optional(condition, expr)),
+            ("increment", optional(increment, |increment| stmt(increment))),
+            ("body", stmt(body)),
+        ]),
+        StmtType::Function { name, parameters, body, doc } => ("Function", vec![
+            ("name", Json::String_(name.clone())),
+            ("parameters", Json::Array(parameters.iter().map(|parameter| Json::String_(parameter.clone())).collect())),
+            ("body", stmt(body)),
+            ("doc", optional(doc, |doc| Json::String_(doc.clone()))),
+        ]),
+        StmtType::If { condition, then_body, else_body } => ("If", vec![
+            ("condition", expr(condition)),
+            ("then_body", stmt(then_body)),
+            ("else_body", optional(else_body, |else_body| stmt(else_body))),
+        ]),
+        StmtType::Print { expression } => ("Print", vec![("expression", expr(expression))]),
+        StmtType::Repeat { count, body } => ("Repeat", vec![
+            ("count", expr(count)),
+            ("body", stmt(body)),
+        ]),
+        StmtType::Return { expression } => ("Return", vec![("expression", expr(expression))]),
+        StmtType::Try { body, catch, finally } => ("Try", vec![
+            ("body", stmt(body)),
+            ("catch", optional(catch, |(name, body)| Json::Object(vec![("name", Json::String_(name.clone())), ("body", stmt(body))]))),
+            ("finally", optional(finally, |finally| stmt(finally))),
+        ]),
+        StmtType::VarDecl { name, value } => ("VarDecl", vec![
+            ("name", Json::String_(name.clone())),
+            ("value", expr(value)),
+        ]),
+        StmtType::VarUnpack { names, value } => ("VarUnpack", vec![
+            ("names", Json::Array(names.iter().map(|name| Json::String_(name.clone())).collect())),
+            ("value", expr(value)),
+        ]),
+        StmtType::While { condition, body } => ("While", vec![
+            ("condition", expr(condition)),
+            ("body", stmt(body)),
+        ]),
+    };
+    node(kind, stmt_.line, stmt_.column, fields)
+}
+
+/// Returns the expression as a JSON node.
+fn expr(expr_: &Expr) -> Json {
+    let exprs = |exprs: &[Expr]| Json::Array(exprs.iter().map(expr).collect());
+    let (kind, fields) = match &expr_.expr_type {
+        ExprType::Array { elements } => ("Array", vec![("elements", exprs(elements))]),
+        ExprType::Assignment { target, value } => ("Assignment", vec![
+            ("target", expr(target)),
+            ("value", expr(value)),
+        ]),
+        ExprType::Binary { left, operator, right } => ("Binary", vec![
+            ("left", expr(left)),
+            ("operator", token(operator)),
+            ("right", expr(right)),
+        ]),
+        ExprType::Call { callee, arguments, named_arguments } => ("Call", vec![
+            ("callee", expr(callee)),
+            ("arguments", exprs(arguments)),
+            ("named_arguments", Json::Array(named_arguments.iter().map(|(name, argument)| Json::Object(vec![
+                ("name", Json::String_(name.clone())),
+                ("value", expr(argument)),
+            ])).collect())),
+        ]),
+        ExprType::Dictionary { elements } => ("Dictionary", vec![
+            ("elements", Json::Array(elements.iter().map(|element| Json::Object(vec![
+                ("key", expr(&element.key)),
+                ("value", expr(&element.value)),
+            ])).collect())),
+        ]),
+        ExprType::Element { array, index } => ("Element", vec![
+            ("array", expr(array)),
+            ("index", expr(index)),
+        ]),
+        ExprType::Grouping { expression } => ("Grouping", vec![("expression", expr(expression))]),
+        ExprType::Literal { value } => ("Literal", vec![("value", literal(value))]),
+        ExprType::Slice { array, start, stop, step } => ("Slice", vec![
+            ("array", expr(array)),
+            ("start", optional(start, |start| expr(start))),
+            ("stop", optional(stop, |stop| expr(stop))),
+            ("step", optional(step, |step| expr(step))),
+        ]),
+        ExprType::Tuple { elements } => ("Tuple", vec![("elements", exprs(elements))]),
+        ExprType::Unary { operator, right } => ("Unary", vec![
+            ("operator", token(operator)),
+            ("right", expr(right)),
+        ]),
+        ExprType::Variable { name } => ("Variable", vec![("name", Json::String_(name.clone()))]),
+    };
+    node(kind, expr_.line, expr_.column, fields)
+}
+
+/// Returns the token as a JSON object.
+fn token(token: &Token) -> Json {
+    Json::Object(vec![
+        ("kind", Json::String_(format!("{:?}", token.type_))),
+        ("lexeme", Json::String_(token.lexeme.clone())),
+        ("line", Json::Number(token.line as f64)),
+        ("column", Json::Number(token.column as f64)),
+    ])
+}
+
+/// Returns the literal as a JSON value.
+fn literal(literal: &Literal) -> Json {
+    match literal {
+        Literal::Number(number) => Json::Number(*number),
+        Literal::String_(string) => Json::String_(string.clone()),
+        Literal::Bool(bool) => Json::Bool(*bool),
+        Literal::Null => Json::Null,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::Parser;
+    use crate::tokenizer::Tokenizer;
+
+    use super::{to_json, write_string};
+
+    fn to_json_source(source: &str) -> String {
+        to_json(&Parser::new(Tokenizer::new(source).tokenize().unwrap()).parse().unwrap())
+    }
+
+    #[test]
+    fn expression() {
+        assert_eq!(to_json_source("print -x"), r#"{
+  "version": 1,
+  "statements": [
+    {
+      "kind": "Print",
+      "line": 1,
+      "column": 1,
+      "expression": {
+        "kind": "Unary",
+        "line": 1,
+        "column": 8,
+        "operator": {
+          "kind": "Minus",
+          "lexeme": "-",
+          "line": 1,
+          "column": 7
+        },
+        "right": {
+          "kind": "Variable",
+          "line": 1,
+          "column": 8,
+          "name": "x"
+        }
+      }
+    }
+  ]
+}
+"#);
+    }
+
+    #[test]
+    fn optional_parts_and_literals() {
+        let json = to_json_source("if (true) { f('a \"b\"', 2.5, null) }");
+        assert!(json.contains(r#""else_body": null"#));
+        assert!(json.contains(r#""value": true"#));
+        assert!(json.contains(r#""value": "a \"b\"""#));
+        assert!(json.contains(r#""value": 2.5"#));
+        assert!(json.contains(r#""value": null"#));
+        assert_eq!(to_json(&[]), "{\n  \"version\": 1,\n  \"statements\": []\n}\n");
+    }
+
+    #[test]
+    fn escaping() {
+        let mut output = String::new();
+        write_string("a\"b\\c\nd\u{1}é", &mut output);
+        assert_eq!(output, r#""a\"b\\c\nd\u0001é""#);
+    }
+}
@@ -0,0 +1,352 @@
+use crate::expr::{Expr, ExprType};
+use crate::stmt::{Stmt, StmtType};
+use crate::token::Literal;
+
+/// The text each level of the tree is indented by.
+const INDENT: &str = "  ";
+
+/// Returns a human-readable, indented tree of the abstract syntax tree, with one node per line.
+/// Each child is indented one level more than its parent, and children with different roles, e.g., the condition and body of a loop, are labelled.
+pub fn print(ast: &[Stmt]) -> String {
+    let mut printer = AstPrinter { output: String::new() };
+    for stmt in ast {
+        printer.stmt(stmt, 0);
+    }
+    printer.output
+}
+
+/// Returns the tree of a single statement, as `print()` shows it, without a line break at the end.
+pub fn print_stmt(stmt: &Stmt) -> String {
+    let mut printer = AstPrinter { output: String::new() };
+    printer.stmt(stmt, 0);
+    printer.output.pop();
+    printer.output
+}
+
+/// Returns the tree of a single expression, in the same form, without a line break at the end.
+pub fn print_expr(expr: &Expr) -> String {
+    let mut printer = AstPrinter { output: String::new() };
+    printer.expr(expr, 0);
+    printer.output.pop();
+    printer.output
+}
+
+/// Builds the text of the tree while walking over it.
+struct AstPrinter {
+    output: String,
+}
+
+impl AstPrinter {
+    /// Appends a line for a node, indented to the given depth.
+    fn line(&mut self, depth: usize, text: &str) {
+        self.output.push_str(&INDENT.repeat(depth));
+        self.output.push_str(text);
+        self.output.push('\n');
+    }
+
+    /// Appends a label, with the statement under it.
+    fn labelled_stmt(&mut self, label: &str, stmt: &Stmt, depth: usize) {
+        self.line(depth, &format!("{}:", label));
+        self.stmt(stmt, depth + 1);
+    }
+
+    /// Appends a label, with the expression under it.
+    fn labelled_expr(&mut self, label: &str, expr: &Expr, depth: usize) {
+        self.line(depth, &format!("{}:", label));
+        self.expr(expr, depth + 1);
+    }
+
+    /// Appends the statement and its children.
+    fn stmt(&mut self, stmt: &Stmt, depth: usize) {
+        match &stmt.stmt_type {
+            StmtType::Block { body } => {
+                self.line(depth, "Block");
+                for body_stmt in body {
+                    self.stmt(body_stmt, depth + 1);
+                }
+            },
+
+            StmtType::Break => self.line(depth, "Break"),
+
+            StmtType::Expression { expression } => {
+                self.line(depth, "Expression");
+                self.expr(expression, depth + 1);
+            },
+
+            StmtType::For { initialiser, condition, increment, body } => {
+                self.line(depth, "For");
+                // Each part of the loop header is optional, so only the parts which were given are shown.
+                if let Some(initialiser) = initialiser {
+                    self.labelled_stmt("initialiser", initialiser, depth + 1);
+                }
+                if let Some(condition) = condition {
+                    self.labelled_expr("condition", condition, depth + 1);
+                }
+                if let Some(increment) = increment {
+                    self.labelled_stmt("increment", increment, depth + 1);
+                }
+                self.labelled_stmt("body", body, depth + 1);
+            },
+
+            StmtType::Function { name, parameters, body, .. } => {
+                self.line(depth, &format!("Function {}({})", name, parameters.join(", ")));
+                self.stmt(body, depth + 1);
+            },
+
+            StmtType::If { condition, then_body, else_body } => {
+                self.line(depth, "If");
+                self.labelled_expr("condition", condition, depth + 1);
+                self.labelled_stmt("then", then_body, depth + 1);
+                if let Some(else_body) = else_body {
+                    self.labelled_stmt("else", else_body, depth + 1);
+                }
+            },
+
+            StmtType::Print { expression } => {
+                self.line(depth, "Print");
+                self.expr(expression, depth + 1);
+            },
+
+            StmtType::Repeat { count, body } => {
+                self.line(depth, "Repeat");
+                self.labelled_expr("count", count, depth + 1);
+                self.labelled_stmt("body", body, depth + 1);
+            },
+
+            StmtType::Return { expression } => {
+                self.line(depth, "Return");
+                self.expr(expression, depth + 1);
+            },
+
+            StmtType::Try { body, catch, finally } => {
+                self.line(depth, "Try");
+                self.labelled_stmt("body", body, depth + 1);
+                if let Some((name, catch_body)) = catch {
+                    self.labelled_stmt(&format!("catch {}", name), catch_body, depth + 1);
+                }
+                if let Some(finally) = finally {
+                    self.labelled_stmt("finally", finally, depth + 1);
+                }
+            },
+
+            StmtType::VarDecl { name, value } => {
+                self.line(depth, &format!("Var {}", name));
+                self.expr(value, depth + 1);
+            },
+
+            StmtType::VarUnpack { names, value } => {
+                self.line(depth, &format!("Var {}", names.join(", ")));
+                self.expr(value, depth + 1);
+            },
+
+            StmtType::While { condition, body } => {
+                self.line(depth, "While");
+                self.labelled_expr("condition", condition, depth + 1);
+                self.labelled_stmt("body", body, depth + 1);
+            },
+        }
+    }
+
+    /// Appends the expression and its children.
+    fn expr(&mut self, expr: &Expr, depth: usize) {
+        match &expr.expr_type {
+            ExprType::Array { elements } => {
+                self.line(depth, "Array");
+                for element in elements {
+                    self.expr(element, depth + 1);
+                }
+            },
+
+            ExprType::Assignment { target, value } => {
+                self.line(depth, "Assignment");
+                self.labelled_expr("target", target, depth + 1);
+                self.labelled_expr("value", value, depth + 1);
+            },
+
+            ExprType::Binary { left, operator, right } => {
+                self.line(depth, &format!("Binary {}", operator.lexeme));
+                self.expr(left, depth + 1);
+                self.expr(right, depth + 1);
+            },
+
+            ExprType::Call { callee, arguments, named_arguments } => {
+                self.line(depth, "Call");
+                self.labelled_expr("callee", callee, depth + 1);
+                if !arguments.is_empty() {
+                    self.line(depth + 1, "arguments:");
+                    for argument in arguments {
+                        self.expr(argument, depth + 2);
+                    }
+                }
+                if !named_arguments.is_empty() {
+                    self.line(depth + 1, "named arguments:");
+                    for (name, argument) in named_arguments {
+                        self.labelled_expr(name, argument, depth + 2);
+                    }
+                }
+            },
+
+            ExprType::Dictionary { elements } => {
+                self.line(depth, "Dictionary");
+                for element in elements {
+                    self.line(depth + 1, "Entry");
+                    self.labelled_expr("key", &element.key, depth + 2);
+                    self.labelled_expr("value", &element.value, depth + 2);
+                }
+            },
+
+            ExprType::Element { array, index } => {
+                self.line(depth, "Element");
+                self.labelled_expr("array", array, depth + 1);
+                self.labelled_expr("index", index, depth + 1);
+            },
+
+            ExprType::Grouping { expression } => {
+                self.line(depth, "Grouping");
+                self.expr(expression, depth + 1);
+            },
+
+            ExprType::Literal { value } => {
+                // Strings are quoted and escaped, so that they can be told apart from other literals and stay on one line.
+                let value = match value {
+                    Literal::Number(number) => number.to_string(),
+                    Literal::String_(string) => format!("{:?}", string),
+                    Literal::Bool(bool) => bool.to_string(),
+                    Literal::Null => String::from("null"),
+                };
+                self.line(depth, &format!("Literal {}", value));
+            },
+
+            ExprType::Slice { array, start, stop, step } => {
+                self.line(depth, "Slice");
+                self.labelled_expr("array", array, depth + 1);
+                // The bounds and the step are optional, so only the parts which were given are shown.
+                for (label, part) in [("start", start), ("stop", stop), ("step", step)] {
+                    if let Some(part) = part {
+                        self.labelled_expr(label, part, depth + 1);
+                    }
+                }
+            },
+
+            ExprType::Tuple { elements } => {
+                self.line(depth, "Tuple");
+                for element in elements {
+                    self.expr(element, depth + 1);
+                }
+            },
+
+            ExprType::Unary { operator, right } => {
+                self.line(depth, &format!("Unary {}", operator.lexeme));
+                self.expr(right, depth + 1);
+            },
+
+            ExprType::Variable { name } => self.line(depth, &format!("Variable {}", name)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::Parser;
+    use crate::tokenizer::Tokenizer;
+
+    use crate::parser::parse_no_panic;
+    use crate::stmt::StmtType;
+
+    use super::print;
+
+    fn print_source(source: &str) -> String {
+        print(&Parser::new(Tokenizer::new(source).tokenize().unwrap()).parse().unwrap())
+    }
+
+    #[test]
+    fn expressions() {
+        assert_eq!(print_source("print -(1 + x) * f(2, 'a')"), "\
+Print
+  Binary *
+    Unary -
+      Grouping
+        Binary +
+          Literal 1
+          Variable x
+    Call
+      callee:
+        Variable f
+      arguments:
+        Literal 2
+        Literal \"a\"
+");
+        assert_eq!(print_source("a[0] = {'k': [true, null]}"), "\
+Expression
+  Assignment
+    target:
+      Element
+        array:
+          Variable a
+        index:
+          Literal 0
+    value:
+      Dictionary
+        Entry
+          key:
+            Literal \"k\"
+          value:
+            Array
+              Literal true
+              Literal null
+");
+    }
+
+    #[test]
+    fn statements() {
+        let source = "func f(a, b) {\n    if (a) { return b } else { break }\n}\nfor (var i = 0; i < 3; i = i + 1) { while (true) {} }";
+        assert_eq!(print_source(source), "\
+Function f(a, b)
+  Block
+    If
+      condition:
+        Variable a
+      then:
+        Block
+          Return
+            Variable b
+      else:
+        Block
+          Break
+For
+  initialiser:
+    Var i
+      Literal 0
+  condition:
+    Binary <
+      Variable i
+      Literal 3
+  increment:
+    Expression
+      Assignment
+        target:
+          Variable i
+        value:
+          Binary +
+            Variable i
+            Literal 1
+  body:
+    Block
+      While
+        condition:
+          Literal true
+        body:
+          Block
+");
+    }
+
+    #[test]
+    fn display() {
+        // The alternate form of `Display` is the tree of the node.
+        let ast = parse_no_panic("var x = -y").unwrap();
+        assert_eq!(format!("{:#}", ast[0]), "Var x\n  Unary -\n    Variable y");
+        let StmtType::VarDecl { value, .. } = &ast[0].stmt_type else { unreachable!() };
+        assert_eq!(format!("{:#}", value), "Unary -\n  Variable y");
+        assert_eq!(format!("{:#}\n", ast[0]), print(&ast));
+    }
+}
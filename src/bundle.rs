@@ -0,0 +1,108 @@
+//! Bundling a script into a copy of the `nea` binary with `--bundle`, so that it can be given to people without NEAL installed.
+//!
+//! A bundle is the binary with the source code of the script after it, followed by a trailer: the length of the source code as
+//! eight little-endian bytes, then `MAGIC`. Operating systems ignore data after an executable, so a bundle starts as `nea` does,
+//! and `nea` first looks for a trailer at the end of its own file. If it finds one, it runs the script in it instead of reading
+//! its arguments, which all go to the script.
+
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::Path;
+use std::env;
+
+/// The bytes which end every bundle.
+const MAGIC: &[u8; 8] = b"NEALSRC\0";
+
+/// The number of bytes in the trailer: the length of the source code, then `MAGIC`.
+const TRAILER_LEN: usize = 16;
+
+/// Writes a bundle of the running binary and the source code to the file path, making it executable.
+pub fn write_bundle(source: &str, path: &Path) -> io::Result<()> {
+    let binary = fs::read(env::current_exe()?)?;
+    // Bundling from a bundle replaces its script rather than adding another.
+    let binary = split(&binary).map_or(binary.as_slice(), |(binary, _)| binary);
+    fs::write(path, bundle(binary, source))?;
+    make_executable(path)
+}
+
+/// Returns the source code of the script bundled with the running binary, if it is a bundle.
+pub fn embedded_script() -> Option<String> {
+    // Only the end of the binary is read, as most runs are of `nea` itself, which has no trailer.
+    let mut file = File::open(env::current_exe().ok()?).ok()?;
+    let size = file.metadata().ok()?.len();
+    let trailer_start = size.checked_sub(TRAILER_LEN as u64)?;
+    file.seek(SeekFrom::Start(trailer_start)).ok()?;
+    let mut trailer = [0; TRAILER_LEN];
+    file.read_exact(&mut trailer).ok()?;
+
+    let length = source_len(&trailer)?;
+    file.seek(SeekFrom::Start(trailer_start.checked_sub(length as u64)?)).ok()?;
+    let mut source = vec![0; length];
+    file.read_exact(&mut source).ok()?;
+    String::from_utf8(source).ok()
+}
+
+/// Returns the binary with the source code and trailer after it.
+fn bundle(binary: &[u8], source: &str) -> Vec<u8> {
+    let mut bundle = Vec::with_capacity(binary.len() + source.len() + TRAILER_LEN);
+    bundle.extend_from_slice(binary);
+    bundle.extend_from_slice(source.as_bytes());
+    bundle.extend_from_slice(&(source.len() as u64).to_le_bytes());
+    bundle.extend_from_slice(MAGIC);
+    bundle
+}
+
+/// Splits a bundle into the binary and the source code, or returns `None` if the bytes have no trailer.
+fn split(bytes: &[u8]) -> Option<(&[u8], &str)> {
+    let trailer_start = bytes.len().checked_sub(TRAILER_LEN)?;
+    let length = source_len(bytes[trailer_start..].try_into().ok()?)?;
+    let source_start = trailer_start.checked_sub(length)?;
+    let source = std::str::from_utf8(&bytes[source_start..trailer_start]).ok()?;
+    Some((&bytes[..source_start], source))
+}
+
+/// Returns the length of the source code recorded in the trailer, or `None` if it is not a trailer.
+fn source_len(trailer: &[u8; TRAILER_LEN]) -> Option<usize> {
+    let (length, magic) = trailer.split_at(8);
+    if magic != MAGIC {
+        return None;
+    }
+    usize::try_from(u64::from_le_bytes(length.try_into().ok()?)).ok()
+}
+
+/// Lets the file be executed by everyone who can read it. Other platforms decide what is executable from the file name.
+#[cfg(unix)]
+fn make_executable(path: &Path) -> io::Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut permissions = fs::metadata(path)?.permissions();
+    permissions.set_mode(permissions.mode() | 0o111);
+    fs::set_permissions(path, permissions)
+}
+
+#[cfg(not(unix))]
+fn make_executable(_path: &Path) -> io::Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{bundle, split};
+
+    #[test]
+    fn round_trip() {
+        let bundled = bundle(b"\x7fELF binary", "print 'héllo'");
+        assert_eq!(split(&bundled), Some((&b"\x7fELF binary"[..], "print 'héllo'")));
+        assert_eq!(split(&bundle(b"", "")), Some((&b""[..], "")));
+        // Bundling the binary of a bundle again replaces the script.
+        let (binary, _) = split(&bundled).unwrap();
+        assert_eq!(split(&bundle(binary, "print 2")), Some((&b"\x7fELF binary"[..], "print 2")));
+
+        assert_eq!(split(b"\x7fELF binary"), None);
+        assert_eq!(split(b"NEALSRC\0"), None);
+        // A length longer than the file is not a trailer.
+        let mut truncated = bundle(b"", "print 1");
+        truncated.remove(0);
+        assert_eq!(split(&truncated), None);
+    }
+}
@@ -0,0 +1,252 @@
+//! The message catalogue, which holds the text of every diagnostic.
+//!
+//! Errors are identified by their code (see `ErrorType::code()`), and the text shown for each code comes from a catalogue of
+//! message templates. The built-in catalogue is in English; a translation can be loaded from a file to replace any of its messages.
+//!
+//! Templates refer to the values of an error by name in braces, e.g., `` `{name}` is not defined. ``.
+//! The names available for each error are those returned by `ErrorType::arguments()`.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use crate::error::ErrorType;
+
+/// The catalogue installed for the whole process. If none is installed, the built-in English catalogue is used.
+static INSTALLED: OnceLock<Catalogue> = OnceLock::new();
+
+/// The messages which are not specific to a single error.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Key {
+    Header,  // Printed before a group of errors.
+    MoreErrors,  // Summarises the errors left out by an error limit. Takes `{count}`.
+    Position,  // Prefixes every error message, separated from it by a space. Takes `{line}` and `{column}`.
+    StackTrace,  // Printed before a stack trace.
+    StackFrame,  // A call in a stack trace, which is printed indented. Takes `{function}`, `{line}` and `{column}`.
+    MoreCalls,  // Summarises the calls left out of a deep stack trace, which is printed indented. Takes `{count}`.
+}
+
+impl Key {
+    /// Returns the name the key is given in a catalogue file.
+    fn name(&self) -> &'static str {
+        match self {
+            Key::Header => "header",
+            Key::MoreErrors => "more_errors",
+            Key::Position => "position",
+            Key::StackTrace => "stack_trace",
+            Key::StackFrame => "stack_frame",
+            Key::MoreCalls => "more_calls",
+        }
+    }
+}
+
+/// Returns the built-in English template for the message with the given name (a `Key` name or an error code).
+/// If there is no message with the name, `None` is returned.
+fn english(name: &str) -> Option<&'static str> {
+    let template = match name {
+        "header" => "An error has occurred.",
+        "more_errors" => "... and {count} more error(s).",
+        "position" => "Line {line}, column {column}:",
+        "stack_trace" => "Stack trace (most recent call first):",
+        "stack_frame" => "in `{function}`, called at line {line}, column {column}",
+        "more_calls" => "... {count} more call(s) ...",
+
+        // Lexical analysis errors, i.e., tokenization errors.
+        "E0001" => "unexpected character `{character}`.",
+        "E0002" => "this string was never closed by the end of the program.",
+        "E0031" => "`{lexeme}` is not a valid number.",
+
+        // Syntax analysis errors, i.e., syntax errors.
+        "E0003" => "expected character `{expected}`",
+        "E0004" => "expected expression.",
+        "E0005" => "expected function name. Make sure it is not a keyword.",
+        "E0006" => "expected parameter name in function declaration.",
+        "E0007" => "expected variable name. Make sure it is not a keyword.",
+        "E0008" => "expected `;` after initialising statement in `for` loop.",
+        "E0009" => "expected `;` after condition in `for` loop.",
+        "E0010" => "expected `)` after increment statement in `for` loop.",
+        "E0011" => "expected colon after dictionary key.",
+        "E0032" => "the code is nested too deeply.",
+        "E0035" => "expected a new line before the next statement. `--strict` allows only one statement per line.",
+        "E0042" => "expected the end of the expression, as only one expression can be given here.",
+        "E0053" => "expected `catch` or `finally` after the `try` block.",
+        "E0056" => "expected a named argument, as the arguments given by position must come before those given by name.",
+        "E0059" => "expected as many targets as values, e.g., `a, b = 1, 2`, as several values can only be assigned by unpacking them.",
+        "E0060" => "`{name}` is given more than once as a target to unpack values into.",
+
+        // Environment errors.
+        "E0012" => "`{name}` is not defined.",
+        "E0013" => "the value is not indexable.",
+        "E0014" => "index `{index}` is out of bounds.",
+        "E0015" => "attempted to insert a non-string into a string.",
+        "E0036" => "`{name}` is already declared in an enclosing scope. `--strict` does not allow shadowing it.",
+        "E0037" => "`{name}` is declared outside the function. `--strict` does not allow assigning to it.",
+        "E0040" => "the value is frozen, so it cannot be changed.",
+
+        // Execution errors, i.e., runtime errors.
+        "E0016" => "invalid assignment target. Make sure you are not assigning to a literal.",
+        "E0017" => "expected type {expected}; instead got type {got}.",
+        "E0018" => "index evaluated to {got}, which is not a positive integer.",
+        "E0019" => "index evaluated to a {got}, which is not a positive integer.",
+        "E0020" => "this operation requires both sides' types to be {expected}. Instead, got {got_left} and {got_right} respectively.",
+        "E0021" => "divisor is 0.",
+        "E0033" => "the right-hand side of `%` is 0.",
+        "E0022" => "the `if` condition did not evaluate to a Boolean value.",
+        "E0023" => "the condition of the loop did not evaluate to a Boolean value.",
+        "E0024" => "cannot call name as a function.",
+        "E0025" => "attempted to call function with {arg_number} argument(s), but function accepts {param_number}.",
+        "E0026" => "could not convert to a number.",
+        "E0034" => "the program was interrupted.",
+        "E0038" => "assertion failed: `{left}` is not equal to `{right}`.",
+        "E0039" => "the number of decimal places is {got}, which is not a whole number from 0 to 100.",
+        "E0041" => "the number of times to repeat is {got}, which is not a whole number of at least 0.",
+        "E0043" => "`{path}` is already being included, so including it again would never finish.",
+        "E0044" => "could not read `{path}`: {reason}.",
+        "E0045" => "the cursor cannot be moved to row or column {got}, which is not a whole number of at least 1.",
+        "E0046" => "`{name}` is not a colour or style which the terminal can show.",
+        "E0047" => "could not write to `{path}`: {reason}.",
+        "E0048" => "row {row} of the grid has {length} element(s), but the first row has {expected}.",
+        "E0049" => "{got} is not a whole number which can be stored exactly.",
+        "E0050" => "the base is {got}, which is not a whole number from 2 to 36.",
+        "E0051" => "a function cannot be saved, as it cannot be written to a file.",
+        "E0052" => "`{path}` is not a store of saved values.",
+        "E0054" => "the step of a slice cannot be 0.",
+        "E0055" => "{expected} variables are being assigned, but the array being unpacked into them has {got} element(s).",
+        "E0057" => "`{function}` has no parameter named `{name}`.",
+        "E0058" => "the argument for `{name}` is given more than once in the call to `{function}`.",
+        "E0061" => "the output could not be written: {reason}.",
+        "E0062" => "there is not enough memory for an array of {size} elements.",
+        "E0063" => "more than {limit} calls are running inside each other, e.g., because a function calls itself without stopping.",
+
+        // Hash table errors.
+        "E0027" => "cannot hash function (functions cannot be used as keys in dictionary entries).",
+        "E0028" => "key `{key}` does not exist in the dictionary.",
+
+        // Special errors.
+        "E0029" => "`break` has to be used within a loop.",
+        "E0030" => "`return` has to be used within a function.",
+
+        _ => return None,
+    };
+    Some(template)
+}
+
+/// Fills the `{name}` placeholders in `template` with the given arguments. Placeholders without an argument are left as they are.
+fn fill(template: &str, arguments: &[(&str, String)]) -> String {
+    let mut message = String::from(template);
+    for (name, value) in arguments {
+        message = message.replace(&format!("{{{}}}", name), value);
+    }
+    message
+}
+
+/// A set of message templates which replace the built-in English ones.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Catalogue {
+    translations: HashMap<String, String>,  // Maps each translated message's name to its template. Messages without a translation fall back to English.
+}
+
+impl Catalogue {
+    /// Parses a catalogue file. Each line of the file is either blank, a comment starting with `#`, or of the form `name = template`,
+    /// where `name` is a `Key` name, e.g., `header`, or an error code, e.g., `E0012`. Spaces around the template are ignored.
+    /// If a line is malformed or the name is not recognised, returns a message saying which line is wrong.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let mut translations = HashMap::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            let Some((name, template)) = line.split_once('=') else {
+                return Err(format!("line {} of the catalogue is not of the form `name = template`.", i + 1));
+            };
+            let name = name.trim();
+            if english(name).is_none() {
+                return Err(format!("line {} of the catalogue has unknown message name `{}`.", i + 1, name));
+            }
+            translations.insert(String::from(name), String::from(template.trim()));
+        }
+        Ok(Self { translations })
+    }
+
+    /// Returns the template for the message with the given name, from the translations if there is one, or in English otherwise.
+    fn template(&self, name: &str) -> &str {
+        match self.translations.get(name) {
+            Some(template) => template,
+            // Every name used is a `Key` name or an error code, all of which have English templates.
+            None => english(name).unwrap_or_default(),
+        }
+    }
+
+    /// Returns the message for the given key, filled with the given arguments.
+    pub fn render(&self, key: Key, arguments: &[(&str, String)]) -> String {
+        fill(self.template(key.name()), arguments)
+    }
+
+    /// Returns the full message for the given error, including its position.
+    pub fn render_error(&self, error: &ErrorType) -> String {
+        format!("{} {}", self.render(Key::Position, &error.arguments()), self.render_message(error))
+    }
+
+    /// Returns the message for the given error without its position, e.g., for the error caught by a `catch` block.
+    pub fn render_message(&self, error: &ErrorType) -> String {
+        fill(self.template(error.code()), &error.arguments())
+    }
+}
+
+/// Installs `catalogue` as the one used to report every error for the rest of the process.
+/// A catalogue can only be installed once; if one already is, `catalogue` is given back as an `Err`.
+pub fn install(catalogue: Catalogue) -> Result<(), Catalogue> {
+    INSTALLED.set(catalogue)
+}
+
+/// Returns the installed catalogue, or an empty one (i.e., the English messages) if none has been installed.
+pub fn current() -> &'static Catalogue {
+    static ENGLISH: OnceLock<Catalogue> = OnceLock::new();
+    INSTALLED.get().unwrap_or_else(|| ENGLISH.get_or_init(Catalogue::default))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::error::ErrorType;
+
+    use super::{english, Catalogue, Key};
+
+    #[test]
+    fn english_messages() {
+        let catalogue = Catalogue::default();
+        let error = ErrorType::NameError { name: String::from("x"), line: 2, column: 5 };
+        assert_eq!(catalogue.render_error(&error), "Line 2, column 5: `x` is not defined.");
+        assert_eq!(catalogue.render(Key::MoreErrors, &[("count", String::from("3"))]), "... and 3 more error(s).");
+    }
+
+    #[test]
+    fn every_key_and_code_has_an_english_message() {
+        for key in [Key::Header, Key::MoreErrors, Key::Position, Key::StackTrace, Key::StackFrame, Key::MoreCalls] {
+            assert!(english(key.name()).is_some());
+        }
+        for number in 1..=63 {
+            assert!(english(&format!("E{:04}", number)).is_some());
+        }
+    }
+
+    #[test]
+    fn translations() {
+        let text = "# French\n\nposition = Ligne {line}, colonne {column} :\nE0012 = `{name}` n'est pas défini.\n";
+        let catalogue = Catalogue::parse(text).unwrap();
+        let error = ErrorType::NameError { name: String::from("x"), line: 2, column: 5 };
+        assert_eq!(catalogue.render_error(&error), "Ligne 2, colonne 5 : `x` n'est pas défini.");
+
+        // Messages which have not been translated fall back to English.
+        assert_eq!(catalogue.render(Key::Header, &[]), "An error has occurred.");
+        let error = ErrorType::DivideByZero { line: 1, column: 1 };
+        assert_eq!(catalogue.render_error(&error), "Ligne 1, colonne 1 : divisor is 0.");
+    }
+
+    #[test]
+    fn invalid_catalogues() {
+        assert!(Catalogue::parse("E0012").is_err());
+        assert!(Catalogue::parse("E9999 = unknown").is_err());
+        assert!(Catalogue::parse("footer = unknown").is_err());
+    }
+}
@@ -0,0 +1,592 @@
+//! The command-line interface: the options the binary accepts, and the parser which turns the arguments into them.
+
+use std::env;
+use std::io::{self, IsTerminal};
+
+use nea::error::ErrorLimit;
+use nea::lint::{Lint, LintConfig, Severity};
+
+/// What to do with the source code of a script.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Mode {
+    #[default]
+    Run,  // Execute the script.
+    Tokens,  // Only tokenize the script, and print the token sequence.
+    Ast,  // Only tokenize and parse the script, and print the abstract syntax tree.
+    AstJson,  // Only tokenize and parse the script, and print the abstract syntax tree as JSON.
+    Check,  // Only tokenize and parse the script, reporting any errors.
+    Fmt,  // Print the script laid out by the formatter, with its comments kept.
+    FmtCheck,  // Report whether the script is already laid out as the formatter would lay it out, without printing it.
+    Lint,  // Only tokenize and parse the script, and report code which is probably a mistake.
+    Doc,  // Only tokenize and parse the script, and print the documentation of its functions in Markdown.
+    Test,  // Run the script's `test_` functions, or those of every script in a directory, and report which passed.
+    Transpile,  // Only tokenize and parse the script, and print it translated into Python.
+    Bundle,  // Only tokenize and parse the script, and write an executable which runs it to the file path in `Options::bundle`.
+}
+
+impl Mode {
+    /// Returns the command-line flag which selects the mode.
+    fn flag(&self) -> &'static str {
+        match self {
+            Self::Run => "",
+            Self::Tokens => "--tokens",
+            Self::Ast => "--ast",
+            Self::AstJson => "--ast-json",
+            Self::Check => "--check",
+            Self::Fmt => "--fmt",
+            Self::FmtCheck => "--fmt --check",
+            Self::Lint => "--lint",
+            Self::Doc => "--doc",
+            Self::Test => "--test",
+            Self::Transpile => "--transpile py",
+            Self::Bundle => "--bundle",
+        }
+    }
+}
+
+/// Where the source code of a script comes from.
+#[derive(Debug, PartialEq)]
+pub enum Input {
+    File(String),  // The file path of the source code.
+    Stdin,  // The standard input stream, given as `-`, e.g., `cat program.nea | nea -`.
+    Eval(String),  // The source code itself, given with `-e`.
+}
+
+/// The options for running a script or the REPL.
+#[derive(Debug, PartialEq)]
+pub struct Options {
+    pub script: Option<Input>,  // The source code to run. If it is not given, the REPL interface is run instead.
+    pub args: Vec<String>,  // The arguments after the script, which are passed to it through `args()`.
+    pub mode: Mode,  // What to do with the script.
+    pub error_limit: ErrorLimit,  // How many errors are collected and reported.
+    pub strict: bool,  // Whether code which is valid but often a mistake, e.g., shadowing a variable, is rejected.
+    pub truthy: bool,  // Whether the conditions of `if` statements and loops can be any value rather than only a Boolean.
+    pub lint: LintConfig,  // The severity of each lint reported by `--lint`.
+    pub bundle: Option<String>,  // The file path `--bundle` writes the executable to.
+    pub messages: Option<String>,  // The file path of a message catalogue which translates the error messages.
+    pub time: bool,  // Whether to report how long each stage of running the script took.
+    pub trace: bool,  // Whether to log each statement as it is executed.
+    pub profile: bool,  // Whether to report how often and for how long each line and function ran.
+    pub coverage: bool,  // Whether to list which lines of the script were executed.
+    pub coverage_file: Option<String>,  // The file path the coverage listing is written to. If it is not given, it is printed to the standard error stream.
+    pub pretty: Option<bool>,  // Whether `print` lays arrays and dictionaries out over lines and truncates them. If it is not given, only the REPL does.
+    pub max_elements: usize,  // How many elements of each array or dictionary are printed when pretty-printing.
+    pub colour: bool,  // Whether the REPL highlights its input in colour, and programs can write colours.
+    pub history: Option<String>,  // The file path the REPL history is kept in. If it is not given, `~/.neal_history` is used.
+    pub preload: Vec<String>,  // The file paths of scripts run in the REPL before the first prompt, in order, after `~/.nealrc`.
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            script: None,
+            args: Vec::new(),
+            mode: Mode::Run,
+            error_limit: ErrorLimit::default(),
+            strict: false,
+            truthy: false,
+            lint: LintConfig::default(),
+            bundle: None,
+            messages: None,
+            time: false,
+            trace: false,
+            profile: false,
+            coverage: false,
+            coverage_file: None,
+            pretty: None,
+            max_elements: DEFAULT_MAX_ELEMENTS,
+            colour: true,
+            history: None,
+            preload: Vec::new(),
+        }
+    }
+}
+
+impl Options {
+    /// Returns how many elements of each array or dictionary `print` writes if it pretty-prints values, or `None` if it does not.
+    /// Values are pretty-printed if `by_default`, which is true for the REPL, unless `--pretty` or `--no-pretty` says otherwise.
+    pub fn pretty_elements(&self, by_default: bool) -> Option<usize> {
+        self.pretty.unwrap_or(by_default).then_some(self.max_elements)
+    }
+
+    /// Returns whether `set_color()` and `colored()` write colours: only if the output is a terminal, and colour has not been turned
+    /// off with `--no-color` or the `NO_COLOR` environment variable.
+    pub fn output_colour(&self) -> bool {
+        self.colour && env::var_os("NO_COLOR").is_none() && io::stdout().is_terminal()
+    }
+}
+
+/// How many elements of each array or dictionary are printed when pretty-printing, unless `--max-elements` is given.
+pub const DEFAULT_MAX_ELEMENTS: usize = 100;
+
+/// What the binary has been asked to do.
+#[derive(Debug, PartialEq)]
+pub enum Action {
+    Run(Options),  // Run a script, or the REPL if there is none.
+    Help,  // Print the help text.
+    Version,  // Print the version number.
+    Explain(String),  // Print the explanation of the given error code.
+}
+
+/// A command-line flag.
+struct Flag {
+    long: &'static str,  // The full name of the flag, e.g., `--eval`.
+    short: Option<&'static str>,  // The one-letter name of the flag, if it has one, e.g., `-e`.
+    value: Option<&'static str>,  // The name of the value which has to follow the flag, if it takes one, e.g., `code`.
+    help: &'static str,  // What the flag does, as shown by `--help`.
+}
+
+/// Every flag the binary accepts, in the order they are listed by `--help`.
+const FLAGS: [Flag; 33] = [
+    Flag { long: "--eval", short: Some("-e"), value: Some("code"), help: "Run the given code instead of a script." },
+    Flag { long: "--tokens", short: None, value: None, help: "Print the tokens of the script instead of running it." },
+    Flag { long: "--ast", short: None, value: None, help: "Print the parse tree of the script instead of running it." },
+    Flag { long: "--ast-json", short: None, value: None, help: "Print the parse tree of the script as JSON instead of running it." },
+    Flag { long: "--check", short: None, value: None, help: "Report errors in the script without running it." },
+    Flag { long: "--fmt", short: None, value: None, help: "Print the script formatted instead of running it. With `--check`, only report whether it is formatted." },
+    Flag { long: "--doc", short: None, value: None, help: "Print the documentation of the script's functions, from their `##` comments, in Markdown." },
+    Flag { long: "--test", short: None, value: None, help: "Run the `test_` functions of the script, or of every script in the directory it names." },
+    Flag { long: "--transpile", short: None, value: Some("language"), help: "Print the script translated into another language instead of running it. The only language is `py`, for Python." },
+    Flag { long: "--bundle", short: None, value: Some("file"), help: "Write an executable to the file which runs the script, and which can be run without NEAL installed." },
+    Flag { long: "--lint", short: None, value: None, help: "Report code in the script which is probably a mistake, without running it." },
+    Flag { long: "--allow", short: None, value: Some("lint"), help: "Do not report the lint with the given code or name, e.g., L0003 or unused-parameter." },
+    Flag { long: "--warn", short: None, value: Some("lint"), help: "Report the lint, but do not fail because of it. Every lint warns by default." },
+    Flag { long: "--deny", short: None, value: Some("lint"), help: "Fail `--lint` if the lint is found." },
+    Flag { long: "--max-errors", short: None, value: Some("n"), help: "Report at most `n` errors." },
+    Flag { long: "--fail-fast", short: None, value: None, help: "Stop at the first error." },
+    Flag { long: "--strict", short: None, value: None, help: "Require one statement per line, and forbid shadowing and assigning to the caller's variables. With `--lint`, deny every lint." },
+    Flag { long: "--truthy", short: None, value: None, help: "Let conditions be any value: `null`, `0`, `''`, `[]` and `{}` count as `false`, and other values as `true`." },
+    Flag { long: "--time", short: None, value: None, help: "Report how long each stage of running the script took." },
+    Flag { long: "--trace", short: None, value: None, help: "Log each statement to standard error as it is executed." },
+    Flag { long: "--profile", short: None, value: None, help: "Report how often and for how long each line and function ran." },
+    Flag { long: "--coverage", short: None, value: None, help: "List the lines of the script, marking the ones which were not executed." },
+    Flag { long: "--coverage-file", short: None, value: Some("file"), help: "Write the `--coverage` listing to the file instead of standard error." },
+    Flag { long: "--pretty", short: None, value: None, help: "Print arrays and dictionaries over several lines, leaving out elements after the first 100. The REPL does this by default." },
+    Flag { long: "--no-pretty", short: None, value: None, help: "Print arrays and dictionaries on one line in full, including in the REPL." },
+    Flag { long: "--max-elements", short: None, value: Some("n"), help: "Pretty-print, printing at most `n` elements of each array or dictionary." },
+    Flag { long: "--no-color", short: None, value: None, help: "Do not highlight the input to the REPL in colour, or let `set_color()` change the colour of the output." },
+    Flag { long: "--history", short: None, value: Some("file"), help: "Keep the REPL history in the file instead of `~/.neal_history`." },
+    Flag { long: "--preload", short: None, value: Some("file"), help: "Run the file in the REPL before the first prompt. Can be given more than once." },
+    Flag { long: "--messages", short: None, value: Some("file"), help: "Translate error messages with the catalogue in the file." },
+    Flag { long: "--explain", short: None, value: Some("code"), help: "Explain the error or lint with the given code, e.g., E0012 or L0003." },
+    Flag { long: "--help", short: Some("-h"), value: None, help: "Print this help text." },
+    Flag { long: "--version", short: Some("-V"), value: None, help: "Print the version number." },
+];
+
+/// The one-line summary of how the binary is used, which is printed after a usage error.
+pub const USAGE: &str = "Usage: nea.exe [options] [script | - | -e <code>] [args...]";
+
+/// Returns the text printed by `--help`.
+pub fn help() -> String {
+    let mut text = format!(
+        "{}\n\nRuns the script, reading it from standard input if it is `-`, or starts the REPL if no script is given.\n\
+        Arguments after the script are passed to it through `args()`.\n\nOptions:\n",
+        USAGE,
+    );
+
+    // The names of the flags are padded to the same width so that the descriptions line up.
+    let names: Vec<String> = FLAGS.iter().map(|flag| {
+        let mut name = match flag.short {
+            Some(short) => format!("{}, {}", short, flag.long),
+            None => format!("    {}", flag.long),
+        };
+        if let Some(value) = flag.value {
+            name.push_str(&format!(" <{}>", value));
+        }
+        name
+    }).collect();
+    let width = names.iter().map(|name| name.len()).max().unwrap_or_default();
+    for (name, flag) in names.iter().zip(FLAGS.iter()) {
+        text.push_str(&format!("  {:<width$}  {}\n", name, flag.help, width = width));
+    }
+    text
+}
+
+/// Returns the number of single-character insertions, deletions and substitutions needed to turn `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    // `previous[j]` is the distance between the part of `a` seen so far, less its last character, and the first `j` characters of `b`.
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+/// Returns the message for an unknown flag, suggesting the closest known flag if it is likely to be a typo.
+fn unknown_flag(flag: &str) -> String {
+    let closest = FLAGS.iter()
+        .map(|known| (edit_distance(flag, known.long), known.long))
+        .min();
+    match closest {
+        Some((distance, known)) if distance <= 2 => format!("Unknown option `{}`. Did you mean `{}`?", flag, known),
+        _ => format!("Unknown option `{}`.", flag),
+    }
+}
+
+/// Parses the command-line arguments, not including the name of the binary.
+/// Options have to come before the script; every argument after the script is passed to it, even if it looks like an option.
+/// If the arguments are invalid, returns a message saying why.
+pub fn parse_args(args: &[String]) -> Result<Action, String> {
+    let mut options = Options::default();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "-" || !arg.starts_with('-') {
+            // The argument is the script. The rest of the arguments belong to the script.
+            options.script = Some(if arg == "-" { Input::Stdin } else { Input::File(arg.clone()) });
+            options.args = args.cloned().collect();
+            break;
+        }
+
+        let Some(flag) = FLAGS.iter().find(|flag| flag.long == arg || flag.short == Some(arg)) else {
+            return Err(unknown_flag(arg));
+        };
+        // If the flag takes a value, it has to be the next argument.
+        let value = match flag.value {
+            Some(value_name) => match args.next() {
+                Some(value) => value.clone(),
+                None => return Err(format!("`{}` has to be followed by <{}>.", arg, value_name)),
+            },
+            None => String::new(),
+        };
+
+        match flag.long {
+            "--eval" => {
+                // As with a script, the rest of the arguments belong to the code.
+                options.script = Some(Input::Eval(value));
+                options.args = args.cloned().collect();
+                break;
+            },
+            "--tokens" => set_mode(&mut options, Mode::Tokens)?,
+            "--ast" => set_mode(&mut options, Mode::Ast)?,
+            "--ast-json" => set_mode(&mut options, Mode::AstJson)?,
+            "--check" => set_mode(&mut options, Mode::Check)?,
+            "--fmt" => set_mode(&mut options, Mode::Fmt)?,
+            "--lint" => set_mode(&mut options, Mode::Lint)?,
+            "--doc" => set_mode(&mut options, Mode::Doc)?,
+            "--test" => set_mode(&mut options, Mode::Test)?,
+            "--bundle" => {
+                set_mode(&mut options, Mode::Bundle)?;
+                options.bundle = Some(value);
+            },
+            "--transpile" => {
+                if value != "py" {
+                    return Err(format!("Scripts cannot be translated into `{}`; the only language is `py`, for Python.", value));
+                }
+                set_mode(&mut options, Mode::Transpile)?;
+            },
+            "--allow" | "--warn" | "--deny" => {
+                let Some(lint) = Lint::find(&value) else {
+                    return Err(format!("`{}` is not the code or name of a lint.", value));
+                };
+                let severity = match flag.long {
+                    "--allow" => Severity::Allow,
+                    "--warn" => Severity::Warn,
+                    _ => Severity::Deny,
+                };
+                options.lint.set(lint, severity);
+            },
+            "--max-errors" => match value.parse() {
+                Ok(max_reported) => options.error_limit.max_reported = Some(max_reported),
+                Err(_) => return Err(format!("`{}` is not a valid number of errors.", value)),
+            },
+            "--fail-fast" => options.error_limit.fail_fast = true,
+            "--strict" => options.strict = true,
+            "--truthy" => options.truthy = true,
+            "--time" => options.time = true,
+            "--trace" => options.trace = true,
+            "--profile" => options.profile = true,
+            "--coverage" => options.coverage = true,
+            "--coverage-file" => {
+                // Giving a file is enough to ask for the listing.
+                options.coverage = true;
+                options.coverage_file = Some(value);
+            },
+            "--pretty" => options.pretty = Some(true),
+            "--no-pretty" => options.pretty = Some(false),
+            "--max-elements" => match value.parse() {
+                Ok(max_elements) => {
+                    // Giving a number is enough to ask for pretty-printing.
+                    options.pretty = Some(true);
+                    options.max_elements = max_elements;
+                },
+                Err(_) => return Err(format!("`{}` is not a valid number of elements.", value)),
+            },
+            "--no-color" => options.colour = false,
+            "--history" => options.history = Some(value),
+            "--preload" => options.preload.push(value),
+            "--messages" => options.messages = Some(value),
+            // These flags do something other than run a script, so the rest of the arguments are not needed.
+            "--explain" => return Ok(Action::Explain(value)),
+            "--help" => return Ok(Action::Help),
+            "--version" => return Ok(Action::Version),
+            // Every flag in `FLAGS` is handled above.
+            _ => unreachable!("Unhandled flag `{}`.", flag.long),
+        }
+    }
+
+    if options.mode != Mode::Run && options.script.is_none() {
+        // The REPL only runs source code, so the other modes need a script.
+        return Err(format!("A script has to be given with `{}`.", options.mode.flag()));
+    }
+    Ok(Action::Run(options))
+}
+
+/// Selects the mode to run the script in. Only one mode can be selected.
+/// The exception is `--fmt` with `--check`, given in either order, which checks the formatting.
+fn set_mode(options: &mut Options, mode: Mode) -> Result<(), String> {
+    if matches!((options.mode, mode), (Mode::Fmt | Mode::FmtCheck, Mode::Check) | (Mode::Check | Mode::FmtCheck, Mode::Fmt)) {
+        options.mode = Mode::FmtCheck;
+        return Ok(());
+    }
+    if options.mode != Mode::Run && options.mode != mode {
+        return Err(format!("`{}` and `{}` cannot be used together.", options.mode.flag(), mode.flag()));
+    }
+    options.mode = mode;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use nea::error::ErrorLimit;
+    use nea::lint::{Lint, LintConfig, Severity};
+
+    use super::{edit_distance, help, parse_args, Action, Input, Mode, Options, DEFAULT_MAX_ELEMENTS, FLAGS};
+
+    fn args(args: &[&str]) -> Vec<String> {
+        args.iter().map(|arg| String::from(*arg)).collect()
+    }
+
+    /// Parses the arguments, which are expected to run a script or the REPL, into the options for the run.
+    fn options(arguments: &[&str]) -> Result<Options, String> {
+        match parse_args(&args(arguments))? {
+            Action::Run(options) => Ok(options),
+            action => panic!("Expected the arguments to run a script, not {:?}.", action),
+        }
+    }
+
+    #[test]
+    fn no_args() {
+        assert_eq!(options(&[]), Ok(Options::default()));
+    }
+
+    #[test]
+    fn error_limit_flags() {
+        assert_eq!(options(&["--max-errors", "3", "--fail-fast", "a.nea"]), Ok(Options {
+            script: Some(Input::File(String::from("a.nea"))),
+            args: Vec::new(),
+            mode: Mode::Run,
+            error_limit: ErrorLimit { max_reported: Some(3), fail_fast: true },
+            strict: false,
+            truthy: false,
+            lint: LintConfig::default(),
+            bundle: None,
+            messages: None,
+            time: false,
+            trace: false,
+            profile: false,
+            coverage: false,
+            coverage_file: None,
+            pretty: None,
+            max_elements: DEFAULT_MAX_ELEMENTS,
+            colour: true,
+            history: None,
+            preload: Vec::new(),
+        }));
+    }
+
+    #[test]
+    fn tokens_flag() {
+        assert_eq!(options(&["--tokens", "a.nea"]).map(|options| options.mode), Ok(Mode::Tokens));
+        // There is nothing to tokenize in the REPL.
+        assert!(options(&["--tokens"]).is_err());
+    }
+
+    #[test]
+    fn ast_flag() {
+        assert_eq!(options(&["--ast", "a.nea"]).map(|options| options.mode), Ok(Mode::Ast));
+        // Repeating a flag is allowed, but only one mode can be chosen.
+        assert_eq!(options(&["--ast", "--ast", "a.nea"]).map(|options| options.mode), Ok(Mode::Ast));
+        assert!(options(&["--tokens", "--ast", "a.nea"]).is_err());
+        assert_eq!(options(&["--ast-json", "a.nea"]).map(|options| options.mode), Ok(Mode::AstJson));
+        assert!(options(&["--ast", "--ast-json", "a.nea"]).is_err());
+    }
+
+    #[test]
+    fn check_flag() {
+        assert_eq!(options(&["--check", "a.nea"]).map(|options| options.mode), Ok(Mode::Check));
+        assert!(options(&["--check"]).is_err());
+        assert!(options(&["--check", "--ast", "a.nea"]).is_err());
+    }
+
+    #[test]
+    fn fmt_flag() {
+        assert_eq!(options(&["--fmt", "a.nea"]).map(|options| options.mode), Ok(Mode::Fmt));
+        assert_eq!(options(&["--fmt", "--check", "a.nea"]).map(|options| options.mode), Ok(Mode::FmtCheck));
+        assert_eq!(options(&["--check", "--fmt", "--fmt", "a.nea"]).map(|options| options.mode), Ok(Mode::FmtCheck));
+        assert_eq!(options(&["--fmt", "--check"]), Err(String::from("A script has to be given with `--fmt --check`.")));
+        assert_eq!(options(&["--fmt", "--check", "--ast", "a.nea"]), Err(String::from("`--fmt --check` and `--ast` cannot be used together.")));
+        assert!(options(&["--tokens", "--fmt", "a.nea"]).is_err());
+    }
+
+    #[test]
+    fn test_flag() {
+        assert_eq!(options(&["--test", "tests", "x"]).map(|options| (options.mode, options.script, options.args)), Ok((Mode::Test, Some(Input::File(String::from("tests"))), vec![String::from("x")])));
+        assert!(options(&["--test"]).is_err());
+        assert!(options(&["--test", "--lint", "a.nea"]).is_err());
+        assert_eq!(options(&["--doc", "a.nea"]).map(|options| options.mode), Ok(Mode::Doc));
+        assert!(options(&["--doc", "--test", "a.nea"]).is_err());
+        assert_eq!(options(&["--transpile", "py", "a.nea"]).map(|options| options.mode), Ok(Mode::Transpile));
+        assert!(options(&["--transpile", "js", "a.nea"]).is_err());
+        assert!(options(&["--transpile", "py", "--doc", "a.nea"]).is_err());
+        assert_eq!(options(&["--bundle", "prog", "a.nea"]).map(|options| (options.mode, options.bundle)), Ok((Mode::Bundle, Some(String::from("prog")))));
+        assert!(options(&["--bundle", "a.nea"]).is_err());
+    }
+
+    #[test]
+    fn lint_flags() {
+        assert_eq!(options(&["--lint", "a.nea"]).map(|options| options.mode), Ok(Mode::Lint));
+        assert!(options(&["--lint", "--check", "a.nea"]).is_err());
+
+        let mut expected = LintConfig::default();
+        expected.set(Lint::UnusedParameter, Severity::Allow);
+        expected.set(Lint::DeepNesting, Severity::Deny);
+        // A later flag for the same lint replaces an earlier one.
+        let arguments = ["--allow", "L0003", "--deny", "deep-nesting", "--deny", "L0001", "--warn", "assignment-in-condition", "--lint", "a.nea"];
+        assert_eq!(options(&arguments).map(|options| options.lint), Ok(expected));
+        assert_eq!(options(&["--deny", "L9999", "a.nea"]), Err(String::from("`L9999` is not the code or name of a lint.")));
+    }
+
+    #[test]
+    fn strict_flag() {
+        assert_eq!(options(&["a.nea"]).map(|options| options.strict), Ok(false));
+        assert_eq!(options(&["--strict", "a.nea"]).map(|options| options.strict), Ok(true));
+        assert_eq!(options(&["--strict", "--check", "a.nea"]).map(|options| (options.strict, options.mode)), Ok((true, Mode::Check)));
+    }
+
+    #[test]
+    fn truthy_flag() {
+        assert_eq!(options(&["a.nea"]).map(|options| options.truthy), Ok(false));
+        assert_eq!(options(&["--truthy"]).map(|options| options.truthy), Ok(true));
+        assert_eq!(options(&["--truthy", "--strict", "a.nea"]).map(|options| (options.truthy, options.strict)), Ok((true, true)));
+    }
+
+    #[test]
+    fn instrumentation_flags() {
+        assert_eq!(options(&["--time", "a.nea"]).map(|options| options.time), Ok(true));
+        assert_eq!(options(&["a.nea", "--time"]).map(|options| options.time), Ok(false));
+        assert_eq!(options(&["--trace", "a.nea"]).map(|options| options.trace), Ok(true));
+        assert_eq!(options(&["--profile", "a.nea"]).map(|options| options.profile), Ok(true));
+        assert_eq!(options(&["--coverage", "a.nea"]).map(|options| (options.coverage, options.coverage_file)), Ok((true, None)));
+        assert_eq!(
+            options(&["--coverage-file", "c.txt", "a.nea"]).map(|options| (options.coverage, options.coverage_file)),
+            Ok((true, Some(String::from("c.txt")))),
+        );
+    }
+
+    #[test]
+    fn pretty_flags() {
+        let pretty = |arguments: &[&str], by_default| options(arguments).map(|options| options.pretty_elements(by_default));
+        assert_eq!(pretty(&[], true), Ok(Some(DEFAULT_MAX_ELEMENTS)));
+        assert_eq!(pretty(&["a.nea"], false), Ok(None));
+        assert_eq!(pretty(&["--pretty", "a.nea"], false), Ok(Some(DEFAULT_MAX_ELEMENTS)));
+        assert_eq!(pretty(&["--no-pretty"], true), Ok(None));
+        assert_eq!(pretty(&["--max-elements", "5", "a.nea"], false), Ok(Some(5)));
+        assert_eq!(pretty(&["--max-elements", "5", "--no-pretty"], true), Ok(None));
+        assert_eq!(options(&["--max-elements", "-1"]), Err(String::from("`-1` is not a valid number of elements.")));
+    }
+
+    #[test]
+    fn no_color_flag() {
+        assert_eq!(options(&[]).map(|options| options.colour), Ok(true));
+        assert_eq!(options(&["--no-color"]).map(|options| options.colour), Ok(false));
+    }
+
+    #[test]
+    fn history_flag() {
+        assert_eq!(options(&["--history", "h.txt"]).map(|options| options.history), Ok(Some(String::from("h.txt"))));
+        assert!(options(&["--history"]).is_err());
+    }
+
+    #[test]
+    fn preload_flag() {
+        assert_eq!(options(&[]).map(|options| options.preload), Ok(Vec::new()));
+        assert_eq!(options(&["--preload", "a.nea", "--preload", "b.nea"]).map(|options| options.preload), Ok(args(&["a.nea", "b.nea"])));
+        assert!(options(&["--preload"]).is_err());
+    }
+
+    #[test]
+    fn invalid_args() {
+        assert!(options(&["--max-errors"]).is_err());
+        assert!(options(&["--max-errors", "many"]).is_err());
+        assert!(options(&["--unknown"]).is_err());
+        assert!(options(&["-x", "a.nea"]).is_err());
+        assert!(options(&["--messages"]).is_err());
+    }
+
+    #[test]
+    fn unknown_flag_suggestions() {
+        assert_eq!(options(&["--chek", "a.nea"]), Err(String::from("Unknown option `--chek`. Did you mean `--check`?")));
+        assert_eq!(options(&["--max-error", "3"]), Err(String::from("Unknown option `--max-error`. Did you mean `--max-errors`?")));
+        assert_eq!(options(&["--colour"]), Err(String::from("Unknown option `--colour`.")));
+    }
+
+    #[test]
+    fn script_args() {
+        let options_ = options(&["--fail-fast", "a.nea", "input.csv", "--fail-fast", "-e", "10"]).unwrap();
+        assert_eq!(options_.script, Some(Input::File(String::from("a.nea"))));
+        // Options after the script are passed to it rather than being used.
+        assert_eq!(options_.args, args(&["input.csv", "--fail-fast", "-e", "10"]));
+
+        assert_eq!(options(&["-e", "print args()", "a", "b"]).map(|options| options.args), Ok(args(&["a", "b"])));
+        assert_eq!(options(&["-", "-"]).map(|options| options.args), Ok(args(&["-"])));
+    }
+
+    #[test]
+    fn eval_flag() {
+        let script = |arguments: &[&str]| options(arguments).map(|options| options.script);
+        assert_eq!(script(&["-e", "print 1"]), Ok(Some(Input::Eval(String::from("print 1")))));
+        assert_eq!(script(&["--eval", "print 1"]), Ok(Some(Input::Eval(String::from("print 1")))));
+        // The code is taken as is, even if it looks like a flag.
+        assert_eq!(script(&["-e", "--tokens"]), Ok(Some(Input::Eval(String::from("--tokens")))));
+        assert_eq!(options(&["--ast", "-e", "1"]).map(|options| options.mode), Ok(Mode::Ast));
+        assert!(script(&["-e"]).is_err());
+    }
+
+    #[test]
+    fn stdin_script() {
+        assert_eq!(options(&["--check", "-"]).map(|options| options.script), Ok(Some(Input::Stdin)));
+    }
+
+    #[test]
+    fn other_actions() {
+        assert_eq!(parse_args(&args(&["--help"])), Ok(Action::Help));
+        assert_eq!(parse_args(&args(&["--fail-fast", "-h", "a.nea"])), Ok(Action::Help));
+        assert_eq!(parse_args(&args(&["-V"])), Ok(Action::Version));
+        assert_eq!(parse_args(&args(&["--explain", "E0012"])), Ok(Action::Explain(String::from("E0012"))));
+        assert!(parse_args(&args(&["--explain"])).is_err());
+        // After the script, these are passed to it instead.
+        assert!(matches!(parse_args(&args(&["a.nea", "--help"])), Ok(Action::Run(_))));
+    }
+
+    #[test]
+    fn help_lists_every_flag() {
+        let help = help();
+        for flag in FLAGS {
+            assert!(help.contains(flag.long));
+        }
+    }
+
+    #[test]
+    fn distances() {
+        assert_eq!(edit_distance("--check", "--check"), 0);
+        assert_eq!(edit_distance("--chek", "--check"), 1);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+        assert_eq!(edit_distance("", "abc"), 3);
+    }
+}
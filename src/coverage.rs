@@ -0,0 +1,108 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::expr::Expr;
+use crate::hooks::Hooks;
+use crate::stmt::{Stmt, StmtType};
+use crate::visitor::{walk_stmt, Visitor};
+
+/// Records which lines of a script were executed, for `--coverage`.
+///
+/// A line is *executable* if a statement starts on it, other than a block, which is positioned at its closing brace.
+/// The statements in the bodies of functions are executable even if the function is never called, so that they show up as missed.
+#[derive(Debug, Default)]
+pub struct Coverage {
+    executable: BTreeSet<usize>,
+    counts: HashMap<usize, usize>,  // The number of times a statement on each line was executed.
+}
+
+impl Coverage {
+    /// Marks the lines the statements, and every statement nested in them, start on as executable.
+    pub fn add_statements(&mut self, statements: &[Stmt]) {
+        self.visit_stmts(statements);
+    }
+
+    /// Records that a statement on the line was executed.
+    pub fn record(&mut self, line: usize) {
+        *self.counts.entry(line).or_default() += 1;
+    }
+
+    /// Returns the executable lines which were never executed, in order.
+    pub fn missed(&self) -> Vec<usize> {
+        self.executable.iter().copied().filter(|line| !self.counts.contains_key(line)).collect()
+    }
+
+    /// Returns the listing printed by `--coverage`: each line of the source code, preceded by the number of times it was executed.
+    /// Executable lines which were never executed are marked with `#####`, and lines which are not executable with `-`.
+    /// The listing ends with how many of the executable lines were executed.
+    pub fn listing(&self, source: &str) -> String {
+        let mut listing = String::new();
+        for (i, text) in source.lines().enumerate() {
+            let line = i + 1;
+            let count = match self.counts.get(&line) {
+                Some(count) => count.to_string(),
+                None if self.executable.contains(&line) => String::from("#####"),
+                None => String::from("-"),
+            };
+            listing.push_str(&format!("{:>8}: {:>4}: {}\n", count, line, text));
+        }
+
+        let executed = self.executable.len() - self.missed().len();
+        let percentage = if self.executable.is_empty() { 100.0 } else { executed as f64 * 100.0 / self.executable.len() as f64 };
+        listing.push_str(&format!("Executed {} of {} lines ({:.1}%).\n", executed, self.executable.len(), percentage));
+        listing
+    }
+}
+
+impl Visitor for Coverage {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        if !matches!(stmt.stmt_type, StmtType::Block { .. }) {
+            self.executable.insert(stmt.line);
+        }
+        walk_stmt(self, stmt);
+    }
+
+    // Expressions cannot contain statements, so there is no need to look inside them.
+    fn visit_expr(&mut self, _expr: &Expr) {}
+}
+
+impl Hooks for Coverage {
+    fn before_statement(&mut self, stmt: &Stmt) {
+        self.record(stmt.line);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::Parser;
+    use crate::tokenizer::Tokenizer;
+
+    use super::Coverage;
+
+    fn coverage(source: &str) -> Coverage {
+        let mut coverage = Coverage::default();
+        coverage.add_statements(&Parser::new(Tokenizer::new(source).tokenize().unwrap()).parse().unwrap());
+        coverage
+    }
+
+    #[test]
+    fn executable_lines() {
+        let coverage = coverage("func f() {\n    return 1\n}\n\nif (true) {\n    print 1\n} else {\n    print 2\n}");
+        assert_eq!(coverage.missed(), vec![1, 2, 5, 6, 8]);
+    }
+
+    #[test]
+    fn listing() {
+        let source = "var x = 1\n# A comment.\nif (x > 1) {\n    print x\n}";
+        let mut coverage = coverage(source);
+        coverage.record(1);
+        coverage.record(3);
+        assert_eq!(coverage.missed(), vec![4]);
+        assert_eq!(coverage.listing(source), "       1:    1: var x = 1
+       -:    2: # A comment.
+       1:    3: if (x > 1) {
+   #####:    4:     print x
+       -:    5: }
+Executed 2 of 3 lines (66.7%).
+");
+    }
+}
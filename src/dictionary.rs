@@ -0,0 +1,296 @@
+use crate::hash_table::{HashState, HashTable, Hasher, KeyValue, NealHash};
+use crate::value::Value;
+use crate::error::ErrorType;
+
+/// Returned when a `Value` cannot be hashed, i.e., it is or contains a function.
+#[derive(Debug, PartialEq)]
+pub struct Unhashable;
+
+/// Allows `Value`s to be used as keys in a `HashTable`.
+/// Each variant starts with a different tag, and arrays, strings and dictionaries also write their length,
+/// so that keys of different shapes do not turn into the same sequence of words.
+impl NealHash for Value {
+    type Error = Unhashable;
+
+    fn hash_into(&self, state: &mut HashState) -> Result<(), Self::Error> {
+        match self {
+            Value::Array(array) => {
+                state.write(0);
+                state.write(array.len() as u64);
+                for element in array {
+                    element.hash_into(state)?;
+                }
+            },
+            Value::Bool(b) => {
+                state.write(1);
+                state.write(*b as u64);
+            },
+            Value::Dictionary(dict) => {
+                // Equal dictionaries can store their entries in different orders, so the hash cannot depend on the order of the entries.
+                // Instead, each entry is hashed on its own and the hashes are added together, as addition does not depend on the order.
+                // Note the dictionary's own stored hashes are not used, as it could be using a different hasher.
+                // Keys are stored as copies, so modifying the original dictionary afterwards does not change the hash of the key.
+                let mut sum: u64 = 0;
+                for key_value in dict.iter() {
+                    let mut entry_state = state.fork();
+                    key_value.key.hash_into(&mut entry_state)?;
+                    key_value.value.hash_into(&mut entry_state)?;
+                    sum = sum.wrapping_add(entry_state.finish());
+                }
+                state.write(5);
+                state.write(dict.size() as u64);
+                state.write(sum);
+            },
+            Value::Function {..} | Value::BuiltinFunction(..) => {
+                // It is tricky to hash functions as the comparison of two functions is not set in stone.
+                // So we raise a descriptive error instead.
+                return Err(Unhashable);
+            },
+            Value::Null => state.write(2),
+            Value::Number(x) => {
+                // `0.0` and `-0.0` are equal but have different bit patterns, so they are written as the same word, as is every `NaN`.
+                state.write(3);
+                state.write(if *x == 0.0 { 0 } else if x.is_nan() { f64::NAN.to_bits() } else { x.to_bits() });
+            },
+            Value::String_(s) => {
+                state.write(4);
+                state.write_bytes(s.as_bytes());
+            },
+        }
+        Ok(())
+    }
+}
+
+/// A dictionary in the language, i.e., a `HashTable` from `Value`s to `Value`s.
+/// This reports failures as `ErrorType`s with the line and column numbers they occurred at, so the rest of the interpreter can use it directly.
+/// As with arrays, a dictionary can be frozen with `freeze()`, and code which changes it has to check `is_frozen()` first.
+#[derive(Clone, Debug)]
+pub struct Dictionary {
+    table: HashTable<Value, Value>,
+    frozen: bool,
+}
+
+/// Freezing a dictionary does not change what it is equal to.
+impl PartialEq for Dictionary {
+    fn eq(&self, other: &Self) -> bool {
+        self.table == other.table
+    }
+}
+
+impl Default for Dictionary {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Dictionary {
+    /// Initialises a new instance of `Dictionary` using the `Fixed` hasher.
+    pub fn new() -> Self {
+        Self::with_capacity(0, Hasher::Fixed)
+    }
+
+    /// Initialises a new instance of `Dictionary` using the given hasher, with enough room for `capacity` entries without rehashing.
+    pub fn with_capacity(capacity: usize, hasher: Hasher) -> Self {
+        Self { table: HashTable::with_capacity(capacity, hasher), frozen: false }
+    }
+
+    /// Makes sure at least `additional` more entries can be inserted without rehashing.
+    pub fn reserve(&mut self, additional: usize) {
+        self.table.reserve(additional);
+    }
+
+    /// Returns the value associated with `key`.
+    pub fn get(&self, key: &Value, line: usize, column: usize) -> Result<&Value, ErrorType> {
+        match self.table.get(key) {
+            Ok(Some(value)) => Ok(value),
+            // If the key does not exist in the dictionary, return a KeyError, providing the `key` for detail.
+            Ok(None) => Err(ErrorType::KeyError { key: key.clone(), line, column }),
+            Err(Unhashable) => Err(ErrorType::CannotHashFunction { line, column }),
+        }
+    }
+
+    /// Returns a mutable reference to the value associated with `key`. As above.
+    pub fn get_mut(&mut self, key: &Value, line: usize, column: usize) -> Result<&mut Value, ErrorType> {
+        match self.table.get_mut(key) {
+            Ok(Some(value)) => Ok(value),
+            Ok(None) => Err(ErrorType::KeyError { key: key.clone(), line, column }),
+            Err(Unhashable) => Err(ErrorType::CannotHashFunction { line, column }),
+        }
+    }
+
+    /// Inserts a key-value pair to the dictionary if the key does not already exist; otherwise, updates the existing pair with the new value.
+    pub fn insert(&mut self, key: &Value, value: &Value, line: usize, column: usize) -> Result<(), ErrorType> {
+        self.table.insert(key.clone(), value.clone()).map_err(|_| ErrorType::CannotHashFunction { line, column })?;
+        Ok(())
+    }
+
+    /// Returns a mutable reference to the value associated with `key`, inserting the value returned by `default` if the key does not already exist.
+    pub fn get_or_insert_with(&mut self, key: &Value, default: impl FnOnce() -> Value, line: usize, column: usize) -> Result<&mut Value, ErrorType> {
+        self.table.get_or_insert_with(key.clone(), default).map_err(|_| ErrorType::CannotHashFunction { line, column })
+    }
+
+    /// Removes a key-value pair from the dictionary.
+    pub fn remove(&mut self, key: &Value, line: usize, column: usize) -> Result<(), ErrorType> {
+        match self.table.remove(key) {
+            Ok(Some(..)) => Ok(()),
+            Ok(None) => Err(ErrorType::KeyError { key: key.clone(), line, column }),
+            Err(Unhashable) => Err(ErrorType::CannotHashFunction { line, column }),
+        }
+    }
+
+    /// Returns the number of entries in the dictionary.
+    pub fn size(&self) -> usize {
+        self.table.size()
+    }
+
+    /// Returns an iterator over the key-value pairs in the dictionary, without copying them.
+    pub fn iter(&self) -> impl Iterator<Item = &KeyValue<Value>> {
+        self.table.iter()
+    }
+
+    /// Returns whether the dictionary has been frozen, so that it cannot be changed.
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// Freezes the dictionary and its values. The keys are copies, so they cannot be changed anyway.
+    pub fn freeze(&mut self) {
+        self.frozen = true;
+        self.table.iter_mut().for_each(|(_, value)| value.freeze());
+    }
+
+    /// Returns an iterator over the key-value pairs in the dictionary which allows the values to be modified.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&Value, &mut Value)> {
+        self.table.iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use crate::{error::ErrorType, hash_table::{hash, Hasher}, stmt::{Stmt, StmtType}, value::{BuiltinFunction, Value}};
+
+    use super::Dictionary;
+
+    #[test]
+    fn insert_and_get() {
+        let mut dict = Dictionary::new();
+        assert!(dict.insert(&Value::Number(5.0), &Value::String_("hello".to_string()), 1, 1).is_ok());
+        assert_eq!(dict.get(&Value::Number(5.0), 1, 1), Ok(&Value::String_("hello".to_string())));
+    }
+
+    #[test]
+    fn insert_remove_size() {
+        let mut dict = Dictionary::new();
+        assert!(dict.insert(&Value::Number(5.0), &Value::String_("hello".to_string()), 1, 1).is_ok());
+        assert!(dict.insert(&Value::String_("key1".to_string()), &Value::String_("hi".to_string()), 1, 1).is_ok());
+        assert_eq!(dict.size(), 2);
+
+        assert!(dict.remove(&Value::Number(5.0), 1, 1).is_ok());
+        assert_eq!(dict.size(), 1);
+        assert_eq!(dict.remove(&Value::Number(5.0), 1, 1), Err(ErrorType::KeyError { key: Value::Number(5.0), line: 1, column: 1 }));
+    }
+
+    #[test]
+    fn signed_zero_keys() {
+        let mut dict = Dictionary::new();
+        assert!(dict.insert(&Value::Number(0.0), &Value::Null, 1, 1).is_ok());
+        assert!(dict.insert(&Value::Number(-0.0), &Value::Bool(true), 1, 1).is_ok());
+        assert_eq!(dict.size(), 1);
+        assert_eq!(dict.get(&Value::Number(0.0), 1, 1), Ok(&Value::Bool(true)));
+    }
+
+    #[test]
+    fn get_or_insert_with() {
+        let mut dict = Dictionary::new();
+        assert!(dict.insert(&Value::Number(1.0), &Value::Number(10.0), 1, 1).is_ok());
+        assert_eq!(dict.get_or_insert_with(&Value::Number(1.0), || panic!("default computed"), 1, 1), Ok(&mut Value::Number(10.0)));
+        assert_eq!(dict.get_or_insert_with(&Value::Number(2.0), || Value::Null, 1, 1), Ok(&mut Value::Null));
+        assert_eq!(dict.size(), 2);
+
+        // As with `insert()`, unhashable keys are rejected.
+        let function = Value::Function { parameters: vec![], body: Arc::new(Stmt { line: 1, column: 1, stmt_type: StmtType::Break }) };
+        assert_eq!(dict.get_or_insert_with(&function, || Value::Null, 1, 1), Err(ErrorType::CannotHashFunction { line: 1, column: 1 }));
+    }
+
+    #[test]
+    fn key_error() {
+        let dict = Dictionary::new();
+        assert_eq!(dict.get(&Value::Number(5.0), 1, 1), Err(ErrorType::KeyError { key: Value::Number(5.0), line: 1, column: 1 }));
+    }
+
+    #[test]
+    fn cannot_hash_errors() {
+        let dict = Dictionary::new();
+        assert_eq!(dict.get(&Value::Function { parameters: vec![], body: Arc::new(Stmt { line: 1, column: 1, stmt_type: StmtType::Break }) }, 1, 1), Err(ErrorType::CannotHashFunction { line: 1, column: 1 }));
+
+        // Dictionaries are only hashable if all of their keys and values are.
+        let mut dict_with_function = Dictionary::new();
+        assert!(dict_with_function.insert(&Value::Null, &Value::BuiltinFunction(BuiltinFunction::Size), 1, 1).is_ok());
+        assert_eq!(dict.get(&Value::Dictionary(dict_with_function), 1, 1), Err(ErrorType::CannotHashFunction { line: 1, column: 1 }));
+    }
+
+    #[test]
+    fn nested_keys() {
+        // Nested strings should not be confused with each other.
+        let key1 = Value::Array(vec![Value::String_("ab".to_string())].into());
+        let key2 = Value::Array(vec![Value::String_("a".to_string()), Value::String_("b".to_string())].into());
+        for hasher in [Hasher::Fixed, Hasher::Seeded { k0: 1, k1: 2 }] {
+            assert_ne!(hash(&key1, hasher), hash(&key2, hasher));
+        }
+    }
+
+    #[test]
+    fn dictionary_keys() {
+        for hasher in [Hasher::Fixed, Hasher::Seeded { k0: 1, k1: 2 }] {
+            // The same entries inserted in different orders, into dictionaries with different hashers.
+            let mut key1 = Dictionary::with_capacity(0, hasher);
+            let mut key2 = Dictionary::with_capacity(0, Hasher::Seeded { k0: 3, k1: 4 });
+            for i in 0..20 {
+                assert!(key1.insert(&Value::Number(i as f64), &Value::String_(i.to_string()), 1, 1).is_ok());
+                assert!(key2.insert(&Value::Number((19 - i) as f64), &Value::String_((19 - i).to_string()), 1, 1).is_ok());
+            }
+
+            let mut dict = Dictionary::with_capacity(0, hasher);
+            assert!(dict.insert(&Value::Dictionary(key1.clone()), &Value::Bool(true), 1, 1).is_ok());
+            assert_eq!(dict.get(&Value::Dictionary(key2.clone()), 1, 1), Ok(&Value::Bool(true)));
+            assert_eq!(dict.get(&Value::Dictionary(Dictionary::new()), 1, 1), Err(ErrorType::KeyError { key: Value::Dictionary(Dictionary::new()), line: 1, column: 1 }));
+
+            // Swapping values between keys changes the dictionary, so it should not be found.
+            assert!(key2.insert(&Value::Number(0.0), &Value::String_("1".to_string()), 1, 1).is_ok());
+            assert!(key2.insert(&Value::Number(1.0), &Value::String_("0".to_string()), 1, 1).is_ok());
+            assert!(dict.get(&Value::Dictionary(key2), 1, 1).is_err());
+        }
+    }
+
+    #[test]
+    fn equality() {
+        let mut dict1 = Dictionary::new();
+        let mut dict2 = Dictionary::with_capacity(0, Hasher::random());
+        assert!(dict1.insert(&Value::Number(5.0), &Value::String_("hello".to_string()), 1, 1).is_ok());
+        assert!(dict1.insert(&Value::Number(6.0), &Value::String_("hello".to_string()), 1, 1).is_ok());
+        assert!(dict1.insert(&Value::Number(7.0), &Value::String_("hello".to_string()), 1, 1).is_ok());
+
+        assert!(dict2.insert(&Value::Number(7.0), &Value::String_("hello".to_string()), 1, 1).is_ok());
+        assert!(dict2.insert(&Value::Number(6.0), &Value::String_("hello".to_string()), 1, 1).is_ok());
+        assert!(dict2.insert(&Value::Number(5.0), &Value::String_("hello".to_string()), 1, 1).is_ok());
+
+        assert_eq!(dict1, dict2);
+    }
+
+    #[test]
+    fn inequality() {
+        let mut dict1 = Dictionary::new();
+        let mut dict2 = Dictionary::new();
+        assert!(dict1.insert(&Value::Number(5.0), &Value::String_("hello".to_string()), 1, 1).is_ok());
+        assert!(dict1.insert(&Value::Number(6.0), &Value::String_("hello".to_string()), 1, 1).is_ok());
+        assert!(dict1.insert(&Value::Number(7.0), &Value::String_("hello".to_string()), 1, 1).is_ok());
+
+        assert!(dict2.insert(&Value::Number(8.0), &Value::String_("hello".to_string()), 1, 1).is_ok());
+        assert!(dict2.insert(&Value::Number(6.0), &Value::String_("hello".to_string()), 1, 1).is_ok());
+        assert!(dict2.insert(&Value::Number(5.0), &Value::String_("hello".to_string()), 1, 1).is_ok());
+
+        assert_ne!(dict1, dict2);
+    }
+}
@@ -0,0 +1,93 @@
+//! Documentation of a script's functions, written in Markdown from their `##` doc comments, as `--doc` prints it.
+//!
+//! A doc comment is a comment starting with `##` on a line of its own. The doc comments on the lines directly before a function
+//! declaration document it, and are written as Markdown, so they can contain lists, code and links:
+//!
+//! ```text
+//! ## Returns the area of a rectangle.
+//! ## - `width`: the length of one side.
+//! func area(width, height) {
+//! ```
+//!
+//! The parser only keeps doc comments if it is given the comments with `Parser::set_comments()`.
+
+use crate::stmt::{Stmt, StmtType};
+
+/// Returns the Markdown documentation of the functions declared at the top level of the abstract syntax tree, in source order.
+/// Functions without doc comments are listed too, so that every function which can be called is documented.
+pub fn markdown(ast: &[Stmt]) -> String {
+    let mut output = String::from("# Functions\n");
+    let mut any = false;
+    for stmt in ast {
+        let StmtType::Function { name, parameters, doc, .. } = &stmt.stmt_type else {
+            continue;
+        };
+        any = true;
+        output.push_str(&format!("\n## `{}({})`\n", name, parameters.join(", ")));
+        if let Some(doc) = doc {
+            output.push_str(&format!("\n{}\n", doc));
+        }
+        if !parameters.is_empty() {
+            let parameters: Vec<String> = parameters.iter().map(|parameter| format!("`{}`", parameter)).collect();
+            output.push_str(&format!("\nParameters: {}.\n", parameters.join(", ")));
+        }
+        output.push_str(&format!("\nDeclared on line {}.\n", stmt.line));
+    }
+    if !any {
+        output.push_str("\nThe script declares no functions.\n");
+    }
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::Parser;
+    use crate::stmt::StmtType;
+    use crate::tokenizer::Tokenizer;
+
+    use super::markdown;
+
+    /// Parses the source code with its doc comments.
+    fn parse(source: &str) -> Vec<crate::stmt::Stmt> {
+        let mut tokenizer = Tokenizer::new(source);
+        let mut parser = Parser::new(tokenizer.tokenize().unwrap());
+        parser.set_comments(tokenizer.comments());
+        parser.parse().unwrap()
+    }
+
+    #[test]
+    fn doc_comments() {
+        let source = "## Not attached, as a line is between.\n\n# Not a doc comment.\n## Adds.\n##\n##  Indented.\nfunc add(a, b) { return a + b }  ## trailing\nfunc f() {\n    ## Nested.\n    func g() {}\n}";
+        let ast = parse(source);
+        let docs: Vec<Option<String>> = ast.iter().map(|stmt| match &stmt.stmt_type {
+            StmtType::Function { doc, .. } => doc.clone(),
+            _ => unreachable!(),
+        }).collect();
+        assert_eq!(docs, [Some(String::from("Adds.\n\n Indented.")), None]);
+        let StmtType::Function { body, .. } = &ast[1].stmt_type else { unreachable!() };
+        let StmtType::Block { body } = &body.stmt_type else { unreachable!() };
+        assert!(matches!(&body[0].stmt_type, StmtType::Function { doc: Some(doc), .. } if doc == "Nested."));
+    }
+
+    #[test]
+    fn markdown_output() {
+        let ast = parse("## Returns the area.\n## - `w`: the width.\nfunc area(w, h) {\n    return w * h\n}\nvar x = 1\nfunc reset() {}");
+        assert_eq!(markdown(&ast), "\
+# Functions
+
+## `area(w, h)`
+
+Returns the area.
+- `w`: the width.
+
+Parameters: `w`, `h`.
+
+Declared on line 3.
+
+## `reset()`
+
+Declared on line 7.
+");
+        assert_eq!(markdown(&parse("print 1")), "# Functions\n\nThe script declares no functions.\n");
+    }
+}
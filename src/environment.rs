@@ -20,15 +20,9 @@ impl Environment {
     pub fn new() -> Self {
         Self {
             // Initialises the built-in functions in the base scope.
-            scopes: vec![HashMap::from([
-                (String::from("append"), Value::BuiltinFunction(BuiltinFunction::Append)),
-                (String::from("input"), Value::BuiltinFunction(BuiltinFunction::Input)),
-                (String::from("remove"), Value::BuiltinFunction(BuiltinFunction::Remove)),
-                (String::from("size"), Value::BuiltinFunction(BuiltinFunction::Size)),
-                (String::from("sort"), Value::BuiltinFunction(BuiltinFunction::Sort)),
-                (String::from("to_number"), Value::BuiltinFunction(BuiltinFunction::ToNumber)),
-                (String::from("to_string"), Value::BuiltinFunction(BuiltinFunction::ToString)),
-            ])],
+            scopes: vec![BuiltinFunction::ALL.iter()
+                .map(|builtin| (String::from(builtin.name()), Value::BuiltinFunction(builtin.clone())))
+                .collect()],
         }
     }
 
@@ -56,9 +50,19 @@ impl Environment {
         }
     }
 
+    /// Returns the number of scopes, including the base scope.
+    pub fn depth(&self) -> usize {
+        self.scopes.len()
+    }
+
+    /// Returns the index of the right-most scope in which `name` is declared, where the base scope is 0, or `None` if it is not declared.
+    pub fn scope_of(&self, name: &str) -> Option<usize> {
+        self.scopes.iter().rposition(|scope| scope.contains_key(name))
+    }
+
     /// Returns the value associated with `name`. As there could be multiple values associated with `name`
     /// across all the scopes, return the one in the right-most scope.
-    pub fn get(&self, name: String, line: usize) -> Result<Value, ErrorType> {
+    pub fn get(&self, name: String, line: usize, column: usize) -> Result<Value, ErrorType> {
         for scope in self.scopes.iter().rev() {
             // Iterate from the right-most scope.
             if let Some(object) = scope.get(&name) {
@@ -68,105 +72,225 @@ impl Environment {
         }
         // We have iterated through all the scopes and no value have been found to be associated with `name`.
         // So raise a `NameError`, giving the `name` in question to be as detailed as possible.
-        Err(ErrorType::NameError { name, line })
+        Err(ErrorType::NameError { name, line, column })
+    }
+
+    /// Returns the value associated with `name` in the base scope, ignoring any declarations in the scopes to its right.
+    pub fn get_global(&self, name: &str) -> Option<&Value> {
+        self.scopes[0].get(name)
+    }
+
+    /// Declares a name-value pair in the base scope, replacing any value already associated with `name` there.
+    pub fn set_global(&mut self, name: String, value: Value) {
+        self.scopes[0].insert(name, value);
+    }
+
+    /// Returns every variable and function declared in the base scope, with its value, in alphabetical order of name.
+    /// Built-in functions are left out unless their names have been declared again, e.g., `var size = 5`.
+    pub fn globals(&self) -> Vec<(String, Value)> {
+        let mut globals: Vec<(String, Value)> = self.scopes[0].iter()
+            .filter(|(name, value)| !matches!(value, Value::BuiltinFunction(builtin) if builtin.name() == name.as_str()))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        globals.sort_by(|a, b| a.0.cmp(&b.0));
+        globals
+    }
+
+    /// Returns every variable and function declared in the scopes from `first_scope` to the right-most, with its value, in
+    /// alphabetical order of name. Where a name is declared in more than one of them, the value in the right-most scope is given.
+    /// As with `globals()`, built-in functions are left out unless their names have been declared again.
+    pub fn locals(&self, first_scope: usize) -> Vec<(String, Value)> {
+        let mut locals: HashMap<&String, &Value> = HashMap::new();
+        for scope in &self.scopes[first_scope..] {
+            // Scopes further right replace the values of the names they declare again.
+            locals.extend(scope.iter());
+        }
+        let mut locals: Vec<(String, Value)> = locals.into_iter()
+            .filter(|(name, value)| !matches!(value, Value::BuiltinFunction(builtin) if builtin.name() == name.as_str()))
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        locals.sort_by(|a, b| a.0.cmp(&b.0));
+        locals
+    }
+
+    /// Returns every name which can currently be accessed, from every scope, in alphabetical order and without duplicates.
+    pub fn visible_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.scopes.iter().flat_map(|scope| scope.keys().cloned()).collect();
+        names.sort();
+        // A name declared in more than one scope is only listed once.
+        names.dedup();
+        names
+    }
+
+    /// Returns every variable and user-defined function which can currently be accessed, with its value, in alphabetical order of name.
+    /// Where a name is declared in more than one scope, the value in the right-most scope is given. Built-in functions are left out.
+    pub fn variables(&self) -> Vec<(String, Value)> {
+        self.visible_names()
+            .into_iter()
+            .filter_map(|name| {
+                // Every visible name has a value, so the position given for a `NameError` is never used.
+                let value = self.get(name.clone(), 0, 0).ok()?;
+                (!matches!(value, Value::BuiltinFunction(_))).then_some((name, value))
+            })
+            .collect()
     }
 
     /// Updates the value associated with the pointer. Again, update the one in the right-most scope only.
-    pub fn update(&mut self, pointer: &Pointer, value: &Value, line: usize) -> Result<(), ErrorType> {
+    pub fn update(&mut self, pointer: &Pointer, value: &Value, line: usize, column: usize) -> Result<(), ErrorType> {
+        self.replace(pointer, value, line, column).map(|_| ())
+    }
+
+    /// Updates the value associated with the pointer as `update()` does, and returns the value it replaced, or `None` if there was
+    /// none, i.e., the value was appended to an array or inserted into a dictionary with a new key. `restore()` undoes the update.
+    pub fn replace(&mut self, pointer: &Pointer, value: &Value, line: usize, column: usize) -> Result<Option<Value>, ErrorType> {
         for scope in self.scopes.iter_mut().rev() {
             // Iterate from the right-most scope.
             if let Some(object) = scope.get_mut(&pointer.name) {
                 // If there is a value associated with `pointer.name`...
-                if !pointer.indices.is_empty() {
+                if let Some((last_index, other_indices)) = pointer.indices.split_last() {
                     // If indices were provided...
 
-                    // This is the array/dictionary associated with `pointer.name`.
-                    let mut current_element = object;
-
-                    // For each index in `pointer.indices` except the last, replace `current_element` with `current_element[index]`.
-                    for i in pointer.indices.iter().take(pointer.indices.len() - 1) {
-                        match current_element {
-                            Value::Array(array) => {
-                                // If `current_element` is an array, we have to convert the index into `usize` and make sure
-                                // it is not out-of-bounds.
-                                let idx = index_value_to_usize(i, line)?;
-                                if let Some(el) = array.get_mut(idx) {
-                                    current_element = el;
-                                } else {
-                                    // If the index provided is out-of-bounds, raise an `OutOfBoundsIndexError`.
-                                    return Err(ErrorType::OutOfBoundsIndex { index: idx, line });
-                                }
-                            },
-                            Value::Dictionary(dict) => {
-                                // If `current_element` is a dictionary, we can let `HashTable` get `current_element[index]`.
-                                current_element = dict.get_mut(i, line)?;
-                            },
-                            // If it is any other variant of `Value`, then we cannot index it.
-                            // Note: strings can only be indexed with the last index, so it is not included here.
-                            _ => return Err(ErrorType::NotIndexable { line }),
-                        }
+                    // Follow every index except the last to find the array/dictionary/string to be updated.
+                    let current_element = element_mut(object, other_indices, line, column)?;
+                    if current_element.is_frozen() {
+                        // A character of a string is changed by changing the string in its array or dictionary, which `element_mut()` has checked.
+                        return Err(ErrorType::FrozenValueMutation { line, column });
                     }
 
                     // Note that the last index is separated so that:
                     // 1. Dictionaries can insert key-value pairs with the last key if it does not exist already.
                     //    For example, `a[1][5] = 1` inserts `5` as a key if it does not exist already (`a[1]` is a dictionary).
                     // 2. For strings, you have to do it this way to allow mutations like `a[2][1] = 'h'`.
-                    let last_index = pointer.indices.last().unwrap();
                     match current_element {
                         Value::Array(array) => {
-                            // As above.
-                            let idx = index_value_to_usize(last_index, line)?;
+                            // Overwrite `current_element[index]`, making sure the index is not out-of-bounds.
+                            let idx = index_value_to_usize(last_index, line, column)?;
                             if let Some(el) = array.get_mut(idx) {
-                                current_element = el;
+                                return Ok(Some(std::mem::replace(el, value.clone())));
+                            } else if idx == array.len() {
+                                // Assigning to the index one past the end, i.e., `a[size(a)] = x`, appends to the array.
+                                array.push(value.clone());
                             } else {
                                 // If the index provided is out-of-bounds or similar...
-                                return Err(ErrorType::OutOfBoundsIndex { index: idx, line });
+                                return Err(ErrorType::OutOfBoundsIndex { index: idx, line, column });
                             }
-                            *current_element = value.clone();
                         },
                         Value::Dictionary(dict) => {
-                            // `HashTable` inserts key-value pairs if the key does not exist already and updates them otherwise.
-                            dict.insert(last_index, value, line)?;
+                            // `Dictionary` inserts key-value pairs if the key does not exist already and updates them otherwise.
+                            if let Ok(el) = dict.get_mut(last_index, line, column) {
+                                return Ok(Some(std::mem::replace(el, value.clone())));
+                            }
+                            dict.insert(last_index, value, line, column)?;
                         },
                         Value::String_(s) => {
                             // Convert the index value into a `usize`.
-                            let idx = index_value_to_usize(last_index, line)?;
+                            let idx = index_value_to_usize(last_index, line, column)?;
 
-                            // Make sure it is not out-of-bounds.
-                            if s.get(idx..idx+1).is_none() {
-                                return Err(ErrorType::OutOfBoundsIndex { index: idx, line });
-                            }
+                            // Strings are indexed by character, as `size()` counts them, so find the bytes of the character, making sure it is not out-of-bounds.
+                            let Some((start, old)) = s.char_indices().nth(idx) else {
+                                return Err(ErrorType::OutOfBoundsIndex { index: idx, line, column });
+                            };
 
                             if let Value::String_(c) = value {
                                 // If `value` is a string, replace `current_element[index]` with `value`.
-                                s.replace_range(idx..idx+1, c);
+                                s.replace_range(start..start + old.len_utf8(), c);
+                                return Ok(Some(Value::String_(old.to_string())));
                             } else {
                                 // Otherwise, it cannot be inserted into a string.
-                                return Err(ErrorType::InsertNonStringIntoString { line });
+                                return Err(ErrorType::InsertNonStringIntoString { line, column });
                             }
                         },
                         // Any other variant of `Value` cannot be indexed.
-                        _ => return Err(ErrorType::NotIndexable { line }),
+                        _ => return Err(ErrorType::NotIndexable { line, column }),
                     }
 
-                    return Ok(());
+                    return Ok(None);
 
                 } else {
                     // If no indices were provided, simply replace the value associated with `pointer.name` with `value`.
-                    // Note: `insert()` will update the key-value pair if the key exists already.
-                    scope.insert(pointer.name.clone(), value.clone());
-                    return Ok(());
+                    return Ok(Some(std::mem::replace(object, value.clone())));
                 }
             }
         }
         // We have iterated through all the scopes and no value have been found to be associated with `name`.
         // So raise a `NameError`, giving the `name` in question to be as detailed as possible.
-        Err(ErrorType::NameError { name: pointer.name.clone(), line })
+        Err(ErrorType::NameError { name: pointer.name.clone(), line, column })
+    }
+
+    /// Undoes an update made by `replace()`, given the value it returned, by putting the old value back or removing the new one.
+    pub fn restore(&mut self, pointer: &Pointer, old: Option<Value>, line: usize, column: usize) -> Result<(), ErrorType> {
+        match (old, pointer.indices.split_last()) {
+            // The value was replaced, so the old one is put back where it was.
+            (Some(old), _) => self.update(pointer, &old, line, column),
+            // Otherwise, it was appended to an array or inserted into a dictionary with a new key, so it is removed again.
+            (None, Some((last_index, other_indices))) => {
+                let parent = Pointer { name: pointer.name.clone(), indices: other_indices.to_vec() };
+                match self.get_mut(&parent, line, column)? {
+                    Value::Array(array) => {
+                        array.pop();
+                        Ok(())
+                    },
+                    Value::Dictionary(dict) => dict.remove(last_index, line, column),
+                    _ => Err(ErrorType::NotIndexable { line, column }),
+                }
+            },
+            // A variable is only ever replaced, as it has to be declared before it can be assigned.
+            (None, None) => Err(ErrorType::NameError { name: pointer.name.clone(), line, column }),
+        }
+    }
+
+    /// Returns a mutable reference to the value the pointer points to, so that it can be modified without being copied.
+    /// As with `get()`, the one in the right-most scope is used.
+    pub fn get_mut(&mut self, pointer: &Pointer, line: usize, column: usize) -> Result<&mut Value, ErrorType> {
+        for scope in self.scopes.iter_mut().rev() {
+            // Iterate from the right-most scope.
+            if let Some(object) = scope.get_mut(&pointer.name) {
+                // If there is a value associated with `pointer.name`, follow all of the indices.
+                return element_mut(object, &pointer.indices, line, column);
+            }
+        }
+        // As above.
+        Err(ErrorType::NameError { name: pointer.name.clone(), line, column })
     }
 }
 
+/// Follows the sequence of indices starting from `object`, i.e., `object[indices[0]][indices[1]]...`, and returns a mutable reference to the element.
+/// Strings are not included, as a character of a string is not stored as a `Value` of its own.
+/// The element is returned to be changed, which changes every array and dictionary it is inside, so none of them can be frozen.
+fn element_mut<'a>(object: &'a mut Value, indices: &[Value], line: usize, column: usize) -> Result<&'a mut Value, ErrorType> {
+    let mut current_element = object;
+
+    // For each index, replace `current_element` with `current_element[index]`.
+    for i in indices {
+        if current_element.is_frozen() {
+            return Err(ErrorType::FrozenValueMutation { line, column });
+        }
+        match current_element {
+            Value::Array(array) => {
+                // If `current_element` is an array, we have to convert the index into `usize` and make sure
+                // it is not out-of-bounds.
+                let idx = index_value_to_usize(i, line, column)?;
+                if let Some(el) = array.get_mut(idx) {
+                    current_element = el;
+                } else {
+                    // If the index provided is out-of-bounds, raise an `OutOfBoundsIndexError`.
+                    return Err(ErrorType::OutOfBoundsIndex { index: idx, line, column });
+                }
+            },
+            Value::Dictionary(dict) => {
+                // If `current_element` is a dictionary, we can let `Dictionary` get `current_element[index]`.
+                current_element = dict.get_mut(i, line, column)?;
+            },
+            // If it is any other variant of `Value`, then we cannot index it.
+            _ => return Err(ErrorType::NotIndexable { line, column }),
+        }
+    }
+
+    Ok(current_element)
+}
+
 /// Converts a variant of `Value` into a usize. If it cannot, raises an appropriate error.
-pub fn index_value_to_usize(index: &Value, line: usize) -> Result<usize, ErrorType> {
+pub fn index_value_to_usize(index: &Value, line: usize, column: usize) -> Result<usize, ErrorType> {
     match index {
         Value::Number(index_num) => {
             // If `index` is a `Number` variant...
@@ -177,11 +301,11 @@ pub fn index_value_to_usize(index: &Value, line: usize) -> Result<usize, ErrorTy
                 Ok(*index_num as usize)
             } else {
                 // If it is not non-negative or it is not an integer, then raise an error as it cannot be used as an index.
-                Err(ErrorType::NonNaturalIndex { got: index.clone(), line })
+                Err(ErrorType::NonNaturalIndex { got: index.clone(), line, column })
             }
         },
         // If it is not a `Number` variant, then it cannot be used as an index, so raise an error.
-        _ => Err(ErrorType::NonNumberIndex { got: index.type_to_string(), line })
+        _ => Err(ErrorType::NonNumberIndex { got: index.type_to_string(), line, column })
     }
 }
 
@@ -198,13 +322,13 @@ mod tests {
         //  b = "abc"
         let mut env = Environment::new();
         env.declare(String::from("a"), &Value::Number(5.0));
-        env.declare(String::from("b"), &Value::Array(vec![Value::Bool(true), Value::String_(String::from("hello world!"))]));
-        assert_eq!(env.get(String::from("a"), 1), Ok(Value::Number(5.0)));
-        assert_eq!(env.get(String::from("b"), 1), Ok(Value::Array(vec![Value::Bool(true), Value::String_(String::from("hello world!"))])));
+        env.declare(String::from("b"), &Value::Array(vec![Value::Bool(true), Value::String_(String::from("hello world!"))].into()));
+        assert_eq!(env.get(String::from("a"), 1, 1), Ok(Value::Number(5.0)));
+        assert_eq!(env.get(String::from("b"), 1, 1), Ok(Value::Array(vec![Value::Bool(true), Value::String_(String::from("hello world!"))].into())));
 
-        let _ = env.update(&Pointer { name: String::from("b"), indices: vec![] }, &Value::String_(String::from("abc")), 1);
-        assert_eq!(env.get(String::from("a"), 1), Ok(Value::Number(5.0)));
-        assert_eq!(env.get(String::from("b"), 1), Ok(Value::String_(String::from("abc"))));
+        let _ = env.update(&Pointer { name: String::from("b"), indices: vec![] }, &Value::String_(String::from("abc")), 1, 1);
+        assert_eq!(env.get(String::from("a"), 1, 1), Ok(Value::Number(5.0)));
+        assert_eq!(env.get(String::from("b"), 1, 1), Ok(Value::String_(String::from("abc"))));
     }
 
     #[test]
@@ -229,33 +353,33 @@ mod tests {
         env.declare(String::from("b"), &Value::Number(2.0));
 
         env.new_scope();
-        let _ = env.update(&Pointer { name: String::from("a"), indices: vec![] }, &Value::Number(10.0), 1);
+        let _ = env.update(&Pointer { name: String::from("a"), indices: vec![] }, &Value::Number(10.0), 1, 1);
         env.declare(String::from("b"), &Value::Number(20.0));
-        assert_eq!(env.get(String::from("a"), 1), Ok(Value::Number(10.0)));
-        assert_eq!(env.get(String::from("b"), 1), Ok(Value::Number(20.0)));
+        assert_eq!(env.get(String::from("a"), 1, 1), Ok(Value::Number(10.0)));
+        assert_eq!(env.get(String::from("b"), 1, 1), Ok(Value::Number(20.0)));
 
         env.new_scope();
-        let _ = env.update(&Pointer { name: String::from("b"), indices: vec![] }, &Value::Number(30.0), 1);
-        assert_eq!(env.get(String::from("b"), 1), Ok(Value::Number(30.0)));
+        let _ = env.update(&Pointer { name: String::from("b"), indices: vec![] }, &Value::Number(30.0), 1, 1);
+        assert_eq!(env.get(String::from("b"), 1, 1), Ok(Value::Number(30.0)));
 
         env.exit_scope();
-        assert_eq!(env.get(String::from("b"), 1), Ok(Value::Number(30.0)));
+        assert_eq!(env.get(String::from("b"), 1, 1), Ok(Value::Number(30.0)));
 
         env.exit_scope();
-        assert_eq!(env.get(String::from("a"), 1), Ok(Value::Number(10.0)));
-        assert_eq!(env.get(String::from("b"), 1), Ok(Value::Number(2.0)));
+        assert_eq!(env.get(String::from("a"), 1, 1), Ok(Value::Number(10.0)));
+        assert_eq!(env.get(String::from("b"), 1, 1), Ok(Value::Number(2.0)));
     }
 
     #[test]
     fn name_error_get() {
         let env = Environment::new();
-        assert_eq!(env.get(String::from("b"), 1), Err(ErrorType::NameError { name: String::from("b"), line: 1 }));
+        assert_eq!(env.get(String::from("b"), 1, 1), Err(ErrorType::NameError { name: String::from("b"), line: 1, column: 1 }));
     }
 
     #[test]
     fn name_error_assign() {
         let mut env = Environment::new();
-        assert_eq!(env.update(&Pointer { name: String::from("b"), indices: vec![] }, &Value::Null, 1), Err(ErrorType::NameError { name: String::from("b"), line: 1 }));
+        assert_eq!(env.update(&Pointer { name: String::from("b"), indices: vec![] }, &Value::Null, 1, 1), Err(ErrorType::NameError { name: String::from("b"), line: 1, column: 1 }));
     }
 
     #[test]
@@ -263,6 +387,113 @@ mod tests {
         let mut env = Environment::new();
         env.declare(String::from("b"), &Value::Number(123.0));
         env.declare(String::from("b"), &Value::Number(55.0));
-        assert_eq!(env.get(String::from("b"), 1), Ok(Value::Number(55.0)));
+        assert_eq!(env.get(String::from("b"), 1, 1), Ok(Value::Number(55.0)));
+    }
+
+    #[test]
+    fn globals() {
+        let mut env = Environment::new();
+        env.set_global(String::from("a"), Value::Number(1.0));
+        env.new_scope();
+        env.declare(String::from("a"), &Value::Number(2.0));
+        env.set_global(String::from("b"), Value::Bool(true));
+        assert_eq!(env.get_global("a"), Some(&Value::Number(1.0)));
+        assert_eq!(env.get(String::from("a"), 1, 1), Ok(Value::Number(2.0)));
+        assert_eq!(env.get(String::from("b"), 1, 1), Ok(Value::Bool(true)));
+        assert_eq!(env.get_global("c"), None);
+    }
+
+    #[test]
+    fn update_string() {
+        // Characters are replaced whole, however many bytes they take up.
+        let mut env = Environment::new();
+        env.declare(String::from("s"), &Value::String_(String::from("héllo😀")));
+        let pointer = |index: f64| Pointer { name: String::from("s"), indices: vec![Value::Number(index)] };
+        assert_eq!(env.update(&pointer(1.0), &Value::String_(String::from("e")), 1, 1), Ok(()));
+        assert_eq!(env.update(&pointer(5.0), &Value::String_(String::from("🙂!")), 1, 1), Ok(()));
+        assert_eq!(env.get(String::from("s"), 1, 1), Ok(Value::String_(String::from("hello🙂!"))));
+        assert_eq!(env.update(&pointer(7.0), &Value::String_(String::from("x")), 1, 1), Err(ErrorType::OutOfBoundsIndex { index: 7, line: 1, column: 1 }));
+    }
+
+    #[test]
+    fn update_past_end() {
+        // Assigning to the index one past the end of an array appends to it, but any further out is still out-of-bounds.
+        let mut env = Environment::new();
+        env.declare(String::from("a"), &Value::Array(vec![Value::Number(1.0), Value::Array(vec![].into())].into()));
+        let pointer = |indices: &[f64]| Pointer { name: String::from("a"), indices: indices.iter().map(|i| Value::Number(*i)).collect() };
+        assert_eq!(env.update(&pointer(&[2.0]), &Value::Number(3.0), 1, 1), Ok(()));
+        assert_eq!(env.update(&pointer(&[1.0, 0.0]), &Value::Null, 1, 1), Ok(()));
+        assert_eq!(env.get(String::from("a"), 1, 1), Ok(Value::Array(vec![Value::Number(1.0), Value::Array(vec![Value::Null].into()), Value::Number(3.0)].into())));
+        assert_eq!(env.update(&pointer(&[4.0]), &Value::Null, 1, 1), Err(ErrorType::OutOfBoundsIndex { index: 4, line: 1, column: 1 }));
+    }
+
+    #[test]
+    fn visible_names() {
+        let mut environment = Environment::new();
+        environment.declare(String::from("zeta"), &Value::Null);
+        environment.new_scope();
+        environment.declare(String::from("alpha"), &Value::Null);
+        environment.declare(String::from("zeta"), &Value::Number(1.0));
+
+        let names = environment.visible_names();
+        assert!(names.contains(&String::from("alpha")));
+        assert!(names.contains(&String::from("append")));  // Built-in functions are visible too.
+        assert_eq!(names.iter().filter(|name| *name == "zeta").count(), 1);
+        assert!(names.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // Names in a scope which has been exited are no longer visible.
+        environment.exit_scope();
+        assert!(!environment.visible_names().contains(&String::from("alpha")));
+    }
+
+    #[test]
+    fn variables() {
+        let mut environment = Environment::new();
+        environment.declare(String::from("zeta"), &Value::Null);
+        environment.declare(String::from("beta"), &Value::Bool(true));
+        environment.new_scope();
+        environment.declare(String::from("zeta"), &Value::Number(1.0));
+
+        // Built-in functions are left out, and the innermost value of a name is given.
+        assert_eq!(environment.variables(), vec![
+            (String::from("beta"), Value::Bool(true)),
+            (String::from("zeta"), Value::Number(1.0)),
+        ]);
+    }
+
+    #[test]
+    fn locals() {
+        let mut environment = Environment::new();
+        environment.declare(String::from("a"), &Value::Null);
+        environment.declare(String::from("sort"), &Value::Null);
+        environment.new_scope();
+        environment.declare(String::from("b"), &Value::Bool(true));
+        environment.new_scope();
+        environment.declare(String::from("b"), &Value::Number(1.0));
+
+        // The innermost value of a name is given, and built-in functions are left out unless declared again.
+        assert_eq!(environment.locals(1), vec![(String::from("b"), Value::Number(1.0))]);
+        assert_eq!(environment.locals(0), vec![
+            (String::from("a"), Value::Null),
+            (String::from("b"), Value::Number(1.0)),
+            (String::from("sort"), Value::Null),
+        ]);
+        assert_eq!(environment.locals(3), vec![]);
+    }
+
+    #[test]
+    fn scopes() {
+        let mut environment = Environment::new();
+        environment.declare(String::from("a"), &Value::Null);
+        environment.new_scope();
+        environment.declare(String::from("b"), &Value::Null);
+        environment.new_scope();
+        environment.declare(String::from("a"), &Value::Null);
+
+        assert_eq!(environment.depth(), 3);
+        assert_eq!(environment.scope_of("a"), Some(2));
+        assert_eq!(environment.scope_of("b"), Some(1));
+        assert_eq!(environment.scope_of("size"), Some(0));
+        assert_eq!(environment.scope_of("c"), None);
     }
 }
@@ -1,3 +1,6 @@
+use std::io::{self, Write};
+
+use crate::catalogue::{self, Key};
 use crate::value::Value;
 
 /// Possible errors that may occur during execution. This type will be used when bubbling up errors.
@@ -7,109 +10,298 @@ pub enum ErrorType {
     UnexpectedCharacter {
         character: char,
         line: usize,
+        column: usize,
+    },
+    UnterminatedString {
+        line: usize,
+        column: usize,
+    },
+    InvalidNumber {
+        lexeme: String,
+        line: usize,
+        column: usize,
     },
-    UnterminatedString,
 
     // Syntax analysis errors, i.e., syntax errors.
     ExpectedCharacter {
         expected: char,
         line: usize,
+        column: usize,
     },
     ExpectedExpression {
         line: usize,
+        column: usize,
     },
     ExpectedFunctionName {
         line: usize,
+        column: usize,
     },
     ExpectedParameterName {
         line: usize,
+        column: usize,
     },
     ExpectedVariableName {
         line: usize,
+        column: usize,
     },
     ExpectedSemicolonAfterInit {
         line: usize,
+        column: usize,
     },
     ExpectedSemicolonAfterCondition {
         line: usize,
+        column: usize,
     },
     ExpectedParenAfterIncrement {
         line: usize,
+        column: usize,
     },
     ExpectedColonAfterKey {
         line: usize,
+        column: usize,
+    },
+    TooDeeplyNested {
+        line: usize,
+        column: usize,
+    },
+    ExpectedNewline {
+        line: usize,
+        column: usize,
+    },
+    ExpectedEndOfExpression {
+        line: usize,
+        column: usize,
+    },
+    ExpectedCatchOrFinally {
+        line: usize,
+        column: usize,
+    },
+    ExpectedNamedArgument {
+        line: usize,
+        column: usize,
+    },
+    ExpectedUnpackTargets {
+        line: usize,
+        column: usize,
+    },
+    DuplicateUnpackTarget {
+        name: String,
+        line: usize,
+        column: usize,
     },
     
     // Environment errors.
     NameError {
         name: String,
         line: usize,
+        column: usize,
     },
     NotIndexable {
         line: usize,
+        column: usize,
     },
     OutOfBoundsIndex {
         index: usize,
         line: usize,
+        column: usize,
     },
     InsertNonStringIntoString {
         line: usize,
+        column: usize,
+    },
+    Shadowing {
+        name: String,
+        line: usize,
+        column: usize,
+    },
+    OuterAssignment {
+        name: String,
+        line: usize,
+        column: usize,
+    },
+    FrozenValueMutation {
+        line: usize,
+        column: usize,
     },
     
     // Execution errors, i.e., runtime errors.
     InvalidAssignmentTarget {
         line: usize,
+        column: usize,
     },
     ExpectedType {
         expected: String,
         got: String,
         line: usize,
+        column: usize,
     },
     NonNaturalIndex {
         got: Value,
         line: usize,
+        column: usize,
     },
     NonNumberIndex {
         got: String,
         line: usize,
+        column: usize,
     },
     BinaryTypeError {
         expected: String,
         got_left: String,
         got_right: String,
         line: usize,
+        column: usize,
     },
     DivideByZero {
         line: usize,
+        column: usize,
+    },
+    ModuloByZero {
+        line: usize,
+        column: usize,
     },
     IfConditionNotBoolean {
         line: usize,
+        column: usize,
     },
     LoopConditionNotBoolean {
         line: usize,
+        column: usize,
     },
     CannotCallName {
         line: usize,
+        column: usize,
     },
     ArgParamNumberMismatch {
         arg_number: usize,
         param_number: usize,
         line: usize,
+        column: usize,
     },
     CannotConvertToNumber {
         line: usize,
+        column: usize,
+    },
+    Interrupted {
+        line: usize,
+        column: usize,
+    },
+    AssertionFailed {
+        left: Value,
+        right: Value,
+        line: usize,
+        column: usize,
+    },
+    InvalidDigits {
+        got: Value,
+        line: usize,
+        column: usize,
+    },
+    InvalidRepeatCount {
+        got: Value,
+        line: usize,
+        column: usize,
+    },
+    IncludeCycle {
+        path: String,
+        line: usize,
+        column: usize,
+    },
+    CannotReadFile {
+        path: String,
+        reason: String,
+        line: usize,
+        column: usize,
+    },
+    CannotWriteFile {
+        path: String,
+        reason: String,
+        line: usize,
+        column: usize,
+    },
+    JaggedMatrix {
+        row: usize,
+        length: usize,
+        expected: usize,
+        line: usize,
+        column: usize,
+    },
+    CannotStoreFunction {
+        line: usize,
+        column: usize,
+    },
+    CorruptStore {
+        path: String,
+        line: usize,
+        column: usize,
+    },
+    NotAWholeNumber {
+        got: Value,
+        line: usize,
+        column: usize,
+    },
+    InvalidBase {
+        got: Value,
+        line: usize,
+        column: usize,
+    },
+    InvalidCursorPosition {
+        got: Value,
+        line: usize,
+        column: usize,
+    },
+    UnknownColour {
+        name: String,
+        line: usize,
+        column: usize,
+    },
+    ZeroSliceStep {
+        line: usize,
+        column: usize,
+    },
+    UnpackMismatch {
+        expected: usize,
+        got: usize,
+        line: usize,
+        column: usize,
+    },
+    UnknownParameter {
+        name: String,
+        function: String,
+        line: usize,
+        column: usize,
+    },
+    DuplicateArgument {
+        name: String,
+        function: String,
+        line: usize,
+        column: usize,
+    },
+    CannotWriteOutput {
+        reason: String,
+        line: usize,
+        column: usize,
+    },
+    ArrayTooLarge {
+        size: usize,
+        line: usize,
+        column: usize,
+    },
+    TooManyNestedCalls {
+        limit: usize,
+        line: usize,
+        column: usize,
     },
 
     // Hash table errors.
     CannotHashFunction {
         line: usize,
-    },
-    CannotHashDictionary {
-        line: usize,
+        column: usize,
     },
     KeyError {
         key: Value,
         line: usize,
+        column: usize,
     },
 
     // Special errors.
@@ -118,127 +310,838 @@ pub enum ErrorType {
     // If not, e.g., a return statement was used outside a function, the error will be reported.
     ThrownBreak {
         line: usize,
+        column: usize,
     },
     ThrownReturn {
         value: Value,
         line: usize,
+        column: usize,
     },
 }
 
-/// Prints the error message for each error in `errors`.
+impl ErrorType {
+    /// Returns the stable code identifying the kind of error, e.g., `E0012` for a `NameError`.
+    /// Codes are never reused or renumbered, so new variants must be given the next unused code.
+    /// A longer explanation of each code can be obtained from `explain()`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            // Lexical analysis errors, i.e., tokenization errors.
+            ErrorType::UnexpectedCharacter { .. } => "E0001",
+            ErrorType::UnterminatedString { .. } => "E0002",
+            ErrorType::InvalidNumber { .. } => "E0031",
+
+            // Syntax analysis errors, i.e., syntax errors.
+            ErrorType::ExpectedCharacter { .. } => "E0003",
+            ErrorType::ExpectedExpression { .. } => "E0004",
+            ErrorType::ExpectedFunctionName { .. } => "E0005",
+            ErrorType::ExpectedParameterName { .. } => "E0006",
+            ErrorType::ExpectedVariableName { .. } => "E0007",
+            ErrorType::ExpectedSemicolonAfterInit { .. } => "E0008",
+            ErrorType::ExpectedSemicolonAfterCondition { .. } => "E0009",
+            ErrorType::ExpectedParenAfterIncrement { .. } => "E0010",
+            ErrorType::ExpectedColonAfterKey { .. } => "E0011",
+            ErrorType::TooDeeplyNested { .. } => "E0032",
+            ErrorType::ExpectedNewline { .. } => "E0035",
+            ErrorType::ExpectedEndOfExpression { .. } => "E0042",
+            ErrorType::ExpectedCatchOrFinally { .. } => "E0053",
+            ErrorType::ExpectedNamedArgument { .. } => "E0056",
+            ErrorType::ExpectedUnpackTargets { .. } => "E0059",
+            ErrorType::DuplicateUnpackTarget { .. } => "E0060",
+
+            // Environment errors.
+            ErrorType::NameError { .. } => "E0012",
+            ErrorType::NotIndexable { .. } => "E0013",
+            ErrorType::OutOfBoundsIndex { .. } => "E0014",
+            ErrorType::InsertNonStringIntoString { .. } => "E0015",
+            ErrorType::Shadowing { .. } => "E0036",
+            ErrorType::OuterAssignment { .. } => "E0037",
+            ErrorType::FrozenValueMutation { .. } => "E0040",
+
+            // Execution errors, i.e., runtime errors.
+            ErrorType::InvalidAssignmentTarget { .. } => "E0016",
+            ErrorType::ExpectedType { .. } => "E0017",
+            ErrorType::NonNaturalIndex { .. } => "E0018",
+            ErrorType::NonNumberIndex { .. } => "E0019",
+            ErrorType::BinaryTypeError { .. } => "E0020",
+            ErrorType::DivideByZero { .. } => "E0021",
+            ErrorType::ModuloByZero { .. } => "E0033",
+            ErrorType::IfConditionNotBoolean { .. } => "E0022",
+            ErrorType::LoopConditionNotBoolean { .. } => "E0023",
+            ErrorType::CannotCallName { .. } => "E0024",
+            ErrorType::ArgParamNumberMismatch { .. } => "E0025",
+            ErrorType::CannotConvertToNumber { .. } => "E0026",
+            ErrorType::Interrupted { .. } => "E0034",
+            ErrorType::AssertionFailed { .. } => "E0038",
+            ErrorType::InvalidDigits { .. } => "E0039",
+            ErrorType::InvalidRepeatCount { .. } => "E0041",
+            ErrorType::IncludeCycle { .. } => "E0043",
+            ErrorType::CannotReadFile { .. } => "E0044",
+            ErrorType::InvalidCursorPosition { .. } => "E0045",
+            ErrorType::UnknownColour { .. } => "E0046",
+            ErrorType::CannotWriteFile { .. } => "E0047",
+            ErrorType::JaggedMatrix { .. } => "E0048",
+            ErrorType::NotAWholeNumber { .. } => "E0049",
+            ErrorType::InvalidBase { .. } => "E0050",
+            ErrorType::CannotStoreFunction { .. } => "E0051",
+            ErrorType::CorruptStore { .. } => "E0052",
+            ErrorType::ZeroSliceStep { .. } => "E0054",
+            ErrorType::UnpackMismatch { .. } => "E0055",
+            ErrorType::UnknownParameter { .. } => "E0057",
+            ErrorType::DuplicateArgument { .. } => "E0058",
+            ErrorType::CannotWriteOutput { .. } => "E0061",
+            ErrorType::ArrayTooLarge { .. } => "E0062",
+            ErrorType::TooManyNestedCalls { .. } => "E0063",
+
+            // Hash table errors.
+            ErrorType::CannotHashFunction { .. } => "E0027",
+            ErrorType::KeyError { .. } => "E0028",
+
+            // Special errors.
+            ErrorType::ThrownBreak { .. } => "E0029",
+            ErrorType::ThrownReturn { .. } => "E0030",
+        }
+    }
+}
+
+impl ErrorType {
+    /// Returns the values which are filled into the error's message, by name, so that the message can be rendered from a template in any language.
+    /// Every error except `ThrownReturn`'s value is included, and every error has a `line` and a `column`.
+    pub fn arguments(&self) -> Vec<(&'static str, String)> {
+        // The position of every error, and any values specific to the error.
+        let (line, column, mut arguments) = match self {
+            // Lexical analysis errors, i.e., tokenization errors.
+            ErrorType::UnexpectedCharacter { character, line, column } => (line, column, vec![("character", character.to_string())]),
+            ErrorType::UnterminatedString { line, column } => (line, column, vec![]),
+            ErrorType::InvalidNumber { lexeme, line, column } => (line, column, vec![("lexeme", lexeme.clone())]),
+
+            // Syntax analysis errors, i.e., syntax errors.
+            ErrorType::ExpectedCharacter { expected, line, column } => (line, column, vec![("expected", expected.to_string())]),
+            ErrorType::ExpectedExpression { line, column }
+            | ErrorType::ExpectedFunctionName { line, column }
+            | ErrorType::ExpectedParameterName { line, column }
+            | ErrorType::ExpectedVariableName { line, column }
+            | ErrorType::ExpectedSemicolonAfterInit { line, column }
+            | ErrorType::ExpectedSemicolonAfterCondition { line, column }
+            | ErrorType::ExpectedParenAfterIncrement { line, column }
+            | ErrorType::ExpectedColonAfterKey { line, column }
+            | ErrorType::TooDeeplyNested { line, column }
+            | ErrorType::ExpectedNewline { line, column }
+            | ErrorType::ExpectedEndOfExpression { line, column }
+            | ErrorType::ExpectedCatchOrFinally { line, column }
+            | ErrorType::ExpectedNamedArgument { line, column }
+            | ErrorType::ExpectedUnpackTargets { line, column } => (line, column, vec![]),
+            ErrorType::DuplicateUnpackTarget { name, line, column } => (line, column, vec![("name", name.clone())]),
+
+            // Environment errors.
+            ErrorType::NameError { name, line, column } => (line, column, vec![("name", name.clone())]),
+            ErrorType::NotIndexable { line, column } => (line, column, vec![]),
+            ErrorType::OutOfBoundsIndex { index, line, column } => (line, column, vec![("index", index.to_string())]),
+            ErrorType::InsertNonStringIntoString { line, column }
+            | ErrorType::FrozenValueMutation { line, column } => (line, column, vec![]),
+            ErrorType::Shadowing { name, line, column }
+            | ErrorType::OuterAssignment { name, line, column } => (line, column, vec![("name", name.clone())]),
+
+            // Execution errors, i.e., runtime errors.
+            ErrorType::InvalidAssignmentTarget { line, column } => (line, column, vec![]),
+            ErrorType::ExpectedType { expected, got, line, column } => (line, column, vec![("expected", expected.clone()), ("got", got.clone())]),
+            ErrorType::NonNaturalIndex { got, line, column } => (line, column, vec![("got", got.to_string())]),
+            ErrorType::NonNumberIndex { got, line, column } => (line, column, vec![("got", got.clone())]),
+            ErrorType::BinaryTypeError { expected, got_left, got_right, line, column } => (line, column, vec![
+                ("expected", expected.clone()),
+                ("got_left", got_left.clone()),
+                ("got_right", got_right.clone()),
+            ]),
+            ErrorType::DivideByZero { line, column }
+            | ErrorType::ModuloByZero { line, column }
+            | ErrorType::IfConditionNotBoolean { line, column }
+            | ErrorType::LoopConditionNotBoolean { line, column }
+            | ErrorType::CannotCallName { line, column } => (line, column, vec![]),
+            ErrorType::ArgParamNumberMismatch { arg_number, param_number, line, column } => (line, column, vec![
+                ("arg_number", arg_number.to_string()),
+                ("param_number", param_number.to_string()),
+            ]),
+            ErrorType::CannotConvertToNumber { line, column }
+            | ErrorType::Interrupted { line, column }
+            | ErrorType::CannotStoreFunction { line, column }
+            | ErrorType::ZeroSliceStep { line, column } => (line, column, vec![]),
+            ErrorType::AssertionFailed { left, right, line, column } => (line, column, vec![("left", left.to_string()), ("right", right.to_string())]),
+            ErrorType::InvalidDigits { got, line, column }
+            | ErrorType::InvalidRepeatCount { got, line, column }
+            | ErrorType::InvalidCursorPosition { got, line, column }
+            | ErrorType::NotAWholeNumber { got, line, column }
+            | ErrorType::InvalidBase { got, line, column } => (line, column, vec![("got", got.to_string())]),
+            ErrorType::IncludeCycle { path, line, column }
+            | ErrorType::CorruptStore { path, line, column } => (line, column, vec![("path", path.clone())]),
+            ErrorType::CannotReadFile { path, reason, line, column }
+            | ErrorType::CannotWriteFile { path, reason, line, column } => (line, column, vec![("path", path.clone()), ("reason", reason.clone())]),
+            ErrorType::UnknownColour { name, line, column } => (line, column, vec![("name", name.clone())]),
+            ErrorType::JaggedMatrix { row, length, expected, line, column } => (line, column, vec![
+                ("row", row.to_string()),
+                ("length", length.to_string()),
+                ("expected", expected.to_string()),
+            ]),
+            ErrorType::UnpackMismatch { expected, got, line, column } => (line, column, vec![
+                ("expected", expected.to_string()),
+                ("got", got.to_string()),
+            ]),
+            ErrorType::UnknownParameter { name, function, line, column }
+            | ErrorType::DuplicateArgument { name, function, line, column } => (line, column, vec![
+                ("name", name.clone()),
+                ("function", function.clone()),
+            ]),
+            ErrorType::CannotWriteOutput { reason, line, column } => (line, column, vec![("reason", reason.clone())]),
+            ErrorType::ArrayTooLarge { size, line, column } => (line, column, vec![("size", size.to_string())]),
+            ErrorType::TooManyNestedCalls { limit, line, column } => (line, column, vec![("limit", limit.to_string())]),
+
+            // Hash table errors.
+            ErrorType::CannotHashFunction { line, column } => (line, column, vec![]),
+            ErrorType::KeyError { key, line, column } => (line, column, vec![("key", key.to_string())]),
+
+            // Special errors.
+            ErrorType::ThrownBreak { line, column } => (line, column, vec![]),
+            ErrorType::ThrownReturn { value: _, line, column } => (line, column, vec![]),
+        };
+        arguments.push(("line", line.to_string()));
+        arguments.push(("column", column.to_string()));
+        arguments
+    }
+}
+
+/// Settings which control how many errors the tokenizer and parser collect and report in one run.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ErrorLimit {
+    pub max_reported: Option<usize>,  // The maximum number of errors printed; the rest are summarised in a single line. `None` means every error is printed.
+    pub fail_fast: bool,  // Whether to stop at the first error, rather than recovering and carrying on to find more.
+}
+
+/// Prints the error message for each error in `errors` to the standard error stream.
 pub fn report_errors(errors: &[ErrorType]) {
-    println!("An error has occurred.");
-    for error in errors {
-        print_report(error);
+    report_errors_up_to(errors, None);
+}
+
+/// Prints the error messages for at most `max_reported` of the `errors`, followed by the number of errors not printed.
+/// If `max_reported` is `None`, every error is printed.
+pub fn report_errors_up_to(errors: &[ErrorType], max_reported: Option<usize>) {
+    // If the standard error stream cannot be written to, there is nowhere else to report the errors.
+    let _ = write_errors(&mut io::stderr(), errors, max_reported);
+}
+
+/// Writes the error messages to `output` in the same way as `report_errors_up_to()` prints them.
+/// Each message is prefixed by the error's code, and is in the language of the installed message catalogue.
+pub fn write_errors(output: &mut dyn Write, errors: &[ErrorType], max_reported: Option<usize>) -> io::Result<()> {
+    let catalogue = catalogue::current();
+    writeln!(output, "{}", catalogue.render(Key::Header, &[]))?;
+    let max_reported = max_reported.unwrap_or(errors.len());
+    for error in errors.iter().take(max_reported) {
+        writeln!(output, "[{}] {}", error.code(), catalogue.render_error(error))?;
     }
+    if errors.len() > max_reported {
+        writeln!(output, "{}", catalogue.render(Key::MoreErrors, &[("count", (errors.len() - max_reported).to_string())]))?;
+    }
+    Ok(())
 }
 
-/// Prints the error message for an individual error.
-fn print_report(error: &ErrorType) {
-    match error {
+/// Returns a longer explanation of the error with the given code, including an example that causes it and how to fix it.
+/// The code is matched case-insensitively. If no error has the code, `None` is returned.
+pub fn explain(code: &str) -> Option<&'static str> {
+    let explanation = match code.to_ascii_uppercase().as_str() {
         // Lexical analysis errors, i.e., tokenization errors.
-        ErrorType::UnexpectedCharacter { character, line } => {
-            println!("Line {}: unexpected character `{}`.", line, character);
-        },
-        ErrorType::UnterminatedString => {
-            println!("A string was never closed by the end of the program.");
-        },
+        "E0001" => "\
+A character that is not part of the language was found in the source code.
+
+Example:
+    var x = 5 @ 2
+
+Fix: remove the character, or replace it with the intended operator, e.g., `var x = 5 * 2`.
+If the character was meant to be text, put it inside a string: `var x = '@'`.",
+        "E0002" => "\
+A string was opened with a quote mark but never closed before the end of the program.
+
+Example:
+    print 'hello
+
+Fix: close the string with the same kind of quote mark that opened it: `print 'hello'`.",
 
         // Syntax analysis errors, i.e., syntax errors.
-        ErrorType::ExpectedCharacter { expected, line } => {
-            println!("Line {}: expected character `{}`", line, expected);
-        },
-        ErrorType::ExpectedExpression { line } => {
-            println!("Line {}: expected expression.", line);
-        },
-        ErrorType::ExpectedFunctionName { line } => {
-            println!("Line {}: expected function name. Make sure it is not a keyword.", line);
-        },
-        ErrorType::ExpectedParameterName { line } => {
-            println!("Line {}: expected parameter name in function declaration.", line);
-        },
-        ErrorType::ExpectedVariableName { line } => {
-            println!("Line {}: expected variable name. Make sure it is not a keyword.", line);
-        },
-        ErrorType::ExpectedSemicolonAfterInit { line } => {
-            println!("Line {}: expected `;` after initialising statement in `for` loop.", line);
-        },
-        ErrorType::ExpectedSemicolonAfterCondition { line } => {
-            println!("Line {}: expected `;` after condition in `for` loop.", line);
-        },
-        ErrorType::ExpectedParenAfterIncrement { line } => {
-            println!("Line {}: expected `)` after increment statement in `for` loop.", line);
-        },
-        ErrorType::ExpectedColonAfterKey { line } => {
-            println!("Line {}: expected colon after dictionary key.", line);
-        },
+        "E0003" => "\
+A specific character, such as a closing bracket, was expected but something else was found.
+
+Example:
+    print (1 + 2
+
+Fix: add the missing character given in the message: `print (1 + 2)`.
+Check that every `(`, `[` and `{` has a matching `)`, `]` and `}`.",
+        "E0004" => "\
+An expression (a value, a name, or a calculation) was expected but was not found.
+
+Example:
+    var x = 
+
+Fix: give the missing expression: `var x = 5`.
+This error also occurs when an operator is missing an operand, e.g., `1 + `.",
+        "E0005" => "\
+A function declaration must be followed by the name of the function.
+
+Example:
+    func (x) { return x }
+    func print(x) { return x }
+
+Fix: give the function a name that is not a keyword: `func identity(x) { return x }`.",
+        "E0006" => "\
+The parameters of a function declaration must be names, separated by commas.
+
+Example:
+    func add(1, b) { return b }
+
+Fix: use names for every parameter: `func add(a, b) { return a + b }`.",
+        "E0007" => "\
+A variable declaration must be followed by the name of the variable.
+
+Example:
+    var = 5
+    var while = 5
+
+Fix: give the variable a name that is not a keyword: `var count = 5`.",
+        "E0008" => "\
+The initialising statement of a `for` loop must be followed by a `;`.
+
+Example:
+    for (var i = 0 i < 10; i = i + 1) { print i }
+
+Fix: separate the three parts of the loop with semicolons: `for (var i = 0; i < 10; i = i + 1) { print i }`.",
+        "E0009" => "\
+The condition of a `for` loop must be followed by a `;`.
+
+Example:
+    for (var i = 0; i < 10 i = i + 1) { print i }
+
+Fix: separate the three parts of the loop with semicolons: `for (var i = 0; i < 10; i = i + 1) { print i }`.",
+        "E0010" => "\
+The increment statement of a `for` loop must be followed by a `)`.
+
+Example:
+    for (var i = 0; i < 10; i = i + 1 { print i }
+
+Fix: close the loop header with a `)`: `for (var i = 0; i < 10; i = i + 1) { print i }`.",
+        "E0011" => "\
+Each key in a dictionary literal must be followed by a `:` and then its value.
+
+Example:
+    var ages = {'alice' 20}
+
+Fix: put a colon between the key and the value: `var ages = {'alice': 20}`.",
 
         // Environment errors.
-        ErrorType::NameError { ref name, line } => {
-            println!("Line {}: `{}` is not defined.", line, name);
-        },
-        ErrorType::NotIndexable { line } => {
-            println!("Line {}: the value is not indexable.", line);
-        },
-        ErrorType::OutOfBoundsIndex { index, line } => {
-            println!("Line {}: index `{}` is out of bounds.", line, index);
-        },
-        ErrorType::InsertNonStringIntoString { line } => {
-            println!("Line {}: attempted to insert a non-string into a string.", line);
-        },
+        "E0012" => "\
+A name was used that has not been declared, or that was declared in a scope which has since ended.
+
+Example:
+    print total
+
+Fix: declare the variable before using it, and check its spelling:
+    var total = 0
+    print total",
+        "E0013" => "\
+Only arrays, strings and dictionaries can be indexed with `[]`.
+
+Example:
+    var x = 5
+    print x[0]
+
+Fix: make sure the value being indexed is an array, string or dictionary, e.g., `var x = [5]`.",
+        "E0014" => "\
+An index was larger than the last valid index of the array or string. Indices start from 0, so the last index is one less than the size.
+
+Example:
+    var a = [1, 2, 3]
+    print a[3]
+
+Fix: use an index from 0 up to `size(a) - 1`, e.g., `print a[2]`. To add to the end of an array, assign to the index `size(a)`,
+e.g., `a[3] = 4`, or use `append(a, 4)`, and to change its size, use `resize(a, 5, 0)`.",
+        "E0015" => "\
+Only strings can be assigned to an index of a string.
+
+Example:
+    var s = 'cat'
+    s[0] = 5
+
+Fix: assign a string instead: `s[0] = 'b'`. Use `to_string()` to convert other values.",
 
         // Execution errors, i.e., runtime errors.
-        ErrorType::InvalidAssignmentTarget { line } => {
-            println!("Line {}: invalid assignment target. Make sure you are not assigning to a literal.", line);
-        },
-        ErrorType::ExpectedType { ref expected, ref got, line } => {
-            println!("Line {}: expected type {}; instead got type {}.", line, expected, got);
-        },
-        ErrorType::NonNaturalIndex { got, line } => {
-            println!("Line {}: index evaluated to {}, which is not a positive integer.", line, got);
-        },
-        ErrorType::NonNumberIndex { got, line } => {
-            println!("Line {}: index evaluated to a {}, which is not a positive integer.", line, got);
-        },
-        ErrorType::BinaryTypeError { ref expected, ref got_left, ref got_right, line } => {
-            println!("Line {}: this operation requires both sides' types to be {}. Instead, got {} and {} respectively.", line, expected, got_left, got_right);
-        },
-        ErrorType::DivideByZero { line } => {
-            println!("Line {}: divisor is 0.", line);
-        },
-        ErrorType::IfConditionNotBoolean { line } => {
-            println!("Line {}: the `if` condition did not evaluate to a Boolean value.", line);
-        },
-        ErrorType::LoopConditionNotBoolean { line } => {
-            println!("Line {}: the condition of the loop did not evaluate to a Boolean value.", line);
-        },
-        ErrorType::CannotCallName { line } => {
-            println!("Line {}: cannot call name as a function.", line);
-        },
-        ErrorType::ArgParamNumberMismatch { arg_number, param_number, line } => {
-            println!("Line {}: attempted to call function with {} argument(s), but function accepts {}.", line, arg_number, param_number);
-        },
-        ErrorType::CannotConvertToNumber { line } => {
-            println!("Line {}: could not convert to a number.", line);
-        },
+        "E0016" => "\
+The left side of an `=` must be a variable or an indexed variable.
+
+Example:
+    var x = 1
+    5 = x
+
+Fix: put the variable being assigned to on the left: `x = 5`.",
+        "E0017" => "\
+A value of the wrong type was used, e.g., given to a built-in function which does not accept it.
+
+Example:
+    var a = 5
+    append(a, 1)
+
+Fix: pass a value of the type named in the message, e.g., `var a = [5]` and then `append(a, 1)`.",
+        "E0018" => "\
+An index must be a whole number which is not negative.
+
+Example:
+    var a = [1, 2, 3]
+    print a[1.5]
+    print a[-1]
+
+Fix: use a whole number from 0 upwards, e.g., `print a[1]`.",
+        "E0019" => "\
+An index into an array or string must be a number.
+
+Example:
+    var a = [1, 2, 3]
+    print a['1']
+
+Fix: use a number, converting if necessary: `print a[to_number('1')]`.",
+        "E0020" => "\
+Both sides of the operator must be values of the types given in the message.
+
+Example:
+    print 5 + true
+
+Fix: convert one side so that both have the same type: `print 5 + to_number(true)`. If either side of `+` is a string, the other
+side is converted to a string for you, e.g., `print 'age: ' + 5`.",
+        "E0021" => "\
+A number was divided by 0, which has no answer.
+
+Example:
+    var n = 0
+    print 10 / n
+
+Fix: check the divisor before dividing:
+    if (n != 0) { print 10 / n }",
+        "E0022" => "\
+The condition of an `if` statement must be `true` or `false`.
+
+Example:
+    var x = 5
+    if (x) { print x }
+
+Fix: write a comparison which gives a Boolean value: `if (x != 0) { print x }`. Alternatively, run the script with `--truthy`,
+which lets `0`, `''`, `null` and empty arrays and dictionaries count as `false`, and every other value as `true`.",
+        "E0023" => "\
+The condition of a `while` or `for` loop must be `true` or `false`.
+
+Example:
+    var x = 5
+    while (x) { x = x - 1 }
+
+Fix: write a comparison which gives a Boolean value: `while (x > 0) { x = x - 1 }`. As with `if`, `--truthy` lets any value be a condition.",
+        "E0024" => "\
+Only functions can be called with `()`.
+
+Example:
+    var x = 5
+    print x()
+
+Fix: make sure the name refers to a function, or remove the brackets: `print x`.",
+        "E0025" => "\
+A function was called with a different number of arguments than it has parameters.
+
+Example:
+    func add(a, b) { return a + b }
+    print add(1)
+
+Fix: give one argument for each parameter: `print add(1, 2)`.",
+        "E0026" => "\
+A string passed to `to_number()` does not contain a valid number, or the input ran out before `input_number()` read one.
+
+Example:
+    print to_number('twelve')
+
+Fix: make sure the string only contains digits, with an optional decimal point: `print to_number('12')`. To read a number typed
+by the user, use `input_number(prompt)`, which asks again until a number is typed. With a base, e.g., `to_number('ff', 16)`, the
+string may only contain the digits of that base, after an optional sign.",
 
         // Hash table errors.
-        ErrorType::CannotHashFunction { line } => {
-            println!("Line {}: cannot hash function (functions cannot be used as keys in dictionary entries).", line);
-        },
-        ErrorType::CannotHashDictionary { line } => {
-            println!("Line {}: cannot hash dictionary (dictionaries cannot be used as keys in dictionary entries).", line);
-        },
-        ErrorType::KeyError { key, line } => {
-            println!("Line {}: key `{}` does not exist in the dictionary.", line, key);
-        },
+        "E0027" => "\
+Functions cannot be used as keys in a dictionary, because they cannot be hashed.
+
+Example:
+    func f() { return 1 }
+    var d = {f: 1}
+
+Fix: use a number, string, Boolean, array or dictionary as the key instead, e.g., `var d = {'f': 1}`.",
+        "E0028" => "\
+The key does not exist in the dictionary.
+
+Example:
+    var ages = {'alice': 20}
+    print ages['bob']
+
+Fix: add the key before reading it, e.g., `ages['bob'] = 21`, or read it with `get(ages, 'bob', 0)`, which gives the default `0` if the key does not exist.",
 
         // Special errors.
-        ErrorType::ThrownBreak { line } => {
-            println!("Line {}: `break` has to be used within a loop.", line);
-        },
-        ErrorType::ThrownReturn { value: _ , line} => {
-            println!("Line {}: `return` has to be used within a function.", line);
-        },
+        "E0029" => "\
+A `break` statement was used outside of a loop.
+
+Example:
+    if (true) { break }
+
+Fix: only use `break` inside the body of a `while` or `for` loop.",
+        "E0030" => "\
+A `return` statement was used outside of a function.
+
+Example:
+    return 5
+
+Fix: only use `return` inside the body of a function declared with `func`.",
+        "E0031" => "\
+A number in the source code could not be read. Numbers are written as digits with at most one decimal point and an optional
+exponent, e.g., `2.5e-3`, or as whole numbers in hexadecimal, octal or binary after the prefix `0x`, `0o` or `0b`, whose digits
+must all be digits in that base. Underscores can only go between two digits. A number also cannot be larger than about `1.8e308`.
+
+Example:
+    print 0b1012 + 1__000 + 1e + 1e400
+
+Fix: check the digits, e.g., `0b1010`, which only has the binary digits `0` and `1`, `1_000`, with one underscore between digits,
+`1e3`, whose exponent has digits, and `1e300`, which is small enough to be kept.",
+        "E0032" => "\
+Brackets, blocks or operators are nested inside each other too many times, or an expression chains together more than about a
+thousand operators, indexes or calls, for the program to be parsed.
+
+Example:
+    print ((((((((((1))))))))))  # but nested hundreds of times
+
+Fix: split the expression into smaller parts, storing each part in a variable:
+    var inner = (1 + 2) * 3
+    print (inner - 4) / 5",
+        "E0033" => "\
+The remainder of a division by 0 was taken with `%`, which has no answer.
+
+Example:
+    var n = 0
+    print 10 % n
+
+Fix: check the right-hand side before taking the remainder:
+    if (n != 0) { print 10 % n }",
+        "E0034" => "\
+The program was stopped by pressing Ctrl-C in the REPL before it finished, e.g., because it was stuck in an infinite loop.
+The variables and functions declared before it was stopped are kept. A program embedding NEAL can also stop it, e.g., after a time limit.
+
+Example:
+    while (true) {}
+
+Fix: check that the condition of each loop eventually becomes `false`, or that the loop reaches a `break`.",
+        "E0035" => "\
+With `--strict`, each statement has to be on a line of its own. A statement may be followed on the same line only by the `}` which ends its block.
+
+Example:
+    var x = 1 print x
+
+Fix: start the next statement on a new line:
+    var x = 1
+    print x",
+        "E0042" => "\
+Only a single expression was expected, e.g., by `Interpreter::eval_expression()`, but more code followed it.
+
+Example:
+    1 + 2 3
+
+Fix: give one expression, such as `1 + 2` or `total * 2`, without any statements after it. Statements such as `var x = 1` are not
+expressions, so they cannot be evaluated on their own either.",
+        "E0053" => "\
+A `try` block must be followed by a `catch` block, which is executed if an error is raised in it, or a `finally` block, which is
+executed however it finishes, or both, in that order.
+
+Example:
+    try {
+        print scores['Alice']
+    }
+
+Fix: add a `catch` block with the name to give the error, e.g., `catch (error) { print error['message'] }`, or remove `try` and
+keep the block.",
+        "E0056" => "\
+An argument can be given with the name of its parameter, e.g., `color: 'red'`, but the arguments given by position must come
+before all of those given by name, so that it is clear which parameter each of them is for.
+
+Example:
+    plot(color: 'red', x)
+
+Fix: give the arguments by position first, e.g., `plot(x, color: 'red')`, or give the others by name as well.",
+        "E0038" => "\
+The two values given to `assert_eq()` were not equal. This is how a test written in NEAL reports that it failed.
+
+Example:
+    func test_double() {
+        assert_eq(double(2), 5)
+    }
+
+Fix: if the test is right, fix the code being tested; if the expected value is wrong, correct it, e.g., `assert_eq(double(2), 4)`.
+Values are equal in the same way as with `==`, so `1` and `'1'` are not equal.",
+        "E0039" => "\
+The number of decimal places given to `to_fixed()` must be a whole number from 0 to 100.
+
+Example:
+    print to_fixed(3.14159, -1)
+    print to_fixed(3.14159, 2.5)
+
+Fix: give the number of digits to keep after the decimal point, e.g., `to_fixed(3.14159, 2)` gives `3.14`.",
+        "E0041" => "\
+The number of times a `repeat` loop executes its body must be a whole number of at least 0.
+
+Example:
+    repeat 2.5 {
+        print 'hello'
+    }
+
+Fix: round the number first, or give a whole number, e.g., `repeat 3 { print 'hello' }`. To loop until something happens
+instead, use a `while` loop.",
+        "E0043" => "\
+A file given to `include()` includes itself, directly or through other files, which would never finish.
+
+Example:
+    # In a.nea:
+    include('b.nea')
+    # In b.nea:
+    include('a.nea')
+
+Fix: move the functions both files need into a third file, and include that from each of them instead.",
+        "E0044" => "\
+The file given to `include()` could not be read, e.g., because it does not exist. The reason is given in the message. The same
+error is raised if the file which `store_load()` and `store_save()` keep values in exists but cannot be read.
+
+Example:
+    include('utils.nea')
+
+Fix: check the path. A relative path is found from the directory of the file which includes it, not from the directory the
+program is run in, e.g., `include('lib/utils.nea')` in `game/main.nea` reads `game/lib/utils.nea`.",
+        "E0045" => "\
+The row and column given to `move_cursor()` must be whole numbers of at least 1. The top-left corner of the terminal is row 1,
+column 1.
+
+Example:
+    move_cursor(0, 0)
+
+Fix: count rows and columns from 1, e.g., `move_cursor(row + 1, column + 1)` for a position counted from 0.",
+        "E0046" => "\
+The name given to `set_color()` or `colored()` is not one of the colours or styles of the terminal. The names are `black`, `red`,
+`green`, `yellow`, `blue`, `magenta`, `cyan`, `white` and `grey`, the styles `bold` and `underline`, and `reset`, which goes back
+to the terminal's own colour. `pen_color()` takes the same colours, and `reset`, which goes back to black, but not the styles.
+
+Example:
+    set_color('orange')
+
+Fix: use one of the names, written in lower case, e.g., `set_color('yellow')`.",
+        "E0047" => "\
+The drawing could not be written to the file given to `save_drawing()`, e.g., because its directory does not exist, or a value
+could not be written to the file which `store_save()` keeps values in. The reason is given in the message.
+
+Example:
+    save_drawing('pictures/house.svg')
+
+Fix: check the path. A relative path is found from the directory the program is run in, so `save_drawing('house.svg')` always
+writes a file where it can be found.",
+        "E0048" => "\
+`transpose()` swaps the rows and columns of a grid, which is an array of rows, so every row must have the same number of elements.
+
+Example:
+    transpose([[1, 2], [3]])
+
+Fix: make the rows the same length, e.g., by filling the gaps with `null`. `matrix(rows, columns, fill)` makes a grid whose rows
+are all the same length.",
+        "E0049" => "\
+`gcd()`, `lcm()`, `is_prime()`, `factors()` and `to_base()` only work with whole numbers, and only with those which numbers can
+hold exactly, between -9007199254740992 and 9007199254740992. Beyond them, not every whole number can be stored, so the answer
+could be wrong. For the same reason, the least common multiple returned by `lcm()` has to be within them too.
+
+Example:
+    print gcd(7.5, 3)
+
+Fix: round the number first, e.g., `gcd(to_number(to_fixed(x, 0)), 3)`, or check that it is a whole number before calling the function.",
+        "E0050" => "\
+The base given to `to_number()` or `to_base()` must be a whole number from 2 to 36. Digits after `9` are written with the letters
+`a` to `z`, so base 36 is the largest which has a digit for every value.
+
+Example:
+    print to_base(255, 64)
+
+Fix: use a base from 2 to 36, e.g., `to_base(255, 16)` for hexadecimal, or `to_base(255, 2)` for binary.",
+        "E0051" => "\
+`store_save()` writes the value to a file, to be loaded by `store_load()` when the program is run again, but a function cannot be
+written to a file, so it cannot be saved, and neither can an array or dictionary which contains one.
+
+Example:
+    store_save('scorer', func(x) { return x * 10 })
+
+Fix: save the data the function works on instead, e.g., `store_save('multiplier', 10)`, and keep the function in the program.",
+        "E0052" => "\
+The file which `store_load()` and `store_save()` keep values in is not a store, e.g., because it has been edited by hand. A store
+holds one dictionary of the names and values saved, written as NEAL literals, and no other code, as loading it never runs any.
+
+Example:
+    # quiz.store
+    {'best': 12 + 1}
+
+Fix: correct the file, e.g., `{'best': 13}`, or delete it to start again with nothing saved.",
+        "E0054" => "\
+The step of a slice is how far it moves between the elements it takes, so a step of 0 would take the same element forever. A
+negative step takes the elements backwards, from the end.
+
+Example:
+    var a = [1, 2, 3, 4]
+    print a[::0]
+
+Fix: use a step of 1 or more, e.g., `a[::2]` for every other element, or -1 to reverse the array, as `a[::-1]` does.",
+        "E0055" => "\
+Assigning to several variables at once, or declaring them, unpacks an array into them, e.g., the values a function returns with
+`return a, b`, so the array must have one element for each variable.
+
+Example:
+    func divide(a, b) {
+        return (a - a % b) / b, a % b
+    }
+    var quotient, remainder, rest = divide(7, 2)
+
+Fix: give one variable for each value, e.g., `var quotient, remainder = divide(7, 2)`, or return the missing value as well.",
+        "E0057" => "\
+An argument was given with a name which is not the name of any of the function's parameters. The parameters of built-in
+functions have no names, so their arguments can only be given by position.
+
+Example:
+    func plot(x, color) { print x }
+    plot(1, colour: 'red')
+
+Fix: use the name the parameter is declared with, e.g., `plot(1, color: 'red')`.",
+        "E0058" => "\
+An argument was given for the same parameter twice, either by name twice, or by name as well as by position.
+
+Example:
+    func plot(x, color) { print x }
+    plot(1, x: 2, color: 'red')
+
+Fix: give the argument only once, e.g., `plot(1, color: 'red')` or `plot(x: 1, color: 'red')`.",
+        "E0059" => "\
+Several values separated by commas were assigned to a single target. Values are only unpacked into as many targets as there are
+values, with the targets separated by commas too.
+
+Example:
+    var x = 0
+    x = 1, 2
+
+Fix: give one target for each value, e.g., `x, y = 1, 2`, or put the values in brackets to assign them as an array, e.g., `x = [1, 2]`.",
+        "E0060" => "\
+The same variable was given more than once as a target when unpacking values, so it is not clear which value it should be given.
+
+Example:
+    var q, q = divide(7, 2)
+
+Fix: give a different name to each target, e.g., `var q, r = divide(7, 2)`.",
+        "E0061" => "\
+The output of the program could not be written, e.g., by `print`, usually because the program reading it, such as `head` at the
+end of a pipe, has stopped before reading all of it.
+
+Example:
+    nea count.nea | head -n 1
+
+Fix: there is usually nothing to fix when only part of the output is wanted. Otherwise, check that there is space on the disk
+which the output is written to.",
+        "E0062" => "\
+There is not enough memory to make an array as large as the size given to `resize()`.
+
+Example:
+    var grid = []
+    resize(grid, 1e12, 0)
+
+Fix: check the size, e.g., that it is a number of elements rather than a number of bytes, or store only the elements which are
+used, e.g., in a dictionary whose keys are their indices.",
+        "E0063" => "\
+Functions were called inside each other too many times, usually by a function which calls itself without ever stopping. Calls to
+`eval()` count as calls too. At most 500 calls can be running at once.
+
+Example:
+    func countdown(n) {
+        print n
+        countdown(n - 1)
+    }
+    countdown(3)
+
+Fix: make sure that the function stops calling itself, e.g., `if (n > 0) { countdown(n - 1) }`, or use a loop instead, e.g.,
+`while (n > 0) { ... }`, for a large number of steps.",
+        "E0040" => "\
+An array or dictionary which has been frozen with `freeze()` cannot be changed, and neither can the arrays and dictionaries inside it.
+This includes assigning to its elements and calling `append()`, `remove()`, `resize()`, `sort_in_place()` or `set_default()` on it.
+
+Example:
+    var primes = freeze([2, 3, 5])
+    append(primes, 7)
+
+Fix: if the value has to change, do not freeze it. Copies of a frozen value are frozen too, but a variable holding one can still
+be given a new value, e.g., `primes = [2, 3, 5, 7]`, as that does not change the frozen value itself.",
+        "E0036" => "\
+With `--strict`, a variable or function cannot be declared with the same name as one in an enclosing block or in the global scope,
+as it would hide the other one until the end of the block.
+
+Example:
+    var total = 0
+    for (var i = 0; i < 3; i = i + 1) {
+        var total = i
+    }
+
+Fix: give the new variable a different name, or assign to the existing one instead: `total = i`.",
+        "E0037" => "\
+With `--strict`, a function can only assign to its parameters and the variables declared inside it, not to the variables of the code which called it,
+so that every change a function makes is visible from its parameters and return value.
+
+Example:
+    var count = 0
+    func increment() {
+        count = count + 1
+    }
+
+Fix: pass the variable in and return the new value:
+    func increment(count) {
+        return count + 1
+    }
+    count = increment(count)",
+
+        _ => return None,
+    };
+    Some(explanation)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{explain, write_errors, ErrorType};
+
+    #[test]
+    fn codes() {
+        assert_eq!("E0002", ErrorType::UnterminatedString { line: 1, column: 1 }.code());
+        assert_eq!("E0012", ErrorType::NameError { name: String::from("x"), line: 1, column: 1 }.code());
+        assert_eq!("E0030", ErrorType::ThrownReturn { value: crate::value::Value::Null, line: 1, column: 1 }.code());
+    }
+
+    #[test]
+    fn every_code_is_explained() {
+        for number in 1..=63 {
+            assert!(explain(&format!("E{:04}", number)).is_some());
+        }
+        assert!(explain("E0000").is_none());
+        assert!(explain("E0064").is_none());
+        assert!(explain("not a code").is_none());
+    }
+
+    #[test]
+    fn written_errors() {
+        let errors = [
+            ErrorType::DivideByZero { line: 1, column: 5 },
+            ErrorType::NameError { name: String::from("x"), line: 2, column: 1 },
+        ];
+        let mut output = Vec::new();
+        write_errors(&mut output, &errors, Some(1)).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), "An error has occurred.\n[E0021] Line 1, column 5: divisor is 0.\n... and 1 more error(s).\n");
+    }
+
+    #[test]
+    fn explain_ignores_case() {
+        assert_eq!(explain("E0021"), explain("e0021"));
     }
 }
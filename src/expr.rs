@@ -1,13 +1,28 @@
+use std::fmt;
+
 use crate::token;
+use crate::{ast_printer, source_printer};
 use crate::hash_table::KeyValue;
 
 /// An expression.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Expr {
     pub line: usize,  // The line of the source code from which the expression was derived.
+    pub column: usize,  // The column of the source code from which the expression was derived.
     pub expr_type: ExprType,  // The type of expression.
 }
 
+/// Shows the expression as source code, e.g., `x + 1`, or with `{:#}` as the tree `--ast` shows it, which makes the nodes clear.
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_str(&ast_printer::print_expr(self))
+        } else {
+            f.write_str(&source_printer::print_expr(self))
+        }
+    }
+}
+
 /// Possible types of expressions.
 #[derive(Clone, Debug, PartialEq)]
 pub enum ExprType {
@@ -19,7 +34,7 @@ pub enum ExprType {
         // 1. Expressions like `a = b = 5` work;
         // 2. Calls have to be checked *before* assignments.
         // Otherwise, the statement `f()` will not work because we expect an `=` after the identifier.
-        // Note `target` is an expression to allow both `Variable`s and `Element`s.
+        // Note `target` is an expression to allow `Variable`s and `Element`s, and `Tuple`s of them.
         target: Box<Expr>,
         value: Box<Expr>,
     },
@@ -31,6 +46,7 @@ pub enum ExprType {
     Call {
         callee: Box<Expr>,
         arguments: Vec<Expr>,
+        named_arguments: Vec<(String, Expr)>,  // The arguments given with the name of their parameter, e.g., `color: 'red'`, which come after the others.
     },
     Dictionary {
         elements: Vec<KeyValue<Expr>>,
@@ -45,6 +61,18 @@ pub enum ExprType {
     Literal {
         value: token::Literal,
     },
+    Slice {
+        // Each of the bounds and the step can be left out, e.g., `a[2:]`, in which case it takes its default.
+        array: Box<Expr>,
+        start: Option<Box<Expr>>,
+        stop: Option<Box<Expr>>,
+        step: Option<Box<Expr>>,
+    },
+    Tuple {
+        // Values separated by commas, e.g., `return a, b`, which evaluate to an array of them. A tuple of variables and elements
+        // can also be assigned to, which unpacks the array being assigned into them.
+        elements: Vec<Expr>,
+    },
     Unary {
         operator: token::Token,
         right: Box<Expr>,
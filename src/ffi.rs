@@ -0,0 +1,236 @@
+//! A C API for embedding the interpreter in programs written in other languages, e.g., as a scripting engine.
+//!
+//! The functions are exported with C names when the library is built with the `ffi` feature, and are declared in `include/neal.h`:
+//!
+//! ```text
+//! cargo build --release --lib --features ffi
+//! cc host.c -Iinclude -Ltarget/release -lnea
+//! ```
+//!
+//! An interpreter is created with `neal_new()`, runs source code with `neal_run()` as many times as needed, keeping its variables
+//! between runs, and is destroyed with `neal_free()`. `neal_get_global()` reads a variable into a `NealValue`, whose layout will not
+//! change, and which is released with `neal_value_free()`.
+
+use std::ffi::{c_char, c_int, CStr, CString};
+use std::ptr;
+
+use crate::{Interpreter, Parser, Tokenizer, Value};
+
+/// Returned by `neal_run()` if the source code ran without errors.
+pub const NEAL_OK: c_int = 0;
+/// Returned by `neal_run()` if a pointer was null or the source code was not valid UTF-8.
+pub const NEAL_INVALID_ARGUMENT: c_int = 64;
+/// Returned by `neal_run()` if the source code could not be tokenized.
+pub const NEAL_TOKENIZE_ERROR: c_int = 65;
+/// Returned by `neal_run()` if the token sequence could not be parsed.
+pub const NEAL_PARSE_ERROR: c_int = 66;
+/// Returned by `neal_run()` if an error occurred while executing the program.
+pub const NEAL_RUNTIME_ERROR: c_int = 70;
+
+/// The type of a `NealValue`. The numbers are part of the C API, so they must not change.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NealValueType {
+    Null = 0,
+    Number = 1,
+    String = 2,
+    Bool = 3,
+    Array = 4,
+    Dictionary = 5,
+    Function = 6,
+}
+
+/// A value passed to C. Which fields are meaningful depends on `value_type`:
+/// - `Number`: `number`.
+/// - `Bool`: `boolean`.
+/// - `String`: `string`, a NUL-terminated UTF-8 string. A string containing a NUL character is cut off at it.
+/// - `Array`, `Dictionary` and `Function`: `string`, the value as `print` would show it, e.g., `[1, 2]`.
+/// - `Null`: none.
+///
+/// `string` is null when it is not meaningful. Otherwise, it is owned by the value, and freed by `neal_value_free()`.
+#[repr(C)]
+#[derive(Debug)]
+pub struct NealValue {
+    pub value_type: NealValueType,
+    pub number: f64,
+    pub boolean: bool,
+    pub string: *mut c_char,
+}
+
+impl NealValue {
+    fn from_value(value: &Value) -> NealValue {
+        let (value_type, number, boolean, string) = match value {
+            Value::Null => (NealValueType::Null, 0.0, false, None),
+            Value::Number(x) => (NealValueType::Number, *x, false, None),
+            Value::Bool(x) => (NealValueType::Bool, 0.0, *x, None),
+            Value::String_(x) => (NealValueType::String, 0.0, false, Some(x.clone())),
+            Value::Array(..) => (NealValueType::Array, 0.0, false, Some(value.to_string())),
+            Value::Dictionary(..) => (NealValueType::Dictionary, 0.0, false, Some(value.to_string())),
+            Value::Function { .. } | Value::BuiltinFunction(..) => (NealValueType::Function, 0.0, false, Some(value.to_string())),
+        };
+        let string = match string {
+            Some(mut text) => {
+                if let Some(nul) = text.find('\0') {
+                    text.truncate(nul);
+                }
+                CString::new(text).expect("NUL characters were removed").into_raw()
+            },
+            None => ptr::null_mut(),
+        };
+        NealValue { value_type, number, boolean, string }
+    }
+}
+
+/// Creates an interpreter, which must be destroyed with `neal_free()`.
+#[cfg_attr(feature = "ffi", no_mangle)]
+pub extern "C" fn neal_new() -> *mut Interpreter {
+    Box::into_raw(Box::new(Interpreter::new()))
+}
+
+/// Runs the NUL-terminated source code with the interpreter, returning `NEAL_OK` or the kind of error which stopped it.
+/// Output is written to the standard output stream, and errors are reported to the interpreter's error output, which is the standard
+/// error stream.
+///
+/// # Safety
+/// `interpreter` must have been returned by `neal_new()` and not yet freed, and `source` must point to a NUL-terminated string.
+#[cfg_attr(feature = "ffi", no_mangle)]
+pub unsafe extern "C" fn neal_run(interpreter: *mut Interpreter, source: *const c_char) -> c_int {
+    if interpreter.is_null() || source.is_null() {
+        return NEAL_INVALID_ARGUMENT;
+    }
+    let interpreter = &mut *interpreter;
+    let Ok(source) = CStr::from_ptr(source).to_str() else {
+        return NEAL_INVALID_ARGUMENT;
+    };
+
+    // The stages are run separately, rather than with `run_source()`, so that the kind of error can be returned.
+    let tokens = match Tokenizer::new(source).tokenize_unreported() {
+        Ok(tokens) => tokens,
+        Err(errors) => {
+            interpreter.report_errors(&errors);
+            return NEAL_TOKENIZE_ERROR;
+        },
+    };
+    let mut parser = Parser::new(tokens);
+    parser.set_strict(interpreter.is_strict());
+    let ast = match parser.parse_unreported() {
+        Ok(ast) => ast,
+        Err(errors) => {
+            interpreter.report_errors(&errors);
+            return NEAL_PARSE_ERROR;
+        },
+    };
+    match interpreter.interpret(ast) {
+        Ok(_) => NEAL_OK,
+        Err(_) => NEAL_RUNTIME_ERROR,
+    }
+}
+
+/// Reads the variable with the NUL-terminated name into `value`, returning whether it exists.
+/// If it does, the value must be released with `neal_value_free()`; if not, `value` is left unchanged.
+///
+/// # Safety
+/// `interpreter` must have been returned by `neal_new()` and not yet freed, `name` must point to a NUL-terminated string, and `value`
+/// must point to writable memory for a `NealValue`.
+#[cfg_attr(feature = "ffi", no_mangle)]
+pub unsafe extern "C" fn neal_get_global(interpreter: *const Interpreter, name: *const c_char, value: *mut NealValue) -> bool {
+    if interpreter.is_null() || name.is_null() || value.is_null() {
+        return false;
+    }
+    let Ok(name) = CStr::from_ptr(name).to_str() else {
+        return false;
+    };
+    match (*interpreter).get_global(name) {
+        Some(found) => {
+            value.write(NealValue::from_value(&found));
+            true
+        },
+        None => false,
+    }
+}
+
+/// Releases the string owned by the value, if any. The value's other fields are left as they are.
+///
+/// # Safety
+/// `value` must be null or point to a value written by `neal_get_global()` which has not yet been released.
+#[cfg_attr(feature = "ffi", no_mangle)]
+pub unsafe extern "C" fn neal_value_free(value: *mut NealValue) {
+    if let Some(value) = value.as_mut() {
+        if !value.string.is_null() {
+            drop(CString::from_raw(value.string));
+            value.string = ptr::null_mut();
+        }
+    }
+}
+
+/// Destroys the interpreter. Freeing a null pointer does nothing.
+///
+/// # Safety
+/// `interpreter` must be null or have been returned by `neal_new()` and not yet freed.
+#[cfg_attr(feature = "ffi", no_mangle)]
+pub unsafe extern "C" fn neal_free(interpreter: *mut Interpreter) {
+    if !interpreter.is_null() {
+        drop(Box::from_raw(interpreter));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::{CStr, CString};
+    use std::mem::MaybeUninit;
+    use std::ptr;
+
+    use super::*;
+
+    /// Reads the variable, converting its string to a Rust string.
+    unsafe fn get(interpreter: *mut Interpreter, name: &str) -> Option<(NealValueType, f64, bool, Option<String>)> {
+        let name = CString::new(name).unwrap();
+        let mut value = MaybeUninit::<NealValue>::uninit();
+        if !neal_get_global(interpreter, name.as_ptr(), value.as_mut_ptr()) {
+            return None;
+        }
+        let mut value = value.assume_init();
+        let string = (!value.string.is_null()).then(|| CStr::from_ptr(value.string).to_str().unwrap().to_owned());
+        neal_value_free(&mut value);
+        assert!(value.string.is_null());
+        Some((value.value_type, value.number, value.boolean, string))
+    }
+
+    #[test]
+    fn run_and_get_globals() {
+        unsafe {
+            let interpreter = neal_new();
+            let source = CString::new("var n = 1.5\nvar s = 'text'\nvar b = true\nvar a = [1, 'x']\nvar z = null").unwrap();
+            assert_eq!(neal_run(interpreter, source.as_ptr()), NEAL_OK);
+
+            assert_eq!(get(interpreter, "n"), Some((NealValueType::Number, 1.5, false, None)));
+            assert_eq!(get(interpreter, "s"), Some((NealValueType::String, 0.0, false, Some(String::from("text")))));
+            assert_eq!(get(interpreter, "b"), Some((NealValueType::Bool, 0.0, true, None)));
+            assert_eq!(get(interpreter, "a"), Some((NealValueType::Array, 0.0, false, Some(String::from("[1, x]")))));
+            assert_eq!(get(interpreter, "z"), Some((NealValueType::Null, 0.0, false, None)));
+            assert_eq!(get(interpreter, "missing"), None);
+
+            // Variables are kept between runs.
+            let source = CString::new("n = n * 2").unwrap();
+            assert_eq!(neal_run(interpreter, source.as_ptr()), NEAL_OK);
+            assert_eq!(get(interpreter, "n"), Some((NealValueType::Number, 3.0, false, None)));
+
+            neal_free(interpreter);
+        }
+    }
+
+    #[test]
+    fn errors() {
+        unsafe {
+            let interpreter = neal_new();
+            for (source, expected) in [("print 'a", NEAL_TOKENIZE_ERROR), ("var = 1", NEAL_PARSE_ERROR), ("print 1 / 0", NEAL_RUNTIME_ERROR)] {
+                let source = CString::new(source).unwrap();
+                assert_eq!(neal_run(interpreter, source.as_ptr()), expected);
+            }
+            assert_eq!(neal_run(interpreter, ptr::null()), NEAL_INVALID_ARGUMENT);
+            assert_eq!(neal_run(interpreter, c"\xff".as_ptr()), NEAL_INVALID_ARGUMENT);
+            neal_free(interpreter);
+            neal_free(ptr::null_mut());
+        }
+    }
+}
@@ -1,317 +1,749 @@
-use std::fmt::Debug;
+//! A generic hash table using open addressing with linear probing.
+//! Nothing in this module depends on the rest of the interpreter, so it can be reused for any key type implementing `NealHash`.
+//! The dictionaries of the language are built on top of it in `dictionary.rs`.
 
-use crate::value::Value;
-use crate::error::ErrorType;
+use std::collections::hash_map::RandomState;
+use std::convert::Infallible;
+use std::fmt::Debug;
+use std::hash::BuildHasher;
 
 // Hash table constants.
-const INITIAL_NUM_BUCKETS: usize = 16;  // Initial number of buckets in the table.
-const MAX_NUM_BUCKETS: usize = 65536;  // The maximum number of buckets in the table.
-const MAX_CALC: usize = 65381;  // A prime used to prevent overflow in intermediate calculations.
+const INITIAL_NUM_BUCKETS: usize = 16;  // Initial number of buckets in the table. This has to be a power of two.
 const MAX_LOAD_FACTOR_NUMERATOR: usize = 3;  // Numerator of the maximum load factor before a rehash is required (3/4).
 const MAX_LOAD_FACTOR_DENOMINATOR: usize = 4;  // Denominator of the maximum load factor before a rehash is required (3/4).
-const HASH_FIRST_N: usize = 300;  // Number of elements to hash to keep constant time operation.
+const MIN_LOAD_FACTOR_NUMERATOR: usize = 1;  // Numerator of the minimum load factor before the table is shrunk (1/4).
+const MIN_LOAD_FACTOR_DENOMINATOR: usize = 4;  // Denominator of the minimum load factor before the table is shrunk (1/4).
+const FIBONACCI_MULTIPLIER: u64 = 11400714819323198485;  // 2^64 divided by the golden ratio, used to spread hashes across the buckets.
+
+/// The hash function used by a `HashTable`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Hasher {
+    // `djb2` over the words of the key, followed by a final mix. This is fast and deterministic,
+    // but anyone can work out keys which collide and make the table degrade to linear searches.
+    Fixed,
+    // A SipHash-like keyed hash (SipHash-1-3 over 64-bit words). Without knowing the seed, colliding keys cannot be worked out in advance.
+    Seeded { k0: u64, k1: u64 },
+}
+
+impl Hasher {
+    /// Returns a `Seeded` hasher with a random seed.
+    pub fn random() -> Self {
+        // The standard library seeds `RandomState` from the operating system's random number generator,
+        // so it is used as a source of random numbers rather than hashing with it directly.
+        let state = RandomState::new();
+        Self::Seeded {
+            k0: state.hash_one(0_u64),
+            k1: state.hash_one(1_u64),
+        }
+    }
+}
+
+/// A type which can be used as a key in a `HashTable`.
+/// Keys which are equal must write the same sequence of words into the state.
+pub trait NealHash: PartialEq {
+    /// The error returned if a key cannot be hashed.
+    type Error;
+
+    /// Writes the key into `state`.
+    fn hash_into(&self, state: &mut HashState) -> Result<(), Self::Error>;
+}
+
+/// The state of a hasher while a key is being hashed.
+/// Keys are fed in as 64-bit words rather than as a stream of bytes, as most keys are easily turned into words.
+#[derive(Clone, Copy)]
+pub struct HashState {
+    kind: StateKind,
+    length: u64,  // The number of words written so far.
+}
+
+#[derive(Clone, Copy)]
+enum StateKind {
+    // The `djb2` algorithm (https://theartincode.stanis.me/008-djb2/).
+    Fixed(u64),
+    // SipHash-1-3 (https://www.aumasson.jp/siphash/siphash.pdf).
+    Seeded { v0: u64, v1: u64, v2: u64, v3: u64 },
+}
+
+impl HashState {
+    /// Initialises the state for `hasher`.
+    fn new(hasher: Hasher) -> Self {
+        let kind = match hasher {
+            Hasher::Fixed => StateKind::Fixed(5381),
+            Hasher::Seeded { k0, k1 } => StateKind::Seeded {
+                v0: k0 ^ 0x736f6d6570736575,
+                v1: k1 ^ 0x646f72616e646f6d,
+                v2: k0 ^ 0x6c7967656e657261,
+                v3: k1 ^ 0x7465646279746573,
+            },
+        };
+        Self { kind, length: 0 }
+    }
+
+    /// Mixes a word into the state.
+    pub fn write(&mut self, word: u64) {
+        match &mut self.kind {
+            StateKind::Fixed(hash_value) => {
+                *hash_value = (*hash_value << 5).wrapping_add(*hash_value).wrapping_add(word);  // Equivalent to `* 33 + word`, but faster
+            },
+            StateKind::Seeded { v0, v1, v2, v3 } => {
+                *v3 ^= word;
+                sip_round(v0, v1, v2, v3);
+                *v0 ^= word;
+            },
+        }
+        self.length += 1;
+    }
+
+    /// Mixes a sequence of bytes into the state, along with its length so that, e.g., `["ab"]` and `["a", "b"]` are written differently.
+    pub fn write_bytes(&mut self, bytes: &[u8]) {
+        self.write(bytes.len() as u64);
+        // Write the bytes eight at a time, padding the last chunk with zeroes.
+        for chunk in bytes.chunks(8) {
+            let mut word = [0; 8];
+            word[..chunk.len()].copy_from_slice(chunk);
+            self.write(u64::from_le_bytes(word));
+        }
+    }
+
+    /// Returns a copy of the state with nothing written to it since this point.
+    /// This can be used to hash parts of a key on their own, e.g., to combine them in a way which does not depend on their order.
+    pub fn fork(&self) -> Self {
+        Self { kind: self.kind, length: 0 }
+    }
+
+    /// Finalises the state and returns the hash.
+    pub fn finish(self) -> u64 {
+        match self.kind {
+            StateKind::Fixed(mut hash_value) => {
+                // `djb2` is linear in the words written, so a final mix (from `splitmix64`) is used to make sure
+                // combining hashes, e.g., by adding them together, does not cancel out differences between keys.
+                hash_value ^= hash_value >> 30;
+                hash_value = hash_value.wrapping_mul(0xbf58476d1ce4e5b9);
+                hash_value ^= hash_value >> 27;
+                hash_value = hash_value.wrapping_mul(0x94d049bb133111eb);
+                hash_value ^ (hash_value >> 31)
+            },
+            StateKind::Seeded { mut v0, mut v1, mut v2, mut v3 } => {
+                let last = self.length << 56;
+                v3 ^= last;
+                sip_round(&mut v0, &mut v1, &mut v2, &mut v3);
+                v0 ^= last;
+                v2 ^= 0xff;
+                for _ in 0..3 {
+                    sip_round(&mut v0, &mut v1, &mut v2, &mut v3);
+                }
+                v0 ^ v1 ^ v2 ^ v3
+            },
+        }
+    }
+}
+
+/// One 'SipRound', which mixes the four state words together.
+fn sip_round(v0: &mut u64, v1: &mut u64, v2: &mut u64, v3: &mut u64) {
+    *v0 = v0.wrapping_add(*v1);
+    *v1 = v1.rotate_left(13);
+    *v1 ^= *v0;
+    *v0 = v0.rotate_left(32);
+    *v2 = v2.wrapping_add(*v3);
+    *v3 = v3.rotate_left(16);
+    *v3 ^= *v2;
+    *v0 = v0.wrapping_add(*v3);
+    *v3 = v3.rotate_left(21);
+    *v3 ^= *v0;
+    *v2 = v2.wrapping_add(*v1);
+    *v1 = v1.rotate_left(17);
+    *v1 ^= *v2;
+    *v2 = v2.rotate_left(32);
+}
+
+// Implementations for common key types, so that the table can be used without the rest of the interpreter.
+impl NealHash for u64 {
+    type Error = Infallible;
+
+    fn hash_into(&self, state: &mut HashState) -> Result<(), Self::Error> {
+        state.write(*self);
+        Ok(())
+    }
+}
+
+impl NealHash for usize {
+    type Error = Infallible;
+
+    fn hash_into(&self, state: &mut HashState) -> Result<(), Self::Error> {
+        state.write(*self as u64);
+        Ok(())
+    }
+}
+
+impl NealHash for String {
+    type Error = Infallible;
+
+    fn hash_into(&self, state: &mut HashState) -> Result<(), Self::Error> {
+        state.write_bytes(self.as_bytes());
+        Ok(())
+    }
+}
+
+/// Computes and returns the hash of a key using `hasher`.
+pub fn hash<K: NealHash>(key: &K, hasher: Hasher) -> Result<u64, K::Error> {
+    let mut state = HashState::new(hasher);
+    key.hash_into(&mut state)?;
+    Ok(state.finish())
+}
 
 // A key-value pair in the hash table.
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct KeyValue<T> {
-    pub key: T,
-    pub value: T,
+pub struct KeyValue<K, V = K> {
+    pub key: K,
+    pub value: V,
+}
+
+/// An entry in the hash table, i.e., a key-value pair along with the hash of the key.
+/// The hash is stored so that it does not have to be re-computed when rehashing, and so that most non-matching keys can be skipped without comparing them.
+#[derive(Clone)]
+struct Entry<K, V> {
+    hash: u64,
+    key_value: KeyValue<K, V>,
 }
 
-/// A hash table.
+/// A bucket in the hash table.
+#[derive(Clone, Copy)]
+enum Bucket {
+    Empty,  // The bucket has never held an entry, so a search can stop here.
+    Tombstone,  // The bucket held an entry which has been removed, so a search has to continue past it.
+    Occupied(usize),  // The bucket holds the index of an entry in `entries`.
+}
+
+/// A hash table using open addressing with linear probing.
+/// If a key's bucket is taken, the key is stored in the next free bucket along.
+/// The key-value pairs themselves are stored contiguously in `entries`, with the buckets only holding indices into it,
+/// so that empty buckets are small and iterating through the entries does not have to skip over empty buckets.
 #[derive(Clone)]
-pub struct HashTable {
-    array: Vec<Vec<KeyValue<Value>>>,  // The internal array of the hash table.
-    entries: usize, // The number of entries in the hash table.
-    current_num_buckets: usize,  // The current number of buckets in the table.
+pub struct HashTable<K, V> {
+    array: Vec<Bucket>,  // The internal array of the hash table.
+    entries: Vec<Entry<K, V>>, // The entries in the hash table.
+    tombstones: usize,  // The number of `Tombstone` buckets in the table.
+    current_num_buckets: usize,  // The current number of buckets in the table. This is always a power of two.
+    hasher: Hasher,  // The hash function used for the keys.
 }
 
-impl HashTable {
-    /// Initialises a new instance of `HashTable`.
-    pub fn new() -> Self {
+impl<K: NealHash, V> HashTable<K, V> {
+    /// Initialises a new instance of `HashTable` using the given hasher, with enough buckets to hold `capacity` entries without rehashing.
+    pub fn with_capacity(capacity: usize, hasher: Hasher) -> Self {
+        let num_buckets = num_buckets_for(capacity);
         Self {
-            array: vec![Vec::new(); INITIAL_NUM_BUCKETS],  // Initialise 2D array with `INITIAL_NUM_BUCKETS` number of empty arrays.
-            entries: 0,
-            current_num_buckets: INITIAL_NUM_BUCKETS,
+            array: vec![Bucket::Empty; num_buckets],  // Initialise the array with `num_buckets` number of empty buckets.
+            entries: Vec::with_capacity(capacity),
+            tombstones: 0,
+            current_num_buckets: num_buckets,
+            hasher,
         }
     }
 
-    /// Returns the value associated with `key`.
-    pub fn get(&self, key: &Value, line: usize) -> Result<&Value, ErrorType> {
-        // Calculate the bucket number of the key.
-        let bucket_number = self.get_bucket_number(key, line)?;
-
-        // Iterate through the bucket.
-        if let Some(key_value) = self.array[bucket_number].iter().find(|key_value| key_value.key == key.clone()) {
-            // If a `key_value` is found such that `key_value.key == key`, then return `key_value.value`.
-            Ok(&key_value.value)
-        } else {
-            // Otherwise, the key does not exist in the table. Return a KeyError, providing the `key` for detail.
-            Err(ErrorType::KeyError { key: key.clone(), line })
+    /// Makes sure at least `additional` more entries can be inserted without rehashing.
+    pub fn reserve(&mut self, additional: usize) {
+        let num_buckets = num_buckets_for(self.entries.len() + additional);
+        if num_buckets > self.current_num_buckets {
+            self.rehash(num_buckets);
         }
+        self.entries.reserve(additional);
+    }
+
+    /// Returns the value associated with `key`, or `None` if the key does not exist in the table.
+    pub fn get(&self, key: &K) -> Result<Option<&V>, K::Error> {
+        // Find the entry containing the key.
+        let index = self.find(key, hash(key, self.hasher)?);
+        Ok(index.map(|index| &self.entries[index].key_value.value))
     }
 
     /// Returns a mutable reference to the value associated with `key`. As above.
-    pub fn get_mut(&mut self, key: &Value, line: usize) -> Result<&mut Value, ErrorType> {
-        let bucket_number = self.get_bucket_number(key, line)?;
-        if let Some(key_value) = self.array[bucket_number].iter_mut().find(|key_value| key_value.key == key.clone()) {
-            Ok(&mut key_value.value)
-        } else {
-            Err(ErrorType::KeyError { key: key.clone(), line })
-        }
+    pub fn get_mut(&mut self, key: &K) -> Result<Option<&mut V>, K::Error> {
+        let index = self.find(key, hash(key, self.hasher)?);
+        Ok(index.map(|index| &mut self.entries[index].key_value.value))
     }
 
     /// Inserts a key-value pair to the table if the key does not already exist; otherwise, updates the existing pair with the new value.
-    pub fn insert(&mut self, key: &Value, value: &Value, line: usize) -> Result<(), ErrorType> {
-        // Calculate the bucket number of the key.
-        let bucket_number = self.get_bucket_number(key, line)?;
-
-        // Iterate through the bucket.
-        if let Some(key_value) = self.array[bucket_number].iter_mut().find(|key_value| key_value.key == key.clone()) {
-            // If a `key_value` is found such that `key_value.key == key`, then update `key_value.value` to `value`.
-            key_value.value = value.clone();
-        } else {
+    /// Returns the old value if there was one.
+    pub fn insert(&mut self, key: K, value: V) -> Result<Option<V>, K::Error> {
+        // Calculate the hash of the key.
+        let hash_value = hash(&key, self.hasher)?;
+
+        match self.probe(&key, hash_value) {
+            // If an entry is found such that `entry.key == key`, then update its value to `value`.
+            Ok(index) => Ok(Some(std::mem::replace(&mut self.entries[index].key_value.value, value))),
             // Otherwise, we are adding a new entry.
-            self.entries += 1;  // Increment the number of entries in the table.
-            self.array[bucket_number].push(KeyValue {  // Push the new key-value pair into the bucket.
-                key: key.clone(),
-                value: value.clone()
-            });
+            Err(bucket_number) => {
+                self.push_entry(bucket_number, hash_value, KeyValue { key, value });
+                Ok(None)
+            },
         }
-        
-        // Check if the table needs rehashing.
-        self.check_load(line)?;
+    }
 
-        Ok(())
+    /// Returns a mutable reference to the value associated with `key`. If the key does not already exist,
+    /// it is first inserted with the value returned by `default`.
+    /// Unlike a `get()` followed by an `insert()`, the key is only hashed and searched for once.
+    pub fn get_or_insert_with(&mut self, key: K, default: impl FnOnce() -> V) -> Result<&mut V, K::Error> {
+        let hash_value = hash(&key, self.hasher)?;
+
+        let index = match self.probe(&key, hash_value) {
+            Ok(index) => index,
+            Err(bucket_number) => self.push_entry(bucket_number, hash_value, KeyValue { key, value: default() }),
+        };
+        Ok(&mut self.entries[index].key_value.value)
+    }
+
+    /// Removes a key-value pair from the table, returning the value, or `None` if the key does not exist in the table.
+    pub fn remove(&mut self, key: &K) -> Result<Option<V>, K::Error> {
+        let Some(index) = self.find(key, hash(key, self.hasher)?) else {
+            return Ok(None);
+        };
+        let bucket_number = self.bucket_of(index);
+
+        // Replace the bucket with a tombstone so that searches for keys stored after it do not stop early.
+        self.array[bucket_number] = Bucket::Tombstone;
+        self.tombstones += 1;
+
+        // Remove the entry by moving the last entry into its place.
+        // If the last entry is not the one being removed, its bucket has to be updated with its new index first.
+        let last_index = self.entries.len() - 1;
+        if index != last_index {
+            let moved_bucket = self.bucket_of(last_index);
+            self.array[moved_bucket] = Bucket::Occupied(index);
+        }
+        let entry = self.entries.swap_remove(index);
+
+        // Check if the table should be shrunk.
+        self.check_shrink();
+        Ok(Some(entry.key_value.value))
+    }
+
+    /// Searches for `key`, where `hash_value` is the hash of `key`.
+    /// Returns `Ok` with the index of its entry if the key exists in the table; otherwise returns `Err` with the bucket number a new entry should go in.
+    fn probe(&self, key: &K, hash_value: u64) -> Result<usize, usize> {
+        let mut bucket_number = self.get_bucket_number(hash_value);
+        let mut first_tombstone: Option<usize> = None;  // The first `Tombstone` passed, which can be reused for a new entry.
+
+        // Probe the buckets starting from `bucket_number`.
+        // The load factor guarantees there is at least one `Empty` bucket, so the loop terminates.
+        loop {
+            match self.array[bucket_number] {
+                Bucket::Occupied(index) if self.entries[index].matches(key, hash_value) => return Ok(index),
+                Bucket::Occupied(..) => (),
+                Bucket::Tombstone => {
+                    // The key could still be further along, so remember the tombstone and keep probing.
+                    first_tombstone = first_tombstone.or(Some(bucket_number));
+                },
+                Bucket::Empty => break,
+            }
+            bucket_number = self.next_bucket_number(bucket_number);
+        }
+
+        // Reuse the first tombstone passed if there was one; otherwise use the `Empty` bucket found.
+        Err(first_tombstone.unwrap_or(bucket_number))
     }
 
-    /// Removes a key-value pair from the table.
-    pub fn remove(&mut self, key: &Value, line: usize) -> Result<(), ErrorType> {
+    /// Returns the index of the entry with the given key, or `None` if the key does not exist in the table.
+    /// `hash_value` has to be the hash of `key`.
+    fn find(&self, key: &K, hash_value: u64) -> Option<usize> {
         // Calculate the bucket number of the key.
-        let bucket_number = self.get_bucket_number(key, line)?;
-
-        // Iterate through the bucket.
-        if let Some(index) = self.array[bucket_number].iter().position(|key_value| key_value.key == key.clone()) {
-            // If an `index` is found such that `bucket[index].key == key`, then remove the entry at that index.
-            self.array[bucket_number].remove(index);
-            self.entries -= 1;  // Decrement the number of entries in the table.
-            Ok(())
-        } else {
-            // Otherwise, the key does not exist in the table. Return a KeyError, providing the `key` for detail.
-            Err(ErrorType::KeyError { key: key.clone(), line })
+        let mut bucket_number = self.get_bucket_number(hash_value);
+
+        // Probe the buckets until the key or an `Empty` bucket is found.
+        // Every bucket is checked at most once in case there are no `Empty` buckets.
+        for _ in 0..self.current_num_buckets {
+            match self.array[bucket_number] {
+                Bucket::Occupied(index) if self.entries[index].matches(key, hash_value) => return Some(index),
+                Bucket::Empty => break,
+                _ => (),
+            }
+            bucket_number = self.next_bucket_number(bucket_number);
         }
+        None
     }
+}
 
+impl<K, V> HashTable<K, V> {
     /// Returns the number of entries in the table.
     pub fn size(&self) -> usize {
-        self.entries
+        self.entries.len()
     }
 
-    /// Checks the load factor of the table and performs rehashing if required.
-    fn check_load(&mut self, line: usize) -> Result<(), ErrorType> {
-        if self.current_num_buckets < MAX_NUM_BUCKETS && self.entries * MAX_LOAD_FACTOR_DENOMINATOR > self.current_num_buckets * MAX_LOAD_FACTOR_NUMERATOR {
-            // If `current_capacity` is less than the maximum capacity and greater than the maximum load factor, perform rehashing.
-
-            // Make a copy of the entries in the table.
-            let copy = self.flatten();
-
-            // Double the current capacity of the table.
-            self.current_num_buckets <<= 1;
+    /// Returns an iterator over the key-value pairs in the table, without copying them.
+    pub fn iter(&self) -> impl Iterator<Item = &KeyValue<K, V>> {
+        self.entries.iter().map(|entry| &entry.key_value)
+    }
 
-            // Repopulate the internal array with `current_capacity` number of empty buckets.
-            self.array = vec![Vec::new(); self.current_num_buckets];
+    /// Returns an iterator over the key-value pairs in the table which allows the values to be modified.
+    /// The keys cannot be modified, as that would invalidate their stored hashes.
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = (&K, &mut V)> {
+        self.entries.iter_mut().map(|entry| (&entry.key_value.key, &mut entry.key_value.value))
+    }
 
-            // For each entry in the saved table, re-insert it in the new table.
-            for entry in copy.iter() {
-                self.insert(&entry.key, &entry.value, line)?;
-            }
+    /// Shrinks the table to the smallest number of buckets which can hold the current entries without exceeding the maximum load factor.
+    /// The table never has fewer than `INITIAL_NUM_BUCKETS` buckets.
+    pub fn shrink_to_fit(&mut self) {
+        let num_buckets = num_buckets_for(self.entries.len());
+        if num_buckets < self.current_num_buckets {
+            self.rehash(num_buckets);
         }
-        Ok(())
+        self.entries.shrink_to_fit();
     }
 
-    /// Calculates the bucket number of a key.
-    fn get_bucket_number(&self, key: &Value, line: usize) -> Result<usize, ErrorType> {
-        Ok(hash(key, HASH_FIRST_N, line)?.0 % self.current_num_buckets)
-    }
+    /// Adds a new entry in the bucket `bucket_number`, which must have been returned by `probe()`, and returns the index of the entry.
+    fn push_entry(&mut self, bucket_number: usize, hash_value: u64, key_value: KeyValue<K, V>) -> usize {
+        if let Bucket::Tombstone = self.array[bucket_number] {
+            self.tombstones -= 1;
+        }
+        let index = self.entries.len();
+        self.array[bucket_number] = Bucket::Occupied(index);
+        self.entries.push(Entry { hash: hash_value, key_value });
 
-    /// Returns all the key-value pairs in the table in a one-dimensional array.
-    pub fn flatten(&self) -> Vec<KeyValue<Value>> {
-        self.array.clone().into_iter().flatten().collect()
+        // Check if the table needs rehashing.
+        // Rehashing only moves the buckets around, so `index` still refers to the new entry afterwards.
+        self.check_load();
+        index
     }
-}
 
-/// Other parts of the interpreter rely on being able to compare two `Value`s.
-/// Since `HashTable` will be used as as part of a `Value` variant, it has to be comparable.
-/// Here, two `HashTable`s are equal if they contain the same set of key-value pairs.
-impl PartialEq for HashTable {
-    fn eq(&self, other: &Self) -> bool {
-        // One-dimensional array of entries in `self`.
-        let self_flattened = self.flatten();
+    /// Returns the bucket number of the bucket holding the entry at `index`.
+    fn bucket_of(&self, index: usize) -> usize {
+        // The entry is stored in the first bucket holding its index, starting from the bucket number of its hash.
+        let mut bucket_number = self.get_bucket_number(self.entries[index].hash);
+        loop {
+            if let Bucket::Occupied(i) = self.array[bucket_number] {
+                if i == index {
+                    return bucket_number;
+                }
+            }
+            bucket_number = self.next_bucket_number(bucket_number);
+        }
+    }
 
-        // Array of entries in `other`.
-        let mut other_flattened = other.flatten();
+    /// Checks the load factor of the table and performs rehashing if required.
+    /// Tombstones count towards the load, as they have to be probed past just like entries.
+    fn check_load(&mut self) {
+        if (self.entries.len() + self.tombstones) * MAX_LOAD_FACTOR_DENOMINATOR > self.current_num_buckets * MAX_LOAD_FACTOR_NUMERATOR {
+            // If the load is greater than the maximum load factor, perform rehashing.
+            if self.entries.len() * 2 > self.current_num_buckets {
+                // If at least half of the buckets hold entries, double the current capacity of the table.
+                self.rehash(self.current_num_buckets << 1);
+            } else {
+                // Otherwise, most of the load is tombstones, which are cleared by rehashing into the same number of buckets.
+                self.rehash(self.current_num_buckets);
+            }
+        }
+    }
 
-        // If they do not contain the same number of entries, they are not equal.
-        if self_flattened.len() != other_flattened.len() {
-            return false;
+    /// Checks the load factor of the table and shrinks it if it has fallen below the minimum load factor, e.g., after many removals.
+    fn check_shrink(&mut self) {
+        if self.current_num_buckets > INITIAL_NUM_BUCKETS && self.entries.len() * MIN_LOAD_FACTOR_DENOMINATOR < self.current_num_buckets * MIN_LOAD_FACTOR_NUMERATOR {
+            self.shrink_to_fit();
         }
+    }
 
-        // Iterate through the entries of `self`. If it exists in `other` as well, remove it from `other`.
-        // If it does not, then the two hash tables do not contain the same entries, so we return `false`.
-        for self_key_value in self_flattened {
-            if let Some(index) = other_flattened.iter().position(|other_key_value| *other_key_value == self_key_value) {
-                other_flattened.remove(index);
-            } else {
-                return false;
+    /// Rebuilds the buckets with `num_buckets` number of buckets, clearing all tombstones.
+    fn rehash(&mut self, num_buckets: usize) {
+        // Repopulate the internal array with `num_buckets` number of empty buckets.
+        self.array = vec![Bucket::Empty; num_buckets];
+        self.current_num_buckets = num_buckets;
+        self.tombstones = 0;
+
+        // For each entry, store its index in the first free bucket from its bucket number.
+        // Note the entries are not re-inserted with `insert()`, as they do not change and all keys are distinct.
+        // The stored hashes are used, so no key has to be hashed again.
+        for index in 0..self.entries.len() {
+            let mut bucket_number = self.get_bucket_number(self.entries[index].hash);
+            while let Bucket::Occupied(..) = self.array[bucket_number] {
+                bucket_number = self.next_bucket_number(bucket_number);
             }
+            self.array[bucket_number] = Bucket::Occupied(index);
         }
+    }
+
+    /// Calculates the bucket number of a key from its hash.
+    fn get_bucket_number(&self, hash_value: u64) -> usize {
+        // Linear probing is sensitive to similar hashes ending up in neighbouring buckets,
+        // so the hash is multiplied by `FIBONACCI_MULTIPLIER` to spread it out, and the top bits are used as the bucket number.
+        let bits = self.current_num_buckets.trailing_zeros();
+        (hash_value.wrapping_mul(FIBONACCI_MULTIPLIER) >> (u64::BITS - bits)) as usize
+    }
 
-        // All entries in `self` correspond to an entry in `other` and they have the same number of entries, so they must be equal.
-        true
+    /// Returns the bucket number after `bucket_number`, wrapping around to the start of the array.
+    fn next_bucket_number(&self, bucket_number: usize) -> usize {
+        (bucket_number + 1) & (self.current_num_buckets - 1)
     }
 }
 
-/// Used for printing hash tables.
-impl Debug for HashTable {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{:?}", self.flatten())
+impl<K: PartialEq, V> Entry<K, V> {
+    /// Returns whether this entry is for `key`, where `hash_value` is the hash of `key`.
+    /// Keys with different hashes cannot be equal, so the keys themselves are only compared if the hashes are equal.
+    fn matches(&self, key: &K, hash_value: u64) -> bool {
+        self.hash == hash_value && self.key_value.key == *key
     }
 }
 
-/// Computes and returns the (hash, elements_left) of a key.
-fn hash(key: &Value, mut elements_left: usize, line: usize) -> Result<(usize, usize), ErrorType> {
-    match key {
-        Value::Array(array) => {
-            // The `djb2` algorithm is used. (https://theartincode.stanis.me/008-djb2/)
-            let mut hash_value: usize = 5381;
-            let mut index: usize = 0;
-
-            // Limit the number of elements to `elements_left`, which can change depending on the recursive calls.
-            while elements_left > 0 && index < array.len() {
-                let result = hash(&array[index], elements_left, line)?;  // (hash, elements_left)
-                let curr = result.0 % MAX_CALC;
-                hash_value = (((hash_value << 5) + hash_value) + curr) % MAX_CALC; // Equivalent to `* 33 + curr`, but faster
-                
-                elements_left = result.1;
-                index += 1;
-            }
+/// Returns the smallest number of buckets which can hold `num_entries` entries without exceeding the maximum load factor.
+/// This is a power of two, and is never less than `INITIAL_NUM_BUCKETS`.
+fn num_buckets_for(num_entries: usize) -> usize {
+    let mut num_buckets = INITIAL_NUM_BUCKETS;
+    while num_entries * MAX_LOAD_FACTOR_DENOMINATOR > num_buckets * MAX_LOAD_FACTOR_NUMERATOR {
+        num_buckets <<= 1;
+    }
+    num_buckets
+}
 
-            Ok((hash_value, elements_left))
-        },
-        Value::Bool(b) => {
-            if *b {
-                Ok((1, elements_left - 1))
+/// Two `HashTable`s are equal if they contain the same set of key-value pairs.
+impl<K: NealHash, V: PartialEq> PartialEq for HashTable<K, V> {
+    fn eq(&self, other: &Self) -> bool {
+        // If they do not contain the same number of entries, they are not equal.
+        // Otherwise, every entry in `self` has to have an equal entry in `other`.
+        // If both use the same hasher, the stored hashes are used to look up the keys in `other`, so no key has to be hashed again.
+        self.size() == other.size() && self.entries.iter().all(|entry| {
+            let hash_value = if self.hasher == other.hasher {
+                entry.hash
             } else {
-                Ok((2, elements_left - 1))
-            }
-        },
-        Value::Dictionary(..) => {
-            // Hashing dictionaries in constant time will involve more sophisticated techniques.
-            Err(ErrorType::CannotHashDictionary { line })
-        },
-        Value::Function {..} | Value::BuiltinFunction(..) => {
-            // It is tricky to hash functions as the comparison of two functions is not set in stone.
-            // So we raise a descriptive error instead.
-            Err(ErrorType::CannotHashFunction { line })
-        },
-        Value::Null => Ok((3, elements_left - 1)),
-        Value::Number(x) => {
-            // We will discard the 12 least significant bits to mask floating point inaccuracy.
-            let mut binary: usize = (x.to_bits() >> 12).try_into().unwrap();
-            binary %= MAX_CALC;
-
-            // The 'Knuth Variant on Division' (https://www.cs.hmc.edu/~geoff/classes/hmc.cs070.200101/homework10/hashfuncs.html)
-            binary = (binary * (binary + 3)) % MAX_CALC;
-            Ok((binary as usize, elements_left - 1))
-        },
-        Value::String_(s) => {
-            // Similar to arrays, we use the `djb2` algorithm.
-            let mut hash_value = 5381;
-            let mut index = 0;
-
-            while elements_left > 0 && index < s.chars().count() {
-                hash_value = (((hash_value << 5) + hash_value) + s.chars().nth(index).unwrap() as usize) % MAX_CALC;
-                elements_left -= 1;
-                index += 1;
+                // The key is already in `self`, so it must be hashable.
+                match hash(&entry.key_value.key, other.hasher) {
+                    Ok(hash_value) => hash_value,
+                    Err(..) => return false,
+                }
+            };
+            match other.find(&entry.key_value.key, hash_value) {
+                Some(index) => other.entries[index].key_value.value == entry.key_value.value,
+                None => false,
             }
+        })
+    }
+}
 
-            Ok((hash_value, elements_left))
-        },
+/// Used for printing hash tables.
+impl<K: Debug, V: Debug> Debug for HashTable<K, V> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{error::ErrorType, stmt::{Stmt, StmtType}, value::Value};
+    use std::time::Instant;
 
-    use super::HashTable;
+    use super::{hash, HashTable, Hasher};
 
     #[test]
     fn insert_and_get() {
-        let mut dict = HashTable::new();
-        assert!(dict.insert(&Value::Number(5.0), &Value::String_("hello".to_string()), 1).is_ok());
-        assert_eq!(dict.get(&Value::Number(5.0), 1), Ok(&Value::String_("hello".to_string())));
+        let mut table = HashTable::with_capacity(0, Hasher::Fixed);
+        assert_eq!(table.insert(5_u64, "hello"), Ok(None));
+        assert_eq!(table.get(&5), Ok(Some(&"hello")));
+        assert_eq!(table.get(&6), Ok(None));
     }
 
     #[test]
     fn insert_duplicate_and_get() {
-        let mut dict = HashTable::new();
-        assert!(dict.insert(&Value::Number(5.0), &Value::String_("hello".to_string()), 1).is_ok());
-        assert!(dict.insert(&Value::Number(5.0), &Value::String_("hi".to_string()), 1).is_ok());
-        assert_eq!(dict.get(&Value::Number(5.0), 1), Ok(&Value::String_("hi".to_string())));
+        let mut table = HashTable::with_capacity(0, Hasher::Fixed);
+        assert_eq!(table.insert(5_u64, "hello"), Ok(None));
+        assert_eq!(table.insert(5_u64, "hi"), Ok(Some("hello")));
+        assert_eq!(table.get(&5), Ok(Some(&"hi")));
+        assert_eq!(table.size(), 1);
     }
 
     #[test]
     fn insert_remove_size() {
-        let mut dict = HashTable::new();
-        assert!(dict.insert(&Value::Number(5.0), &Value::String_("hello".to_string()), 1).is_ok());
-        assert!(dict.insert(&Value::String_("key1".to_string()), &Value::String_("hi".to_string()), 1).is_ok());
-        assert_eq!(dict.size(), 2);
+        let mut table = HashTable::with_capacity(0, Hasher::Fixed);
+        assert!(table.insert(String::from("key1"), 1).is_ok());
+        assert!(table.insert(String::from("key2"), 2).is_ok());
+        assert_eq!(table.size(), 2);
+
+        assert_eq!(table.remove(&String::from("key1")), Ok(Some(1)));
+        assert_eq!(table.remove(&String::from("key1")), Ok(None));
+        assert_eq!(table.size(), 1)
+    }
 
-        assert!(dict.remove(&Value::Number(5.0), 1).is_ok());
-        assert_eq!(dict.size(), 1)
+    #[test]
+    fn size_after_rehash() {
+        let mut table = HashTable::with_capacity(0, Hasher::Fixed);
+        for i in 0..100_u64 {
+            assert!(table.insert(i, ()).is_ok());
+        }
+        assert_eq!(table.size(), 100);
+        assert_eq!(table.current_num_buckets, 256);
+        assert_eq!(table.get(&57), Ok(Some(&())));
     }
-    
+
     #[test]
-    fn key_error() {
-        let dict = HashTable::new();
-        assert_eq!(dict.get(&Value::Number(5.0), 1), Err(ErrorType::KeyError { key: Value::Number(5.0), line: 1 }));
+    fn shrink_after_remove() {
+        let mut table = HashTable::with_capacity(0, Hasher::Fixed);
+        for i in 0..100_u64 {
+            assert!(table.insert(i, ()).is_ok());
+        }
+        for i in 0..99 {
+            assert_eq!(table.remove(&i), Ok(Some(())));
+        }
+        assert_eq!(table.size(), 1);
+        assert_eq!(table.current_num_buckets, 16);
+        assert_eq!(table.get(&99), Ok(Some(&())));
     }
 
     #[test]
-    fn cannot_hash_errors() {
-        let dict = HashTable::new();
-        assert_eq!(dict.get(&Value::Function { parameters: vec![], body: Stmt { line: 1, stmt_type: StmtType::Break } }, 1), Err(ErrorType::CannotHashFunction { line: 1 }));
-        assert_eq!(dict.get(&Value::Dictionary(HashTable::new()), 1), Err(ErrorType::CannotHashDictionary { line: 1 }));
+    fn with_capacity_and_reserve() {
+        // 100 entries need 256 buckets at a maximum load factor of 3/4, so no rehashing should happen while inserting them.
+        let mut table = HashTable::with_capacity(100, Hasher::Fixed);
+        assert_eq!(table.current_num_buckets, 256);
+        for i in 0..100_u64 {
+            assert!(table.insert(i, ()).is_ok());
+        }
+        assert_eq!(table.current_num_buckets, 256);
+
+        // Room for 100 more needs 512 buckets.
+        table.reserve(100);
+        assert_eq!(table.current_num_buckets, 512);
+        assert_eq!(table.get(&50), Ok(Some(&())));
+
+        // Reserving less than there is already room for does nothing.
+        table.reserve(1);
+        assert_eq!(table.current_num_buckets, 512);
+        assert_eq!(HashTable::<u64, ()>::with_capacity(0, Hasher::Fixed).current_num_buckets, 16);
     }
 
     #[test]
-    fn equality() {
-        let mut dict1 = HashTable::new();
-        let mut dict2 = HashTable::new();
-        assert!(dict1.insert(&Value::Number(5.0), &Value::String_("hello".to_string()), 1).is_ok());
-        assert!(dict1.insert(&Value::Number(6.0), &Value::String_("hello".to_string()), 1).is_ok());
-        assert!(dict1.insert(&Value::Number(7.0), &Value::String_("hello".to_string()), 1).is_ok());
+    fn remove_then_get() {
+        // Keys stored after a removed key have to remain reachable.
+        let mut table = HashTable::with_capacity(0, Hasher::Fixed);
+        for i in 0..10_u64 {
+            assert!(table.insert(i, i).is_ok());
+        }
+        assert!(table.remove(&3).is_ok());
+        for i in (0..10).filter(|i| *i != 3) {
+            assert_eq!(table.get(&i), Ok(Some(&i)));
+        }
+        assert_eq!(table.get(&3), Ok(None));
+
+        // Re-inserting a removed key reuses its tombstone.
+        assert!(table.insert(3, 30).is_ok());
+        assert_eq!(table.size(), 10);
+        assert_eq!(table.get(&3), Ok(Some(&30)));
+    }
+
+    #[test]
+    fn repeated_insert_remove() {
+        // Tombstones must not fill up the table.
+        let mut table = HashTable::with_capacity(0, Hasher::Fixed);
+        for i in 0..1000_u64 {
+            assert!(table.insert(i, ()).is_ok());
+            assert!(table.remove(&i).is_ok());
+        }
+        assert_eq!(table.size(), 0);
+        assert_eq!(table.current_num_buckets, 16);
+        assert_eq!(table.get(&5), Ok(None));
+    }
+
+    /// Run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn insert_get_remove_100k_benchmark() {
+        let mut table = HashTable::with_capacity(0, Hasher::random());
+        let keys: Vec<String> = (0..100_000).map(|i| format!("key{}", i)).collect();
+
+        let start = Instant::now();
+        for key in keys.iter() {
+            assert!(table.insert(key.clone(), ()).is_ok());
+        }
+        println!("Inserted 100000 keys in {:?}.", start.elapsed());
+
+        let start = Instant::now();
+        for key in keys.iter() {
+            assert_eq!(table.get(key), Ok(Some(&())));
+        }
+        println!("Got 100000 keys in {:?}.", start.elapsed());
 
-        assert!(dict2.insert(&Value::Number(7.0), &Value::String_("hello".to_string()), 1).is_ok());
-        assert!(dict2.insert(&Value::Number(6.0), &Value::String_("hello".to_string()), 1).is_ok());
-        assert!(dict2.insert(&Value::Number(5.0), &Value::String_("hello".to_string()), 1).is_ok());
+        let start = Instant::now();
+        for key in keys.iter() {
+            assert_eq!(table.remove(key), Ok(Some(())));
+        }
+        println!("Removed 100000 keys in {:?}.", start.elapsed());
+    }
 
-        assert_eq!(dict1, dict2);
+    #[test]
+    fn long_keys() {
+        // Keys which only differ after many bytes are still told apart.
+        let prefix = "a".repeat(1000);
+        let key1 = format!("{}1", prefix);
+        let key2 = format!("{}2", prefix);
+        assert_ne!(hash(&key1, Hasher::Fixed), hash(&key2, Hasher::Fixed));
+
+        let mut table = HashTable::with_capacity(0, Hasher::Fixed);
+        assert!(table.insert(key1.clone(), 1).is_ok());
+        assert!(table.insert(key2.clone(), 2).is_ok());
+        assert_eq!(table.get(&key1), Ok(Some(&1)));
+        assert_eq!(table.get(&key2), Ok(Some(&2)));
     }
 
     #[test]
-    fn inequality() {
-        let mut dict1 = HashTable::new();
-        let mut dict2 = HashTable::new();
-        assert!(dict1.insert(&Value::Number(5.0), &Value::String_("hello".to_string()), 1).is_ok());
-        assert!(dict1.insert(&Value::Number(6.0), &Value::String_("hello".to_string()), 1).is_ok());
-        assert!(dict1.insert(&Value::Number(7.0), &Value::String_("hello".to_string()), 1).is_ok());
-
-        assert!(dict2.insert(&Value::Number(8.0), &Value::String_("hello".to_string()), 1).is_ok());
-        assert!(dict2.insert(&Value::Number(6.0), &Value::String_("hello".to_string()), 1).is_ok());
-        assert!(dict2.insert(&Value::Number(5.0), &Value::String_("hello".to_string()), 1).is_ok());
-
-        assert_ne!(dict1, dict2);
+    fn iter() {
+        let mut table = HashTable::with_capacity(0, Hasher::Fixed);
+        assert!(table.insert(1_u64, 10).is_ok());
+        assert!(table.insert(2_u64, 20).is_ok());
+
+        for (_, value) in table.iter_mut() {
+            *value += 1;
+        }
+
+        let mut values: Vec<i32> = table.iter().map(|key_value| key_value.value).collect();
+        values.sort();
+        assert_eq!(values, vec![11, 21]);
+    }
+
+    #[test]
+    fn get_or_insert_with() {
+        let mut table = HashTable::with_capacity(0, Hasher::Fixed);
+        assert!(table.insert(1_u64, 10).is_ok());
+
+        // An existing key is not overwritten, and the default is not computed.
+        assert_eq!(table.get_or_insert_with(1, || panic!("default computed")), Ok(&mut 10));
+
+        // A new key is inserted with the default, and the returned reference can be used to update it.
+        for _ in 0..3 {
+            let Ok(count) = table.get_or_insert_with(2, || 0);
+            *count += 1;
+        }
+        assert_eq!(table.get(&2), Ok(Some(&3)));
+        assert_eq!(table.size(), 2);
+    }
+
+    #[test]
+    fn seeded_hasher() {
+        let mut table = HashTable::with_capacity(0, Hasher::Seeded { k0: 1, k1: 2 });
+        for i in 0..100 {
+            assert!(table.insert(i.to_string(), i).is_ok());
+        }
+        assert!(table.remove(&String::from("50")).is_ok());
+
+        assert_eq!(table.size(), 99);
+        assert_eq!(table.get(&String::from("99")), Ok(Some(&99)));
+        assert_eq!(table.get(&String::from("50")), Ok(None));
+    }
+
+    #[test]
+    fn seeds_change_hashes() {
+        let key = String::from("a fairly long string key");
+        let hash1 = hash(&key, Hasher::Seeded { k0: 1, k1: 2 });
+        let hash2 = hash(&key, Hasher::Seeded { k0: 3, k1: 4 });
+        assert_ne!(hash1, hash2);
+        assert_eq!(hash1, hash(&key, Hasher::Seeded { k0: 1, k1: 2 }));
+    }
+
+    #[test]
+    fn equality() {
+        let mut table1 = HashTable::with_capacity(0, Hasher::Fixed);
+        let mut table2 = HashTable::with_capacity(0, Hasher::Fixed);
+        let mut table3 = HashTable::with_capacity(0, Hasher::random());
+        for i in 0..10_u64 {
+            assert!(table1.insert(i, i * 2).is_ok());
+            assert!(table2.insert(9 - i, (9 - i) * 2).is_ok());
+            assert!(table3.insert(i, i * 2).is_ok());
+        }
+        assert_eq!(table1, table2);
+        assert_eq!(table1, table3);
+
+        assert!(table2.insert(0, 1).is_ok());
+        assert_ne!(table1, table2);
     }
 }
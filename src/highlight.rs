@@ -0,0 +1,135 @@
+//! Classification of the source code into spans of keywords, names, literals, comments and operators, for syntax highlighters.
+//!
+//! The spans come straight from the tokenizer, so a highlighter built on them always agrees with how the source code will be run.
+//! The REPL highlights its input with them, and an editor can use them for semantic highlighting:
+//!
+//! ```
+//! use nea::highlight::{classify, Span, SpanKind};
+//!
+//! let spans = classify("print x  # y");
+//! assert_eq!(spans[0], Span { kind: SpanKind::Keyword, line: 1, column: 1, start: 0, end: 5 });
+//! let kinds: Vec<SpanKind> = spans.iter().map(|span| span.kind).collect();
+//! assert_eq!(kinds, [SpanKind::Keyword, SpanKind::Identifier, SpanKind::Comment]);
+//! ```
+
+use crate::error::ErrorType;
+use crate::token::TokenType;
+use crate::Tokenizer;
+
+/// What a span of source code is.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum SpanKind {
+    Keyword,  // A keyword other than the constants, e.g., `while`, `and`.
+    Constant,  // `true`, `false` or `null`.
+    Identifier,  // The name of a variable or function.
+    Number,
+    String,  // A string literal, including its quotes. A string which is never closed runs to the end of the source code.
+    Comment,  // A comment, from the `#` to the end of its line.
+    Operator,  // An operator, e.g., `+` or `<=`.
+    Punctuation,  // A bracket, comma, colon or semicolon.
+}
+
+/// A classified piece of the source code.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Span {
+    pub kind: SpanKind,
+    pub line: usize,  // The line number of the first character of the span.
+    pub column: usize,  // The column number of the first character of the span, starting from 1.
+    pub start: usize,  // The index of the first character of the span in the source code, counting characters rather than bytes.
+    pub end: usize,  // The index of the character after the span. Strings can span several lines, so this may be on a later line.
+}
+
+/// Returns the spans of the source code, in order. Whitespace, and characters which cannot be tokenized, are not in any span.
+pub fn classify(source: &str) -> Vec<Span> {
+    // The index of the first character of each line, to turn the line and column numbers of tokens into indexes.
+    let mut line_starts = vec![0];
+    let mut length = 0;
+    for (i, c) in source.chars().enumerate() {
+        if c == '\n' {
+            line_starts.push(i + 1);
+        }
+        length = i + 1;
+    }
+    let span = |kind, line: usize, column: usize, chars: usize| {
+        let start = line_starts[line - 1] + column - 1;
+        Span { kind, line, column, start, end: (start + chars).min(length) }
+    };
+
+    let mut spans = Vec::new();
+    let mut tokenizer = Tokenizer::new(source);
+    for result in tokenizer.by_ref() {
+        match result {
+            Ok(token) => {
+                if let Some(kind) = kind_of(&token.type_) {
+                    spans.push(span(kind, token.line, token.column, token.lexeme.chars().count()));
+                }
+            },
+            // A string which has not been closed yet, e.g., while it is being typed, runs to the end.
+            Err(ErrorType::UnterminatedString { line, column }) => spans.push(span(SpanKind::String, line, column, length)),
+            // Other errors are left for when the source code is run.
+            Err(_) => (),
+        }
+    }
+    // The tokenizer does not produce comments as tokens, but collects them as it goes.
+    for comment in tokenizer.comments() {
+        spans.push(span(SpanKind::Comment, comment.line, comment.column, comment.text.chars().count()));
+    }
+    spans.sort_by_key(|span| span.start);
+    spans
+}
+
+/// Returns the kind of span a token of the type is, or `None` for the `Eof` token.
+fn kind_of(type_: &TokenType) -> Option<SpanKind> {
+    let kind = match type_ {
+        TokenType::And | TokenType::Break | TokenType::Catch | TokenType::Else | TokenType::Finally | TokenType::Func | TokenType::For
+        | TokenType::If | TokenType::Or | TokenType::Print | TokenType::Repeat | TokenType::Return | TokenType::Try | TokenType::Var
+        | TokenType::While => SpanKind::Keyword,
+        TokenType::True | TokenType::False | TokenType::Null => SpanKind::Constant,
+        TokenType::Identifier => SpanKind::Identifier,
+        TokenType::Number => SpanKind::Number,
+        TokenType::String_ => SpanKind::String,
+        TokenType::LeftParen | TokenType::RightParen | TokenType::LeftCurly | TokenType::RightCurly | TokenType::LeftSquare
+        | TokenType::RightSquare | TokenType::Colon | TokenType::Comma | TokenType::Semicolon => SpanKind::Punctuation,
+        TokenType::Minus | TokenType::Percent | TokenType::Plus | TokenType::Slash | TokenType::Star | TokenType::Bang
+        | TokenType::BangEqual | TokenType::Equal | TokenType::EqualEqual | TokenType::Greater | TokenType::GreaterEqual
+        | TokenType::Less | TokenType::LessEqual => SpanKind::Operator,
+        TokenType::Eof => return None,
+    };
+    Some(kind)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{classify, Span, SpanKind};
+
+    /// Returns the kind and text of each span.
+    fn spans(source: &str) -> Vec<(SpanKind, String)> {
+        let chars: Vec<char> = source.chars().collect();
+        classify(source).iter().map(|span| (span.kind, chars[span.start..span.end].iter().collect())).collect()
+    }
+
+    #[test]
+    fn kinds() {
+        use SpanKind::*;
+        let expected = [
+            (Keyword, "func"), (Identifier, "f"), (Punctuation, "("), (Identifier, "a"), (Punctuation, ")"), (Punctuation, "{"),
+            (Keyword, "return"), (Identifier, "a"), (Operator, ">="), (Number, "1.5"), (Keyword, "and"), (Constant, "null"),
+            (Operator, "=="), (String, "'#é'"), (Comment, "# é"), (Punctuation, "}"),
+        ];
+        let expected: Vec<(SpanKind, std::string::String)> = expected.iter().map(|(kind, text)| (*kind, text.to_string())).collect();
+        assert_eq!(spans("func f(a) {\n    return a >= 1.5 and null == '#é'  # é\n}"), expected);
+    }
+
+    #[test]
+    fn positions() {
+        // Strings can span lines, and one which is never closed runs to the end, while characters which cannot be tokenized are left out.
+        let source = "x = 'a\nb'\n@ 'c\nd";
+        assert_eq!(classify(source), [
+            Span { kind: SpanKind::Identifier, line: 1, column: 1, start: 0, end: 1 },
+            Span { kind: SpanKind::Operator, line: 1, column: 3, start: 2, end: 3 },
+            Span { kind: SpanKind::String, line: 1, column: 5, start: 4, end: 9 },
+            Span { kind: SpanKind::String, line: 3, column: 3, start: 12, end: 16 },
+        ]);
+        assert_eq!(classify(""), []);
+    }
+}
@@ -0,0 +1,49 @@
+//! Callbacks which the interpreter makes while it runs a program, for tools such as the profiler and coverage, and for embedding programs.
+//!
+//! A tool implements `Hooks`, overriding the methods for the events it wants to know about; the others do nothing. Hooks are given
+//! to an interpreter with `Interpreter::add_hooks()`. They only observe the program: they cannot change its values or stop it.
+//!
+//! ```
+//! use std::sync::{Arc, Mutex};
+//!
+//! use nea::hooks::Hooks;
+//! use nea::stmt::Stmt;
+//! use nea::{run_source, Interpreter};
+//!
+//! /// Counts the statements executed on each line, shared with the embedding program.
+//! struct LineCounts(Arc<Mutex<Vec<usize>>>);
+//!
+//! impl Hooks for LineCounts {
+//!     fn before_statement(&mut self, stmt: &Stmt) {
+//!         self.0.lock().unwrap().push(stmt.line);
+//!     }
+//! }
+//!
+//! let lines = Arc::new(Mutex::new(Vec::new()));
+//! let mut interpreter = Interpreter::new();
+//! interpreter.add_hooks(Box::new(LineCounts(Arc::clone(&lines))));
+//! run_source("var x = 0\nwhile (x < 2) {\n    x = x + 1\n}", &mut interpreter).unwrap();
+//! assert_eq!(*lines.lock().unwrap(), [1, 2, 3, 3]);
+//! ```
+
+use crate::expr::Expr;
+use crate::stmt::Stmt;
+use crate::value::Value;
+
+/// Observes the execution of a program. See the module documentation for how to use it.
+pub trait Hooks {
+    /// Called before a statement is executed. Blocks are left out, as their statements are reported instead.
+    fn before_statement(&mut self, _stmt: &Stmt) {}
+
+    /// Called after a statement given to `before_statement()` has finished, whether or not it raised an error.
+    fn after_statement(&mut self, _stmt: &Stmt) {}
+
+    /// Called after an expression has been evaluated without an error, with its value. Each part of an expression is reported before the whole.
+    fn after_expression(&mut self, _expr: &Expr, _value: &Value) {}
+
+    /// Called before the body of a user-defined function is executed, with the name it was called by, or `<anonymous>`.
+    fn before_call(&mut self, _function: &str) {}
+
+    /// Called after a call given to `before_call()` has finished, whether or not it raised an error.
+    fn after_call(&mut self, _function: &str) {}
+}
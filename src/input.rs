@@ -0,0 +1,194 @@
+//! Where the `input()` builtin reads its lines from, and `get_key()` its keypresses.
+
+use std::io::{self, BufRead};
+
+/// A source of the lines read by `input()`, e.g., the standard input stream, or a fixed set of answers in a test.
+/// Every `BufRead` is a source, e.g., `io::Cursor::new("Alice\n")`.
+pub trait InputSource {
+    /// Reads the next line, including its line ending, and appends it to `line`.
+    /// Returns the number of bytes read, which is 0 at the end of the input.
+    fn read_line(&mut self, line: &mut String) -> io::Result<usize>;
+
+    /// Reads the next keypress, returning its name (see `decode_key()`), or `None` at the end of the input.
+    /// By default, this is the first character of the next line, for sources which can only be read a line at a time.
+    fn read_key(&mut self) -> io::Result<Option<String>> {
+        let mut line = String::new();
+        if self.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        decode_key(&mut io::Cursor::new(line), &mut |_| Ok(()))
+    }
+}
+
+impl<R: BufRead> InputSource for R {
+    fn read_line(&mut self, line: &mut String) -> io::Result<usize> {
+        BufRead::read_line(self, line)
+    }
+
+    fn read_key(&mut self) -> io::Result<Option<String>> {
+        decode_key(self, &mut |_| Ok(()))
+    }
+}
+
+/// The standard input stream, which is the source used unless another is given.
+/// The stream is only locked while a line is read, so that other readers, e.g., the REPL's line editor, can use it in between.
+pub struct Stdin;
+
+impl InputSource for Stdin {
+    fn read_line(&mut self, line: &mut String) -> io::Result<usize> {
+        io::stdin().read_line(line)
+    }
+
+    fn read_key(&mut self) -> io::Result<Option<String>> {
+        let mut stdin = io::stdin().lock();
+        // Only a terminal waits for Enter and echoes what is typed. Piped input is read as it is.
+        #[cfg(unix)]
+        if io::IsTerminal::is_terminal(&stdin) {
+            // The terminal is put back as it was when `raw` is dropped, including when the read fails.
+            let raw = terminal::RawMode::enable()?;
+            // The bytes after an escape are only waited for briefly, so that Escape on its own is not mistaken for the start of an arrow key.
+            return decode_key(&mut stdin, &mut |follow_up| raw.set_waiting(!follow_up));
+        }
+        decode_key(&mut stdin, &mut |_| Ok(()))
+    }
+}
+
+/// Returns the next byte without reading it, or `None` at the end of the input.
+fn peek_byte(reader: &mut dyn BufRead) -> io::Result<Option<u8>> {
+    Ok(reader.fill_buf()?.first().copied())
+}
+
+/// Reads a keypress from the reader. Before each byte, `prepare` is told whether the byte would follow another of the same key.
+///
+/// Most keys are named by the character they type, e.g., `a` or `%`. Other keys have names: `up`, `down`, `left` and `right` for the
+/// arrow keys, and `enter`, `escape`, `backspace` and `tab`. Ctrl-C is an `Interrupted` error, as it is not seen as a signal while
+/// the terminal is in raw mode.
+fn decode_key(reader: &mut dyn BufRead, prepare: &mut dyn FnMut(bool) -> io::Result<()>) -> io::Result<Option<String>> {
+    prepare(false)?;
+    let Some(first) = peek_byte(reader)? else {
+        return Ok(None);
+    };
+    reader.consume(1);
+
+    // Reads the next byte if it is part of the same key.
+    let mut next = |reader: &mut dyn BufRead, wanted: &dyn Fn(u8) -> bool| -> io::Result<Option<u8>> {
+        prepare(true)?;
+        let byte = peek_byte(reader)?.filter(|&byte| wanted(byte));
+        if byte.is_some() {
+            reader.consume(1);
+        }
+        Ok(byte)
+    };
+
+    let name = match first {
+        0x03 => return Err(io::Error::from(io::ErrorKind::Interrupted)),
+        b'\n' | b'\r' => "enter",
+        b'\t' => "tab",
+        0x08 | 0x7f => "backspace",
+        // The arrow keys are sent as an escape followed by `[` or `O` and a letter. Any other byte is the start of the next key.
+        0x1b => match next(reader, &|byte| byte == b'[' || byte == b'O')? {
+            Some(_) => match next(reader, &|byte| byte.is_ascii_alphabetic())? {
+                Some(b'A') => "up",
+                Some(b'B') => "down",
+                Some(b'C') => "right",
+                Some(b'D') => "left",
+                _ => "escape",
+            },
+            _ => "escape",
+        },
+        _ => {
+            // The number of bytes in a UTF-8 character is given by its first byte.
+            let length = match first {
+                0xf0.. => 4,
+                0xe0.. => 3,
+                0xc0.. => 2,
+                _ => 1,
+            };
+            let mut bytes = vec![first];
+            for _ in 1..length {
+                match next(reader, &|byte| byte & 0xc0 == 0x80)? {
+                    Some(byte) => bytes.push(byte),
+                    None => break,
+                }
+            }
+            return Ok(Some(String::from_utf8_lossy(&bytes).into_owned()));
+        },
+    };
+    Ok(Some(String::from(name)))
+}
+
+#[cfg(unix)]
+mod terminal {
+    use std::io;
+    use std::mem::MaybeUninit;
+
+    /// Puts the terminal on the standard input stream into raw mode, where each key is read as soon as it is pressed, and is
+    /// not echoed. The terminal is put back into the mode it was in when this is dropped.
+    pub struct RawMode {
+        original: libc::termios,
+        raw: libc::termios,
+    }
+
+    impl RawMode {
+        pub fn enable() -> io::Result<Self> {
+            let mut original = MaybeUninit::uninit();
+            // SAFETY: `tcgetattr()` only writes to the `termios` it is given, which is only read if it succeeds.
+            let original = unsafe {
+                if libc::tcgetattr(libc::STDIN_FILENO, original.as_mut_ptr()) != 0 {
+                    return Err(io::Error::last_os_error());
+                }
+                original.assume_init()
+            };
+            let mut raw = original;
+            // Ctrl-C is read as a key rather than raising a signal, so that the terminal is always put back before the program stops.
+            raw.c_lflag &= !(libc::ICANON | libc::ECHO | libc::ISIG);
+            set(&raw)?;
+            Ok(RawMode { original, raw })
+        }
+
+        /// Sets whether reads wait until a key is pressed, or only wait a tenth of a second before finding no input.
+        pub fn set_waiting(&self, waiting: bool) -> io::Result<()> {
+            let mut raw = self.raw;
+            raw.c_cc[libc::VMIN] = u8::from(waiting) as libc::cc_t;
+            raw.c_cc[libc::VTIME] = (!waiting) as libc::cc_t;
+            set(&raw)
+        }
+    }
+
+    impl Drop for RawMode {
+        fn drop(&mut self) {
+            // There is nothing to be done if this fails, e.g., because the terminal has been closed.
+            let _ = set(&self.original);
+        }
+    }
+
+    /// Sets the mode of the terminal on the standard input stream.
+    fn set(mode: &libc::termios) -> io::Result<()> {
+        // SAFETY: `tcsetattr()` only reads the `termios` it is given.
+        if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, mode) } != 0 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Cursor;
+
+    use super::InputSource;
+
+    /// Returns the names of the keys read from the bytes, until the end of the input.
+    fn keys(bytes: &[u8]) -> Vec<String> {
+        let mut input = Cursor::new(bytes.to_vec());
+        std::iter::from_fn(|| input.read_key().unwrap()).collect()
+    }
+
+    #[test]
+    fn read_key() {
+        assert_eq!(keys(b"a\n\x1b[A\x1bOD\x1b\x7f\x1b[Zb\t\xc3\xa9%"), ["a", "enter", "up", "left", "escape", "backspace", "escape", "b", "tab", "é", "%"]);
+        assert_eq!(keys(b""), Vec::<String>::new());
+        let error = Cursor::new(b"\x03".to_vec()).read_key().unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::Interrupted);
+    }
+}
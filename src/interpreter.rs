@@ -1,63 +1,621 @@
+use std::cmp::Ordering;
+use std::fs;
 use std::io::{Write, self};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
 
+use crate::catalogue::{self, Key};
 use crate::environment::{Environment, Pointer, self};
 use crate::expr::{Expr, ExprType};
-use crate::token::{TokenType, Literal};
-use crate::error::{ErrorType, self};
+use crate::parser::Parser;
+use crate::token::{Literal, Token, TokenType};
+use crate::tokenizer::Tokenizer;
+use crate::error::{ErrorLimit, ErrorType, self};
 use crate::stmt::{Stmt, StmtType};
-use crate::value::{Value, BuiltinFunction};
-use crate::hash_table::HashTable;
+use crate::value::{self, Array, Value, BuiltinFunction};
+use crate::dictionary::Dictionary;
+use crate::hash_table::Hasher;
+use crate::input::{self, InputSource};
+use crate::profiler::Profiler;
+use crate::coverage::Coverage;
+use crate::hooks::Hooks;
+use crate::store;
+use crate::turtle::{self, Turtle};
+
+/// The number of innermost and outermost calls shown in a stack trace. Calls in between are summarised, so that deep recursion does not flood the output.
+const STACK_TRACE_LIMIT: usize = 8;
+
+/// The most decimal places `to_fixed()` writes a number to.
+const MAX_DECIMAL_PLACES: f64 = 100.0;
+
+/// The largest number up to which every whole number can be stored exactly, as numbers are `f64`s.
+const MAX_EXACT_INTEGER: f64 = 9007199254740992.0;
+
+/// The most calls to user-defined functions and `eval()` which can be executed inside each other, e.g., by a function which calls
+/// itself, before an error is raised. Each one uses the stack of the thread running the interpreter, and this many fit in the 8 MiB
+/// of a main thread with room to spare, rather than overflowing it and aborting the program.
+const MAX_CALL_DEPTH: usize = 500;
+
+/// Returns the value as a whole number, for the number theory built-in functions, or an error if it is not a whole number which
+/// can be stored exactly.
+fn whole_number(value: Value, line: usize, column: usize) -> Result<i64, ErrorType> {
+    match value {
+        Value::Number(x) if x.fract() == 0.0 && x.abs() <= MAX_EXACT_INTEGER => Ok(x as i64),
+        Value::Number(..) => Err(ErrorType::NotAWholeNumber { got: value, line, column }),
+        _ => Err(ErrorType::ExpectedType { expected: String::from("Number"), got: value.type_to_string(), line, column }),
+    }
+}
+
+/// Returns the base given to `to_number()` or `to_base()`, or an error if it is not a whole number from 2 to 36.
+fn base(value: Value, line: usize, column: usize) -> Result<u32, ErrorType> {
+    match value {
+        Value::Number(x) if x.fract() == 0.0 && (2.0..=36.0).contains(&x) => Ok(x as u32),
+        _ => Err(ErrorType::InvalidBase { got: value, line, column }),
+    }
+}
+
+/// Returns the whole number written in the base, with an optional sign, or `None` if it has no digits or any which are not in the base.
+/// Digits after `9` are the letters from `a`, in either case. Numbers too large to be stored exactly are rounded.
+fn parse_in_base(text: &str, base: u32) -> Option<f64> {
+    let (negative, digits) = match text.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    if digits.is_empty() {
+        return None;
+    }
+    let mut number = 0.0;
+    for c in digits.chars() {
+        number = number * base as f64 + c.to_digit(base)? as f64;
+    }
+    Some(if negative { -number } else { number })
+}
+
+/// Returns the greatest common divisor of the numbers, which is 0 only if both are 0.
+fn gcd(mut a: u64, mut b: u64) -> u64 {
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    a
+}
+
+/// Returns the smallest prime factor of the number, which must be at least 2.
+fn smallest_prime_factor(n: u64) -> u64 {
+    if n.is_multiple_of(2) {
+        return 2;
+    }
+    // Only odd divisors up to the square root need to be tried, as a larger factor would come with a smaller one.
+    let mut divisor = 3;
+    while divisor * divisor <= n {
+        if n.is_multiple_of(divisor) {
+            return divisor;
+        }
+        divisor += 2;
+    }
+    n
+}
+
+/// The ANSI escape code which `set_color()` writes for the colour or style, or `None` if the terminal has no such colour.
+fn colour_code(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "reset" => "\x1b[0m",
+        "bold" => "\x1b[1m",
+        "underline" => "\x1b[4m",
+        "black" => "\x1b[30m",
+        "red" => "\x1b[31m",
+        "green" => "\x1b[32m",
+        "yellow" => "\x1b[33m",
+        "blue" => "\x1b[34m",
+        "magenta" => "\x1b[35m",
+        "cyan" => "\x1b[36m",
+        "white" => "\x1b[37m",
+        "grey" => "\x1b[90m",
+        _ => return None,
+    })
+}
+
+/// A call to a user-defined function which is currently being executed.
+#[derive(Clone, Debug, PartialEq)]
+struct CallFrame {
+    function: String,  // The name of the function, or `<anonymous>` if the callee was not a variable, e.g., `functions[0]()`.
+    line: usize,  // The line of the call expression.
+    column: usize,  // The column of the call expression.
+}
+
+/// A handle for stopping an interpreter from another thread, e.g., as a kill switch for an untrusted script which runs for too long.
+/// It is created by `Interpreter::cancellation_token()`, and can be cloned and sent to any number of threads.
+///
+/// Unlike the interrupt flag, cancelling lasts: every statement raises an `Interrupted` error, including those in later runs, until
+/// `reset()` is called. The check is made before each statement, so a single long built-in call, e.g., sorting a huge array, finishes first.
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    /// Stops the interpreter at its next statement.
+    pub fn cancel(&self) {
+        self.0.store(true, AtomicOrdering::Relaxed);
+    }
+
+    /// Returns whether `cancel()` has been called since the token was created or last reset.
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(AtomicOrdering::Relaxed)
+    }
+
+    /// Lets the interpreter run statements again.
+    pub fn reset(&self) {
+        self.0.store(false, AtomicOrdering::Relaxed);
+    }
+}
 
 /// Recursively traverses the abstract syntax tree, executes statements, and evaluates expressions.
+///
+/// An interpreter and its values are `Send`, so a server can run one interpreter per request and move it between the threads of a pool.
+/// They are not `Sync`: an interpreter runs one program at a time, and is only stopped from another thread by its `CancellationToken`.
 pub struct Interpreter {
     environment: Environment,
+    hasher: Hasher,  // The hasher used for every dictionary created by this interpreter. It is seeded randomly so that colliding keys cannot be worked out in advance.
+    call_stack: Vec<CallFrame>,  // The calls currently being executed, outermost first. Frames are only popped when a call finishes normally, so after an error it holds the calls the error was raised in.
+    args: Vec<String>,  // The command-line arguments passed to the script, which are returned by `args()`.
+    statements_executed: usize,  // The number of statements executed so far, for `--time`.
+    trace: bool,  // Whether each statement is logged to the standard error stream as it is executed, for `--trace`.
+    pretty: Option<usize>,  // If `print` pretty-prints values, for `--pretty`, the number of elements of each array or dictionary it writes.
+    block_depth: usize,  // The number of blocks currently being executed, including function bodies. Traces are indented by this.
+    profiler: Option<Profiler>,  // Collects how often and for how long each line and function runs, if profiling is enabled for `--profile`.
+    coverage: Option<Coverage>,  // Records which lines are executed, if coverage is enabled for `--coverage`.
+    hooks: Vec<Box<dyn Hooks + Send>>,  // Told about each statement, expression and call, after the profiler and coverage, e.g., by an embedding program.
+    interrupt: Arc<AtomicBool>,  // Set from outside the interpreter, e.g., by a Ctrl-C handler, to stop the program at the next statement.
+    cancellation: CancellationToken,  // Cancelled from outside the interpreter, e.g., by an embedding program, to stop every statement until it is reset.
+    strict: bool,  // Whether shadowing and assigning to variables declared outside the current function are errors, for `--strict`.
+    colour: bool,  // Whether `set_color()` and `colored()` write colours, which they do not for `--no-color` or when the output is not a terminal.
+    truthy: bool,  // Whether conditions which are not Booleans are used by their truthiness (see `Value::is_truthy()`), for `--truthy`.
+    call_depth: usize,  // The number of calls to user-defined functions and `eval()` currently being executed.
+    function_scopes: Vec<usize>,  // For each call currently being executed, outermost first, the index of the scope created for it.
+    turtle: Turtle,  // The pen moved by `forward()` and `turn()`, and the lines it has drawn, which `save_drawing()` writes.
+    files: Vec<PathBuf>,  // The script being run, if it is a file, then each file being run by `include()` from the one before it.
+    output: Box<dyn Write + Send>,  // Where `print` writes to, and `input()` writes its prompt to. This is the standard output stream unless set otherwise.
+    error_output: Box<dyn Write + Send>,  // Where runtime errors, stack traces and traces are written to. This is the standard error stream unless set otherwise.
+    input: Box<dyn InputSource + Send>,  // Where `input()` reads lines from. This is the standard input stream unless set otherwise.
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl Interpreter {
-    /// Initialises a new instance of `Interpreter`.
+    /// Initialises a new instance of `Interpreter`, with no arguments passed to the script.
     pub fn new() -> Self {
+        Self::with_args(Vec::new())
+    }
+
+    /// Initialises a new instance of `Interpreter`, with `args` as the command-line arguments passed to the script.
+    pub fn with_args(args: Vec<String>) -> Self {
         Self {
             environment: Environment::new(),
+            hasher: Hasher::random(),
+            call_stack: Vec::new(),
+            args,
+            statements_executed: 0,
+            trace: false,
+            pretty: None,
+            block_depth: 0,
+            profiler: None,
+            coverage: None,
+            hooks: Vec::new(),
+            interrupt: Arc::new(AtomicBool::new(false)),
+            cancellation: CancellationToken::default(),
+            strict: false,
+            colour: true,
+            truthy: false,
+            call_depth: 0,
+            function_scopes: Vec::new(),
+            turtle: Turtle::new(),
+            files: Vec::new(),
+            output: Box::new(io::stdout()),
+            error_output: Box::new(io::stderr()),
+            input: Box::new(input::Stdin),
+        }
+    }
+
+    /// Sets the file path of the script being run, so that `include()` finds relative paths from its directory rather than the
+    /// current directory, and cannot include the script itself.
+    pub fn set_script_path(&mut self, path: &Path) {
+        self.files = vec![fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())];
+    }
+
+    /// Returns the turtle, and the lines it has drawn so far, e.g., for a GUI to show the drawing without `save_drawing()`.
+    pub fn turtle(&self) -> &Turtle {
+        &self.turtle
+    }
+
+    /// Sets where `input()` reads lines from, e.g., a fixed set of answers when testing a program which asks for input.
+    /// The source has to be `Send`, like the outputs, so that the interpreter can still be moved to another thread.
+    pub fn set_input(&mut self, input: Box<dyn InputSource + Send>) {
+        self.input = input;
+    }
+
+    /// Sets where the program's output is written to, e.g., a buffer in a test or a text box in a GUI.
+    pub fn set_output(&mut self, output: Box<dyn Write + Send>) {
+        self.output = output;
+    }
+
+    /// Sets where runtime errors, their stack traces and traces for `--trace` are written to.
+    pub fn set_error_output(&mut self, error_output: Box<dyn Write + Send>) {
+        self.error_output = error_output;
+    }
+
+    /// Sets whether shadowing a variable or function, and assigning to a variable declared outside the current function, are errors.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    /// Returns whether the interpreter is strict (see `set_strict()`). Source code run by a strict interpreter is also parsed strictly.
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Sets whether `set_color()` and `colored()` write ANSI colour codes. Without them, e.g., when the output is written to a file or
+    /// the terminal cannot show colours, the text is written as it is, so that a program does not have to check before using colours.
+    pub fn set_colour(&mut self, colour: bool) {
+        self.colour = colour;
+    }
+
+    /// Sets whether the conditions of `if` statements and loops can be any value rather than only a Boolean. A value which is not a
+    /// Boolean counts as `false` if it is `null`, `0`, `''` or an empty array or dictionary, and as `true` otherwise. By default,
+    /// such conditions raise an error, as they are often a mistake, e.g., `if (size(a))` where `if (size(a) > 1)` was meant.
+    pub fn set_truthy(&mut self, truthy: bool) {
+        self.truthy = truthy;
+    }
+
+    /// Evaluates the condition of an `if` statement or loop, returning whether it goes ahead, or `None` if the value cannot be a condition.
+    fn evaluate_condition(&mut self, condition: &Expr) -> Result<Option<bool>, ErrorType> {
+        Ok(match self.evaluate(condition)? {
+            Value::Bool(x) => Some(x),
+            value if self.truthy => Some(value.is_truthy()),
+            _ => None,
+        })
+    }
+
+    /// Returns the index of the scope created for the innermost call being executed, or 0 (the base scope) outside any function.
+    fn function_scope(&self) -> usize {
+        self.function_scopes.last().copied().unwrap_or(0)
+    }
+
+    /// In strict mode, checks that declaring `name` in the current scope would not shadow a name declared in an enclosing scope.
+    /// Because functions see the variables of whichever code called them, only the scopes of the current call and the base scope count,
+    /// so that, e.g., the variables of a recursive function do not shadow those of the call before.
+    fn check_shadowing(&self, name: &str, line: usize, column: usize) -> Result<(), ErrorType> {
+        match self.environment.scope_of(name) {
+            Some(scope) if self.strict && scope + 1 < self.environment.depth() && (scope == 0 || scope >= self.function_scope()) => {
+                Err(ErrorType::Shadowing { name: String::from(name), line, column })
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// In strict mode, checks that `name` is declared within the current call, if there is one, so that it can be assigned to.
+    fn check_outer_assignment(&self, name: &str, line: usize, column: usize) -> Result<(), ErrorType> {
+        match self.environment.scope_of(name) {
+            Some(scope) if self.strict && !self.function_scopes.is_empty() && scope < self.function_scope() => {
+                Err(ErrorType::OuterAssignment { name: String::from(name), line, column })
+            },
+            _ => Ok(()),
         }
     }
 
+    /// Sets the flag which stops the program when it is set, e.g., from a Ctrl-C handler on another thread.
+    /// The next statement executed after the flag is set raises an `Interrupted` error, which clears the flag again.
+    /// Every iteration of a loop and every call executes a statement, so this also stops infinite loops and recursion.
+    pub fn set_interrupt_flag(&mut self, interrupt: Arc<AtomicBool>) {
+        self.interrupt = interrupt;
+    }
+
+    /// Returns a token which stops the program when it is cancelled, e.g., from another thread. See `CancellationToken`.
+    pub fn cancellation_token(&self) -> CancellationToken {
+        self.cancellation.clone()
+    }
+
+    /// Adds hooks which are told about each statement, expression and call as the program runs. See `Hooks`.
+    pub fn add_hooks(&mut self, hooks: Box<dyn Hooks + Send>) {
+        self.hooks.push(hooks);
+    }
+
+    /// Starts collecting how often and for how long each line and function runs.
+    pub fn enable_profiling(&mut self) {
+        self.profiler = Some(Profiler::default());
+    }
+
+    /// Returns the statistics collected since profiling was enabled, or `None` if it has not been.
+    pub fn profiler(&self) -> Option<&Profiler> {
+        self.profiler.as_ref()
+    }
+
+    /// Starts recording which lines of the source code run afterwards are executed.
+    pub fn enable_coverage(&mut self) {
+        self.coverage = Some(Coverage::default());
+    }
+
+    /// Returns the lines recorded since coverage was enabled, or `None` if it has not been.
+    pub fn coverage(&self) -> Option<&Coverage> {
+        self.coverage.as_ref()
+    }
+
+    /// Sets whether each statement is logged to the standard error stream as it is executed.
+    pub fn set_trace(&mut self, trace: bool) {
+        self.trace = trace;
+    }
+
+    /// Sets whether `print` writes arrays and dictionaries pretty-printed, and if so, how many elements of each it writes before
+    /// saying how many more there are. See `Value::pretty()`. By default, values are written on one line in full.
+    pub fn set_pretty(&mut self, max_elements: Option<usize>) {
+        self.pretty = max_elements;
+    }
+
+    /// Returns the number of elements `print` writes of each array or dictionary, if it pretty-prints them (see `set_pretty()`).
+    pub fn pretty(&self) -> Option<usize> {
+        self.pretty
+    }
+
+    /// Logs a line of the trace, indented by the current block depth plus `extra_depth`.
+    /// Writes `text` to the output, or raises an error at the statement or call which wrote it if it cannot be written, e.g., because
+    /// the program reading it has stopped. The output is flushed with `flush` so that a prompt is shown before waiting for input.
+    fn write_output(&mut self, text: &str, flush: bool, line: usize, column: usize) -> Result<(), ErrorType> {
+        let result = self.output.write_all(text.as_bytes()).and_then(|_| if flush { self.output.flush() } else { Ok(()) });
+        result.map_err(|error| ErrorType::CannotWriteOutput { reason: error.to_string(), line, column })
+    }
+
+    fn trace_line(&mut self, extra_depth: usize, text: &str) {
+        // As with error messages, a trace which cannot be written is dropped rather than stopping the program.
+        let _ = writeln!(self.error_output, "{}{}", "  ".repeat(self.block_depth + extra_depth), text);
+    }
+
     /// Executes statements in the given abstract syntax tree.
-    pub fn interpret(&mut self, ast: Vec<Stmt>) {
+    /// If the last statement is an expression statement other than an assignment, e.g., `1 + 2`, its value is returned so that the REPL can echo it.
+    pub fn interpret(&mut self, ast: Vec<Stmt>) -> Result<Option<Value>, ErrorType> {
+        if let Some(coverage) = &mut self.coverage {
+            coverage.add_statements(&ast);
+        }
+        // An interrupt which arrived after the last program finished is not meant for this one.
+        self.interrupt.store(false, AtomicOrdering::Relaxed);
+        let mut last_value = None;
         for stmt in &ast {
             // Iterate through each statement.
-            if let Err(e) = self.execute(stmt) {
-                // If an error occurred in the execution of the statement, report the error and terminate execution.
-                error::report_errors(&[e]);
-                return;
+            match self.execute_keeping_value(stmt) {
+                Ok(value) => last_value = value,
+                Err(e) => {
+                    // If an error occurred in the execution of the statement, report the error and the calls it was raised in, and terminate execution.
+                    // The error is also returned so that the driver code can exit with the appropriate exit code.
+                    let _ = error::write_errors(&mut self.error_output, std::slice::from_ref(&e), None);
+                    let _ = self.report_stack_trace();
+                    // Clear the stack so that the next source code string run in a REPL session starts afresh.
+                    self.call_stack.clear();
+                    return Err(e);
+                },
+            }
+        }
+        Ok(last_value)
+    }
+
+    /// Reports errors found before the source code could be run, e.g., while parsing it, to the error output, in the same way as
+    /// `interpret()` reports a runtime error.
+    pub fn report_errors(&mut self, errors: &[ErrorType]) {
+        // As with runtime errors, errors which cannot be written are dropped.
+        let _ = error::write_errors(&mut self.error_output, errors, None);
+    }
+
+    /// Evaluates source code which is a single expression, e.g., a formula, against the current variables and functions, and returns
+    /// its value without printing it. Unlike `interpret()`, the first error found while tokenizing, parsing or evaluating is returned
+    /// rather than reported, so that the program embedding the interpreter can show it as it likes.
+    pub fn eval_expression(&mut self, source: &str) -> Result<Value, ErrorType> {
+        let tokens = Tokenizer::new(source).collect::<Result<Vec<Token>, ErrorType>>()?;
+        let expr = Parser::new(tokens).parse_expression()?;
+        self.eval_parsed(&expr)
+    }
+
+    /// Evaluates an expression which has already been parsed, e.g., by `Parser::parse_expression()`, as `eval_expression()` does.
+    /// A formula which is evaluated many times only has to be parsed once.
+    pub fn eval_parsed(&mut self, expr: &Expr) -> Result<Value, ErrorType> {
+        // As with `interpret()`, an interrupt which arrived before now is not meant for this expression.
+        self.interrupt.store(false, AtomicOrdering::Relaxed);
+        let result = self.evaluate(expr);
+        // As with `interpret()`, the calls an error was raised in are forgotten, so that the next expression starts afresh.
+        self.call_stack.clear();
+        result
+    }
+
+    /// Returns the path of the file which `store_load()` and `store_save()` keep values in, next to the script being run. An
+    /// included file shares the store of the script which includes it.
+    fn store_path(&self) -> PathBuf {
+        store::path(self.files.first().map(PathBuf::as_path))
+    }
+
+    /// Returns the values in the store, or none if it does not exist yet.
+    fn read_store(&self, line: usize, column: usize) -> Result<Dictionary, ErrorType> {
+        let path = self.store_path();
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(Dictionary::with_capacity(1, self.hasher)),
+            Err(error) => return Err(ErrorType::CannotReadFile { path: path.display().to_string(), reason: error.to_string(), line, column }),
+        };
+        store::parse(&text).ok_or_else(|| ErrorType::CorruptStore { path: path.display().to_string(), line, column })
+    }
+
+    /// Runs the file at the path for `include()`, in the current scope. A relative path is found from the directory of the file
+    /// which includes it, or from the current directory if that is not known, e.g., in the REPL.
+    fn include(&mut self, path: &str, line: usize, column: usize) -> Result<(), ErrorType> {
+        let cannot_read = |error: io::Error| ErrorType::CannotReadFile { path: String::from(path), reason: error.to_string(), line, column };
+        let relative_to = self.files.last().and_then(|file| file.parent());
+        let file = match relative_to {
+            Some(directory) if Path::new(path).is_relative() => directory.join(path),
+            _ => PathBuf::from(path),
+        };
+        // Files are compared by their canonical paths, so that, e.g., `a.nea` and `./a.nea` are known to be the same.
+        let file = fs::canonicalize(file).map_err(cannot_read)?;
+        if self.files.contains(&file) {
+            return Err(ErrorType::IncludeCycle { path: String::from(path), line, column });
+        }
+        let source = fs::read_to_string(&file).map_err(cannot_read)?;
+
+        // As with `eval()`, the first error found is raised, with its position in the included file.
+        let tokens = Tokenizer::new(&source).collect::<Result<Vec<Token>, ErrorType>>()?;
+        let mut parser = Parser::with_error_limit(tokens, ErrorLimit { max_reported: None, fail_fast: true });
+        parser.set_strict(self.strict);
+        let ast = parser.parse_unreported().map_err(|mut errors| errors.remove(0))?;
+
+        // The file is recorded as a call, so that the stack trace of an error raised in it says which file it was.
+        self.call_stack.push(CallFrame { function: format!("include('{}')", path), line, column });
+        self.files.push(file);
+        let result = ast.iter().try_for_each(|stmt| self.execute(stmt));
+        self.files.pop();
+        match result {
+            // As with calls, the frame is kept for other errors, to be reported in the stack trace.
+            Ok(()) | Err(ErrorType::ThrownReturn { .. } | ErrorType::ThrownBreak { .. }) => {
+                self.call_stack.pop();
+                result
+            },
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Executes the statement, returning its value if it is an expression statement other than an assignment.
+    fn execute_keeping_value(&mut self, stmt: &Stmt) -> Result<Option<Value>, ErrorType> {
+        match &stmt.stmt_type {
+            // Keep the value of expressions instead of discarding it. Assignments are left out, as echoing `x = 5` would only repeat the line.
+            StmtType::Expression { expression } if !matches!(expression.expr_type, ExprType::Assignment { .. }) => {
+                self.instrument(stmt, |interpreter| interpreter.evaluate(expression)).map(Some)
+            },
+            _ => self.execute(stmt).map(|()| None),
+        }
+    }
+
+    /// Returns every name which can currently be accessed, in alphabetical order. This is used, e.g., for tab completion in the REPL.
+    pub fn visible_names(&self) -> Vec<String> {
+        self.environment.visible_names()
+    }
+
+    /// Returns the number of statements executed by this interpreter so far, counting each time a statement is repeated, e.g., in a loop.
+    pub fn statements_executed(&self) -> usize {
+        self.statements_executed
+    }
+
+    /// Returns the value of the global variable or function, e.g., a result left by a script, or `None` if it was never declared.
+    pub fn get_global(&self, name: &str) -> Option<Value> {
+        self.environment.get_global(name).cloned()
+    }
+
+    /// Declares a global variable with the value, e.g., input data for a script, as if by `var name = value`.
+    /// Any global already called `name`, including a built-in function, is replaced.
+    pub fn set_global(&mut self, name: &str, value: Value) {
+        self.environment.set_global(String::from(name), value);
+    }
+
+    /// Returns every global variable and function, with its value, in alphabetical order of name, e.g., for saving a snapshot of the session.
+    /// Built-in functions are left out unless their names have been declared again.
+    pub fn globals(&self) -> Vec<(String, Value)> {
+        self.environment.globals()
+    }
+
+    /// Returns every variable and user-defined function which can currently be accessed, with its value, in alphabetical order of name.
+    pub fn variables(&self) -> Vec<(String, Value)> {
+        self.environment.variables()
+    }
+
+    /// Writes the calls in which the last error was raised to the error output, most recent call first.
+    fn report_stack_trace(&mut self) -> io::Result<()> {
+        if self.call_stack.is_empty() {
+            // The error was raised outside any function, so the line and column of the error are enough.
+            return Ok(());
+        }
+
+        let catalogue = catalogue::current();
+        writeln!(self.error_output, "{}", catalogue.render(Key::StackTrace, &[]))?;
+        let depth = self.call_stack.len();
+        for (i, frame) in self.call_stack.iter().rev().enumerate() {
+            if depth > 2 * STACK_TRACE_LIMIT && i >= STACK_TRACE_LIMIT && i < depth - STACK_TRACE_LIMIT {
+                // Summarise the calls in the middle of a deep stack with a single line.
+                if i == STACK_TRACE_LIMIT {
+                    writeln!(self.error_output, "    {}", catalogue.render(Key::MoreCalls, &[("count", (depth - 2 * STACK_TRACE_LIMIT).to_string())]))?;
+                }
+                continue;
             }
+            writeln!(self.error_output, "    {}", catalogue.render(Key::StackFrame, &[
+                ("function", frame.function.clone()),
+                ("line", frame.line.to_string()),
+                ("column", frame.column.to_string()),
+            ]))?;
         }
+        Ok(())
     }
 
     /// Executes the given statement.
     fn execute(&mut self, stmt: &Stmt) -> Result<(), ErrorType> {
+        self.instrument(stmt, |interpreter| interpreter.execute_stmt(stmt))
+    }
+
+    /// Runs `run`, which executes the statement, with the statement counted, traced, profiled and covered as the options say.
+    /// Top-level expression statements are evaluated rather than executed, so that the REPL can echo their value, but still go through this.
+    fn instrument<T>(&mut self, stmt: &Stmt, run: impl FnOnce(&mut Self) -> Result<T, ErrorType>) -> Result<T, ErrorType> {
+        if self.interrupt.swap(false, AtomicOrdering::Relaxed) || self.cancellation.is_cancelled() {
+            return Err(ErrorType::Interrupted { line: stmt.line, column: stmt.column });
+        }
+        self.statements_executed += 1;
+        // Blocks are positioned at their closing brace, so they are not traced, profiled or covered themselves.
+        // Instead, the statements in them are indented in the trace, and their time is left to the statements in them and the statement they belong to.
+        if matches!(stmt.stmt_type, StmtType::Block { .. }) {
+            return run(self);
+        }
+        if self.trace {
+            self.trace_line(0, &format!("line {}: {}", stmt.line, stmt.stmt_type.kind()));
+        }
+        self.run_hooks(|hooks| hooks.before_statement(stmt));
+        let result = run(self);
+        self.run_hooks(|hooks| hooks.after_statement(stmt));
+        result
+    }
+
+    /// Calls `event` with the profiler and coverage, if they are enabled, and then with each of the hooks added by `add_hooks()`.
+    fn run_hooks(&mut self, mut event: impl FnMut(&mut dyn Hooks)) {
+        if let Some(profiler) = &mut self.profiler {
+            event(profiler);
+        }
+        if let Some(coverage) = &mut self.coverage {
+            event(coverage);
+        }
+        for hooks in &mut self.hooks {
+            event(hooks.as_mut());
+        }
+    }
+
+    /// Executes the statement for `execute()`, which wraps it with the instrumentation.
+    fn execute_stmt(&mut self, stmt: &Stmt) -> Result<(), ErrorType> {
         match &stmt.stmt_type {
             StmtType::Block { body } => {
                 // Create a new variable scope.
                 self.environment.new_scope();
+                self.block_depth += 1;
 
                 // Recursively execute each statement in the body of the `Block`.
                 for block_stmt in body {
                     // We cannot just use `?` here as it will exit this function call right away and not call `exit_scope()`.
                     if let Err(e) = self.execute(block_stmt) {
                         self.environment.exit_scope();
+                        self.block_depth -= 1;
                         return Err(e);
                     }
                 }
                 
                 // Exit and remove the scope.
                 self.environment.exit_scope();
+                self.block_depth -= 1;
                 Ok(())
             },
 
             StmtType::Break => {
                 // Throw a `ThrownBreak` error which can be caught in the `While` statement (see below).
                 // This immediately stops execution and unwinds the call stack to the nearest parent `While` statement, which emulates the behaviour of a `break` statement.
-                Err(ErrorType::ThrownBreak { line: stmt.line })
+                Err(ErrorType::ThrownBreak { line: stmt.line, column: stmt.column })
             },
 
             StmtType::Expression { expression } => {
@@ -67,19 +625,33 @@ impl Interpreter {
                 Ok(())
             },
 
-            StmtType::Function { name, parameters, body } => {
+            StmtType::For { initialiser, condition, increment, body } => {
+                // Create a new variable scope, so that variables declared in the initialising statement are not visible after the loop.
+                self.environment.new_scope();
+
+                // As with `Block`, we cannot use `?` inside the loop as it will not call `exit_scope()`.
+                let result = self.execute_for(initialiser.as_deref(), condition.as_ref(), increment.as_deref(), body.as_ref());
+
+                // Exit and remove the scope.
+                self.environment.exit_scope();
+                result
+            },
+
+            StmtType::Function { name, parameters, body, .. } => {
+                self.check_shadowing(name, stmt.line, stmt.column)?;
                 // Declare the function as a new `Value` in the environment.
+                // The body is copied into a new `Arc`, so that each time the declaration runs, it makes a different function.
                 self.environment.declare(name.clone(), &Value::Function {
                     parameters: parameters.clone(),
-                    body: *body.clone(),
+                    body: Arc::new(*body.clone()),
                 });
                 Ok(())
             },
 
             StmtType::If { condition, then_body, else_body } => {
-                match self.evaluate(condition)? {
-                    Value::Bool(condition_bool) => {
-                        // If the condition evaluated to a Boolean value...
+                match self.evaluate_condition(condition)? {
+                    Some(condition_bool) => {
+                        // If the condition evaluated to a Boolean value, or one which can be used as one...
                         if condition_bool {
                             // and the condition is `true`, execute the `then` body.
                             self.execute(then_body.as_ref())?;
@@ -92,13 +664,18 @@ impl Interpreter {
                     },
                     // If the condition did not evaluate to a Boolean value, we cannot use it as the condition in an `If` statement.
                     // Raise a clear and specific error.
-                    _ => Err(ErrorType::IfConditionNotBoolean { line: condition.line })
+                    None => Err(ErrorType::IfConditionNotBoolean { line: condition.line, column: condition.column })
                 }
             },
 
             StmtType::Print { expression } => {
                 // Print the evaluated expression.
-                println!("{}", self.evaluate(expression)?);
+                let value = self.evaluate(expression)?;
+                let text = match self.pretty {
+                    Some(max_elements) => value.pretty(max_elements),
+                    None => value.to_string(),
+                };
+                self.write_output(&format!("{}\n", text), false, stmt.line, stmt.column)?;
                 Ok(())
             },
 
@@ -107,26 +684,93 @@ impl Interpreter {
                 // We also have to pass the value to be used as the return value of the function call.
                 Err(ErrorType::ThrownReturn {
                     value: self.evaluate(expression)?,
-                    line: stmt.line
+                    line: stmt.line,
+                    column: stmt.column
                 })
             },
 
+            StmtType::Try { body, catch, finally } => {
+                // The calls which an error is raised in are left on the stack for the stack trace, so they are removed if it is caught.
+                let calls = self.call_stack.len();
+                let mut result = self.execute(body.as_ref());
+                if let (Err(error), Some((name, catch_body))) = (&result, catch) {
+                    // `break` and `return` are not errors, and an interrupted program is stopped even in a `try` block.
+                    if !matches!(error, ErrorType::ThrownBreak { .. } | ErrorType::ThrownReturn { .. } | ErrorType::Interrupted { .. }) {
+                        self.call_stack.truncate(calls);
+                        let caught = self.error_value(error);
+
+                        // The error is declared in a scope of its own, around the scope of the `catch` block, as the parameters of a function are.
+                        self.environment.new_scope();
+                        result = self.check_shadowing(name, stmt.line, stmt.column).and_then(|()| {
+                            self.environment.declare(name.clone(), &caught);
+                            self.execute(catch_body.as_ref())
+                        });
+                        self.environment.exit_scope();
+                    }
+                }
+                if let Some(finally) = finally {
+                    // The `finally` block is executed however the others finished, even by `break` or `return`, which carry on afterwards
+                    // unless it raises an error of its own, or `break`s or `return`s itself.
+                    self.execute(finally.as_ref())?;
+                }
+                result
+            },
+
             StmtType::VarDecl { name, value } => {
                 // Evaluate the value.
                 let value_eval = &self.evaluate(value)?;
 
                 // Declare the new variable in the environment.
+                self.check_shadowing(name, stmt.line, stmt.column)?;
                 self.environment.declare(name.clone(), value_eval);
+                if self.trace {
+                    self.trace_line(1, &format!("{} = {}", name, value_eval));
+                }
                 Ok(())
             },
             
+            StmtType::VarUnpack { names, value } => {
+                // Evaluate the value, then declare each variable as the element of the array for it.
+                let value_eval = self.evaluate(value)?;
+                let values = unpack(&value_eval, names.len(), value.line, value.column)?;
+                for (name, element) in names.iter().zip(values) {
+                    self.check_shadowing(name, stmt.line, stmt.column)?;
+                    self.environment.declare(name.clone(), element);
+                    if self.trace {
+                        self.trace_line(1, &format!("{} = {}", name, element));
+                    }
+                }
+                Ok(())
+            },
+
+            StmtType::Repeat { count, body } => {
+                // The count is evaluated once, so changing the variables in it does not change how many times the body is executed.
+                let count_eval = self.evaluate(count)?;
+                let times = match count_eval {
+                    Value::Number(x) if x >= 0.0 && x.fract() == 0.0 => x as usize,
+                    // A body cannot be executed a negative or fractional number of times, and infinitely is what `while (true)` is for.
+                    Value::Number(..) => return Err(ErrorType::InvalidRepeatCount { got: count_eval, line: count.line, column: count.column }),
+                    _ => return Err(ErrorType::ExpectedType { expected: String::from("Number"), got: count_eval.type_to_string(), line: count.line, column: count.column }),
+                };
+
+                for _ in 0..times {
+                    match self.execute(body.as_ref()) {
+                        // As with `while` loops, a `ThrownBreak` error breaks the loop, and other errors bubble up.
+                        Ok(()) => (),
+                        Err(ErrorType::ThrownBreak {..}) => break,
+                        Err(e) => return Err(e),
+                    }
+                }
+                Ok(())
+            },
+
             StmtType::While { condition, body } => {
                 loop {
-                    let continue_ = match self.evaluate(condition)? {
-                        // If `condition` evaluated to a Boolean value, set `continue_` to the result of that.
-                        Value::Bool(condition_bool) => condition_bool,
+                    let continue_ = match self.evaluate_condition(condition)? {
+                        // If `condition` evaluated to a Boolean value, or one which can be used as one, set `continue_` to the result of that.
+                        Some(condition_bool) => condition_bool,
                         // Otherwise, it cannot be used as the condition for a loop, so raise a specific error.
-                        _ => return Err(ErrorType::LoopConditionNotBoolean { line: stmt.line }),
+                        None => return Err(ErrorType::LoopConditionNotBoolean { line: stmt.line, column: stmt.column }),
                     };
 
                     // If the `condition` evaluated to `false`, stop the loop.
@@ -148,12 +792,79 @@ impl Interpreter {
         }
     }
 
+    /// Returns the error caught by a `catch` block as a dictionary of its code, e.g., `E0028`, its message, and its line and column.
+    fn error_value(&self, error: &ErrorType) -> Value {
+        // Every error has a line and a column among the values filled into its message.
+        let arguments = error.arguments();
+        let position = |name: &str| arguments.iter()
+            .find_map(|(argument, value)| if *argument == name { value.parse().ok() } else { None })
+            .map_or(Value::Null, Value::Number);
+        let entries = [
+            ("code", Value::String_(String::from(error.code()))),
+            ("message", Value::String_(catalogue::current().render_message(error))),
+            ("line", position("line")),
+            ("column", position("column")),
+        ];
+        let mut dictionary = Dictionary::with_capacity(entries.len(), self.hasher);
+        for (key, value) in entries {
+            // The keys are strings, which can always be hashed.
+            let _ = dictionary.insert(&Value::String_(String::from(key)), &value, 0, 0);
+        }
+        Value::Dictionary(dictionary)
+    }
+
+    /// Executes a `for` loop inside the scope created for it.
+    fn execute_for(&mut self, initialiser: Option<&Stmt>, condition: Option<&Expr>, increment: Option<&Stmt>, body: &Stmt) -> Result<(), ErrorType> {
+        // Execute the initialising statement once, before the loop starts.
+        if let Some(init) = initialiser {
+            self.execute(init)?;
+        }
+
+        loop {
+            if let Some(condition) = condition {
+                match self.evaluate_condition(condition)? {
+                    // If `condition` evaluated to `false`, stop the loop.
+                    Some(false) => break,
+                    Some(true) => (),
+                    // Otherwise, it cannot be used as the condition for a loop, so raise a specific error.
+                    None => return Err(ErrorType::LoopConditionNotBoolean { line: condition.line, column: condition.column }),
+                }
+            }
+            // If no condition is given, the loop runs until a `break`.
+
+            match self.execute(body) {
+                // If the body executed with no errors, continue as normal.
+                Ok(()) => (),
+                // If a `ThrownBreak` error was thrown somewhere in the body, break the loop.
+                Err(ErrorType::ThrownBreak {..}) => break,
+                // If a different error was thrown, continue to bubble up that error.
+                Err(e) => return Err(e),
+            }
+
+            // Execute the incrementing statement at the end of each iteration.
+            if let Some(inc) = increment {
+                self.execute(inc)?;
+            }
+        }
+        Ok(())
+    }
+
     /// Evaluates the given expression.
     fn evaluate(&mut self, expr: &Expr) -> Result<Value, ErrorType> {
+        let value = self.evaluate_expr(expr)?;
+        // Only the hooks added by `add_hooks()` are told about expressions, so there is nothing to do without any.
+        for hooks in &mut self.hooks {
+            hooks.after_expression(expr, &value);
+        }
+        Ok(value)
+    }
+
+    /// Evaluates the expression for `evaluate()`, which reports its value to the hooks.
+    fn evaluate_expr(&mut self, expr: &Expr) -> Result<Value, ErrorType> {
         match &expr.expr_type {
             ExprType::Array { elements } => {
                 // Evaluate each expression in the array to a `Value`, and collect those in an array.
-                let values: Result<Vec<Value>, _> = elements.iter().map(|x| self.evaluate(x)).collect();
+                let values: Result<Array, _> = elements.iter().map(|x| self.evaluate(x)).collect();
                 Ok(Value::Array(values?))
             },
 
@@ -161,21 +872,45 @@ impl Interpreter {
                 // Evaluate the value.
                 let value_eval = self.evaluate(value.as_ref())?;
 
-                // Construct the pointer to the target.
-                match self.construct_pointer(target, expr.line) {
-                    // Use the pointer to update the value in the environment.
-                    Ok(pointer) => self.environment.update(&pointer, &value_eval, expr.line)?,
-                    // If an error occurred (invalid assignment target), continue to bubble it up.
-                    Err(e) => return Err(e),
-                };
+                if let ExprType::Tuple { elements } = &target.expr_type {
+                    // Assigning to a tuple unpacks the array into its targets in order, e.g., `a, b = b, a` swaps `a` and `b`.
+                    let values = unpack(&value_eval, elements.len(), value.line, value.column)?;
+
+                    // The targets are assigned in order, as a later target can use an earlier one, e.g., `i, a[i] = 1, 2`. If one of them
+                    // cannot be assigned, those before it are put back as they were, so that an unpacking which fails changes nothing.
+                    let mut assigned: Vec<(Pointer, Option<Value>)> = Vec::new();
+                    for (element, element_value) in elements.iter().zip(values) {
+                        match self.assign(element, element_value, expr.line, expr.column) {
+                            Ok(undo) => assigned.push(undo),
+                            Err(error) => {
+                                for (pointer, old) in assigned.into_iter().rev() {
+                                    self.environment.restore(&pointer, old, expr.line, expr.column)?;
+                                }
+                                return Err(error);
+                            },
+                        }
+                    }
+                } else {
+                    self.assign(target, &value_eval, expr.line, expr.column)?;
+                }
 
                 // Evaluate to the right-hand side value, e.g., a = (b = 5) -> a = 5.
                 Ok(value_eval)
             },
 
             ExprType::Binary { left, operator, right } => {
-                // Evaluate the left- and right-hand side expressions.
+                // Evaluate the left-hand side expression.
                 let left_eval = self.evaluate(left.as_ref())?;
+
+                // `and` and `or` short-circuit: if the left-hand side decides the result, the right-hand side is not evaluated, so that,
+                // e.g., `x != 0 and 10 / x > 1` never divides by 0. Otherwise, both sides are checked to be Booleans below.
+                match (&operator.type_, &left_eval) {
+                    (TokenType::And, Value::Bool(false)) => return Ok(Value::Bool(false)),
+                    (TokenType::Or, Value::Bool(true)) => return Ok(Value::Bool(true)),
+                    _ => (),
+                }
+
+                // Evaluate the right-hand side expression.
                 let right_eval = self.evaluate(right.as_ref())?;
 
                 match operator.type_ {
@@ -198,6 +933,7 @@ impl Interpreter {
                                     got_left: left_eval.type_to_string(),
                                     got_right: right_eval.type_to_string(),
                                     line: left.line,
+                                    column: left.column,
                                 })
                             }
                         }
@@ -235,6 +971,7 @@ impl Interpreter {
                                     got_left: left_eval.type_to_string(),
                                     got_right: right_eval.type_to_string(),
                                     line: left.line,
+                                    column: left.column,
                                 })
                             }
                         }
@@ -244,12 +981,16 @@ impl Interpreter {
                         match (&left_eval, &right_eval) {
                             (Value::Number(left_num), Value::Number(right_num)) => Ok(Value::Number(left_num + right_num)),
                             (Value::String_(left_str), Value::String_(right_str)) => Ok(Value::String_(format!("{}{}", left_str, right_str))),
+                            // If only one side is a string, the other side is joined on as `print` would write it, e.g., `'Score: ' + 10`.
+                            (Value::String_(left_str), _) => Ok(Value::String_(format!("{}{}", left_str, right_eval))),
+                            (_, Value::String_(right_str)) => Ok(Value::String_(format!("{}{}", left_eval, right_str))),
                             (_, _) => {
                                 Err(ErrorType::BinaryTypeError {
                                     expected: String::from("Number or String"),
                                     got_left: left_eval.type_to_string(),
                                     got_right: right_eval.type_to_string(),
                                     line: left.line,
+                                    column: left.column,
                                 })
                             }
                         }
@@ -265,12 +1006,19 @@ impl Interpreter {
                                     TokenType::Star => Ok(Value::Number(left_num * right_num)),
                                     TokenType::Slash => {
                                         if *right_num == 0.0 {
-                                            Err(ErrorType::DivideByZero { line: right.line })
+                                            Err(ErrorType::DivideByZero { line: right.line, column: right.column })
                                         } else {
                                             Ok(Value::Number(left_num / right_num))
                                         }
                                     },
-                                    TokenType::Percent => Ok(Value::Number(left_num % right_num)),
+                                    TokenType::Percent => {
+                                        // Without this check, `x % 0` would silently evaluate to NaN.
+                                        if *right_num == 0.0 {
+                                            Err(ErrorType::ModuloByZero { line: right.line, column: right.column })
+                                        } else {
+                                            Ok(Value::Number(left_num % right_num))
+                                        }
+                                    },
                                     _ => unreachable!(),
                                 }
                             },
@@ -280,6 +1028,7 @@ impl Interpreter {
                                     got_left: left_eval.type_to_string(),
                                     got_right: right_eval.type_to_string(),
                                     line: left.line,
+                                    column: left.column,
                                 })
                             }
                         }
@@ -289,306 +1038,113 @@ impl Interpreter {
                 }
             },
 
-            ExprType::Call { callee, arguments } => {
-                // Evaluate the callee.
-                let function = self.evaluate(callee.as_ref())?;
-
-                match function {
-                    Value::Function { parameters, body } => {
-                        // User-defined functions.
-                        if arguments.len() != parameters.len() {
-                            // If the number of arguments given does not match the number of parameters expected, raise a detailed error.
-                            return Err(ErrorType::ArgParamNumberMismatch {
-                                arg_number: arguments.len(),
-                                param_number: parameters.len(),
-                                line: expr.line
-                            });
-                        }
+            ExprType::Call { callee, arguments, named_arguments } => self.call(expr, callee, arguments, named_arguments),
 
-                        // Iterate through the arguments and evaluate each.
-                        let mut args_eval = Vec::new();
-                        for arg in arguments.iter() {
-                            args_eval.push(self.evaluate(arg)?);
-                        }
+            ExprType::Dictionary { elements } => {
+                // Create a new hash table, with enough room for all of the elements so that it does not have to rehash while they are inserted.
+                let mut hash_table = Dictionary::with_capacity(elements.len(), self.hasher);
 
-                        // Create a new variable scope for the arguments and function execution.
-                        self.environment.new_scope();
+                // Iterate through the key-value pairs of the given elements.
+                for key_value in elements.iter() {
+                    // Evaluate each of the keys and values.
+                    let key_eval = self.evaluate(&key_value.key)?;
+                    let value_eval = self.evaluate(&key_value.value)?;
 
-                        // Declare the arguments in the new scope.
-                        for i in 0..arguments.len() {
-                            self.environment.declare(parameters[i].clone(), &args_eval[i]);
-                        }
+                    // Insert the evaluated key and value into the table.
+                    hash_table.insert(&key_eval, &value_eval, expr.line, expr.column)?;
+                }
+                Ok(Value::Dictionary(hash_table))
+            },
 
-                        // Execute function body.
-                        let exec_result = self.execute(&body);
+            ExprType::Element { array, index } => {
+                // Note that 'array' refers to anything to the left of the index, e.g.,
+                // the 'array' in `a[1][2]` is `a[1]` and the index is `2`.
 
-                        // Exit scope.
-                        self.environment.exit_scope();
+                // Evaluate the index expression.
+                let index_eval = self.evaluate(index.as_ref())?;
 
-                        match exec_result {
-                            // If the function execution did not raise any error, evaluate the call to `Null` (no return statement used in function).
-                            Ok(()) => Ok(Value::Null),
-                            // If the execution ended because of a raised `ThrownReturn` error, then evaluate the call to the given return vale.
-                            Err(ErrorType::ThrownReturn { value, line: _ }) => Ok(value),
-                            // If another error occurred, continue to bubble up the error.
-                            Err(e) => Err(e),
+                match self.evaluate(array.as_ref())? {  // Evaluate `array`.
+                    Value::Array(array) => {
+                        // If the evaluated 'array' is an Array variant, convert the evaluated index to a `usize` index.
+                        let index_num = environment::index_value_to_usize(&index_eval, index.line, index.column)?;
+                        
+                        // Try to get the element of `array` at index `index_num`.
+                        if let Some(element) = array.get(index_num) {
+                            Ok(element.clone())
+                        } else {
+                            // In this case, `index_num` was out of bounds.
+                            Err(ErrorType::OutOfBoundsIndex { index: index_num, line: expr.line, column: expr.column })
                         }
                     },
+                    Value::Dictionary(dict) => {
+                        // If the evaluated 'array' is a Dictionary variant, get value from the `Dictionary` object.
+                        dict.get(&index_eval, expr.line, expr.column).cloned()
+                    },
+                    Value::String_(s) => {
+                        // If the evaluated 'array' is a String variant, convert the evaluated index to a `usize` index.
+                        let index_num = environment::index_value_to_usize(&index_eval, index.line, index.column)?;
 
-                    Value::BuiltinFunction(function) => {
-                        // Built-in functions.
-                        match function {
-                            BuiltinFunction::Append => {
-                                // We want two arguments: the target array, and the value to append.
-                                if arguments.len() != 2 {
-                                    // If the number of given arguments was not 2, raise an error, providing the number of arguments received.
-                                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 2, line: expr.line });
-                                }
-
-                                let target = &arguments[0];
-                                let target_eval = self.evaluate(target)?;
-                                let pointer = self.construct_pointer(target, target.line)?;
-
-                                let value_eval = self.evaluate(&arguments[1])?;
-
-                                if let Value::Array(mut array) = target_eval {
-                                    // If `target` is an Array variant of Value, append and update the environment using the pointer.
-                                    array.push(value_eval);
-                                    self.environment.update(&pointer, &Value::Array(array.clone()), expr.line)?;
+                        // Try to get the character of `s` at index `index_num`.
+                        if let Some(c) = s.chars().nth(index_num) {
+                            Ok(Value::String_(String::from(c)))
+                        } else {
+                            // In this case, `index_num` was out of bounds.
+                            Err(ErrorType::OutOfBoundsIndex { index: index_num, line: expr.line, column: expr.column })
+                        }
+                    },
+                    // If the 'array' was not an Array, a Dictionary, or a String variant, it cannot be indexed.
+                    _ => Err(ErrorType::NotIndexable { line: array.line, column: array.column })
+                }
+            },
 
-                                    // Evaluate to changed array.
-                                    Ok(Value::Array(array))
-                                } else {
-                                    // We can only append to arrays.
-                                    // If `target` is not an Array variant, raise an `ExpectedTypeError` and provide the received type.
-                                    Err(ErrorType::ExpectedType { expected: String::from("Array"), got: target_eval.type_to_string(), line: target.line })
-                                }
-                            },
-                            BuiltinFunction::Input => {
-                                // We want one argument: the input prompt.
-                                if arguments.len() != 1 {
-                                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line });
-                                }
+            ExprType::Grouping { expression } => {
+                self.evaluate(expression.as_ref())
+            },
 
-                                // Print the input prompt.
-                                let prompt = self.evaluate(&arguments[0])?;
-                                print!("{}", prompt);
-                                io::stdout().flush().expect("Error: flush failed");
-
-                                // Read input.
-                                let mut input = String::new();
-                                io::stdin().read_line(&mut input).expect("Error: something went wrong while reading input");
-                                input = input.trim().to_string();
-
-                                // Evaluate to input string.
-                                Ok(Value::String_(input))
-                            },
-                            BuiltinFunction::Remove => {
-                                // We want two arguments: the target array/dictionary, and the index/key to remove.
-                                if arguments.len() != 2 {
-                                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 2, line: expr.line });
-                                }
-
-                                let target = &arguments[0];
-                                let target_eval = self.evaluate(target)?;
-                                let pointer = self.construct_pointer(target, target.line)?;
-                                
-                                let key_eval = self.evaluate(&arguments[1])?;
-
-                                match target_eval {
-                                    Value::Array(mut array) => {
-                                        // If `target` is an Array variant...
-
-                                        // Convert `key` into a `usize` index.
-                                        let index = environment::index_value_to_usize(&key_eval, arguments[1].line)?;
-
-                                        if index < array.len() {
-                                            // If `index` is not out-of-bounds, perform the removal.
-                                            // Note `usize` is guaranteed to be non-negative.
-                                            array.remove(index);
-                                        } else {
-                                            // Otherwise, raise an out-of-bounds error.
-                                            return Err(ErrorType::OutOfBoundsIndex { index, line: arguments[1].line });
-                                        }
-                                        
-                                        // Update the environment with the new array.
-                                        self.environment.update(&pointer, &Value::Array(array.clone()), expr.line)?;
-
-                                        // Evaluate to the changed array.
-                                        Ok(Value::Array(array))
-                                    },
-                                    Value::Dictionary(mut dict) => {
-                                        // If `target` is a Dictionary variant, we can let `HashTable` take care of the removal.
-                                        dict.remove(&key_eval, expr.line)?;
-
-                                        // Update the environment with the new dictionary.
-                                        self.environment.update(&pointer, &Value::Dictionary(dict.clone()), expr.line)?;
-                                        
-                                        // Evaluate to the changed dictionary.
-                                        Ok(Value::Dictionary(dict))
-                                    },
-                                    // If it is not an Array or a Dictionary variant, then raise an `ExpectedTypeError`, providing the received type.
-                                    _ => Err(ErrorType::ExpectedType { expected: String::from("Array or Dictionary"), got: target_eval.type_to_string(), line: target.line }),
-                                }
-                            },
-                            BuiltinFunction::Size => {
-                                // We want one argument: the target array/dictionary/string.
-                                if arguments.len() != 1 {
-                                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line });
-                                }
-
-                                let value = self.evaluate(&arguments[0])?;
-                                match value {
-                                    Value::Array(array) => Ok(Value::Number(array.len() as f64)),
-                                    Value::Dictionary(dict) => Ok(Value::Number(dict.size() as f64)),
-                                    Value::String_(s) => Ok(Value::Number(s.len() as f64)),
-                                    // If `value` did not evaluate to an Array, a Dictionary, or a String, raise an error.
-                                    _ => Err(ErrorType::ExpectedType { expected: String::from("Array, Dictionary, or String"), got: value.type_to_string(), line: expr.line }),
-                                }
-                            },
-                            BuiltinFunction::Sort => {
-                                // We want one argument: the array to be sorted.
-                                if arguments.len() != 1 {
-                                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line });
-                                }
-
-                                let value = self.evaluate(&arguments[0])?; 
-                                match value {
-                                    // If given argument is an array, sort using the `merge_sort` function defined below.
-                                    Value::Array(array) => Ok(Value::Array(merge_sort(&array, arguments[0].line)?)),
-
-                                    // We cannot sort objects which are not arrays, so raise an error.
-                                    _ => Err(ErrorType::ExpectedType { expected: String::from("Array"), got: value.type_to_string(), line: expr.line }),
-                                }
-                            },
-                            BuiltinFunction::ToNumber => {
-                                // We want one argument: the Boolean/number/string to be converted.
-                                if arguments.len() != 1 {
-                                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line });
-                                }
-
-                                let value = self.evaluate(&arguments[0])?;
-                                match value {
-                                    Value::Bool(b) => {
-                                        match b {
-                                            true => Ok(Value::Number(1.0)),
-                                            false => Ok(Value::Number(0.0)),
-                                        }
-                                    },
-                                    Value::Number(..) => Ok(value),
-                                    Value::String_(s) => {
-                                        match s.parse::<f64>() {
-                                            Ok(x) => Ok(Value::Number(x)),
-                                            // If something went wrong during Rust's conversion, raise an error.
-                                            Err(..) => Err(ErrorType::CannotConvertToNumber { line: expr.line }),
-                                        }
-                                    },
-
-                                    // We can only construct numeric representations of Booleans, numbers, and strings.
-                                    // If not given one of these, raise an error.
-                                    _ => Err(ErrorType::ExpectedType { expected: String::from("Boolean, Number or String"), got: value.type_to_string(), line: expr.line }),
-                                }
-                            },
-                            BuiltinFunction::ToString => {
-                                // We want one argument: the Boolean/number/string to be converted.
-                                if arguments.len() != 1 {
-                                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line });
-                                }
-
-                                let value = self.evaluate(&arguments[0])?;
-                                match value {
-                                    Value::Bool(b) => {
-                                        match b {
-                                            true => Ok(Value::String_(String::from("true"))),
-                                            false => Ok(Value::String_(String::from("false"))),
-                                        }
-                                    },
-                                    Value::Number(x) => Ok(Value::String_(x.to_string())),
-                                    Value::String_(..) => Ok(value),
-
-                                    // We can only construct string representations of Booleans, numbers, and strings.
-                                    // If not given one of these, raise an error.
-                                    _ => Err(ErrorType::ExpectedType { expected: String::from("Boolean, Number or String"), got: value.type_to_string(), line: expr.line }),
-                                }
-                            },
-                        }
-                    },
-
-                    // If the evaluated `function` was not a `Function` or a `BuiltinFunction` variant, then we cannot 'call' it.
-                    // So raise an error.
-                    _ => Err(ErrorType::CannotCallName { line: callee.line })
-                }
-            },
-
-            ExprType::Dictionary { elements } => {
-                // Create a new hash table.
-                let mut hash_table = HashTable::new();
-
-                // Iterate through the key-value pairs of the given elements.
-                for key_value in elements.iter() {
-                    // Evaluate each of the keys and values.
-                    let key_eval = self.evaluate(&key_value.key)?;
-                    let value_eval = self.evaluate(&key_value.value)?;
-
-                    // Insert the evaluated key and value into the table.
-                    hash_table.insert(&key_eval, &value_eval, expr.line)?;
+            ExprType::Literal { value } => {
+                // Convert a `Literal` enum into a `Value` enum.
+                match value {
+                    Literal::Number(x) => Ok(Value::Number(*x)),
+                    Literal::String_(x) => Ok(Value::String_(x.clone())),
+                    Literal::Bool(x) => Ok(Value::Bool(*x)),
+                    Literal::Null => Ok(Value::Null),
                 }
-                Ok(Value::Dictionary(hash_table))
             },
 
-            ExprType::Element { array, index } => {
-                // Note that 'array' refers to anything to the left of the index, e.g.,
-                // the 'array' in `a[1][2]` is `a[1]` and the index is `2`.
-
-                // Evaluate the index expression.
-                let index_eval = self.evaluate(index.as_ref())?;
+            ExprType::Slice { array, start, stop, step } => {
+                let array_eval = self.evaluate(array.as_ref())?;
+                // Each part which is left out, or is `null`, takes its default, which depends on the direction of the step.
+                let start_num = self.slice_part(start.as_deref())?;
+                let stop_num = self.slice_part(stop.as_deref())?;
+                let step_num = match (self.slice_part(step.as_deref())?, step) {
+                    (Some(0), Some(step)) => return Err(ErrorType::ZeroSliceStep { line: step.line, column: step.column }),
+                    (step_num, _) => step_num.unwrap_or(1),
+                };
 
-                match self.evaluate(array.as_ref())? {  // Evaluate `array`.
+                match array_eval {
                     Value::Array(array) => {
-                        // If the evaluated 'array' is an Array variant, convert the evaluated index to a `usize` index.
-                        let index_num = environment::index_value_to_usize(&index_eval, index.line)?;
-                        
-                        // Try to get the element of `array` at index `index_num`.
-                        if let Some(element) = array.get(index_num) {
-                            Ok(element.clone())
-                        } else {
-                            // In this case, `index_num` was out of bounds.
-                            Err(ErrorType::OutOfBoundsIndex { index: index_num, line: expr.line })
-                        }
-                    },
-                    Value::Dictionary(dict) => {
-                        // If the evaluated 'array' is a Dictionary variant, get value from the `HashTable` object.
-                        dict.get(&index_eval, expr.line).cloned()
+                        let indices = slice_indices(array.len(), start_num, stop_num, step_num);
+                        // The slice is a new array, so changing it leaves the original as it was, even if that was frozen.
+                        Ok(Value::Array(indices.map(|i| array[i].clone()).collect()))
                     },
                     Value::String_(s) => {
-                        // If the evaluated 'array' is a String variant, convert the evaluated index to a `usize` index.
-                        let index_num = environment::index_value_to_usize(&index_eval, index.line)?;
-
-                        // Try to get the character of `s` at index `index_num`.
-                        if let Some(c) = s.chars().nth(index_num) {
-                            Ok(Value::String_(String::from(c)))
-                        } else {
-                            // In this case, `index_num` was out of bounds.
-                            Err(ErrorType::OutOfBoundsIndex { index: index_num, line: expr.line })
-                        }
+                        let chars: Vec<char> = s.chars().collect();
+                        Ok(Value::String_(slice_indices(chars.len(), start_num, stop_num, step_num).map(|i| chars[i]).collect()))
                     },
-                    // If the 'array' was not an Array, a Dictionary, or a String variant, it cannot be indexed.
-                    _ => Err(ErrorType::NotIndexable { line: array.line })
+                    // Only arrays and strings are in order, so dictionaries cannot be sliced either.
+                    other => Err(ErrorType::ExpectedType {
+                        expected: String::from("Array or String"),
+                        got: other.type_to_string(),
+                        line: array.line,
+                        column: array.column,
+                    }),
                 }
             },
 
-            ExprType::Grouping { expression } => {
-                self.evaluate(expression.as_ref())
-            },
-
-            ExprType::Literal { value } => {
-                // Convert a `Literal` enum into a `Value` enum.
-                match value {
-                    Literal::Number(x) => Ok(Value::Number(*x)),
-                    Literal::String_(x) => Ok(Value::String_(x.clone())),
-                    Literal::Bool(x) => Ok(Value::Bool(*x)),
-                    Literal::Null => Ok(Value::Null),
-                }
+            ExprType::Tuple { elements } => {
+                // A tuple is an array of its values, so that they can be indexed, or unpacked into several variables.
+                let values: Result<Array, _> = elements.iter().map(|x| self.evaluate(x)).collect();
+                Ok(Value::Array(values?))
             },
 
             ExprType::Unary { operator, right } => {
@@ -606,6 +1162,7 @@ impl Interpreter {
                                 expected: String::from("Boolean"),
                                 got: right_eval.type_to_string(),
                                 line: right.line,
+                                column: right.column,
                             })
                         }
                     },
@@ -619,6 +1176,7 @@ impl Interpreter {
                                 expected: String::from("Number"),
                                 got: right_eval.type_to_string(),
                                 line: right.line,
+                                column: right.column,
                             })
                         }
                     },
@@ -629,107 +1187,2248 @@ impl Interpreter {
 
             ExprType::Variable { name } => {
                 // Simply retrieve the value of the variable from the environment.
-                self.environment.get(name.clone(), expr.line)
+                self.environment.get(name.clone(), expr.line, expr.column)
             },
         }
     }
 
-    /// Constructs a Pointer object given an expression.
-    fn construct_pointer(&mut self, element: &Expr, line: usize) -> Result<Pointer, ErrorType> {
-        match &element.expr_type {
-            ExprType::Element { array, index } => {
-                // Recursive case.
-                // E.g., a[1][2][3] -> Pointer("a", [1, 2]), [3] -> Pointer("a", [1, 2, 3])
-                // So we simply add the index of the current element to the Pointer constructed in the recursion.
-                let Pointer {name, indices} = self.construct_pointer(array.as_ref(), line)?;
+    /// Calls the value of `callee` with the arguments of the call expression `expr`.
+    /// This is kept out of `evaluate_expr()`, as is `call_builtin()`, so that the stack frame of each nested expression stays small.
+    fn call(&mut self, expr: &Expr, callee: &Expr, arguments: &[Expr], named_arguments: &[(String, Expr)]) -> Result<Value, ErrorType> {
+        // Evaluate the callee.
+        let function = self.evaluate(callee)?;
+        // The name of the function, for the stack trace and errors.
+        let function_name = match &callee.expr_type {
+            ExprType::Variable { name } => name.clone(),
+            _ => String::from("<anonymous>"),
+        };
+
+        match function {
+            Value::Function { parameters, body } => {
+                // User-defined functions.
+                // Find the parameter each named argument is for, which also checks that no parameter is given two arguments.
+                let named_indices = bind_named_arguments(&function_name, &parameters, arguments.len(), named_arguments)?;
+                let arg_number = arguments.len() + named_arguments.len();
+                if arg_number != parameters.len() {
+                    // If the number of arguments given does not match the number of parameters expected, raise a detailed error.
+                    return Err(ErrorType::ArgParamNumberMismatch {
+                        arg_number,
+                        param_number: parameters.len(),
+                        line: expr.line,
+                        column: expr.column
+                    });
+                }
 
-                // Make a copy of the `indices` array and append the index of the current element.
-                let mut indices_copy = indices;
-                indices_copy.push(self.evaluate(index.as_ref())?);
+                // Iterate through the arguments and evaluate each, in the order they were given, then put the named ones
+                // where their parameters are. Every parameter has exactly one argument, as checked above.
+                let mut args_eval = Vec::new();
+                for arg in arguments.iter() {
+                    args_eval.push(self.evaluate(arg)?);
+                }
+                let mut named_eval = Vec::new();
+                for (_, arg) in named_arguments.iter() {
+                    named_eval.push(self.evaluate(arg)?);
+                }
+                let mut named_values: Vec<(usize, Value)> = named_indices.into_iter().zip(named_eval).collect();
+                named_values.sort_by_key(|(index, _)| *index);
+                args_eval.extend(named_values.into_iter().map(|(_, value)| value));
+
+                // Record the call so that a stack trace can be given if an error occurs inside the function.
+                let function = function_name;
+                self.enter_call(expr)?;
+                self.run_hooks(|hooks| hooks.before_call(&function));
+                // The call is recorded at the position of the call expression, where errors raised by the call itself are reported too.
+                self.call_stack.push(CallFrame {
+                    function: function.clone(),
+                    line: expr.line,
+                    column: expr.column,
+                });
 
-                // Return a `Pointer` with the appended index.
-                Ok(Pointer { name, indices: indices_copy })
-            },
-            ExprType::Variable { name } => {
-                // Base case.
-                // Return an empty `indices` array to be populated in the recursion.
-                Ok(Pointer {name: name.clone(), indices: Vec::new()})
+                // Create a new variable scope for the arguments and function execution.
+                self.environment.new_scope();
+                self.function_scopes.push(self.environment.depth() - 1);
+
+                // Declare the arguments in the new scope.
+                for (parameter, arg_eval) in parameters.iter().zip(&args_eval) {
+                    self.environment.declare(parameter.clone(), arg_eval);
+                }
+
+                // Execute function body.
+                let exec_result = self.execute(&body);
+                self.call_depth -= 1;
+
+                // Exit scope.
+                self.environment.exit_scope();
+                self.function_scopes.pop();
+
+                self.run_hooks(|hooks| hooks.after_call(&function));
+
+                match exec_result {
+                    // If the function execution did not raise any error, evaluate the call to `Null` (no return statement used in function).
+                    Ok(()) => {
+                        self.call_stack.pop();
+                        Ok(Value::Null)
+                    },
+                    // If the execution ended because of a raised `ThrownReturn` error, then evaluate the call to the given return vale.
+                    Err(ErrorType::ThrownReturn { value, line: _, column: _ }) => {
+                        self.call_stack.pop();
+                        Ok(value)
+                    },
+                    // A `ThrownBreak` error is used for control flow and may be caught by a loop outside the function, so it must not leave the call on the stack.
+                    Err(e @ ErrorType::ThrownBreak { .. }) => {
+                        self.call_stack.pop();
+                        Err(e)
+                    },
+                    // If another error occurred, continue to bubble up the error.
+                    // The call is left on the stack so that it appears in the stack trace.
+                    Err(e) => Err(e),
+                }
             },
-            // Otherwise, the variant does not support assignment, so raise an error (e.g., a literal array/dictionary, a binary expression, etc.).
-            _ => Err(ErrorType::InvalidAssignmentTarget { line }),
+
+            Value::BuiltinFunction(function) => self.call_builtin(function, expr, arguments, named_arguments),
+
+            // If the evaluated `function` was not a `Function` or a `BuiltinFunction` variant, then we cannot 'call' it.
+            // So raise an error.
+            _ => Err(ErrorType::CannotCallName { line: callee.line, column: callee.column })
         }
     }
-}
-
-/// Sorts the given array using merge sort.
-fn merge_sort(array_to_sort: &Vec<Value>, line: usize) -> Result<Vec<Value>, ErrorType> {
-    let n = array_to_sort.len();
 
-    // Base case.
-    if n <= 1 {
-        return Ok(array_to_sort.to_vec());
+    /// Counts a call to a user-defined function or `eval()` which is about to be executed, raising an error at the call expression `expr`
+    /// if too many are already being executed. The caller decreases `call_depth` again once the call has been executed.
+    fn enter_call(&mut self, expr: &Expr) -> Result<(), ErrorType> {
+        if self.call_depth >= MAX_CALL_DEPTH {
+            return Err(ErrorType::TooManyNestedCalls { limit: MAX_CALL_DEPTH, line: expr.line, column: expr.column });
+        }
+        self.call_depth += 1;
+        Ok(())
     }
 
-    // Recursive case.
+    /// Calls the built-in function with the arguments of the call expression `expr`.
+    fn call_builtin(&mut self, function: BuiltinFunction, expr: &Expr, arguments: &[Expr], named_arguments: &[(String, Expr)]) -> Result<Value, ErrorType> {
+        // Built-in functions.
+        // Their parameters have no names, so none of their arguments can be given by name.
+        if let Some((name, value)) = named_arguments.first() {
+            return Err(ErrorType::UnknownParameter {
+                name: name.clone(),
+                function: String::from(function.name()),
+                line: value.line,
+                column: value.column,
+            });
+        }
+        match function {
+            BuiltinFunction::Append => {
+                // We want two arguments: the target array, and the value to append.
+                if arguments.len() != 2 {
+                    // If the number of given arguments was not 2, raise an error, providing the number of arguments received.
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 2, line: expr.line, column: expr.column });
+                }
+
+                let target = &arguments[0];
+                let target_eval = self.evaluate(target)?;
+                let pointer = self.construct_pointer(target, target.line, target.column)?;
 
-    // Recursively sort the left and right halves of the array.
-    let left = merge_sort(&array_to_sort[0..n/2].to_vec(), line)?;
-    let right = merge_sort(&array_to_sort[n/2..].to_vec(), line)?;
+                let value_eval = self.evaluate(&arguments[1])?;
 
-    // Merge the two sorted arrays using two pointers.
-    let mut left_index = 0;
-    let mut right_index = 0;
-    let mut merged = Vec::new();
+                // A frozen array cannot be appended to, even though only a copy of it has been evaluated.
+                if target_eval.is_frozen() {
+                    return Err(ErrorType::FrozenValueMutation { line: target.line, column: target.column });
+                }
+                if let Value::Array(mut array) = target_eval {
+                    // If `target` is an Array variant of Value, append and update the environment using the pointer.
+                    array.push(value_eval);
+                    self.environment.update(&pointer, &Value::Array(array.clone()), expr.line, expr.column)?;
 
-    while left_index < left.len() && right_index < right.len() {
-        match (&left[left_index], &right[right_index]) {
-            // Append the 'lower' of the two to the merged array, and advance the respective pointer.
-            (Value::Number(left_num), Value::Number(right_num)) => {
-                if left_num < right_num {
-                    merged.push(left[left_index].clone());
-                    left_index += 1;
+                    // Evaluate to changed array.
+                    Ok(Value::Array(array))
                 } else {
-                    merged.push(right[right_index].clone());
-                    right_index += 1;
+                    // We can only append to arrays.
+                    // If `target` is not an Array variant, raise an `ExpectedTypeError` and provide the received type.
+                    Err(ErrorType::ExpectedType { expected: String::from("Array"), got: target_eval.type_to_string(), line: target.line, column: target.column })
+                }
+            },
+            BuiltinFunction::Args => {
+                // We want no arguments.
+                if !arguments.is_empty() {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 0, line: expr.line, column: expr.column });
                 }
+
+                // Evaluate to an array of the arguments, as strings.
+                Ok(Value::Array(self.args.iter().map(|arg| Value::String_(arg.clone())).collect()))
             },
-            (Value::String_(left_str), Value::String_(right_str)) => {
-                if left_str < right_str {
-                    merged.push(left[left_index].clone());
-                    left_index += 1;
+            BuiltinFunction::AssertEq => {
+                // We want two arguments: the value found, and the value expected.
+                if arguments.len() != 2 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 2, line: expr.line, column: expr.column });
+                }
+
+                let left = self.evaluate(&arguments[0])?;
+                let right = self.evaluate(&arguments[1])?;
+                // The values are compared in the same way as `==` compares them.
+                if left == right {
+                    Ok(Value::Null)
                 } else {
-                    merged.push(right[right_index].clone());
-                    right_index += 1;
+                    Err(ErrorType::AssertionFailed { left, right, line: expr.line, column: expr.column })
                 }
             },
+            BuiltinFunction::ClearScreen => {
+                // We want no arguments.
+                if !arguments.is_empty() {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 0, line: expr.line, column: expr.column });
+                }
 
-            // We only support comparisons between numbers and between strings.
-            (_, _) => {
-                return Err(ErrorType::BinaryTypeError {
-                    expected: String::from("Number or String"),
-                    got_left: left[left_index].type_to_string(),
-                    got_right: right[right_index].type_to_string(),
-                    line,
-                });
-            }
-        }
-    }
+                // Clear the terminal, then move the cursor to the top-left corner, where the next output is written.
+                self.write_output("\x1b[2J\x1b[H", false, expr.line, expr.column)?;
+                Ok(Value::Null)
+            },
+            BuiltinFunction::Colored | BuiltinFunction::SetColor => {
+                // We want the text to colour and the name of the colour for `colored()`, or only the name for `set_color()`.
+                let param_number = if function == BuiltinFunction::Colored { 2 } else { 1 };
+                if arguments.len() != param_number {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number, line: expr.line, column: expr.column });
+                }
 
-    // Only one of `left` and `right` will have any elements left.
-    // Append the remainder to the merged array.
-    if left_index < left.len() {
-        while left_index < left.len() {
-            merged.push(left[left_index].clone());
-            left_index += 1;
-        }
-    }
+                let text = if function == BuiltinFunction::Colored { Some(self.evaluate(&arguments[0])?) } else { None };
+                let name_argument = &arguments[param_number - 1];
+                let name = match self.evaluate(name_argument)? {
+                    Value::String_(name) => name,
+                    value => return Err(ErrorType::ExpectedType { expected: String::from("String"), got: value.type_to_string(), line: name_argument.line, column: name_argument.column }),
+                };
+                // The name is checked even without colours, so that a mistake is found wherever the program is run.
+                let Some(code) = colour_code(&name) else {
+                    return Err(ErrorType::UnknownColour { name, line: name_argument.line, column: name_argument.column });
+                };
+                let code = if self.colour { code } else { "" };
+                let reset = if self.colour { "\x1b[0m" } else { "" };
+
+                match text {
+                    // Evaluate to the text, written as `print` writes it, in the colour and then back to the terminal's own colour.
+                    Some(text) => Ok(Value::String_(format!("{}{}{}", code, text, reset))),
+                    // Write the colour, which lasts until it is changed.
+                    None => {
+                        self.write_output(code, false, expr.line, expr.column)?;
+                        Ok(Value::Null)
+                    },
+                }
+            },
+            BuiltinFunction::Defined => {
+                // We want one argument: the name to look for.
+                if arguments.len() != 1 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line, column: expr.column });
+                }
 
-    if right_index < right.len() {
-        while right_index < right.len() {
-            merged.push(right[right_index].clone());
-            right_index += 1;
-        }
-    }
+                // Evaluate to whether the name can be used here, including the names of built-in functions.
+                match self.evaluate(&arguments[0])? {
+                    Value::String_(name) => Ok(Value::Bool(self.environment.scope_of(&name).is_some())),
+                    value => Err(ErrorType::ExpectedType { expected: String::from("String"), got: value.type_to_string(), line: arguments[0].line, column: arguments[0].column }),
+                }
+            },
+            BuiltinFunction::Eval => {
+                // We want one argument: the source code to run.
+                if arguments.len() != 1 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line, column: expr.column });
+                }
+
+                let source = match self.evaluate(&arguments[0])? {
+                    Value::String_(source) => source,
+                    value => return Err(ErrorType::ExpectedType { expected: String::from("String"), got: value.type_to_string(), line: arguments[0].line, column: arguments[0].column }),
+                };
+
+                // The source code is parsed as the program was, and the first error found is raised, with its position in the string.
+                let tokens = Tokenizer::new(&source).collect::<Result<Vec<Token>, ErrorType>>()?;
+                let mut parser = Parser::with_error_limit(tokens, ErrorLimit { max_reported: None, fail_fast: true });
+                parser.set_strict(self.strict);
+                let ast = parser.parse_unreported().map_err(|mut errors| errors.remove(0))?;
+
+                // The statements are executed in the current scope, as if they were written in place of the call, and the call evaluates
+                // to the value of the last one if it is an expression, as in the REPL. Like a call, it counts towards the call depth, so
+                // that a function which calls itself through `eval()` is stopped in the same way.
+                self.enter_call(expr)?;
+                let last_value = ast.iter().try_fold(None, |_, stmt| self.execute_keeping_value(stmt));
+                self.call_depth -= 1;
+                Ok(last_value?.unwrap_or(Value::Null))
+            },
+            BuiltinFunction::Factors | BuiltinFunction::IsPrime => {
+                // We want one argument: the whole number.
+                if arguments.len() != 1 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line, column: expr.column });
+                }
+
+                let n = whole_number(self.evaluate(&arguments[0])?, arguments[0].line, arguments[0].column)?;
+                if function == BuiltinFunction::IsPrime {
+                    // Evaluate to whether the number is only divisible by 1 and itself. Numbers below 2 are not prime.
+                    return Ok(Value::Bool(n >= 2 && smallest_prime_factor(n as u64) == n as u64));
+                }
+
+                // Evaluate to the prime factors of the number, from smallest to largest, with each as many times as it divides it,
+                // e.g., `[2, 2, 3]` for `12`. The factors of a negative number are those of the positive one, and `0` and `1` have none.
+                let mut n = n.unsigned_abs();
+                let mut factors = Array::default();
+                while n >= 2 {
+                    let factor = smallest_prime_factor(n);
+                    factors.push(Value::Number(factor as f64));
+                    n /= factor;
+                }
+                Ok(Value::Array(factors))
+            },
+            BuiltinFunction::Forward | BuiltinFunction::Turn => {
+                // We want one argument: the distance to move, or the angle to turn clockwise in degrees.
+                if arguments.len() != 1 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line, column: expr.column });
+                }
+
+                let amount = match self.evaluate(&arguments[0])? {
+                    Value::Number(x) => x,
+                    value => return Err(ErrorType::ExpectedType { expected: String::from("Number"), got: value.type_to_string(), line: arguments[0].line, column: arguments[0].column }),
+                };
+                if function == BuiltinFunction::Forward {
+                    self.turtle.forward(amount);
+                } else {
+                    self.turtle.turn(amount);
+                }
+                Ok(Value::Null)
+            },
+            BuiltinFunction::Freeze => {
+                // We want one argument: the array or dictionary to freeze.
+                if arguments.len() != 1 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line, column: expr.column });
+                }
+
+                let target = &arguments[0];
+                let mut value = self.evaluate(target)?;
+                if !matches!(value, Value::Array(..) | Value::Dictionary(..)) {
+                    // Other values cannot be changed anyway, so freezing them is probably a mistake.
+                    return Err(ErrorType::ExpectedType { expected: String::from("Array or Dictionary"), got: value.type_to_string(), line: target.line, column: target.column });
+                }
+
+                if !value.is_frozen() {
+                    value.freeze();
+                    // A variable or element is frozen where it is stored, as with `sort_in_place`, while any other value,
+                    // e.g., a literal in `var primes = freeze([2, 3, 5])`, is only frozen in the value returned.
+                    if matches!(target.expr_type, ExprType::Variable { .. } | ExprType::Element { .. }) {
+                        let pointer = self.construct_pointer(target, target.line, target.column)?;
+                        self.environment.update(&pointer, &value, expr.line, expr.column)?;
+                    }
+                }
+                Ok(value)
+            },
+            BuiltinFunction::Gcd | BuiltinFunction::Lcm => {
+                // We want two arguments: the whole numbers.
+                if arguments.len() != 2 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 2, line: expr.line, column: expr.column });
+                }
+
+                let a = whole_number(self.evaluate(&arguments[0])?, arguments[0].line, arguments[0].column)?.unsigned_abs();
+                let b = whole_number(self.evaluate(&arguments[1])?, arguments[1].line, arguments[1].column)?.unsigned_abs();
+                let divisor = gcd(a, b);
+                // The result is never negative. The least common multiple of `0` and any number is `0`.
+                let result = if function == BuiltinFunction::Gcd {
+                    divisor as f64
+                } else {
+                    // Both numbers are at most 2^53, so the product fits in 128 bits, but it is an error if it is too large to be stored exactly.
+                    let multiple = (a as u128 / divisor.max(1) as u128) * b as u128;
+                    if multiple as f64 > MAX_EXACT_INTEGER {
+                        return Err(ErrorType::NotAWholeNumber { got: Value::Number(multiple as f64), line: expr.line, column: expr.column });
+                    }
+                    multiple as f64
+                };
+                Ok(Value::Number(result))
+            },
+            BuiltinFunction::Get => {
+                // We want three arguments: the dictionary, the key, and the value to fall back on if the key does not exist.
+                if arguments.len() != 3 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 3, line: expr.line, column: expr.column });
+                }
+
+                let target = self.evaluate(&arguments[0])?;
+                let key = self.evaluate(&arguments[1])?;
+                let default = self.evaluate(&arguments[2])?;
+                match target {
+                    // Unlike `dict[key]`, a missing key is not an error, but a key which cannot be hashed still is.
+                    // Unlike `set_default()`, the dictionary is left as it is.
+                    Value::Dictionary(dict) => match dict.get(&key, arguments[1].line, arguments[1].column) {
+                        Ok(value) => Ok(value.clone()),
+                        Err(ErrorType::KeyError { .. }) => Ok(default),
+                        Err(error) => Err(error),
+                    },
+                    value => Err(ErrorType::ExpectedType { expected: String::from("Dictionary"), got: value.type_to_string(), line: arguments[0].line, column: arguments[0].column }),
+                }
+            },
+            BuiltinFunction::GetCell => {
+                // We want four arguments: the grid, the row and column of the cell, and the value to fall back on if there is no such cell.
+                if arguments.len() != 4 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 4, line: expr.line, column: expr.column });
+                }
+
+                let grid = self.evaluate(&arguments[0])?;
+                let mut position = [0.0; 2];
+                for (argument, coordinate) in arguments[1..3].iter().zip(&mut position) {
+                    *coordinate = match self.evaluate(argument)? {
+                        Value::Number(x) => x,
+                        value => return Err(ErrorType::NonNumberIndex { got: value.type_to_string(), line: argument.line, column: argument.column }),
+                    };
+                }
+                let default = self.evaluate(&arguments[3])?;
+                let Value::Array(rows) = grid else {
+                    return Err(ErrorType::ExpectedType { expected: String::from("Array"), got: grid.type_to_string(), line: arguments[0].line, column: arguments[0].column });
+                };
+
+                // Unlike `grid[row][column]`, a position off the grid is not an error, so that the neighbours of a cell at the
+                // edge can be looked at without checking first, e.g., `get_cell(grid, row - 1, column, 0)`.
+                let index = |x: f64| (x >= 0.0 && x.fract() == 0.0).then_some(x as usize);
+                match index(position[0]).and_then(|row| rows.get(row)) {
+                    Some(Value::Array(cells)) => Ok(index(position[1]).and_then(|column| cells.get(column)).cloned().unwrap_or(default)),
+                    Some(row) => Err(ErrorType::ExpectedType { expected: String::from("Array"), got: row.type_to_string(), line: arguments[0].line, column: arguments[0].column }),
+                    None => Ok(default),
+                }
+            },
+            BuiltinFunction::GetKey => {
+                // We want no arguments.
+                if !arguments.is_empty() {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 0, line: expr.line, column: expr.column });
+                }
+
+                // Anything printed so far, e.g., a menu, has to be shown before waiting for the key.
+                self.write_output("", true, expr.line, expr.column)?;
+                match self.input.read_key() {
+                    // Evaluate to the name of the key, or `null` at the end of the input.
+                    Ok(key) => Ok(key.map_or(Value::Null, Value::String_)),
+                    // Ctrl-C stops the program in the same way as it does between statements.
+                    Err(error) if error.kind() == io::ErrorKind::Interrupted => Err(ErrorType::Interrupted { line: expr.line, column: expr.column }),
+                    Err(_) => panic!("Error: something went wrong while reading input"),
+                }
+            },
+            BuiltinFunction::Globals | BuiltinFunction::Locals => {
+                // We want no arguments.
+                if !arguments.is_empty() {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 0, line: expr.line, column: expr.column });
+                }
+
+                // The local variables are those declared since the innermost call, or every variable outside any function.
+                // Evaluate to a copy, so changing the dictionary does not change the variables.
+                let bindings = if function == BuiltinFunction::Globals {
+                    self.environment.globals()
+                } else {
+                    self.environment.locals(self.function_scope())
+                };
+                let mut dict = Dictionary::with_capacity(bindings.len(), self.hasher);
+                for (name, value) in bindings {
+                    dict.insert(&Value::String_(name), &value, expr.line, expr.column)?;
+                }
+                Ok(Value::Dictionary(dict))
+            },
+            BuiltinFunction::Identity => {
+                // We want one argument: the number of rows and columns.
+                if arguments.len() != 1 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line, column: expr.column });
+                }
+
+                // The size must be a non-negative integer, just like an index.
+                let size_eval = self.evaluate(&arguments[0])?;
+                let size = environment::index_value_to_usize(&size_eval, arguments[0].line, arguments[0].column)?;
+
+                // Evaluate to a grid of `0`s, with `1`s from the top-left corner to the bottom-right corner.
+                Ok(Value::Array((0..size).map(|row| {
+                    Value::Array((0..size).map(|column| Value::Number(if row == column { 1.0 } else { 0.0 })).collect())
+                }).collect()))
+            },
+            BuiltinFunction::Include => {
+                // We want one argument: the path of the file to run.
+                if arguments.len() != 1 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line, column: expr.column });
+                }
+
+                let path = match self.evaluate(&arguments[0])? {
+                    Value::String_(path) => path,
+                    value => return Err(ErrorType::ExpectedType { expected: String::from("String"), got: value.type_to_string(), line: arguments[0].line, column: arguments[0].column }),
+                };
+                self.include(&path, expr.line, expr.column)?;
+                Ok(Value::Null)
+            },
+            BuiltinFunction::Input => {
+                // We want one argument: the input prompt.
+                if arguments.len() != 1 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line, column: expr.column });
+                }
+
+                // Print the input prompt.
+                let prompt = self.evaluate(&arguments[0])?;
+                self.write_output(&prompt.to_string(), true, expr.line, expr.column)?;
+
+                // Read input.
+                let mut input = String::new();
+                self.input.read_line(&mut input).expect("Error: something went wrong while reading input");
+                input = input.trim().to_string();
+
+                // Evaluate to input string.
+                Ok(Value::String_(input))
+            },
+            BuiltinFunction::InputNumber => {
+                // We want one argument: the input prompt.
+                if arguments.len() != 1 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line, column: expr.column });
+                }
+
+                // Ask again until a number is typed, rather than stopping the program on the first typo.
+                let prompt = self.evaluate(&arguments[0])?;
+                loop {
+                    self.write_output(&prompt.to_string(), true, expr.line, expr.column)?;
+
+                    let mut input = String::new();
+                    let bytes = self.input.read_line(&mut input).expect("Error: something went wrong while reading input");
+                    // At the end of the input, no number will ever be typed, so raise an error instead of asking forever.
+                    if bytes == 0 {
+                        return Err(ErrorType::CannotConvertToNumber { line: expr.line, column: expr.column });
+                    }
+
+                    // `NaN` and infinities are not numbers anyone means to type.
+                    match input.trim().parse::<f64>() {
+                        Ok(x) if x.is_finite() => return Ok(Value::Number(x)),
+                        _ => self.write_output(&format!("`{}` is not a number. Please try again.\n", input.trim()), false, expr.line, expr.column)?,
+                    }
+                }
+            },
+            BuiltinFunction::Inspect => {
+                // We want one argument: the value to describe.
+                if arguments.len() != 1 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line, column: expr.column });
+                }
+
+                // Evaluate to the summary rather than the value itself, which may be too large to print.
+                Ok(Value::String_(self.evaluate(&arguments[0])?.summary()))
+            },
+            BuiltinFunction::Keys | BuiltinFunction::Values => {
+                // We want one argument: the dictionary.
+                if arguments.len() != 1 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line, column: expr.column });
+                }
+
+                let value = self.evaluate(&arguments[0])?;
+                match value {
+                    // Evaluate to an array of the keys or of the values, in the same order as the dictionary is printed.
+                    Value::Dictionary(dict) => Ok(Value::Array(dict.iter().map(|key_value| {
+                        if function == BuiltinFunction::Keys {
+                            key_value.key.clone()
+                        } else {
+                            key_value.value.clone()
+                        }
+                    }).collect())),
+
+                    // Only dictionaries have keys and values.
+                    _ => Err(ErrorType::ExpectedType { expected: String::from("Dictionary"), got: value.type_to_string(), line: expr.line, column: expr.column }),
+                }
+            },
+            BuiltinFunction::Matrix => {
+                // We want three arguments: the number of rows, the number of columns, and the value to fill each cell with.
+                if arguments.len() != 3 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 3, line: expr.line, column: expr.column });
+                }
 
-    Ok(merged)
+                // The sizes must be non-negative integers, just like indexes.
+                let rows_eval = self.evaluate(&arguments[0])?;
+                let rows = environment::index_value_to_usize(&rows_eval, arguments[0].line, arguments[0].column)?;
+                let columns_eval = self.evaluate(&arguments[1])?;
+                let columns = environment::index_value_to_usize(&columns_eval, arguments[1].line, arguments[1].column)?;
+                let fill = self.evaluate(&arguments[2])?;
+
+                // Evaluate to an array of rows, each an array of copies of `fill`.
+                let row = Value::Array(Array::from(vec![fill; columns]));
+                Ok(Value::Array(Array::from(vec![row; rows])))
+            },
+            BuiltinFunction::MoveCursor => {
+                // We want two arguments: the row and the column, counted from 1 at the top-left corner of the terminal.
+                if arguments.len() != 2 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 2, line: expr.line, column: expr.column });
+                }
+
+                let mut position = [0; 2];
+                for (argument, coordinate) in arguments.iter().zip(&mut position) {
+                    *coordinate = match self.evaluate(argument)? {
+                        Value::Number(n) if n.fract() == 0.0 && n >= 1.0 && n <= u32::MAX as f64 => n as u32,
+                        got => return Err(ErrorType::InvalidCursorPosition { got, line: argument.line, column: argument.column }),
+                    };
+                }
+                self.write_output(&format!("\x1b[{};{}H", position[0], position[1]), false, expr.line, expr.column)?;
+                Ok(Value::Null)
+            },
+            BuiltinFunction::PenColor => {
+                // We want one argument: the name of the colour.
+                if arguments.len() != 1 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line, column: expr.column });
+                }
+
+                let name = match self.evaluate(&arguments[0])? {
+                    Value::String_(name) => name,
+                    value => return Err(ErrorType::ExpectedType { expected: String::from("String"), got: value.type_to_string(), line: arguments[0].line, column: arguments[0].column }),
+                };
+                // The pen takes the same colour names as `set_color()`, so that they only have to be learnt once.
+                let Some(colour) = turtle::pen_colour(&name) else {
+                    return Err(ErrorType::UnknownColour { name, line: arguments[0].line, column: arguments[0].column });
+                };
+                self.turtle.set_colour(colour);
+                Ok(Value::Null)
+            },
+            BuiltinFunction::PenDown | BuiltinFunction::PenUp => {
+                // We want no arguments.
+                if !arguments.is_empty() {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 0, line: expr.line, column: expr.column });
+                }
+
+                // While the pen is up, the turtle moves without drawing.
+                self.turtle.set_pen_down(function == BuiltinFunction::PenDown);
+                Ok(Value::Null)
+            },
+            BuiltinFunction::ReadAll | BuiltinFunction::ReadLine => {
+                // We want no arguments. Unlike `input()`, there is no prompt, so that the output can be used in a pipeline.
+                if !arguments.is_empty() {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 0, line: expr.line, column: expr.column });
+                }
+
+                let mut text = String::new();
+                if function == BuiltinFunction::ReadLine {
+                    // At the end of the input, evaluate to `null`, so that it can be told apart from an empty line.
+                    if self.input.read_line(&mut text).expect("Error: something went wrong while reading input") == 0 {
+                        return Ok(Value::Null);
+                    }
+                    // Only the line ending is removed, as other whitespace may be part of the data.
+                    if text.ends_with('\n') {
+                        text.pop();
+                        if text.ends_with('\r') {
+                            text.pop();
+                        }
+                    }
+                } else {
+                    // Evaluate to the rest of the input, as it is, which is an empty string at the end of the input.
+                    while self.input.read_line(&mut text).expect("Error: something went wrong while reading input") != 0 {}
+                }
+                Ok(Value::String_(text))
+            },
+            BuiltinFunction::Remove => {
+                // We want two arguments: the target array/dictionary, and the index/key to remove.
+                if arguments.len() != 2 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 2, line: expr.line, column: expr.column });
+                }
+
+                let target = &arguments[0];
+                let target_eval = self.evaluate(target)?;
+                let pointer = self.construct_pointer(target, target.line, target.column)?;
+
+                let key_eval = self.evaluate(&arguments[1])?;
+
+                // As with `append`.
+                if target_eval.is_frozen() {
+                    return Err(ErrorType::FrozenValueMutation { line: target.line, column: target.column });
+                }
+
+                match target_eval {
+                    Value::Array(mut array) => {
+                        // If `target` is an Array variant...
+
+                        // Convert `key` into a `usize` index.
+                        let index = environment::index_value_to_usize(&key_eval, arguments[1].line, arguments[1].column)?;
+
+                        if index < array.len() {
+                            // If `index` is not out-of-bounds, perform the removal.
+                            // Note `usize` is guaranteed to be non-negative.
+                            array.remove(index);
+                        } else {
+                            // Otherwise, raise an out-of-bounds error.
+                            return Err(ErrorType::OutOfBoundsIndex { index, line: arguments[1].line, column: arguments[1].column });
+                        }
+
+                        // Update the environment with the new array.
+                        self.environment.update(&pointer, &Value::Array(array.clone()), expr.line, expr.column)?;
+
+                        // Evaluate to the changed array.
+                        Ok(Value::Array(array))
+                    },
+                    Value::Dictionary(mut dict) => {
+                        // If `target` is a Dictionary variant, we can let `Dictionary` take care of the removal.
+                        dict.remove(&key_eval, expr.line, expr.column)?;
+
+                        // Update the environment with the new dictionary.
+                        self.environment.update(&pointer, &Value::Dictionary(dict.clone()), expr.line, expr.column)?;
+
+                        // Evaluate to the changed dictionary.
+                        Ok(Value::Dictionary(dict))
+                    },
+                    // If it is not an Array or a Dictionary variant, then raise an `ExpectedTypeError`, providing the received type.
+                    _ => Err(ErrorType::ExpectedType { expected: String::from("Array or Dictionary"), got: target_eval.type_to_string(), line: target.line, column: target.column }),
+                }
+            },
+            BuiltinFunction::Resize => {
+                // We want three arguments: the target array, its new size, and the value to fill any new elements with.
+                if arguments.len() != 3 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 3, line: expr.line, column: expr.column });
+                }
+
+                let target = &arguments[0];
+                let target_eval = self.evaluate(target)?;
+                let pointer = self.construct_pointer(target, target.line, target.column)?;
+
+                // The size must be a non-negative integer, just like an index.
+                let size_eval = self.evaluate(&arguments[1])?;
+                let size = environment::index_value_to_usize(&size_eval, arguments[1].line, arguments[1].column)?;
+                let fill = self.evaluate(&arguments[2])?;
+
+                // As with `append`.
+                if target_eval.is_frozen() {
+                    return Err(ErrorType::FrozenValueMutation { line: target.line, column: target.column });
+                }
+                if let Value::Array(mut array) = target_eval {
+                    // Remove elements from the end, or add copies of `fill` to it, then update the environment using the pointer.
+                    // The memory for any new elements is reserved first, so that a size which is too large is an error rather than an abort.
+                    let additional = size.saturating_sub(array.len());
+                    if array.try_reserve(additional).is_err() {
+                        return Err(ErrorType::ArrayTooLarge { size, line: arguments[1].line, column: arguments[1].column });
+                    }
+                    array.resize(size, fill);
+                    self.environment.update(&pointer, &Value::Array(array.clone()), expr.line, expr.column)?;
+
+                    // Evaluate to the changed array.
+                    Ok(Value::Array(array))
+                } else {
+                    // Only arrays can be resized.
+                    Err(ErrorType::ExpectedType { expected: String::from("Array"), got: target_eval.type_to_string(), line: target.line, column: target.column })
+                }
+            },
+            BuiltinFunction::SaveDrawing => {
+                // We want one argument: the path of the SVG file to write.
+                if arguments.len() != 1 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line, column: expr.column });
+                }
+
+                let path = match self.evaluate(&arguments[0])? {
+                    Value::String_(path) => path,
+                    value => return Err(ErrorType::ExpectedType { expected: String::from("String"), got: value.type_to_string(), line: arguments[0].line, column: arguments[0].column }),
+                };
+                // The drawing is kept, so that it can be drawn on and saved again, e.g., once for each step of an animation.
+                if let Err(error) = fs::write(&path, self.turtle.to_svg()) {
+                    return Err(ErrorType::CannotWriteFile { path, reason: error.to_string(), line: expr.line, column: expr.column });
+                }
+                Ok(Value::Null)
+            },
+            BuiltinFunction::SetDefault => {
+                // We want three arguments: the target dictionary, the key, and the default value.
+                if arguments.len() != 3 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 3, line: expr.line, column: expr.column });
+                }
+
+                // As with `sort_in_place`, the pointer is used so that the dictionary can be updated where it is stored.
+                let target = &arguments[0];
+                let pointer = self.construct_pointer(target, target.line, target.column)?;
+                let key = self.evaluate(&arguments[1])?;
+                let default = self.evaluate(&arguments[2])?;
+
+                match self.environment.get_mut(&pointer, target.line, target.column)? {
+                    // Like the other functions which change their target, `set_default` cannot be used on a frozen dictionary, even if the key exists.
+                    Value::Dictionary(dict) if dict.is_frozen() => Err(ErrorType::FrozenValueMutation { line: target.line, column: target.column }),
+                    // Insert `default` if `key` does not exist, then evaluate to the value associated with `key`.
+                    Value::Dictionary(dict) => Ok(dict.get_or_insert_with(&key, || default, arguments[1].line, arguments[1].column)?.clone()),
+
+                    // Only dictionaries have keys.
+                    value => Err(ErrorType::ExpectedType { expected: String::from("Dictionary"), got: value.type_to_string(), line: target.line, column: target.column }),
+                }
+            },
+            BuiltinFunction::Size => {
+                // We want one argument: the target array/dictionary/string.
+                if arguments.len() != 1 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line, column: expr.column });
+                }
+
+                let value = self.evaluate(&arguments[0])?;
+                match value {
+                    Value::Array(array) => Ok(Value::Number(array.len() as f64)),
+                    Value::Dictionary(dict) => Ok(Value::Number(dict.size() as f64)),
+                    // A string's size is its number of characters, which is how it is indexed, rather than its number of bytes.
+                    Value::String_(s) => Ok(Value::Number(s.chars().count() as f64)),
+                    // If `value` did not evaluate to an Array, a Dictionary, or a String, raise an error.
+                    _ => Err(ErrorType::ExpectedType { expected: String::from("Array, Dictionary, or String"), got: value.type_to_string(), line: expr.line, column: expr.column }),
+                }
+            },
+            BuiltinFunction::Sort => {
+                // We want one argument: the array to be sorted.
+                if arguments.len() != 1 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line, column: expr.column });
+                }
+
+                let value = self.evaluate(&arguments[0])?; 
+                match value {
+                    // If given argument is an array, sort it using the `sort_values` function defined below.
+                    // `value` is already a copy of the argument, so it can be sorted directly without affecting the original array.
+                    Value::Array(mut array) => {
+                        sort_values(&mut array, arguments[0].line, arguments[0].column)?;
+                        Ok(Value::Array(array))
+                    },
+
+                    // We cannot sort objects which are not arrays, so raise an error.
+                    _ => Err(ErrorType::ExpectedType { expected: String::from("Array"), got: value.type_to_string(), line: expr.line, column: expr.column }),
+                }
+            },
+            BuiltinFunction::SortInPlace => {
+                // We want one argument: the target array to be sorted.
+                if arguments.len() != 1 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line, column: expr.column });
+                }
+
+                // Unlike `sort`, the target is not evaluated (which would copy it).
+                // Instead, the pointer is used to sort the array where it is stored in the environment.
+                let target = &arguments[0];
+                let pointer = self.construct_pointer(target, target.line, target.column)?;
+
+                match self.environment.get_mut(&pointer, target.line, target.column)? {
+                    Value::Array(array) if array.is_frozen() => Err(ErrorType::FrozenValueMutation { line: target.line, column: target.column }),
+                    Value::Array(array) => {
+                        sort_values(array, target.line, target.column)?;
+
+                        // Evaluate to `Null` to avoid copying the sorted array.
+                        Ok(Value::Null)
+                    },
+
+                    // As above.
+                    value => Err(ErrorType::ExpectedType { expected: String::from("Array"), got: value.type_to_string(), line: target.line, column: target.column }),
+                }
+            },
+            BuiltinFunction::StoreLoad => {
+                // We want one argument: the name the value was saved with.
+                if arguments.len() != 1 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line, column: expr.column });
+                }
+
+                let name = match self.evaluate(&arguments[0])? {
+                    Value::String_(name) => name,
+                    value => return Err(ErrorType::ExpectedType { expected: String::from("String"), got: value.type_to_string(), line: arguments[0].line, column: arguments[0].column }),
+                };
+                // A name which has never been saved evaluates to `null`, as on the first run of a program, when there is no store yet.
+                let entries = self.read_store(expr.line, expr.column)?;
+                Ok(entries.get(&Value::String_(name), expr.line, expr.column).cloned().unwrap_or(Value::Null))
+            },
+            BuiltinFunction::StoreSave => {
+                // We want two arguments: the name to save the value with, and the value.
+                if arguments.len() != 2 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 2, line: expr.line, column: expr.column });
+                }
+
+                let name = match self.evaluate(&arguments[0])? {
+                    Value::String_(name) => name,
+                    value => return Err(ErrorType::ExpectedType { expected: String::from("String"), got: value.type_to_string(), line: arguments[0].line, column: arguments[0].column }),
+                };
+                let value = self.evaluate(&arguments[1])?;
+
+                // The other values in the store are kept, so the whole store is read and written again with the new value.
+                let mut entries = self.read_store(expr.line, expr.column)?;
+                entries.insert(&Value::String_(name), &value, expr.line, expr.column)?;
+                let Some(text) = store::write(&entries) else {
+                    return Err(ErrorType::CannotStoreFunction { line: arguments[1].line, column: arguments[1].column });
+                };
+                let path = self.store_path();
+                if let Err(error) = fs::write(&path, text) {
+                    return Err(ErrorType::CannotWriteFile { path: path.display().to_string(), reason: error.to_string(), line: expr.line, column: expr.column });
+                }
+                Ok(Value::Null)
+            },
+            BuiltinFunction::ToBase => {
+                // We want two arguments: the whole number, and the base to write it in.
+                if arguments.len() != 2 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 2, line: expr.line, column: expr.column });
+                }
+
+                let n = whole_number(self.evaluate(&arguments[0])?, arguments[0].line, arguments[0].column)?;
+                let base = base(self.evaluate(&arguments[1])?, arguments[1].line, arguments[1].column)?;
+
+                // Write the digits from the least significant, then reverse them. Digits after `9` are lower-case letters.
+                let mut rest = n.unsigned_abs();
+                let mut digits = Vec::new();
+                loop {
+                    digits.push(char::from_digit((rest % base as u64) as u32, base).expect("the digit is less than the base"));
+                    rest /= base as u64;
+                    if rest == 0 {
+                        break;
+                    }
+                }
+                if n < 0 {
+                    digits.push('-');
+                }
+                Ok(Value::String_(digits.into_iter().rev().collect()))
+            },
+            BuiltinFunction::ToFixed => {
+                // We want two arguments: the number, and the number of decimal places to write it to.
+                if arguments.len() != 2 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 2, line: expr.line, column: expr.column });
+                }
+
+                let value = self.evaluate(&arguments[0])?;
+                let Value::Number(x) = value else {
+                    return Err(ErrorType::ExpectedType { expected: String::from("Number"), got: value.type_to_string(), line: arguments[0].line, column: arguments[0].column });
+                };
+                let digits = self.evaluate(&arguments[1])?;
+                let places = match digits {
+                    Value::Number(n) if n.fract() == 0.0 && (0.0..=MAX_DECIMAL_PLACES).contains(&n) => n as usize,
+                    _ => return Err(ErrorType::InvalidDigits { got: digits, line: arguments[1].line, column: arguments[1].column }),
+                };
+
+                if x.is_finite() {
+                    // A tie is rounded to the even digit, e.g., `to_fixed(2.5, 0)` is `2`. As numbers are stored in binary, some
+                    // decimals are slightly below what they look like, e.g., `to_fixed(1.005, 2)` is `1.00`.
+                    Ok(Value::String_(format!("{:.*}", places, x)))
+                } else {
+                    // `NaN` and infinities have no decimal places, so they are written as `print` writes them.
+                    Ok(Value::String_(value::format_number(x)))
+                }
+            },
+            BuiltinFunction::ToNumber => {
+                // We want one argument: the Boolean/number/string to be converted, and optionally the base the string is written in.
+                if arguments.is_empty() || arguments.len() > 2 {
+                    let param_number = if arguments.is_empty() { 1 } else { 2 };
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number, line: expr.line, column: expr.column });
+                }
+
+                let value = self.evaluate(&arguments[0])?;
+                if let Some(base_argument) = arguments.get(1) {
+                    // With a base, only a string of digits can be converted, e.g., `to_number('ff', 16)` is `255`.
+                    let base = base(self.evaluate(base_argument)?, base_argument.line, base_argument.column)?;
+                    let Value::String_(s) = value else {
+                        return Err(ErrorType::ExpectedType { expected: String::from("String"), got: value.type_to_string(), line: arguments[0].line, column: arguments[0].column });
+                    };
+                    return parse_in_base(&s, base).map(Value::Number).ok_or(ErrorType::CannotConvertToNumber { line: expr.line, column: expr.column });
+                }
+                match value {
+                    Value::Bool(b) => {
+                        match b {
+                            true => Ok(Value::Number(1.0)),
+                            false => Ok(Value::Number(0.0)),
+                        }
+                    },
+                    Value::Number(..) => Ok(value),
+                    Value::String_(s) => {
+                        match s.parse::<f64>() {
+                            Ok(x) => Ok(Value::Number(x)),
+                            // If something went wrong during Rust's conversion, raise an error.
+                            Err(..) => Err(ErrorType::CannotConvertToNumber { line: expr.line, column: expr.column }),
+                        }
+                    },
+
+                    // We can only construct numeric representations of Booleans, numbers, and strings.
+                    // If not given one of these, raise an error.
+                    _ => Err(ErrorType::ExpectedType { expected: String::from("Boolean, Number or String"), got: value.type_to_string(), line: expr.line, column: expr.column }),
+                }
+            },
+            BuiltinFunction::Transpose => {
+                // We want one argument: the grid, as an array of rows.
+                if arguments.len() != 1 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line, column: expr.column });
+                }
+
+                let grid = self.evaluate(&arguments[0])?;
+                let not_array = |value: &Value| ErrorType::ExpectedType { expected: String::from("Array"), got: value.type_to_string(), line: arguments[0].line, column: arguments[0].column };
+                let Value::Array(rows) = &grid else {
+                    return Err(not_array(&grid));
+                };
+                // Every row must be an array of the same length as the first, or the grid has no columns to swap with its rows.
+                let mut columns = Vec::new();
+                for (i, row) in rows.iter().enumerate() {
+                    let Value::Array(cells) = row else {
+                        return Err(not_array(row));
+                    };
+                    if i == 0 {
+                        columns = vec![Vec::with_capacity(rows.len()); cells.len()];
+                    } else if cells.len() != columns.len() {
+                        return Err(ErrorType::JaggedMatrix { row: i, length: cells.len(), expected: columns.len(), line: arguments[0].line, column: arguments[0].column });
+                    }
+                    for (column, cell) in columns.iter_mut().zip(cells) {
+                        column.push(cell.clone());
+                    }
+                }
+
+                // Evaluate to a new grid, whose rows are the columns of the old one.
+                Ok(Value::Array(columns.into_iter().map(|column| Value::Array(Array::from(column))).collect()))
+            },
+            BuiltinFunction::ToString => {
+                // We want one argument: the Boolean/number/string to be converted.
+                if arguments.len() != 1 {
+                    return Err(ErrorType::ArgParamNumberMismatch { arg_number: arguments.len(), param_number: 1, line: expr.line, column: expr.column });
+                }
+
+                let value = self.evaluate(&arguments[0])?;
+                match value {
+                    Value::Bool(b) => {
+                        match b {
+                            true => Ok(Value::String_(String::from("true"))),
+                            false => Ok(Value::String_(String::from("false"))),
+                        }
+                    },
+                    Value::Number(x) => Ok(Value::String_(value::format_number(x))),
+                    Value::String_(..) => Ok(value),
+
+                    // We can only construct string representations of Booleans, numbers, and strings.
+                    // If not given one of these, raise an error.
+                    _ => Err(ErrorType::ExpectedType { expected: String::from("Boolean, Number or String"), got: value.type_to_string(), line: expr.line, column: expr.column }),
+                }
+            },
+        }
+    }
+
+    /// Assigns the value to the target of an assignment, which must be a variable or an element.
+    /// Returns the pointer to the target and the value it replaced, which can be given to `Environment::restore()` to undo it.
+    fn assign(&mut self, target: &Expr, value: &Value, line: usize, column: usize) -> Result<(Pointer, Option<Value>), ErrorType> {
+        // Construct the pointer to the target. If an error occurred (invalid assignment target), it bubbles up.
+        let pointer = self.construct_pointer(target, line, column)?;
+
+        // Use the pointer to update the value in the environment.
+        self.check_outer_assignment(&pointer.name, target.line, target.column)?;
+        let old = self.environment.replace(&pointer, value, line, column)?;
+        if self.trace {
+            // Show the target with its indices evaluated, e.g., `a[0] = 5` for `a[i] = 5`.
+            let indices: String = pointer.indices.iter().map(|index| format!("[{}]", index)).collect();
+            self.trace_line(1, &format!("{}{} = {}", pointer.name, indices, value));
+        }
+        Ok((pointer, old))
+    }
+
+    /// Evaluates a bound or the step of a slice, returning `None` if it was left out or is `null`.
+    fn slice_part(&mut self, part: Option<&Expr>) -> Result<Option<i64>, ErrorType> {
+        let Some(part) = part else {
+            return Ok(None);
+        };
+        match self.evaluate(part)? {
+            Value::Null => Ok(None),
+            value => whole_number(value, part.line, part.column).map(Some),
+        }
+    }
+
+    /// Constructs a Pointer object given an expression.
+    fn construct_pointer(&mut self, element: &Expr, line: usize, column: usize) -> Result<Pointer, ErrorType> {
+        match &element.expr_type {
+            ExprType::Element { array, index } => {
+                // Recursive case.
+                // E.g., a[1][2][3] -> Pointer("a", [1, 2]), [3] -> Pointer("a", [1, 2, 3])
+                // So we simply add the index of the current element to the Pointer constructed in the recursion.
+                let Pointer {name, indices} = self.construct_pointer(array.as_ref(), line, column)?;
+
+                // Make a copy of the `indices` array and append the index of the current element.
+                let mut indices_copy = indices;
+                indices_copy.push(self.evaluate(index.as_ref())?);
+
+                // Return a `Pointer` with the appended index.
+                Ok(Pointer { name, indices: indices_copy })
+            },
+            ExprType::Variable { name } => {
+                // Base case.
+                // Return an empty `indices` array to be populated in the recursion.
+                Ok(Pointer {name: name.clone(), indices: Vec::new()})
+            },
+            // Otherwise, the variant does not support assignment, so raise an error (e.g., a literal array/dictionary, a binary expression, etc.).
+            _ => Err(ErrorType::InvalidAssignmentTarget { line, column }),
+        }
+    }
+}
+
+/// Returns the index of the parameter each named argument of a call is for, after the arguments given by position, or an error if
+/// one is not the name of a parameter, or is for a parameter which already has an argument.
+fn bind_named_arguments(function: &str, parameters: &[String], positional: usize, named_arguments: &[(String, Expr)]) -> Result<Vec<usize>, ErrorType> {
+    let mut indices: Vec<usize> = Vec::new();
+    for (name, value) in named_arguments {
+        let Some(index) = parameters.iter().position(|parameter| parameter == name) else {
+            return Err(ErrorType::UnknownParameter { name: name.clone(), function: String::from(function), line: value.line, column: value.column });
+        };
+        if index < positional || indices.contains(&index) {
+            return Err(ErrorType::DuplicateArgument { name: name.clone(), function: String::from(function), line: value.line, column: value.column });
+        }
+        indices.push(index);
+    }
+    Ok(indices)
+}
+
+/// Returns the elements of the array being unpacked into the number of variables, or an error if it is not an array with one
+/// element for each of them.
+fn unpack(value: &Value, count: usize, line: usize, column: usize) -> Result<&Array, ErrorType> {
+    match value {
+        Value::Array(array) if array.len() == count => Ok(array),
+        Value::Array(array) => Err(ErrorType::UnpackMismatch { expected: count, got: array.len(), line, column }),
+        _ => Err(ErrorType::ExpectedType { expected: String::from("Array"), got: value.type_to_string(), line, column }),
+    }
+}
+
+/// Returns the indexes of the elements of a slice of a sequence of the length, in the order they are taken. The step must not be 0.
+/// Bounds past either end are clamped to it rather than being an error, and, as indexes do not count from the end in NEAL, a
+/// negative bound is before the start. Counting up, the slice runs from `start` to just before `stop`, which default to the ends;
+/// counting down, from `start` down to just after `stop`, which default to the last element and before the first.
+fn slice_indices(length: usize, start: Option<i64>, stop: Option<i64>, step: i64) -> impl Iterator<Item = usize> {
+    let length = length as i64;
+    let step_size = usize::try_from(step.unsigned_abs()).unwrap_or(usize::MAX);
+    let indices: Box<dyn Iterator<Item = i64>> = if step > 0 {
+        let start = start.unwrap_or(0).clamp(0, length);
+        let stop = stop.unwrap_or(length).clamp(0, length);
+        Box::new((start..stop).step_by(step_size))
+    } else {
+        // Counting down, -1 stands for before the first element.
+        let start = start.unwrap_or(length - 1).clamp(-1, length - 1);
+        let stop = stop.unwrap_or(-1).clamp(-1, length - 1);
+        Box::new((stop + 1..=start).rev().step_by(step_size))
+    };
+    // The indexes are all within the sequence, so they are not negative.
+    indices.map(|i| i as usize)
+}
+
+/// Sorts the given array in place.
+/// All the elements have to be numbers, or all of them have to be strings, as these are the only types that can be compared.
+fn sort_values(array: &mut [Value], line: usize, column: usize) -> Result<(), ErrorType> {
+    // An array with fewer than two elements is already sorted.
+    let [first, rest @ ..] = &*array else {
+        return Ok(());
+    };
+
+    // Make sure the array can be sorted before sorting it, so that the comparisons below cannot fail.
+    let mismatch = match first {
+        // Find the first element which is not of the same type as the first element.
+        Value::Number(..) => rest.iter().find(|x| !matches!(x, Value::Number(..))),
+        Value::String_(..) => rest.iter().find(|x| !matches!(x, Value::String_(..))),
+        // If the first element is neither a number nor a string, it cannot be compared with the second element.
+        _ => rest.first(),
+    };
+
+    if let Some(other) = mismatch {
+        // We only support comparisons between numbers and between strings.
+        return Err(ErrorType::BinaryTypeError {
+            expected: String::from("Number or String"),
+            got_left: first.type_to_string(),
+            got_right: other.type_to_string(),
+            line,
+            column,
+        });
+    }
+
+    // `sort_by()` is a stable merge sort which only moves the values, rather than copying them.
+    array.sort_by(|left, right| match (left, right) {
+        // `NaN` cannot be compared with any number, so every `NaN` is put after all the other numbers, which keeps the order total.
+        // `0` and `-0` stay equal, and so keep their order, as they do under `==`.
+        (Value::Number(left_num), Value::Number(right_num)) => match (left_num.is_nan(), right_num.is_nan()) {
+            (false, false) => left_num.partial_cmp(right_num).unwrap_or(Ordering::Equal),
+            (left_nan, right_nan) => left_nan.cmp(&right_nan),
+        },
+        (Value::String_(left_str), Value::String_(right_str)) => left_str.cmp(right_str),
+        // All the elements have been checked to have the same type above.
+        _ => unreachable!(),
+    });
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Write};
+    use std::sync::{Arc, Mutex};
+    use std::sync::atomic::{AtomicBool, Ordering as AtomicOrdering};
+    use std::time::Instant;
+
+    use crate::{value::Value, error::ErrorType, expr::Expr, hooks::Hooks, parser::Parser, stmt::Stmt, tokenizer::Tokenizer};
+
+    use super::{sort_values, CallFrame, Interpreter, MAX_CALL_DEPTH};
+
+    /// Executes `source` statement by statement without reporting errors, so that the call stack left by an error can be inspected.
+    fn execute(interpreter: &mut Interpreter, source: &str) -> Result<(), ErrorType> {
+        let tokens = Tokenizer::new(source).tokenize().unwrap();
+        let ast = Parser::new(tokens).parse().unwrap();
+        for stmt in &ast {
+            interpreter.execute(stmt)?;
+        }
+        Ok(())
+    }
+
+    /// A buffer which can be given to the interpreter as an output, while the test keeps a handle to read what was written.
+    #[derive(Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl SharedBuffer {
+        fn contents(&self) -> String {
+            String::from_utf8(self.0.lock().unwrap().clone()).unwrap()
+        }
+    }
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().write(buf)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn frame(function: &str, line: usize, column: usize) -> CallFrame {
+        CallFrame { function: String::from(function), line, column }
+    }
+
+    #[test]
+    fn sort_numbers() {
+        let mut array = vec![Value::Number(3.0), Value::Number(-1.0), Value::Number(2.5), Value::Number(-1.0)];
+        assert_eq!(sort_values(&mut array, 1, 1), Ok(()));
+        assert_eq!(array, vec![Value::Number(-1.0), Value::Number(-1.0), Value::Number(2.5), Value::Number(3.0)]);
+    }
+
+    #[test]
+    fn sort_numbers_with_nan() {
+        let mut array = vec![Value::Number(0.0), Value::Number(f64::NAN), Value::Number(3.0), Value::Number(-1.0), Value::Number(f64::NAN), Value::Number(2.0)];
+        assert_eq!(sort_values(&mut array, 1, 1), Ok(()));
+        // Every `NaN` goes at the end, and `NaN` is equal to itself.
+        assert_eq!(array, vec![Value::Number(-1.0), Value::Number(0.0), Value::Number(2.0), Value::Number(3.0), Value::Number(f64::NAN), Value::Number(f64::NAN)]);
+
+        let mut interpreter = Interpreter::new();
+        execute(&mut interpreter, "var a = [0]
+append(a, to_number('NaN'))
+append(a, 5)
+append(a, -2)
+append(a, 1)
+var b = sort(a)
+sort_in_place(a)").unwrap();
+        assert_eq!(interpreter.get_global("b").map(|b| b.to_string()).as_deref(), Some("[-2, 0, 1, 5, NaN]"));
+        assert_eq!(interpreter.get_global("a").map(|a| a.to_string()).as_deref(), Some("[-2, 0, 1, 5, NaN]"));
+    }
+
+    #[test]
+    fn sort_strings() {
+        let mut array = vec![Value::String_(String::from("b")), Value::String_(String::from("ab")), Value::String_(String::from("a"))];
+        assert_eq!(sort_values(&mut array, 1, 1), Ok(()));
+        assert_eq!(array, vec![Value::String_(String::from("a")), Value::String_(String::from("ab")), Value::String_(String::from("b"))]);
+    }
+
+    #[test]
+    fn sort_mixed_types() {
+        let mut array = vec![Value::Number(3.0), Value::Number(1.0), Value::String_(String::from("a"))];
+        assert_eq!(sort_values(&mut array, 1, 1), Err(ErrorType::BinaryTypeError {
+            expected: String::from("Number or String"),
+            got_left: String::from("Number"),
+            got_right: String::from("String"),
+            line: 1, column: 1,
+        }));
+
+        let mut array = vec![Value::Bool(true), Value::Bool(false)];
+        assert!(sort_values(&mut array, 1, 1).is_err());
+
+        // Arrays with a single element are sorted regardless of type.
+        let mut array = vec![Value::Bool(true)];
+        assert_eq!(sort_values(&mut array, 1, 1), Ok(()));
+    }
+
+    #[test]
+    fn modulo_by_zero() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(execute(&mut interpreter, "print 7 % 0"), Err(ErrorType::ModuloByZero { line: 1, column: 11 }));
+        assert_eq!(execute(&mut interpreter, "print 7 % -0"), Err(ErrorType::ModuloByZero { line: 1, column: 12 }));
+        assert_eq!(execute(&mut interpreter, "print 7 / 0"), Err(ErrorType::DivideByZero { line: 1, column: 11 }));
+        assert_eq!(execute(&mut interpreter, "print 7 % 2"), Ok(()));
+    }
+
+    #[test]
+    fn short_circuit() {
+        let mut interpreter = Interpreter::new();
+        let source = "\
+var calls = 0
+func count() {
+    calls = calls + 1
+    return true
+}
+var x = 0
+var a = x != 0 and 10 / x > 1
+var b = x == 0 or 10 / x > 1
+var c = false and count()
+var d = true or count()
+var e = true and count()";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        let globals: Vec<Option<Value>> = ["a", "b", "c", "d", "e"].iter().map(|name| interpreter.get_global(name)).collect();
+        assert_eq!(globals, [false, true, false, true, true].map(|value| Some(Value::Bool(value))));
+        // The right-hand side is only evaluated when the left-hand side does not decide the result.
+        assert_eq!(interpreter.get_global("calls"), Some(Value::Number(1.0)));
+
+        // Both sides are still checked to be Booleans when both are evaluated.
+        assert!(matches!(execute(&mut interpreter, "true and 1"), Err(ErrorType::BinaryTypeError { .. })));
+        assert!(matches!(execute(&mut interpreter, "1 or x / 0"), Err(ErrorType::DivideByZero { .. })));
+    }
+
+    #[test]
+    fn output() {
+        let mut interpreter = Interpreter::new();
+        let (output, error_output) = (SharedBuffer::default(), SharedBuffer::default());
+        interpreter.set_output(Box::new(output.clone()));
+        interpreter.set_error_output(Box::new(error_output.clone()));
+        interpreter.set_trace(true);
+
+        let source = "var x = 1\nprint x + 1\nfunc f() {\n    print 1 / 0\n}\nf()";
+        let ast = Parser::new(Tokenizer::new(source).tokenize().unwrap()).parse().unwrap();
+        assert!(interpreter.interpret(ast).is_err());
+
+        assert_eq!(output.contents(), "2\n");
+        assert_eq!(error_output.contents(), "\
+line 1: VarDecl
+  x = 1
+line 2: Print
+line 3: Function
+line 6: Expression
+  line 4: Print
+An error has occurred.
+[E0021] Line 4, column 15: divisor is 0.
+Stack trace (most recent call first):
+    in `f`, called at line 6, column 3
+");
+    }
+
+    /// An output which cannot be written to, like a pipe whose reader has stopped.
+    struct ClosedPipe;
+
+    impl Write for ClosedPipe {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::Error::from(io::ErrorKind::BrokenPipe))
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Err(io::Error::from(io::ErrorKind::BrokenPipe))
+        }
+    }
+
+    #[test]
+    fn output_which_cannot_be_written() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output(Box::new(ClosedPipe));
+        interpreter.set_error_output(Box::new(SharedBuffer::default()));
+        let reason = io::Error::from(io::ErrorKind::BrokenPipe).to_string();
+        for (source, column) in [("print 1", 1), ("clear_screen()", 14), ("input('> ')", 11)] {
+            let ast = Parser::new(Tokenizer::new(source).tokenize().unwrap()).parse().unwrap();
+            assert_eq!(interpreter.interpret(ast), Err(ErrorType::CannotWriteOutput { reason: reason.clone(), line: 1, column }), "{}", source);
+        }
+    }
+
+    #[test]
+    fn get() {
+        let mut interpreter = Interpreter::new();
+        let source = "var d = {'a': 1}\nvar found = get(d, 'a', 0)\nvar missing = get(d, 'b', 0)\nvar keys = size(d)";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        assert_eq!(interpreter.get_global("found"), Some(Value::Number(1.0)));
+        assert_eq!(interpreter.get_global("missing"), Some(Value::Number(0.0)));
+        assert_eq!(interpreter.get_global("keys"), Some(Value::Number(1.0)));
+
+        assert_eq!(execute(&mut interpreter, "get(d, size, 0)"), Err(ErrorType::CannotHashFunction { line: 1, column: 8 }));
+        assert!(matches!(execute(&mut interpreter, "get([1], 0, 0)"), Err(ErrorType::ExpectedType { .. })));
+        assert!(matches!(execute(&mut interpreter, "get(d, 'a')"), Err(ErrorType::ArgParamNumberMismatch { arg_number: 2, param_number: 3, .. })));
+    }
+
+    #[test]
+    fn resize() {
+        let mut interpreter = Interpreter::new();
+        let source = "var a = [1, 2, 3]\nvar b = [[1]]\nvar shrunk = resize(a, 1, 0)\nresize(b[0], 3, 'x')\na[size(a)] = 5\nb[size(b)] = []";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        assert_eq!(interpreter.get_global("shrunk").map(|a| a.to_string()).as_deref(), Some("[1]"));
+        assert_eq!(interpreter.get_global("a").map(|a| a.to_string()).as_deref(), Some("[1, 5]"));
+        assert_eq!(interpreter.get_global("b").map(|b| b.to_string()).as_deref(), Some("[[1, x, x], []]"));
+
+        assert_eq!(execute(&mut interpreter, "a[3] = 1"), Err(ErrorType::OutOfBoundsIndex { index: 3, line: 1, column: 8 }));
+        assert!(matches!(execute(&mut interpreter, "resize(a, -1, 0)"), Err(ErrorType::NonNaturalIndex { .. })));
+        assert!(matches!(execute(&mut interpreter, "resize(shrunk[0], 1, 0)"), Err(ErrorType::ExpectedType { .. })));
+        assert!(matches!(execute(&mut interpreter, "resize(a, 1)"), Err(ErrorType::ArgParamNumberMismatch { arg_number: 2, param_number: 3, .. })));
+        // A size which there is not enough memory for is an error, rather than aborting the program.
+        assert_eq!(execute(&mut interpreter, "resize(a, 1e18, 0)"), Err(ErrorType::ArrayTooLarge { size: 1_000_000_000_000_000_000, line: 1, column: 11 }));
+    }
+
+    #[test]
+    fn truthy_conditions() {
+        let source = "\
+var taken = []
+var values = [null, 0, '', [], {}, false, 1, 'a', [0], {0: 0}, to_number('NaN'), size]
+for (var i = 0; i < size(values); i = i + 1) {
+    if (values[i]) { append(taken, i) }
+}
+var queue = [1, 2, 3]
+while (queue) { remove(queue, 0) }
+var n = 3
+for (; n; n = n - 1) {}";
+        // Only Booleans can be conditions by default.
+        let mut interpreter = Interpreter::new();
+        assert_eq!(execute(&mut interpreter, "if (1) {}"), Err(ErrorType::IfConditionNotBoolean { line: 1, column: 5 }));
+        assert!(matches!(execute(&mut interpreter, "while ([]) {}"), Err(ErrorType::LoopConditionNotBoolean { .. })));
+
+        interpreter.set_truthy(true);
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        let expected: Vec<Value> = [6.0, 7.0, 8.0, 9.0, 10.0, 11.0].iter().map(|i| Value::Number(*i)).collect();
+        assert_eq!(interpreter.get_global("taken"), Some(Value::Array(expected.into())));
+        assert_eq!(interpreter.get_global("queue"), Some(Value::Array(Vec::new().into())));
+        assert_eq!(interpreter.get_global("n"), Some(Value::Number(0.0)));
+    }
+
+    #[test]
+    fn freeze() {
+        let mut interpreter = Interpreter::new();
+        let source = "\
+var table = {'primes': [2, 3], 'name': 'x'}
+var same = freeze(table)
+var copy = table
+var literal = freeze([1, [2]])
+var outer = [[1], 'ab']
+freeze(outer[0])
+outer[1][0] = 'c'
+outer[2] = 3
+var equal = [table == {'primes': [2, 3], 'name': 'x'}, get(table, 'name', null) == 'x']
+table = 5";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        let frozen = |interpreter: &Interpreter, name: &str| interpreter.get_global(name).map(|value| value.is_frozen());
+        assert_eq!([frozen(&interpreter, "same"), frozen(&interpreter, "copy"), frozen(&interpreter, "literal")], [Some(true); 3]);
+        assert_eq!(interpreter.get_global("outer").map(|outer| outer.to_string()).as_deref(), Some("[[1], cb, 3]"));
+        assert_eq!(interpreter.get_global("equal"), Some(Value::Array(vec![Value::Bool(true); 2].into())));
+        // Assigning a new value to the variable does not change the frozen value.
+        assert_eq!(interpreter.get_global("table"), Some(Value::Number(5.0)));
+
+        // Every way of changing a frozen value, or one inside it, is an error.
+        let frozen_error = |line, column| Err(ErrorType::FrozenValueMutation { line, column });
+        let mutations = [
+            "copy['primes'][0] = 1", "copy['new'] = 1", "copy['name'][0] = 'y'", "literal[1][0] = 3", "outer[0][1] = 2",
+            "append(literal, 1)", "append(copy['primes'], 1)", "remove(copy, 'name')", "resize(literal, 0, null)",
+            "sort_in_place(literal[1])", "set_default(copy, 'name', 1)",
+        ];
+        for mutation in mutations {
+            assert!(matches!(execute(&mut interpreter, mutation), Err(ErrorType::FrozenValueMutation { .. })), "{}", mutation);
+        }
+        assert_eq!(execute(&mut interpreter, "append(literal, 1)"), frozen_error(1, 8));
+        assert_eq!(interpreter.get_global("copy"), interpreter.get_global("same"));
+
+        // Only arrays and dictionaries can be changed, so only they can be frozen.
+        assert!(matches!(execute(&mut interpreter, "freeze('abc')"), Err(ErrorType::ExpectedType { .. })));
+        assert!(matches!(execute(&mut interpreter, "freeze()"), Err(ErrorType::ArgParamNumberMismatch { arg_number: 0, param_number: 1, .. })));
+    }
+
+    #[test]
+    fn slices() {
+        let mut interpreter = Interpreter::new();
+        let slice = |interpreter: &mut Interpreter, source: &str| interpreter.eval_expression(source).map(|value| value.to_string());
+        execute(&mut interpreter, "var a = freeze([0, 1, 2, 3, 4, 5])").unwrap();
+        let cases = [
+            ("a[1:4]", "[1, 2, 3]"), ("a[::2]", "[0, 2, 4]"), ("a[2:]", "[2, 3, 4, 5]"), ("a[:3]", "[0, 1, 2]"),
+            ("a[::-1]", "[5, 4, 3, 2, 1, 0]"), ("a[4:1:-2]", "[4, 2]"), ("a[null:2:null]", "[0, 1]"), ("a[3:1]", "[]"),
+            // Bounds past the ends are clamped, and negative bounds are before the start.
+            ("a[-2:100]", "[0, 1, 2, 3, 4, 5]"), ("a[100:-2:-3]", "[5, 2]"), ("a[-1::-1]", "[]"),
+            ("'hello'[1:4]", "ell"), ("'héllo'[::-1]", "olléh"), ("[][::-1]", "[]"),
+        ];
+        for (source, expected) in cases {
+            assert_eq!(slice(&mut interpreter, source).as_deref(), Ok(expected), "{}", source);
+        }
+        // A slice is a new array, which can be changed even if the original is frozen.
+        assert_eq!(execute(&mut interpreter, "var b = a[:2]\nb[0] = 9"), Ok(()));
+        assert_eq!(interpreter.get_global("b").map(|b| b.to_string()).as_deref(), Some("[9, 1]"));
+
+        assert_eq!(execute(&mut interpreter, "a[::0]"), Err(ErrorType::ZeroSliceStep { line: 1, column: 5 }));
+        assert_eq!(execute(&mut interpreter, "a[0.5:]"), Err(ErrorType::NotAWholeNumber { got: Value::Number(0.5), line: 1, column: 3 }));
+        assert!(matches!(execute(&mut interpreter, "a['1':]"), Err(ErrorType::ExpectedType { .. })));
+        assert!(matches!(execute(&mut interpreter, "{'k': 1}[1:]"), Err(ErrorType::ExpectedType { .. })));
+        assert_eq!(execute(&mut interpreter, "b[0:1] = [2]"), Err(ErrorType::InvalidAssignmentTarget { line: 1, column: 12 }));
+    }
+
+    #[test]
+    fn tuples() {
+        let mut interpreter = Interpreter::new();
+        let source = "\
+func divide(a, b) {
+    return (a - a % b) / b, a % b
+}
+var q, r = divide(7, 2)
+var pair = divide(9, 4)
+var a = 1
+var b = [2, 3]
+a, b[0] = b[0], a";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        let global = |interpreter: &Interpreter, name: &str| interpreter.get_global(name).map(|value| value.to_string());
+        assert_eq!([global(&interpreter, "q"), global(&interpreter, "r")], [Some(String::from("3")), Some(String::from("1"))]);
+        // A tuple is an array, which can be kept as it is and indexed.
+        assert_eq!(global(&interpreter, "pair").as_deref(), Some("[2, 1]"));
+        assert_eq!([global(&interpreter, "a"), global(&interpreter, "b")], [Some(String::from("2")), Some(String::from("[1, 3]"))]);
+
+        // There must be one element for each target.
+        assert_eq!(execute(&mut interpreter, "var x, y, z = pair"), Err(ErrorType::UnpackMismatch { expected: 3, got: 2, line: 1, column: 15 }));
+        assert_eq!(execute(&mut interpreter, "a, b = 1, 2, 3"), Err(ErrorType::UnpackMismatch { expected: 2, got: 3, line: 1, column: 14 }));
+        assert!(matches!(execute(&mut interpreter, "a, b = 5"), Err(ErrorType::ExpectedType { .. })));
+        assert!(matches!(execute(&mut interpreter, "a, 1 = 1, 2"), Err(ErrorType::InvalidAssignmentTarget { .. })));
+    }
+
+    #[test]
+    fn failed_unpacking_changes_nothing() {
+        let mut interpreter = Interpreter::new();
+        let source = "\
+var c = 1
+var b = [0]
+var d = {'k': 1}
+var s = 'ab'
+var caught = null
+try {
+    c, b[3] = 5, 6
+} catch (error) {
+    caught = c
+}";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        let global = |interpreter: &Interpreter, name: &str| interpreter.get_global(name).map(|value| value.to_string());
+        assert_eq!(global(&interpreter, "caught").as_deref(), Some("1"));
+
+        // Elements which were replaced are put back, and those which were appended or inserted are removed again.
+        assert!(matches!(execute(&mut interpreter, "b[0], b[1], d['k'], d['new'], s[0], x = 1, 2, 3, 4, 'z', 5"), Err(ErrorType::NameError { .. })));
+        assert_eq!([global(&interpreter, "b"), global(&interpreter, "d"), global(&interpreter, "s")], [Some(String::from("[0]")), Some(String::from("{k: 1}")), Some(String::from("ab"))]);
+        assert!(matches!(execute(&mut interpreter, "c, b = 2, freeze([1])\nc, b[0] = 3, 4"), Err(ErrorType::FrozenValueMutation { .. })));
+        assert_eq!(execute(&mut interpreter, "c, b = b, c"), Ok(()));
+        assert_eq!([global(&interpreter, "c"), global(&interpreter, "b")], [Some(String::from("[1]")), Some(String::from("2"))]);
+    }
+
+    #[test]
+    fn named_arguments() {
+        let mut interpreter = Interpreter::new();
+        let source = "\
+func label(x, color, width) {
+    return to_string(x) + ' ' + color + ' ' + to_string(width)
+}
+var a = label(1, color: 'red', width: 2)
+var b = label(1, width: 2, color: 'red')
+var c = label(width: 3, x: 0, color: 'blue')";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        let global = |interpreter: &Interpreter, name: &str| interpreter.get_global(name).map(|value| value.to_string());
+        assert_eq!([global(&interpreter, "a"), global(&interpreter, "b")], [Some(String::from("1 red 2")), Some(String::from("1 red 2"))]);
+        assert_eq!(global(&interpreter, "c").as_deref(), Some("0 blue 3"));
+
+        let unknown = |name: &str, function: &str, column| Err(ErrorType::UnknownParameter { name: String::from(name), function: String::from(function), line: 1, column });
+        assert_eq!(execute(&mut interpreter, "label(1, colour: 'red', width: 2)"), unknown("colour", "label", 18));
+        // Built-in functions have no names for their parameters.
+        assert_eq!(execute(&mut interpreter, "size(array: [1])"), unknown("array", "size", 15));
+        assert_eq!(execute(&mut interpreter, "label(1, x: 2, width: 3)"), Err(ErrorType::DuplicateArgument {
+            name: String::from("x"), function: String::from("label"), line: 1, column: 13,
+        }));
+        assert!(matches!(execute(&mut interpreter, "label(1, width: 2, width: 3)"), Err(ErrorType::DuplicateArgument { .. })));
+        assert!(matches!(execute(&mut interpreter, "label(1, color: 'red')"), Err(ErrorType::ArgParamNumberMismatch { arg_number: 2, param_number: 3, .. })));
+    }
+
+    #[test]
+    fn string_concatenation() {
+        let mut interpreter = Interpreter::new();
+        // If either side of `+` is a string, the other side is written as `print` would write it, while numbers are still added.
+        let source = "var a = 'Score: ' + 10\nvar b = 1 + 2 + 'x' + true\nvar c = [1, 'a'] + '!' + null\nvar d = 0.5 + 0.25";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        assert_eq!(interpreter.get_global("a"), Some(Value::String_(String::from("Score: 10"))));
+        assert_eq!(interpreter.get_global("b"), Some(Value::String_(String::from("3xtrue"))));
+        assert_eq!(interpreter.get_global("c"), Some(Value::String_(String::from("[1, a]!null"))));
+        assert_eq!(interpreter.get_global("d"), Some(Value::Number(0.75)));
+        assert!(matches!(execute(&mut interpreter, "1 + true"), Err(ErrorType::BinaryTypeError { .. })));
+    }
+
+    #[test]
+    fn repeat() {
+        let mut interpreter = Interpreter::new();
+        // The count is evaluated once, so changing `n` in the body does not change how many times it is executed.
+        let source = "var n = 3\nvar total = 0\nrepeat n {\n    n = n + 1\n    total = total + 1\n}\nrepeat 10 {\n    if (total == 5) { break }\n    total = total + 1\n}\nrepeat 0 { total = 100 }";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        assert_eq!(interpreter.get_global("n"), Some(Value::Number(6.0)));
+        assert_eq!(interpreter.get_global("total"), Some(Value::Number(5.0)));
+
+        assert!(matches!(execute(&mut interpreter, "repeat 2.5 {}"), Err(ErrorType::InvalidRepeatCount { got: Value::Number(2.5), line: 1, column: 8 })));
+        assert!(matches!(execute(&mut interpreter, "repeat n - 7 {}"), Err(ErrorType::InvalidRepeatCount { .. })));
+        assert!(matches!(execute(&mut interpreter, "repeat '3' {}"), Err(ErrorType::ExpectedType { .. })));
+    }
+
+    #[test]
+    fn equality() {
+        let mut interpreter = Interpreter::new();
+        let source = "\
+var nan = to_number('NaN')
+func f() {}
+var g = f
+var copies = [f] == [g]
+func f() {}
+var redeclared = f == g
+var checks = [1 != '1', 1 != true, 0 == -0, nan == nan, [nan] == [nan], !(nan < nan), {1: 'a', 2: 'b'} == {2: 'b', 1: 'a'}, size == size, size != sort, null == null]
+var d = {}
+d[nan] = 1
+d[to_number('NaN')] = 2";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        assert_eq!(interpreter.get_global("copies"), Some(Value::Bool(true)));
+        assert_eq!(interpreter.get_global("redeclared"), Some(Value::Bool(false)));
+        assert_eq!(interpreter.get_global("checks"), Some(Value::Array(vec![Value::Bool(true); 10].into())));
+        // Every `NaN` is the same key.
+        assert_eq!(interpreter.get_global("d").map(|d| d.to_string()).as_deref(), Some("{NaN: 2}"));
+    }
+
+    #[test]
+    fn number_printing() {
+        let mut interpreter = Interpreter::new();
+        let output = SharedBuffer::default();
+        interpreter.set_output(Box::new(output.clone()));
+        let source = "print [3, 0.1 + 0.2, -0, 1 / 3, 0.000001, 0.0000005, 1000000000000000000000, 123456789012345680000]\nprint to_string(-2.50)";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        assert_eq!(output.contents(), "[3, 0.30000000000000004, 0, 0.3333333333333333, 0.000001, 5e-7, 1e21, 123456789012345680000]\n-2.5\n");
+        assert_eq!(Value::Number(f64::NAN).to_string(), "NaN");
+        assert_eq!(Value::Number(-f64::INFINITY).summary(), "Number -inf");
+    }
+
+    #[test]
+    fn pretty_printing() {
+        let mut interpreter = Interpreter::new();
+        let output = SharedBuffer::default();
+        interpreter.set_output(Box::new(output.clone()));
+        interpreter.set_pretty(Some(3));
+        let source = "\
+var a = []
+for (var i = 0; i < 1003; i = i + 1) { append(a, i) }
+print [1, [2, 3], {'a': []}]
+print [a, 'x']
+print {'words': ['a fairly long string', 'another fairly long string', 'and one more string'], 'empty': {}}
+print 'not indented'";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        assert_eq!(output.contents(), "\
+[1, [2, 3], {a: []}]
+[[0, 1, 2, … 1,000 more], x]
+{
+    words: [
+        a fairly long string,
+        another fairly long string,
+        and one more string
+    ],
+    empty: {}
+}
+not indented
+");
+        assert_eq!(interpreter.pretty(), Some(3));
+        assert_eq!(Value::Array(vec![Value::Null; 5].into()).pretty(0), "[… 5 more]");
+    }
+
+    #[test]
+    fn to_fixed() {
+        let mut interpreter = Interpreter::new();
+        let source = "var a = to_fixed(0.1 + 0.2, 2)\nvar b = to_fixed(2.5, 0)\nvar c = to_fixed(-1, 3)\nvar d = to_fixed(1000000000000000000000, 1)";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        let string = |name: &str| interpreter.get_global(name).map(|value| value.to_string());
+        assert_eq!([string("a"), string("b"), string("c")], [Some(String::from("0.30")), Some(String::from("2")), Some(String::from("-1.000"))]);
+        assert_eq!(string("d").as_deref(), Some("1000000000000000000000.0"));
+
+        assert_eq!(execute(&mut interpreter, "to_fixed(1, 1.5)"), Err(ErrorType::InvalidDigits { got: Value::Number(1.5), line: 1, column: 13 }));
+        assert_eq!(execute(&mut interpreter, "to_fixed(1, 101)"), Err(ErrorType::InvalidDigits { got: Value::Number(101.0), line: 1, column: 13 }));
+        assert!(matches!(execute(&mut interpreter, "to_fixed('1', 2)"), Err(ErrorType::ExpectedType { .. })));
+    }
+
+    #[test]
+    fn input() {
+        let mut interpreter = Interpreter::new();
+        let output = SharedBuffer::default();
+        interpreter.set_output(Box::new(output.clone()));
+        interpreter.set_input(Box::new(io::Cursor::new("Alice\n  42  \n")));
+
+        let source = "var name = input('Name: ')\nvar age = to_number(input('Age: '))\nvar rest = input('More: ')";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        // The prompts go to the output, and the lines are trimmed. At the end of the input, an empty string is read.
+        assert_eq!(output.contents(), "Name: Age: More: ");
+        assert_eq!(interpreter.variables(), vec![
+            (String::from("age"), Value::Number(42.0)),
+            (String::from("name"), Value::String_(String::from("Alice"))),
+            (String::from("rest"), Value::String_(String::new())),
+        ]);
+    }
+
+    #[test]
+    fn read_line_and_read_all() {
+        let mut interpreter = Interpreter::new();
+        let output = SharedBuffer::default();
+        interpreter.set_output(Box::new(output.clone()));
+        interpreter.set_input(Box::new(io::Cursor::new("  first  \r\n\nthird\nfourth\nfifth")));
+
+        let source = "var a = read_line()\nvar b = read_line()\nvar c = read_line()\nvar rest = read_all()\nvar end = read_line()\nvar empty = read_all()";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        // Nothing is printed, only line endings are removed, and the end of the input is `null` for `read_line()`.
+        assert_eq!(output.contents(), "");
+        assert_eq!(interpreter.get_global("a"), Some(Value::String_(String::from("  first  "))));
+        assert_eq!(interpreter.get_global("b"), Some(Value::String_(String::new())));
+        assert_eq!(interpreter.get_global("c"), Some(Value::String_(String::from("third"))));
+        assert_eq!(interpreter.get_global("rest"), Some(Value::String_(String::from("fourth\nfifth"))));
+        assert_eq!(interpreter.get_global("end"), Some(Value::Null));
+        assert_eq!(interpreter.get_global("empty"), Some(Value::String_(String::new())));
+    }
+
+    #[test]
+    fn terminal_control() {
+        let mut interpreter = Interpreter::new();
+        let output = SharedBuffer::default();
+        interpreter.set_output(Box::new(output.clone()));
+
+        let source = "clear_screen()\nmove_cursor(3, 10)\nset_color('red')\nprint colored(5, 'bold') + '!'";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        assert_eq!(output.contents(), "\x1b[2J\x1b[H\x1b[3;10H\x1b[31m\x1b[1m5\x1b[0m!\n");
+
+        // Without colour, the text is left as it is, but the names are still checked.
+        interpreter.set_colour(false);
+        assert_eq!(execute(&mut interpreter, "set_color('green')\nprint colored('go', 'green')"), Ok(()));
+        assert!(output.contents().ends_with("!\ngo\n"));
+        assert_eq!(execute(&mut interpreter, "set_color('Green')"), Err(ErrorType::UnknownColour { name: String::from("Green"), line: 1, column: 11 }));
+        assert_eq!(execute(&mut interpreter, "move_cursor(1, 0)"), Err(ErrorType::InvalidCursorPosition { got: Value::Number(0.0), line: 1, column: 16 }));
+        assert!(matches!(execute(&mut interpreter, "move_cursor('1', 1)"), Err(ErrorType::InvalidCursorPosition { line: 1, column: 13, .. })));
+    }
+
+    #[test]
+    fn bases() {
+        let mut interpreter = Interpreter::new();
+        let source = "var parsed = [to_number('ff', 16), to_number('-101', 2), to_number('Z', 36), to_number('+7', 8)]\nvar written = [to_base(255, 2), to_base(255, 16), to_base(-35, 36), to_base(0, 2)]";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        let parsed = [255.0, -5.0, 35.0, 7.0].map(Value::Number);
+        assert_eq!(interpreter.get_global("parsed"), Some(Value::Array(parsed.into_iter().collect())));
+        let written = ["11111111", "ff", "-z", "0"].map(|digits| Value::String_(String::from(digits)));
+        assert_eq!(interpreter.get_global("written"), Some(Value::Array(written.into_iter().collect())));
+
+        // Every character must be a digit of the base.
+        for source in ["to_number('12', 2)", "to_number('', 16)", "to_number('-', 16)", "to_number('0x1f', 16)", "to_number(' 1', 10)"] {
+            assert!(matches!(execute(&mut interpreter, source), Err(ErrorType::CannotConvertToNumber { .. })), "{}", source);
+        }
+        assert_eq!(execute(&mut interpreter, "to_base(10, 37)"), Err(ErrorType::InvalidBase { got: Value::Number(37.0), line: 1, column: 13 }));
+        assert!(matches!(execute(&mut interpreter, "to_number('1', 1.5)"), Err(ErrorType::InvalidBase { .. })));
+        assert!(matches!(execute(&mut interpreter, "to_number(11, 2)"), Err(ErrorType::ExpectedType { .. })));
+        assert!(matches!(execute(&mut interpreter, "to_base(0.5, 2)"), Err(ErrorType::NotAWholeNumber { .. })));
+        assert!(matches!(execute(&mut interpreter, "to_number('1', 2, 3)"), Err(ErrorType::ArgParamNumberMismatch { arg_number: 3, param_number: 2, .. })));
+    }
+
+    #[test]
+    fn number_theory() {
+        let mut interpreter = Interpreter::new();
+        let source = "var divisors = [gcd(12, -18), gcd(0, 0), lcm(4, 6), lcm(0, 5)]\nvar primes = [is_prime(1), is_prime(2), is_prime(97), is_prime(91), is_prime(-7)]\nvar factorised = [factors(360), factors(1), factors(-13)]";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        let numbers = |xs: &[f64]| Value::Array(xs.iter().map(|&x| Value::Number(x)).collect());
+        assert_eq!(interpreter.get_global("divisors"), Some(numbers(&[6.0, 0.0, 12.0, 0.0])));
+        assert_eq!(interpreter.get_global("primes"), Some(Value::Array([false, true, true, false, false].map(Value::Bool).into_iter().collect())));
+        let factorised = [numbers(&[2.0, 2.0, 2.0, 3.0, 3.0, 5.0]), numbers(&[]), numbers(&[13.0])];
+        assert_eq!(interpreter.get_global("factorised"), Some(Value::Array(factorised.into_iter().collect())));
+
+        // Only whole numbers which are stored exactly have an exact answer.
+        assert_eq!(execute(&mut interpreter, "gcd(7.5, 3)"), Err(ErrorType::NotAWholeNumber { got: Value::Number(7.5), line: 1, column: 5 }));
+        assert!(matches!(execute(&mut interpreter, "is_prime(9007199254740992 * 2)"), Err(ErrorType::NotAWholeNumber { .. })));
+        assert!(matches!(execute(&mut interpreter, "lcm('4', 6)"), Err(ErrorType::ExpectedType { .. })));
+        // The least common multiple of two numbers which can be stored exactly may be too large to be stored exactly itself.
+        assert!(matches!(execute(&mut interpreter, "lcm(9007199254740881, 9007199254740847)"), Err(ErrorType::NotAWholeNumber { line: 1, column: 39, .. })));
+        assert!(execute(&mut interpreter, "lcm(9007199254740992, 2)").is_ok());
+    }
+
+    #[test]
+    fn grids() {
+        let mut interpreter = Interpreter::new();
+        // The rows of a matrix are copies, so changing one cell only changes that cell.
+        let source = "var grid = matrix(2, 3, 0)\ngrid[0][1] = 5\nvar columns = transpose(grid)\nvar unit = identity(2)\nvar cells = [get_cell(grid, 0, 1, -1), get_cell(grid, -1, 0, -1), get_cell(grid, 1, 3, -1), get_cell(grid, 0.5, 0, -1)]";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        let numbers = |rows: &[&[f64]]| Value::Array(rows.iter().map(|row| Value::Array(row.iter().map(|&x| Value::Number(x)).collect())).collect());
+        assert_eq!(interpreter.get_global("grid"), Some(numbers(&[&[0.0, 5.0, 0.0], &[0.0, 0.0, 0.0]])));
+        assert_eq!(interpreter.get_global("columns"), Some(numbers(&[&[0.0, 0.0], &[5.0, 0.0], &[0.0, 0.0]])));
+        assert_eq!(interpreter.get_global("unit"), Some(numbers(&[&[1.0, 0.0], &[0.0, 1.0]])));
+        // A cell off the grid is the default, rather than an error.
+        let cells = [5.0, -1.0, -1.0, -1.0].map(Value::Number);
+        assert_eq!(interpreter.get_global("cells"), Some(Value::Array(cells.into_iter().collect())));
+
+        assert_eq!(execute(&mut interpreter, "transpose([])"), Ok(()));
+        assert_eq!(execute(&mut interpreter, "transpose([[1, 2], [3]])"), Err(ErrorType::JaggedMatrix { row: 1, length: 1, expected: 2, line: 1, column: 23 }));
+        assert!(matches!(execute(&mut interpreter, "transpose([1])"), Err(ErrorType::ExpectedType { .. })));
+        assert!(matches!(execute(&mut interpreter, "matrix(-1, 2, 0)"), Err(ErrorType::NonNaturalIndex { .. })));
+        assert!(matches!(execute(&mut interpreter, "get_cell(grid, '0', 0, 0)"), Err(ErrorType::NonNumberIndex { .. })));
+    }
+
+    #[test]
+    fn turtle_graphics() {
+        let mut interpreter = Interpreter::new();
+        let source = "repeat 4 {\n    forward(10)\n    turn(90)\n}\npen_up()\nforward(5)\npen_down()\npen_color('red')\nforward(1)";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        let lines = interpreter.turtle().lines();
+        assert_eq!(lines.len(), 5);
+        assert_eq!((lines[0].colour, lines[4].colour), ("black", "red"));
+
+        assert_eq!(execute(&mut interpreter, "pen_color('bold')"), Err(ErrorType::UnknownColour { name: String::from("bold"), line: 1, column: 11 }));
+        assert!(matches!(execute(&mut interpreter, "turn('left')"), Err(ErrorType::ExpectedType { .. })));
+
+        // The drawing is written as SVG, and is kept to be saved again.
+        let path = std::env::temp_dir().join(format!("neal_turtle_{}.svg", std::process::id()));
+        let path = path.to_str().unwrap();
+        assert_eq!(execute(&mut interpreter, &format!("save_drawing('{}')", path)), Ok(()));
+        assert_eq!(std::fs::read_to_string(path).unwrap(), interpreter.turtle().to_svg());
+        std::fs::remove_file(path).unwrap();
+        let missing = std::env::temp_dir().join("neal_missing_directory").join("drawing.svg");
+        let result = execute(&mut interpreter, &format!("save_drawing('{}')", missing.to_str().unwrap()));
+        assert!(matches!(result, Err(ErrorType::CannotWriteFile { .. })), "{:?}", result);
+    }
+
+    #[test]
+    fn try_catch() {
+        let mut interpreter = Interpreter::new();
+        let source = "\
+var log = []
+func lookup(key) { return {'a': 1}[key] }
+try {
+    lookup('b')
+    append(log, 'not reached')
+} catch (error) {
+    append(log, error)
+} finally {
+    append(log, 'finally')
+}";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        let Some(Value::Array(log)) = interpreter.get_global("log") else { unreachable!() };
+        let Value::Dictionary(error) = &log[0] else { unreachable!() };
+        let entry = |key: &str| error.get(&Value::String_(String::from(key)), 0, 0).unwrap().clone();
+        assert_eq!(entry("code"), Value::String_(String::from("E0028")));
+        assert_eq!(entry("message"), Value::String_(String::from("key `b` does not exist in the dictionary.")));
+        assert_eq!((entry("line"), entry("column")), (Value::Number(2.0), Value::Number(36.0)));
+        assert_eq!(log[1], Value::String_(String::from("finally")));
+        // The calls the caught error was raised in are not left on the stack.
+        assert!(interpreter.call_stack.is_empty());
+
+        // `finally` blocks are executed on the way out of a `return` or `break`, which then carry on.
+        let source = "\
+var count = 0
+func f() {
+    try { return 1 } finally { count = count + 1 }
+}
+var result = f()
+while (true) {
+    try { break } finally { count = count + 1 }
+}";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        assert_eq!(interpreter.get_global("result"), Some(Value::Number(1.0)));
+        assert_eq!(interpreter.get_global("count"), Some(Value::Number(2.0)));
+
+        // An error raised in a `catch` block, or with no `catch` block, is raised after the `finally` block, and the error is only
+        // declared inside its `catch` block.
+        assert_eq!(execute(&mut interpreter, "try { print x } catch (e) { print e['y'] } finally { count = 10 }"), Err(ErrorType::KeyError {
+            key: Value::String_(String::from("y")), line: 1, column: 37,
+        }));
+        assert_eq!(interpreter.get_global("count"), Some(Value::Number(10.0)));
+        assert!(matches!(execute(&mut interpreter, "try { print [][0] } finally {}"), Err(ErrorType::OutOfBoundsIndex { .. })));
+        assert!(matches!(execute(&mut interpreter, "try { print 1 + '1' } catch (e) {}\nprint e"), Err(ErrorType::NameError { .. })));
+    }
+
+    #[test]
+    fn store_values() {
+        let directory = std::env::temp_dir().join(format!("neal_store_{}", std::process::id()));
+        std::fs::create_dir_all(&directory).unwrap();
+        let mut interpreter = Interpreter::new();
+        interpreter.set_script_path(&directory.join("scores.nea"));
+
+        // Nothing has been saved on the first run.
+        assert_eq!(execute(&mut interpreter, "var missing = store_load('best')"), Ok(()));
+        assert_eq!(interpreter.get_global("missing"), Some(Value::Null));
+
+        // Saved values are read back by a new interpreter, as they are by the next run of the script.
+        assert_eq!(execute(&mut interpreter, "store_save('best', 12)\nstore_save('names', ['Alice'])\nstore_save('best', 13)"), Ok(()));
+        let mut interpreter = Interpreter::new();
+        interpreter.set_script_path(&directory.join("scores.nea"));
+        assert_eq!(execute(&mut interpreter, "var best = store_load('best')\nvar names = store_load('names')"), Ok(()));
+        assert_eq!(interpreter.get_global("best"), Some(Value::Number(13.0)));
+        assert_eq!(interpreter.get_global("names"), Some(Value::Array(vec![Value::String_(String::from("Alice"))].into())));
+
+        assert_eq!(execute(&mut interpreter, "store_save('f', [size])"), Err(ErrorType::CannotStoreFunction { line: 1, column: 22 }));
+        assert!(matches!(execute(&mut interpreter, "store_load(1)"), Err(ErrorType::ExpectedType { .. })));
+        std::fs::write(directory.join("scores.store"), "{'best': 1 - 2}").unwrap();
+        assert!(matches!(execute(&mut interpreter, "store_load('best')"), Err(ErrorType::CorruptStore { .. })));
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn get_key() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_input(Box::new(io::Cursor::new("y\x1b[B\n")));
+
+        let source = "var keys = [get_key(), get_key(), get_key(), get_key()]";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        let keys = ["y", "down", "enter"].map(|key| Value::String_(String::from(key)));
+        assert_eq!(interpreter.get_global("keys"), Some(Value::Array(keys.into_iter().chain([Value::Null]).collect())));
+
+        // Ctrl-C stops the program, as the terminal does not raise a signal for it while a key is being read.
+        interpreter.set_input(Box::new(io::Cursor::new("\x03")));
+        assert_eq!(execute(&mut interpreter, "get_key()"), Err(ErrorType::Interrupted { line: 1, column: 9 }));
+    }
+
+    #[test]
+    fn input_number() {
+        let mut interpreter = Interpreter::new();
+        let output = SharedBuffer::default();
+        interpreter.set_output(Box::new(output.clone()));
+        interpreter.set_input(Box::new(io::Cursor::new("twelve\n\ninf\n -1.5 \n")));
+
+        // Anything which is not a finite number is asked for again.
+        assert_eq!(execute(&mut interpreter, "var x = input_number('Number: ')"), Ok(()));
+        assert_eq!(interpreter.get_global("x"), Some(Value::Number(-1.5)));
+        assert_eq!(output.contents(), "\
+Number: `twelve` is not a number. Please try again.
+Number: `` is not a number. Please try again.
+Number: `inf` is not a number. Please try again.
+Number: ");
+
+        // At the end of the input, there is nothing to ask again for.
+        assert!(matches!(execute(&mut interpreter, "input_number('Number: ')"), Err(ErrorType::CannotConvertToNumber { line: 1, .. })));
+    }
+
+    #[test]
+    fn reflection() {
+        let mut interpreter = Interpreter::new();
+        let source = "\
+var x = 1
+func f(a) {
+    var b = 2
+    if (true) {
+        var b = 3
+        return [locals(), globals(), defined('b'), defined('x'), defined('y')]
+    }
+}
+var result = f(0)
+var local_names = sort(keys(result[0]))
+var global_names = sort(keys(result[1]))
+var top = size(locals())
+var has_size = defined('size')";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        let strings = |names: &[&str]| Some(Value::Array(names.iter().map(|name| Value::String_(name.to_string())).collect()));
+        // Inside a function, the locals are its parameters and variables, with the innermost value of each name.
+        assert_eq!(interpreter.get_global("local_names"), strings(&["a", "b"]));
+        assert!(matches!(execute(&mut interpreter, "assert_eq(result[0]['b'], 3)"), Ok(())));
+        // The function itself is a global, but the variables declared after the call are not yet.
+        assert_eq!(interpreter.get_global("global_names"), strings(&["f", "x"]));
+        assert!(matches!(execute(&mut interpreter, "assert_eq([result[2], result[3], result[4], has_size], [true, true, false, true])"), Ok(())));
+        // Outside any function, the locals are the globals.
+        assert_eq!(interpreter.get_global("top"), Some(Value::Number(5.0)));
+
+        assert!(matches!(execute(&mut interpreter, "defined(1)"), Err(ErrorType::ExpectedType { .. })));
+        assert!(matches!(execute(&mut interpreter, "locals(1)"), Err(ErrorType::ArgParamNumberMismatch { .. })));
+    }
+
+    #[test]
+    fn eval_expression() {
+        let mut interpreter = Interpreter::new();
+        let output = SharedBuffer::default();
+        interpreter.set_output(Box::new(output.clone()));
+        interpreter.set_error_output(Box::new(output.clone()));
+        assert_eq!(execute(&mut interpreter, "var price = 4\nfunc double(x) { return x * 2 }"), Ok(()));
+
+        // The expression sees the variables and functions, and can change them, but nothing is printed, even for errors.
+        assert_eq!(interpreter.eval_expression("double(price) + 1"), Ok(Value::Number(9.0)));
+        assert_eq!(interpreter.eval_expression("price = 5"), Ok(Value::Number(5.0)));
+        assert_eq!(interpreter.get_global("price"), Some(Value::Number(5.0)));
+        assert!(matches!(interpreter.eval_expression("double(missing)"), Err(ErrorType::NameError { .. })));
+        assert!(matches!(interpreter.eval_expression("1 + 2 3"), Err(ErrorType::ExpectedEndOfExpression { line: 1, column: 7 })));
+        assert!(matches!(interpreter.eval_expression("'open"), Err(ErrorType::UnterminatedString { .. })));
+        assert!(matches!(interpreter.eval_expression("print 1"), Err(ErrorType::ExpectedExpression { .. })));
+        assert_eq!(output.contents(), "");
+
+        // A parsed expression can be evaluated again, e.g., after its variables change.
+        let tokens = Tokenizer::new("price * 2").tokenize().unwrap();
+        let formula = Parser::new(tokens).parse_expression().unwrap();
+        assert_eq!(interpreter.eval_parsed(&formula), Ok(Value::Number(10.0)));
+        interpreter.set_global("price", Value::Number(1.5));
+        assert_eq!(interpreter.eval_parsed(&formula), Ok(Value::Number(3.0)));
+    }
+
+    #[test]
+    fn eval() {
+        let mut interpreter = Interpreter::new();
+        let output = SharedBuffer::default();
+        interpreter.set_output(Box::new(output.clone()));
+        let source = "\
+var x = eval('1 + 2 * 3')
+func f() {
+    eval('var y = 2')
+    return eval('print y\ny * 10')
+}
+var z = f()
+var n = eval('var w = 1')";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        // The code is executed where `eval()` is called, so its declarations stay in that scope.
+        assert_eq!(interpreter.get_global("x"), Some(Value::Number(7.0)));
+        assert_eq!(interpreter.get_global("y"), None);
+        assert_eq!(interpreter.get_global("z"), Some(Value::Number(20.0)));
+        assert_eq!(interpreter.get_global("w"), Some(Value::Number(1.0)));
+        assert_eq!(interpreter.get_global("n"), Some(Value::Null));
+        assert_eq!(output.contents(), "2\n");
+
+        // Errors in the code are raised with their positions in the string.
+        assert!(matches!(execute(&mut interpreter, "eval('1 +')"), Err(ErrorType::ExpectedExpression { line: 1, .. })));
+        assert!(matches!(execute(&mut interpreter, "eval('\n\nmissing')"), Err(ErrorType::NameError { line: 3, column: 1, .. })));
+        assert!(matches!(execute(&mut interpreter, "eval(1)"), Err(ErrorType::ExpectedType { .. })));
+        // Strict rules apply to the code as they do to the program.
+        interpreter.set_strict(true);
+        assert!(matches!(execute(&mut interpreter, "eval('var a = 1 var b = 2')"), Err(ErrorType::ExpectedNewline { .. })));
+    }
+
+    #[test]
+    fn globals() {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_global("data", Value::Array(vec![Value::Number(3.0), Value::Number(4.0)].into()));
+        assert_eq!(execute(&mut interpreter, "var total = 0\nfor (var i = 0; i < size(data); i = i + 1) {\n    total = total + data[i]\n}"), Ok(()));
+        assert_eq!(interpreter.get_global("total"), Some(Value::Number(7.0)));
+        assert_eq!(interpreter.get_global("i"), None);
+        assert_eq!(interpreter.get_global("missing"), None);
+    }
+
+    #[test]
+    fn strict() {
+        let strict = |source: &str| {
+            let mut interpreter = Interpreter::new();
+            interpreter.set_strict(true);
+            execute(&mut interpreter, source)
+        };
+        let shadowing = |name: &str, line: usize, column: usize| Err(ErrorType::Shadowing { name: String::from(name), line, column });
+        let outer_assignment = |name: &str, line: usize, column: usize| Err(ErrorType::OuterAssignment { name: String::from(name), line, column });
+
+        assert_eq!(strict("var x = 1\nif (true) {\n    var x = 2\n}"), shadowing("x", 3, 5));
+        assert_eq!(strict("var x = 1\nfunc f() {\n    var x = 2\n}\nf()"), shadowing("x", 3, 5));
+        assert_eq!(strict("func f() {}\nif (true) {\n    func f() {}\n}"), shadowing("f", 3, 5));
+        // Redeclaring in the same scope, and recursive calls declaring the same variables, are allowed.
+        assert_eq!(strict("var x = 1\nvar x = 2"), Ok(()));
+        assert_eq!(strict("func f(n) {\n    var m = n\n    if (m > 0) { f(m - 1) }\n}\nf(2)"), Ok(()));
+        // A variable of the caller which is not global can be declared again inside the function.
+        assert_eq!(strict("func f() {\n    var y = 2\n}\nif (true) {\n    var y = 1\n    f()\n}"), Ok(()));
+
+        assert_eq!(strict("var count = 0\nfunc f() {\n    count = count + 1\n}\nf()"), outer_assignment("count", 3, 5));
+        assert_eq!(strict("var a = [0]\nfunc f() {\n    a[0] = 1\n}\nf()"), outer_assignment("a", 3, 7));
+        // Outside functions, and to the function's own variables, assignments are allowed.
+        assert_eq!(strict("var i = 0\nwhile (i < 3) {\n    i = i + 1\n}"), Ok(()));
+        assert_eq!(strict("func f(n) {\n    var m = 0\n    m = n\n    n = m\n}\nf(1)"), Ok(()));
+
+        // None of this is an error outside strict mode.
+        assert_eq!(execute(&mut Interpreter::new(), "var count = 0\nfunc f() {\n    var count = 1\n    count = 2\n}\nf()\ncount = 3"), Ok(()));
+    }
+
+    #[test]
+    fn interrupt() {
+        let mut interpreter = Interpreter::new();
+        let interrupt = Arc::new(AtomicBool::new(false));
+        interpreter.set_interrupt_flag(Arc::clone(&interrupt));
+        assert_eq!(execute(&mut interpreter, "var x = 1"), Ok(()));
+
+        // The flag is set on another thread while the loop is running, as a Ctrl-C handler would.
+        let setter = Arc::clone(&interrupt);
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            setter.store(true, AtomicOrdering::Relaxed);
+        });
+        let result = execute(&mut interpreter, "while (true) {\n    x = x + 1\n}");
+        assert!(matches!(result, Err(ErrorType::Interrupted { .. })), "{:?}", result);
+
+        // The flag is cleared, and the variables are kept as they were when the loop was stopped.
+        assert!(!interrupt.load(AtomicOrdering::Relaxed));
+        let x = interpreter.variables()[0].1.clone();
+        assert!(matches!(x, Value::Number(x) if x > 1.0));
+        assert_eq!(execute(&mut interpreter, "x = 0"), Ok(()));
+    }
+
+    #[test]
+    fn runs_on_other_threads() {
+        // The interpreter is moved to a worker thread, which runs a program with it and hands it back with its variables.
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output(Box::new(io::sink()));
+        assert_eq!(execute(&mut interpreter, "var d = {'a': [1]}"), Ok(()));
+        let worker = std::thread::spawn(move || {
+            assert_eq!(execute(&mut interpreter, "append(d['a'], 2)"), Ok(()));
+            interpreter
+        });
+        let interpreter = worker.join().unwrap();
+        assert_eq!(interpreter.get_global("d").unwrap().to_string(), "{a: [1, 2]}");
+    }
+
+    /// Records every event it is told about, shared with the test.
+    struct Events(Arc<Mutex<Vec<String>>>);
+
+    impl Hooks for Events {
+        fn before_statement(&mut self, stmt: &Stmt) {
+            self.0.lock().unwrap().push(format!("{} {}", stmt.stmt_type.kind(), stmt.line));
+        }
+
+        fn after_statement(&mut self, stmt: &Stmt) {
+            self.0.lock().unwrap().push(format!("/{}", stmt.stmt_type.kind()));
+        }
+
+        fn after_expression(&mut self, _expr: &Expr, value: &Value) {
+            self.0.lock().unwrap().push(format!("= {}", value));
+        }
+
+        fn before_call(&mut self, function: &str) {
+            self.0.lock().unwrap().push(format!("call {}", function));
+        }
+
+        fn after_call(&mut self, function: &str) {
+            self.0.lock().unwrap().push(format!("/call {}", function));
+        }
+    }
+
+    #[test]
+    fn hooks() {
+        let mut interpreter = Interpreter::new();
+        let events = Arc::new(Mutex::new(Vec::new()));
+        interpreter.add_hooks(Box::new(Events(Arc::clone(&events))));
+        interpreter.set_output(Box::new(io::sink()));
+        assert_eq!(execute(&mut interpreter, "func f(x) {\n    print x\n}\nf(1 + 2)"), Ok(()));
+        assert_eq!(*events.lock().unwrap(), [
+            "Function 1", "/Function",
+            "Expression 4", "= <function>", "= 1", "= 2", "= 3", "call f",
+            "Print 2", "= 3", "/Print",
+            "/call f", "= null", "/Expression",
+        ]);
+    }
+
+    #[test]
+    fn cancellation() {
+        let mut interpreter = Interpreter::new();
+        let token = interpreter.cancellation_token();
+        assert_eq!(execute(&mut interpreter, "var x = 1"), Ok(()));
+
+        // The token is cancelled on another thread while the loop is running, as a host enforcing a time limit would.
+        let canceller = token.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            canceller.cancel();
+        });
+        let result = execute(&mut interpreter, "while (true) {\n    x = x + 1\n}");
+        assert!(matches!(result, Err(ErrorType::Interrupted { .. })), "{:?}", result);
+
+        // Unlike an interrupt, the cancellation lasts until the token is reset.
+        assert!(token.is_cancelled());
+        assert_eq!(execute(&mut interpreter, "x = 0"), Err(ErrorType::Interrupted { line: 1, column: 1 }));
+        token.reset();
+        assert_eq!(execute(&mut interpreter, "x = 0"), Ok(()));
+    }
+
+    #[test]
+    fn profiling() {
+        let mut interpreter = Interpreter::new();
+        interpreter.enable_profiling();
+        let source = "func f(n) {\n    if (n > 0) { f(n - 1) }\n}\nf(2)\nvar x = 1";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+
+        let profiler = interpreter.profiler().unwrap();
+        let mut lines: Vec<(usize, usize)> = profiler.lines().iter().map(|(line, stats)| (*line, stats.count)).collect();
+        lines.sort();
+        // Line 2 has the `if` statement, which runs three times, and the call in it, which runs twice.
+        assert_eq!(lines, vec![(1, 1), (2, 5), (4, 1), (5, 1)]);
+        let functions: Vec<(String, usize)> = profiler.functions().iter().map(|(name, stats)| (name.clone(), stats.count)).collect();
+        assert_eq!(functions, vec![(String::from("f"), 3)]);
+
+        assert!(Interpreter::new().profiler().is_none());
+    }
+
+    #[test]
+    fn statements_executed() {
+        let mut interpreter = Interpreter::new();
+        // The declaration, then three times the block, the `print` statement in it and the increment.
+        assert_eq!(execute(&mut interpreter, "for (var i = 0; i < 3; i = i + 1) { print i }"), Ok(()));
+        assert_eq!(interpreter.statements_executed(), 11);
+    }
+
+    #[test]
+    fn assert_eq() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(execute(&mut interpreter, "assert_eq([1, 'a'], [1, 'a'])"), Ok(()));
+        assert_eq!(execute(&mut interpreter, "assert_eq(1 + 1, '2')"), Err(ErrorType::AssertionFailed {
+            left: Value::Number(2.0),
+            right: Value::String_(String::from("2")),
+            line: 1,
+            column: 21,
+        }));
+        assert_eq!(execute(&mut interpreter, "assert_eq(1)"), Err(ErrorType::ArgParamNumberMismatch { arg_number: 1, param_number: 2, line: 1, column: 12 }));
+    }
+
+    #[test]
+    fn unicode_strings() {
+        // Sizes, indexes and bounds all count characters, so multi-byte characters work like any other.
+        let mut interpreter = Interpreter::new();
+        let source = "var s = 'né😀'\nvar n = size(s)\nvar c = s[2]\ns[1] = 'e'\nvar last = s[size(s) - 1]";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        assert_eq!(interpreter.get_global("n"), Some(Value::Number(3.0)));
+        assert_eq!(interpreter.get_global("c"), Some(Value::String_(String::from("😀"))));
+        assert_eq!(interpreter.get_global("s"), Some(Value::String_(String::from("ne😀"))));
+        assert_eq!(interpreter.get_global("last"), Some(Value::String_(String::from("😀"))));
+        assert!(matches!(execute(&mut interpreter, "s[3] = 'x'"), Err(ErrorType::OutOfBoundsIndex { index: 3, .. })));
+    }
+
+    #[test]
+    fn inspect() {
+        let mut interpreter = Interpreter::new();
+        let source = "var a = inspect([1, 'x', 2])\nvar d = inspect({'k': [], 1: null})\nvar s = inspect('héllo')\nfunc f(x, y) {}\nvar f_ = inspect(f)\nvar e = inspect({})\nvar b = inspect(size)";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        let summary = |name: &str| interpreter.get_global(name).map(|value| value.to_string());
+        assert_eq!(summary("a").as_deref(), Some("Array of 3 elements (Number/String)"));
+        assert_eq!(summary("d").as_deref(), Some("Dictionary of 2 entries (String/Number keys, Array/Null values)"));
+        assert_eq!(summary("s").as_deref(), Some("String of 5 characters"));
+        assert_eq!(summary("f_").as_deref(), Some("Function with 2 parameters (x, y)"));
+        assert_eq!(summary("e").as_deref(), Some("Dictionary of 0 entries"));
+        assert_eq!(summary("b").as_deref(), Some("Built-in function `size`"));
+        assert_eq!(Value::Array(vec![Value::Bool(true)].into()).summary(), "Array of 1 element (Boolean)");
+    }
+
+    #[test]
+    fn args() {
+        let mut interpreter = Interpreter::with_args(vec![String::from("input.csv"), String::from("10")]);
+        assert_eq!(execute(&mut interpreter, "var a = args()"), Ok(()));
+        assert_eq!(interpreter.environment.get(String::from("a"), 1, 1), Ok(Value::Array(vec![Value::String_(String::from("input.csv")), Value::String_(String::from("10"))].into())));
+        assert_eq!(execute(&mut interpreter, "args(1)"), Err(ErrorType::ArgParamNumberMismatch { arg_number: 1, param_number: 0, line: 1, column: 7 }));
+        assert_eq!(execute(&mut Interpreter::new(), "print size(args())"), Ok(()));
+    }
+
+    #[test]
+    fn interpret_returns_last_expression() {
+        let mut interpreter = Interpreter::new();
+        let mut interpret = |source: &str| interpreter.interpret(Parser::new(Tokenizer::new(source).tokenize().unwrap()).parse().unwrap());
+        assert_eq!(interpret("1 + 2"), Ok(Some(Value::Number(3.0))));
+        assert_eq!(interpret("var x = 5\nx * 2"), Ok(Some(Value::Number(10.0))));
+        // Assignments, declarations and other statements have no value to echo.
+        assert_eq!(interpret("x = 3"), Ok(None));
+        assert_eq!(interpret("var y = 1"), Ok(None));
+        assert_eq!(interpret("x\nprint x"), Ok(None));
+    }
+
+    #[test]
+    fn stack_trace() {
+        let mut interpreter = Interpreter::new();
+        let source = "func c(x) { return x / 0 }\nfunc b(x) { return c(x) }\nfunc a(x) { return b(x) }\nprint a(1)";
+        assert_eq!(execute(&mut interpreter, source), Err(ErrorType::DivideByZero { line: 1, column: 24 }));
+        assert_eq!(interpreter.call_stack, vec![frame("a", 4, 10), frame("b", 3, 23), frame("c", 2, 23)]);
+    }
+
+    #[test]
+    fn stack_unwinds_after_calls() {
+        let mut interpreter = Interpreter::new();
+        let source = "func f(x) { return x }\nfunc g() { var x = f(1) }\ng()\nvar funcs = [f]\nprint funcs[0](1)";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        assert!(interpreter.call_stack.is_empty());
+
+        // A `break` inside a function is caught by the loop around the call, so the call must not be left on the stack.
+        let source = "func h() { break }\nwhile (true) { h() }";
+        assert_eq!(execute(&mut interpreter, source), Ok(()));
+        assert!(interpreter.call_stack.is_empty());
+
+        // Only the call the error was raised in is left on the stack, not the calls which had already finished.
+        let source = "func k() { return f(1) + null }\nk()";
+        assert!(execute(&mut interpreter, source).is_err());
+        assert_eq!(interpreter.call_stack, vec![frame("k", 2, 3)]);
+    }
+
+    #[test]
+    fn call_depth_limit() {
+        // The calls use the stack of the thread, so they are run on one with the 8 MiB stack of a main thread, as the binary's are.
+        std::thread::Builder::new().stack_size(8 * 1024 * 1024).spawn(|| {
+            let mut interpreter = Interpreter::new();
+            let source = "func f(n) {\n    if (n > 0) { return f(n - 1) }\n    return n\n}";
+            assert_eq!(execute(&mut interpreter, source), Ok(()));
+            assert_eq!(interpreter.eval_expression(&format!("f({})", MAX_CALL_DEPTH - 1)), Ok(Value::Number(0.0)));
+            assert_eq!(interpreter.eval_expression(&format!("f({})", MAX_CALL_DEPTH)), Err(ErrorType::TooManyNestedCalls { limit: MAX_CALL_DEPTH, line: 2, column: 32 }));
+
+            // Calls through `eval()` are stopped in the same way, and the limit is not used up by the calls which were stopped.
+            assert_eq!(execute(&mut interpreter, "func g() { eval('g()') }\ng()"), Err(ErrorType::TooManyNestedCalls { limit: MAX_CALL_DEPTH, line: 1, column: 3 }));
+            assert_eq!(interpreter.call_depth, 0);
+            assert_eq!(interpreter.eval_expression(&format!("f({})", MAX_CALL_DEPTH - 1)), Ok(Value::Number(0.0)));
+        }).unwrap().join().unwrap();
+    }
+
+    /// Run with `cargo test --release -- --ignored --nocapture`.
+    #[test]
+    #[ignore]
+    fn sort_100k_benchmark() {
+        // A simple linear congruential generator, so that the benchmark is repeatable.
+        let mut seed: u64 = 12345;
+        let mut array: Vec<Value> = (0..100_000).map(|_| {
+            seed = seed.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            Value::Number((seed >> 33) as f64)
+        }).collect();
+
+        let start = Instant::now();
+        assert_eq!(sort_values(&mut array, 1, 1), Ok(()));
+        println!("Sorted 100000 numbers in {:?}.", start.elapsed());
+
+        assert!(array.windows(2).all(|pair| match pair {
+            [Value::Number(a), Value::Number(b)] => a <= b,
+            _ => false,
+        }));
+    }
 }
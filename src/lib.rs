@@ -0,0 +1,122 @@
+//! The NEAL interpreter as a library, so that it can be embedded in other programs as well as run by the `nea` binary.
+//!
+//! Running source code goes through three stages: the `Tokenizer` turns it into tokens, the `Parser` turns those into an abstract
+//! syntax tree, and the `Interpreter` executes the tree. `run_source()` does all three. An `Interpreter` keeps its variables and
+//! functions between runs, so the same instance can run several pieces of source code in turn, as the REPL does.
+//!
+//! ```
+//! use nea::{run_source, Interpreter, Value};
+//!
+//! let mut interpreter = Interpreter::new();
+//! run_source("var x = 1 + 2", &mut interpreter).unwrap();
+//! assert_eq!(interpreter.variables(), vec![(String::from("x"), Value::Number(3.0))]);
+//! ```
+
+// `ErrorType` is used to unwind the call stack and carries `Value`s (e.g., `ThrownReturn`), so it is large by design.
+#![allow(clippy::result_large_err)]
+
+pub mod ast_json;
+pub mod ast_printer;
+pub mod catalogue;
+pub mod coverage;
+pub mod dictionary;
+pub mod doc;
+mod environment;
+pub mod error;
+pub mod expr;
+pub mod ffi;
+pub mod hash_table;
+pub mod highlight;
+pub mod hooks;
+pub mod input;
+pub mod interpreter;
+pub mod lint;
+pub mod parser;
+pub mod playground;
+pub mod profiler;
+pub mod snapshot;
+pub mod source_printer;
+pub mod stmt;
+pub mod store;
+pub mod token;
+pub mod tokenizer;
+pub mod transpile;
+pub mod turtle;
+pub mod value;
+pub mod visitor;
+
+pub use error::ErrorType;
+pub use input::InputSource;
+pub use interpreter::{CancellationToken, Interpreter};
+pub use parser::Parser;
+pub use tokenizer::Tokenizer;
+pub use value::Value;
+
+/// Tokenizes, parses and executes the source code with the given interpreter.
+/// The source code is parsed strictly if the interpreter is strict (see `Interpreter::set_strict()`).
+///
+/// Any errors are reported to the interpreter's error output (see `Interpreter::set_error_output()`), which is the standard error
+/// stream unless it is set, and also returned. Tokenizing and parsing collect every error they can find before stopping, so several
+/// may be returned; execution stops at the first error, so at most one is.
+pub fn run_source(source: &str, interpreter: &mut Interpreter) -> Result<(), Vec<ErrorType>> {
+    let ast = Tokenizer::new(source).tokenize_unreported().and_then(|tokens| {
+        let mut parser = Parser::new(tokens);
+        parser.set_strict(interpreter.is_strict());
+        parser.parse_unreported()
+    });
+    match ast {
+        Ok(ast) => interpreter.interpret(ast).map(|_| ()).map_err(|error| vec![error]),
+        Err(errors) => {
+            interpreter.report_errors(&errors);
+            Err(errors)
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{run_source, ErrorType, Interpreter, Value};
+
+    #[test]
+    fn run_source_keeps_variables() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(run_source("var x = 1", &mut interpreter), Ok(()));
+        assert_eq!(run_source("x = x + 1", &mut interpreter), Ok(()));
+        assert_eq!(interpreter.variables(), vec![(String::from("x"), Value::Number(2.0))]);
+    }
+
+    #[test]
+    fn run_source_errors() {
+        let mut interpreter = Interpreter::new();
+        assert_eq!(run_source("print 'a", &mut interpreter), Err(vec![ErrorType::UnterminatedString { line: 1, column: 7 }]));
+        assert_eq!(run_source("var = 1\nvar = 2", &mut interpreter), Err(vec![
+            ErrorType::ExpectedVariableName { line: 1, column: 1 },
+            ErrorType::ExpectedVariableName { line: 2, column: 1 },
+        ]));
+        assert_eq!(run_source("print y", &mut interpreter), Err(vec![ErrorType::NameError { name: String::from("y"), line: 1, column: 7 }]));
+
+        interpreter.set_strict(true);
+        assert_eq!(run_source("var x = 1 print x", &mut interpreter), Err(vec![ErrorType::ExpectedNewline { line: 1, column: 11 }]));
+    }
+
+    #[test]
+    fn run_source_at_nesting_limits() {
+        // Code as deeply nested as the parser allows runs on a thread with the 8 MiB stack of a main thread, without a larger one.
+        let sources = [
+            format!("print {}1{}", "(".repeat(62), ")".repeat(62)),
+            format!("print {}1{}", "size([".repeat(31), "])".repeat(31)),
+            format!("{}print 1{}", "if (true) {".repeat(63), "}".repeat(63)),
+            format!("print 1{}", " + 1".repeat(1023)),
+        ];
+        std::thread::Builder::new()
+            .stack_size(8 * 1024 * 1024)
+            .spawn(move || for source in sources {
+                let mut interpreter = Interpreter::new();
+                interpreter.set_output(Box::new(std::io::sink()));
+                assert_eq!(run_source(&source, &mut interpreter), Ok(()));
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+}
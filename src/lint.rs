@@ -0,0 +1,514 @@
+//! Static analysis which finds code that is valid but probably a mistake, as run by `--lint`.
+//!
+//! Each kind of problem is a `Lint`, with a code such as `L0001` and a name such as `assignment-in-condition`. Every lint warns by
+//! default; a `LintConfig` can allow it instead, so that it is not reported, or deny it, so that `--lint` fails if it is found.
+//!
+//! ```
+//! use nea::lint::{self, Lint, LintConfig, Severity};
+//!
+//! let ast = nea::parser::parse_no_panic("func area(w, h) {\n    return w * w\n}").unwrap();
+//! let mut config = LintConfig::default();
+//! config.set(Lint::UnusedParameter, Severity::Deny);
+//! let warnings = lint::lint(&ast, &config);
+//! assert_eq!(warnings[0].to_string(), "error [L0003] Line 1, column 1: the parameter `h` of `area` is never used.");
+//! ```
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use crate::expr::{Expr, ExprType};
+use crate::stmt::{Stmt, StmtType};
+use crate::token::Literal;
+use crate::value::BuiltinFunction;
+use crate::visitor::{walk_expr, walk_stmt, Visitor};
+
+/// The number of blocks code can be nested in before `DeepNesting` reports it.
+pub const MAX_DEPTH: usize = 4;
+
+/// A kind of problem the linter finds.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Lint {
+    AssignmentInCondition,  // An `if` or loop condition which is an assignment, e.g., `if (x = 1)`.
+    ConstantCondition,  // A loop condition which is `true` or `false`, so that the loop never runs or never ends.
+    UnusedParameter,  // A function parameter which the function never uses.
+    DeepNesting,  // Code nested in more than `MAX_DEPTH` blocks.
+    ShadowedBuiltin,  // A variable, function or parameter with the name of a built-in function.
+    ShadowedVariable,  // A variable or function declared with the name of one declared in an enclosing block.
+}
+
+impl Lint {
+    /// Every lint, in order of code.
+    pub const ALL: [Lint; 6] = [
+        Lint::AssignmentInCondition,
+        Lint::ConstantCondition,
+        Lint::UnusedParameter,
+        Lint::DeepNesting,
+        Lint::ShadowedBuiltin,
+        Lint::ShadowedVariable,
+    ];
+
+    /// Returns the code of the lint, e.g., `L0001`.
+    pub fn code(&self) -> &'static str {
+        match self {
+            Lint::AssignmentInCondition => "L0001",
+            Lint::ConstantCondition => "L0002",
+            Lint::UnusedParameter => "L0003",
+            Lint::DeepNesting => "L0004",
+            Lint::ShadowedBuiltin => "L0005",
+            Lint::ShadowedVariable => "L0006",
+        }
+    }
+
+    /// Returns the name of the lint, e.g., `assignment-in-condition`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Lint::AssignmentInCondition => "assignment-in-condition",
+            Lint::ConstantCondition => "constant-condition",
+            Lint::UnusedParameter => "unused-parameter",
+            Lint::DeepNesting => "deep-nesting",
+            Lint::ShadowedBuiltin => "shadowed-builtin",
+            Lint::ShadowedVariable => "shadowed-variable",
+        }
+    }
+
+    /// Returns the lint with the code or name, in any case, or `None` if there is none.
+    pub fn find(code_or_name: &str) -> Option<Lint> {
+        Lint::ALL.into_iter().find(|lint| lint.code().eq_ignore_ascii_case(code_or_name) || lint.name().eq_ignore_ascii_case(code_or_name))
+    }
+
+    /// Returns an explanation of the lint, with an example and how to fix it, as `--explain` prints.
+    pub fn explain(&self) -> &'static str {
+        match self {
+            Lint::AssignmentInCondition => "\
+The condition of an `if` statement or a loop is an assignment. This is usually a typo for a comparison.
+
+Example:
+    if (answer = 42) {
+
+Fix: compare the values with `==` instead: `if (answer == 42) {`.",
+            Lint::ConstantCondition => "\
+The condition of a loop is `true` or `false`, so the loop either never runs, or never ends as it has no `break` or `return`.
+
+Example:
+    while (true) {
+        print 'again'
+    }
+
+Fix: use a condition which changes as the loop runs, or leave the loop with `break` when it is done.",
+            Lint::UnusedParameter => "\
+A parameter of a function is never used in its body, so the argument passed for it is ignored.
+
+Example:
+    func area(width, height) {
+        return width * width
+    }
+
+Fix: use the parameter, e.g., `return width * height`, or remove it and the arguments passed for it.
+Parameters whose names start with `_` are not reported, for the ones which are unused on purpose.",
+            Lint::DeepNesting => "\
+Code is nested in more than 4 blocks, e.g., loops inside `if` statements inside loops, which makes it hard to follow.
+
+Example:
+    for (var i = 0; i < 3; i = i + 1) {
+        for (var j = 0; j < 3; j = j + 1) {
+            if (i != j) {
+                if (grid[i][j]) {
+                    if (grid[j][i]) {
+                        print i
+
+Fix: move the inner blocks into a function, or leave early with `break` or `return` so that fewer blocks are needed.",
+            Lint::ShadowedBuiltin => "\
+A variable, function or parameter has the name of a built-in function, which cannot be called by that name where it is visible.
+
+Example:
+    var size = 10
+    print size([1, 2])
+
+Fix: give the variable another name, e.g., `var grid_size = 10`.",
+            Lint::ShadowedVariable => "\
+A variable or function is declared with the same name as one in an enclosing block, or in the global scope. This makes a new
+variable, which hides the other one until the end of the block, so assigning to it does not change the other one.
+
+Example:
+    var total = 0
+    for (var i = 1; i <= 3; i = i + 1) {
+        var total = total + i
+    }
+    print total
+
+Fix: assign to the existing variable instead, e.g., `total = total + i`, or give the new one a different name.
+Parameters are not reported, as a function's parameters are meant to be its own. `--strict` makes this an error when the code runs.",
+        }
+    }
+}
+
+/// Returns the explanation of the lint with the code, in any case, or `None` if there is none.
+pub fn explain(code: &str) -> Option<&'static str> {
+    Lint::ALL.into_iter().find(|lint| lint.code().eq_ignore_ascii_case(code)).map(|lint| lint.explain())
+}
+
+/// How a lint is treated when it is found.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Severity {
+    Allow,  // Not reported.
+    Warn,  // Reported, but does not make `--lint` fail.
+    Deny,  // Reported, and makes `--lint` fail.
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Severity::Allow => "allowed",
+            Severity::Warn => "warning",
+            Severity::Deny => "error",
+        })
+    }
+}
+
+/// The severity of each lint. By default, every lint warns.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LintConfig {
+    severities: [Severity; Lint::ALL.len()],  // Indexed by the position of the lint in `Lint::ALL`.
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        Self { severities: [Severity::Warn; Lint::ALL.len()] }
+    }
+}
+
+impl LintConfig {
+    /// Returns the severity of the lint.
+    pub fn severity(&self, lint: Lint) -> Severity {
+        self.severities[lint as usize]
+    }
+
+    /// Sets the severity of the lint.
+    pub fn set(&mut self, lint: Lint, severity: Severity) {
+        self.severities[lint as usize] = severity;
+    }
+}
+
+/// A lint found in the source code.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Warning {
+    pub lint: Lint,
+    pub severity: Severity,  // The severity of the lint when it was found, which is never `Allow`.
+    pub line: usize,
+    pub column: usize,
+    pub message: String,  // What was found, starting with a lower-case letter and ending with a full stop, as error messages do.
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} [{}] Line {}, column {}: {}", self.severity, self.lint.code(), self.line, self.column, self.message)
+    }
+}
+
+/// Returns the lints found in the abstract syntax tree which are not allowed by `config`, in the order they appear in the source code.
+pub fn lint(ast: &[Stmt], config: &LintConfig) -> Vec<Warning> {
+    let mut linter = Linter { config, warnings: Vec::new(), depth: 0, scopes: vec![HashMap::new()], function_scopes: Vec::new() };
+    linter.visit_stmts(ast);
+    // The lints are found as their statements are visited, which is not always in source order, e.g., a function's unused
+    // parameters are found before its body.
+    linter.warnings.sort_by_key(|warning| (warning.line, warning.column));
+    linter.warnings
+}
+
+/// Finds the lints in the statements it visits.
+struct Linter<'a> {
+    config: &'a LintConfig,
+    warnings: Vec<Warning>,
+    depth: usize,  // The number of blocks around the statement being visited.
+    scopes: Vec<HashMap<String, usize>>,  // The names declared in each scope around the statement being visited, outermost first, with the lines they were declared on.
+    function_scopes: Vec<usize>,  // For each function around the statement being visited, outermost first, the index of the scope of its parameters.
+}
+
+impl Linter<'_> {
+    /// Records the lint at the position, unless it is allowed.
+    fn warn(&mut self, lint: Lint, line: usize, column: usize, message: String) {
+        let severity = self.config.severity(lint);
+        if severity != Severity::Allow {
+            self.warnings.push(Warning { lint, severity, line, column, message });
+        }
+    }
+
+    /// Checks the condition of an `if` statement or a loop for an assignment.
+    fn check_assignment(&mut self, condition: &Expr) {
+        // An assignment in brackets is left alone, so that one which is meant can be written without a warning.
+        // The assignment is reported at its target, which is where the condition starts.
+        if let ExprType::Assignment { target, .. } = &condition.expr_type {
+            self.warn(Lint::AssignmentInCondition, target.line, target.column, String::from("the condition is an assignment; did you mean `==`?"));
+        }
+    }
+
+    /// Checks the condition of a loop for being always `true` or always `false`.
+    fn check_constant(&mut self, condition: &Expr, body: &Stmt) {
+        let ExprType::Literal { value: Literal::Bool(value) } = condition.expr_type else {
+            return;
+        };
+        if !value {
+            self.warn(Lint::ConstantCondition, condition.line, condition.column, String::from("the loop never runs, as its condition is always `false`."));
+        } else if !exits(body) {
+            // `while (true)` is the usual way to write a loop which is left from the middle, so it is only reported if it cannot be left.
+            let message = String::from("the loop never ends, as its condition is always `true` and it has no `break` or `return`.");
+            self.warn(Lint::ConstantCondition, condition.line, condition.column, message);
+        }
+    }
+
+    /// Checks a declaration against the names already declared in the scopes which can be seen from it, then declares the name.
+    /// As when the code runs, a function sees the global scope and its own scopes, but not those of the code around it, which may
+    /// not be the code which calls it.
+    fn declare(&mut self, name: &str, line: usize, column: usize) {
+        let function_scope = self.function_scopes.last().copied().unwrap_or(0);
+        let current = self.scopes.len() - 1;
+        let outer = (0..current).rev()
+            .filter(|scope| *scope == 0 || *scope >= function_scope)
+            .find_map(|scope| self.scopes[scope].get(name).copied());
+        if let Some(outer_line) = outer {
+            let message = format!("`{}` is declared here, hiding the `{}` declared on line {}.", name, name, outer_line);
+            self.warn(Lint::ShadowedVariable, line, column, message);
+        }
+        self.scopes[current].insert(String::from(name), line);
+    }
+
+    /// Visits the statement's children in a new scope, as blocks, `for` loops and the parameters of functions have.
+    fn walk_in_scope(&mut self, stmt: &Stmt) {
+        self.scopes.push(HashMap::new());
+        walk_stmt(self, stmt);
+        self.scopes.pop();
+    }
+
+    /// Checks a declared name against the names of the built-in functions.
+    fn check_name(&mut self, name: &str, line: usize, column: usize) {
+        if BuiltinFunction::ALL.iter().any(|builtin| builtin.name() == name) {
+            let message = format!("`{}` is declared here, hiding the built-in function with the same name.", name);
+            self.warn(Lint::ShadowedBuiltin, line, column, message);
+        }
+    }
+}
+
+impl Visitor for Linter<'_> {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        match &stmt.stmt_type {
+            StmtType::Block { body } => {
+                self.depth += 1;
+                // Only the outermost block which is too deep is reported, rather than every block inside it.
+                if self.depth == MAX_DEPTH + 1 {
+                    if let Some(first) = body.first() {
+                        let message = format!("this code is nested in more than {} blocks.", MAX_DEPTH);
+                        self.warn(Lint::DeepNesting, first.line, first.column, message);
+                    }
+                }
+                self.walk_in_scope(stmt);
+                self.depth -= 1;
+                return;
+            },
+            StmtType::If { condition, .. } => self.check_assignment(condition),
+            StmtType::While { condition, body } => {
+                self.check_assignment(condition);
+                self.check_constant(condition, body);
+            },
+            StmtType::For { condition, body, .. } => {
+                if let Some(condition) = condition {
+                    self.check_assignment(condition);
+                    self.check_constant(condition, body);
+                }
+                // The initialiser declares its variable in a scope of the loop's own.
+                self.walk_in_scope(stmt);
+                return;
+            },
+            StmtType::Function { name, parameters, body, .. } => {
+                self.check_name(name, stmt.line, stmt.column);
+                self.declare(name, stmt.line, stmt.column);
+                let mut used = UsedNames::default();
+                used.visit_stmt(body);
+                for parameter in parameters {
+                    self.check_name(parameter, stmt.line, stmt.column);
+                    if !parameter.starts_with('_') && !used.0.contains(parameter) {
+                        let message = format!("the parameter `{}` of `{}` is never used.", parameter, name);
+                        self.warn(Lint::UnusedParameter, stmt.line, stmt.column, message);
+                    }
+                }
+
+                // The parameters are declared in a scope of their own, around the scope of the body.
+                self.function_scopes.push(self.scopes.len());
+                self.scopes.push(parameters.iter().map(|parameter| (parameter.clone(), stmt.line)).collect());
+                walk_stmt(self, stmt);
+                self.scopes.pop();
+                self.function_scopes.pop();
+                return;
+            },
+            StmtType::Try { body, catch, finally } => {
+                self.visit_stmt(body);
+                if let Some((name, catch_body)) = catch {
+                    // The caught error is declared in a scope of its own, around the scope of the `catch` block.
+                    self.check_name(name, stmt.line, stmt.column);
+                    self.scopes.push(HashMap::new());
+                    self.declare(name, stmt.line, stmt.column);
+                    self.visit_stmt(catch_body);
+                    self.scopes.pop();
+                }
+                if let Some(finally) = finally {
+                    self.visit_stmt(finally);
+                }
+                return;
+            },
+            StmtType::VarDecl { name, .. } => {
+                self.check_name(name, stmt.line, stmt.column);
+                self.declare(name, stmt.line, stmt.column);
+            },
+            StmtType::VarUnpack { names, .. } => {
+                for name in names {
+                    self.check_name(name, stmt.line, stmt.column);
+                    self.declare(name, stmt.line, stmt.column);
+                }
+            },
+            _ => (),
+        }
+        walk_stmt(self, stmt);
+    }
+}
+
+/// Collects the name of every variable used.
+#[derive(Default)]
+struct UsedNames(HashSet<String>);
+
+impl Visitor for UsedNames {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let ExprType::Variable { name } = &expr.expr_type {
+            self.0.insert(name.clone());
+        }
+        walk_expr(self, expr);
+    }
+}
+
+/// Returns whether the body of a loop contains a `break` of that loop, or a `return`.
+fn exits(body: &Stmt) -> bool {
+    let mut exits = Exits { loops: 0, found: false };
+    exits.visit_stmt(body);
+    exits.found
+}
+
+/// Looks for a way out of a loop.
+struct Exits {
+    loops: usize,  // The number of loops inside the loop around the statement being visited, whose `break`s only leave themselves.
+    found: bool,
+}
+
+impl Visitor for Exits {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        match stmt.stmt_type {
+            StmtType::Break if self.loops == 0 => self.found = true,
+            StmtType::Return { .. } => self.found = true,
+            // A `return` inside a function declared in the loop leaves the function, not the loop.
+            StmtType::Function { .. } => return,
+            StmtType::While { .. } | StmtType::For { .. } | StmtType::Repeat { .. } => {
+                self.loops += 1;
+                walk_stmt(self, stmt);
+                self.loops -= 1;
+                return;
+            },
+            _ => (),
+        }
+        walk_stmt(self, stmt);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::parse_no_panic;
+
+    use super::{explain, lint, Lint, LintConfig, Severity, Warning};
+
+    /// Returns the lints found in the source code with the default configuration, as `(code, line, column)`.
+    fn found(source: &str) -> Vec<(&'static str, usize, usize)> {
+        lint(&parse_no_panic(source).unwrap(), &LintConfig::default()).iter().map(|warning| (warning.lint.code(), warning.line, warning.column)).collect()
+    }
+
+    #[test]
+    fn assignment_in_condition() {
+        assert_eq!(found("var x = 1\nif (x = 2) { print x }\nwhile (x = false) {}"), [("L0001", 2, 5), ("L0001", 3, 8)]);
+        // Comparisons, and assignments in brackets, are fine.
+        assert_eq!(found("var x = 1\nif (x == 2) { print x }\nif ((x = true)) { print x }"), []);
+    }
+
+    #[test]
+    fn constant_condition() {
+        assert_eq!(found("while (false) { print 1 }\nfor (var i = 0; true; i = i + 1) { print i }"), [("L0002", 1, 8), ("L0002", 2, 17)]);
+        // A loop which is left with `break` or `return` is fine, but not one left by a `break` of an inner loop.
+        assert_eq!(found("while (true) { if (input() == 'q') { break } }\nfunc f() { while (true) { return 1 } }"), []);
+        assert_eq!(found("while (true) { while (true) { break } }"), [("L0002", 1, 8)]);
+    }
+
+    #[test]
+    fn unused_parameter() {
+        assert_eq!(found("func f(a, b, _c) {\n    return a\n}\nfunc g(x) { func h() { return x } return h }"), [("L0003", 1, 1)]);
+        let warnings = lint(&parse_no_panic("func f(a) {}").unwrap(), &LintConfig::default());
+        assert_eq!(warnings, [Warning { lint: Lint::UnusedParameter, severity: Severity::Warn, line: 1, column: 1, message: String::from("the parameter `a` of `f` is never used.") }]);
+    }
+
+    #[test]
+    fn deep_nesting() {
+        let source = "if (true) {\n if (true) {\n  if (true) {\n   if (true) {\n    print 1\n    if (true) {\n     print 2\n     if (true) { print 3 }\n    }\n   }\n  }\n }\n}";
+        assert_eq!(found(source), [("L0004", 7, 6)]);
+    }
+
+    #[test]
+    fn shadowed_builtin() {
+        assert_eq!(found("var size = 1\nfunc input(x) { return x }\nfunc f(append) { return append }"), [("L0005", 1, 1), ("L0005", 2, 1), ("L0005", 3, 1)]);
+        assert_eq!(found("var q, keys = 1, 2"), [("L0005", 1, 1)]);
+    }
+
+    #[test]
+    fn shadowed_variable() {
+        let source = "\
+var total = 0
+func f(total) {
+    var total = 1
+    func g() { var total = 2 }
+}
+for (var i = 0; i < 3; i = i + 1) {
+    var total = i
+    if (true) { var i = 1 }
+}
+var total = 5
+if (total > 1) { func f() {} }";
+        let warnings = lint(&parse_no_panic(source).unwrap(), &LintConfig::default());
+        let messages: Vec<(usize, &str)> = warnings.iter().filter(|warning| warning.lint == Lint::ShadowedVariable).map(|warning| (warning.line, warning.message.as_str())).collect();
+        // Parameters and declarations in the same scope are not reported, but a function's parameters can be hidden.
+        assert_eq!(messages, [
+            (3, "`total` is declared here, hiding the `total` declared on line 2."),
+            (4, "`total` is declared here, hiding the `total` declared on line 1."),
+            (7, "`total` is declared here, hiding the `total` declared on line 1."),
+            (8, "`i` is declared here, hiding the `i` declared on line 6."),
+            (11, "`f` is declared here, hiding the `f` declared on line 2."),
+        ]);
+        // A caught error is declared around its `catch` block.
+        let source = "var error = 1\ntry {} catch (error) {}\ntry {} catch (e) { var e = 2 }\ntry {} catch (keys) {}";
+        assert_eq!(found(source), [("L0006", 2, 1), ("L0006", 3, 20), ("L0005", 4, 1)]);
+        // A function sees the global scope, but not the blocks around its declaration.
+        assert_eq!(found("while (true) {\n    var x = 1\n    func f() { var x = 2 }\n    break\n}"), []);
+    }
+
+    #[test]
+    fn severities() {
+        let ast = parse_no_panic("var size = 1\nwhile (false) {}").unwrap();
+        let mut config = LintConfig::default();
+        config.set(Lint::ShadowedBuiltin, Severity::Allow);
+        config.set(Lint::find("constant-condition").unwrap(), Severity::Deny);
+        let warnings = lint(&ast, &config);
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].to_string(), "error [L0002] Line 2, column 8: the loop never runs, as its condition is always `false`.");
+    }
+
+    #[test]
+    fn codes() {
+        for lint in Lint::ALL {
+            assert_eq!(Lint::find(lint.code()), Some(lint));
+            assert_eq!(Lint::find(&lint.name().to_uppercase()), Some(lint));
+            assert!(explain(&lint.code().to_lowercase()).is_some());
+        }
+        assert_eq!(Lint::find("L0007"), None);
+    }
+}
@@ -1,86 +1,385 @@
-mod environment;
-mod error;
-mod expr;
-mod hash_table;
-mod interpreter;
-mod parser;
-mod stmt;
-mod token;
-mod tokenizer;
-mod value;
-
-use std::{env, io, io::Write, fs};
-
-use parser::Parser;
-use tokenizer::Tokenizer;
-use interpreter::Interpreter;
+// `ErrorType` is used to unwind the call stack and carries `Value`s (e.g., `ThrownReturn`), so it is large by design.
+#![allow(clippy::result_large_err)]
+
+mod bundle;
+mod cli;
+mod repl;
+mod test_runner;
+
+use std::{env, fs, io, process::ExitCode};
+use std::time::{Duration, Instant};
+use std::path::Path;
+
+use cli::{Action, Input, Mode, Options};
+use nea::{ast_json, ast_printer, catalogue, doc, error, lint, source_printer, transpile};
+use nea::lint::{Lint, LintConfig, Severity};
+use nea::error::ErrorLimit;
+use nea::stmt::Stmt;
+use nea::{Interpreter, Parser, Tokenizer, Value};
+
+/// The exit codes of the binary. Each stage of execution that can fail has its own code, so that shell scripts can tell the failures apart.
+/// The values follow the conventions of `sysexits.h`, apart from `CHECK`, which follows formatters and linters in other languages.
+mod exit_code {
+    pub const CHECK: u8 = 1;  // `--fmt --check` found that the script is not formatted, `--lint` found a lint which is denied, or a test failed.
+    pub const USAGE: u8 = 64;  // The command was used incorrectly, e.g., with the wrong number of arguments.
+    pub const TOKENIZE: u8 = 65;  // The source code could not be tokenized.
+    pub const STRICT_LINT: u8 = 65;  // `--lint --strict` found a lint. Like a tokenizing error, the source code is invalid data (`EX_DATAERR`).
+    pub const PARSE: u8 = 66;  // The token sequence could not be parsed.
+    pub const RUNTIME: u8 = 70;  // An error occurred while executing the program.
+    pub const IO: u8 = 74;  // The script could not be read, from a file or the standard input stream, or a report could not be written.
+}
+
+/// The stage at which running a source code string failed.
+#[derive(Debug, PartialEq)]
+enum Failure {
+    Tokenize,
+    Parse,
+    Runtime,
+    Check,
+    StrictLint,
+    Io,
+}
+
+impl Failure {
+    /// Returns the exit code that corresponds to the failure.
+    fn exit_code(&self) -> ExitCode {
+        match self {
+            Self::Tokenize => ExitCode::from(exit_code::TOKENIZE),
+            Self::Parse => ExitCode::from(exit_code::PARSE),
+            Self::Runtime => ExitCode::from(exit_code::RUNTIME),
+            Self::Check => ExitCode::from(exit_code::CHECK),
+            Self::StrictLint => ExitCode::from(exit_code::STRICT_LINT),
+            Self::Io => ExitCode::from(exit_code::IO),
+        }
+    }
+}
+
+/// How long each stage of running a script took, as reported by `--time`. A stage which was not reached is `None`.
+#[derive(Debug, Default)]
+struct Timings {
+    tokenize: Option<Duration>,
+    parse: Option<Duration>,
+    execute: Option<Duration>,
+    statements: Option<usize>,  // The number of statements executed, including each time a statement in a loop or function is repeated.
+}
+
+impl Timings {
+    /// Prints the timings to the standard error stream, so that they do not mix with the output of the script.
+    fn report(&self) {
+        let stages = [("Tokenizing", self.tokenize), ("Parsing", self.parse), ("Executing", self.execute)];
+        for (stage, duration) in stages {
+            if let Some(duration) = duration {
+                eprintln!("{:<11} {:>10.3} ms", format!("{}:", stage), duration.as_secs_f64() * 1000.0);
+            }
+        }
+        if let Some(statements) = self.statements {
+            eprintln!("Statements executed: {}", statements);
+        }
+    }
+}
 
 /// Driver code.
-fn main() {
+fn main() -> ExitCode {
     let args: Vec<String> = env::args().collect();
 
+    if let Some(source) = bundle::embedded_script() {
+        // A bundle runs its script as `nea <script>` would, and every argument goes to the script, so that it behaves like a program of its own.
+        return run_script(&source, Options { args: args[1..].to_vec(), ..Options::default() });
+    }
+
     // Note that `args[0]` will be the name of the binary.
-    // So to check whether one argument has been passed, we check if `args.len() == 2`.
-    if args.len() > 2 {
-        // Only one given argument is expected.
-        eprintln!("Usage: nea.exe [script]");
-    } else if args.len() == 2 {
-        // `args[1]` will be the given argument, i.e., the file path of the source code.
-        run_file(&args[1]);
+    let options = match cli::parse_args(&args[1..]) {
+        Ok(Action::Run(options)) => options,
+        Ok(Action::Help) => {
+            print!("{}", cli::help());
+            return ExitCode::SUCCESS;
+        },
+        Ok(Action::Version) => {
+            println!("nea {}", env!("CARGO_PKG_VERSION"));
+            return ExitCode::SUCCESS;
+        },
+        // `nea --explain E0012` prints the longer explanation of the given error code.
+        Ok(Action::Explain(code)) => return explain(&code),
+        Err(message) => {
+            eprintln!("{}", message);
+            eprintln!("{}", cli::USAGE);
+            eprintln!("Run `nea.exe --help` for a list of options.");
+            return ExitCode::from(exit_code::USAGE);
+        },
+    };
+
+    if let Some(messages) = &options.messages {
+        // Install the translated messages before anything can be reported.
+        if let Err(exit_code) = install_catalogue(messages) {
+            return exit_code;
+        }
+    }
+
+    if let (Some(script), Mode::Test) = (&options.script, options.mode) {
+        // A directory of tests can be given, so the runner reads the scripts itself.
+        return match test_runner::run_tests(script, &options) {
+            Ok(summary) if summary.failed == 0 => ExitCode::SUCCESS,
+            Ok(_) => ExitCode::from(exit_code::CHECK),
+            Err(error) => {
+                eprintln!("Could not read the tests: {}.", error);
+                ExitCode::from(exit_code::IO)
+            },
+        };
+    }
+
+    if let Some(script) = &options.script {
+        let source = match script {
+            Input::File(file_path) => match read_file(file_path) {
+                Ok(source) => source,
+                Err(exit_code) => return exit_code,
+            },
+            Input::Stdin => match io::read_to_string(io::stdin()) {
+                Ok(source) => source,
+                Err(error) => {
+                    eprintln!("Could not read standard input: {}.", error);
+                    return ExitCode::from(exit_code::IO);
+                },
+            },
+            Input::Eval(source) => source.clone(),
+        };
+        run_script(&source, options)
     } else {
-        // No arguments were given. In this case, we run the REPL interface.
-        run_repl();
+        // No script was given. In this case, we run the REPL interface.
+        repl::run_repl(&options);
+        ExitCode::SUCCESS
     }
 }
 
-/// Runs the source code given at the file path.
-fn run_file(file_path: &str) {
-    // Reading from the file path. If an error occurs, the `expect()` method will print "Failed to read file." and terminate execution.
-    let source = fs::read_to_string(file_path).expect("Failed to read file.");
+/// Reads the message catalogue at the file path and installs it for every error reported afterwards.
+/// If it cannot be read or is malformed, the problem is reported and the exit code to end with is returned.
+fn install_catalogue(file_path: &str) -> Result<(), ExitCode> {
+    let text = fs::read_to_string(file_path).map_err(|error| {
+        eprintln!("Could not read `{}`: {}.", file_path, error);
+        ExitCode::from(exit_code::IO)
+    })?;
+    let catalogue = catalogue::Catalogue::parse(&text).map_err(|message| {
+        eprintln!("In `{}`: {}", file_path, message);
+        ExitCode::from(exit_code::USAGE)
+    })?;
+    // This is the only place a catalogue is installed, so it cannot already have been.
+    let _ = catalogue::install(catalogue);
+    Ok(())
+}
 
-    // An Interpreter object has to be provided to `run()`, as explained below.
-    let mut interpreter = Interpreter::new();
-    
-    run(&source, &mut interpreter);
+/// Prints the explanation of the given error code.
+fn explain(code: &str) -> ExitCode {
+    // Lint codes can be explained as well as error codes.
+    match error::explain(code).or_else(|| lint::explain(code)) {
+        Some(explanation) => {
+            println!("{}", explanation);
+            ExitCode::SUCCESS
+        },
+        None => {
+            eprintln!("`{}` is not a valid error code.", code);
+            ExitCode::from(exit_code::USAGE)
+        },
+    }
 }
 
-/// Runs the interactive REPL interface in the console.
-fn run_repl() {
-    // We need the same `Interpreter` instance across all REPL source code inputs to preserve the variables and functions stored in the environment.
-    let mut interpreter = Interpreter::new();
-    loop {
-        print!("> ");
-        io::stdout().flush().expect("Error: flush failed");  // to flush out "> "
+/// Reads the source code at the file path. If it cannot be read, the problem is reported and the exit code to end with is returned.
+fn read_file(file_path: &str) -> Result<String, ExitCode> {
+    // If an error occurs, e.g., the file does not exist or is not valid UTF-8, report it along with the path.
+    fs::read_to_string(file_path).map_err(|error| {
+        eprintln!("Could not read `{}`: {}.", file_path, error);
+        ExitCode::from(exit_code::IO)
+    })
+}
 
-        // Read user input into `line`.
-        let mut line = String::new();
-        io::stdin()
-            .read_line(&mut line)
-            .expect("Failed to read line");
+/// Runs the source code of a script as the options say, returning the exit code of the run.
+fn run_script(source: &str, options: Options) -> ExitCode {
+    let error_limit = options.error_limit;
+    let mut timings = Timings::default();
+    let result = match options.mode {
+        Mode::Run => {
+            // An Interpreter object has to be provided to `run()`, as explained below.
+            let pretty = options.pretty_elements(false);
+            let colour = options.output_colour();
+            let mut interpreter = Interpreter::with_args(options.args);
+            interpreter.set_trace(options.trace);
+            interpreter.set_strict(options.strict);
+            interpreter.set_truthy(options.truthy);
+            interpreter.set_colour(colour);
+            interpreter.set_pretty(pretty);
+            if let Some(Input::File(path)) = &options.script {
+                interpreter.set_script_path(Path::new(path));
+            }
+            if options.profile {
+                interpreter.enable_profiling();
+            }
+            if options.coverage {
+                interpreter.enable_coverage();
+            }
+            // Unlike in the REPL, the value of the last expression is not printed.
+            let result = run_timed(source, &mut interpreter, error_limit, &mut timings).map(|_| ());
+            if let Some(profiler) = interpreter.profiler() {
+                // As with the timings, the profile is reported even if the script failed, and on the standard error stream.
+                eprint!("{}", profiler.report());
+            }
+            if let Some(coverage) = interpreter.coverage() {
+                if let Err(exit_code) = write_coverage(&coverage.listing(source), options.coverage_file.as_deref()) {
+                    return exit_code;
+                }
+            }
+            result
+        },
+        Mode::Tokens => print_tokens(source, error_limit, &mut timings),
+        Mode::Ast => parse_timed(source, error_limit, options.strict, &mut timings).map(|ast| print!("{}", ast_printer::print(&ast))),
+        Mode::AstJson => parse_timed(source, error_limit, options.strict, &mut timings).map(|ast| print!("{}", ast_json::to_json(&ast))),
+        // Nothing is printed if the script is valid, so the exit code is the result.
+        Mode::Check => parse_timed(source, error_limit, options.strict, &mut timings).map(|_| ()),
+        Mode::Fmt => parse_timed(source, error_limit, options.strict, &mut timings).map(|ast| print!("{}", source_printer::format(&ast, source))),
+        Mode::FmtCheck => parse_timed(source, error_limit, options.strict, &mut timings).and_then(|ast| check_formatted(source, &ast)),
+        Mode::Doc => parse_timed(source, error_limit, options.strict, &mut timings).map(|ast| print!("{}", doc::markdown(&ast))),
+        Mode::Transpile => parse_timed(source, error_limit, options.strict, &mut timings).map(|ast| print!("{}", transpile::to_python(&ast))),
+        Mode::Bundle => parse_timed(source, error_limit, options.strict, &mut timings).and_then(|_| write_bundle(source, options.bundle.as_deref().unwrap_or_default())),
+        Mode::Lint => parse_timed(source, error_limit, options.strict, &mut timings).and_then(|ast| report_lints(&ast, &options.lint, options.strict)),
+        // Tests are run before the script is read, as the script may be a directory.
+        Mode::Test => unreachable!("`--test` is handled by the test runner."),
+    };
+
+    if options.time {
+        // The timings are reported even if the script failed, for the stages it reached.
+        timings.report();
+    }
 
-        run(&line, &mut interpreter);
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(failure) => failure.exit_code(),
     }
 }
 
-/// Executes the source code string with the given interpreter instance.
-fn run(source: &str, interpreter: &mut Interpreter) {
+/// Writes a bundle of the binary and the script, which has parsed, to the file path.
+fn write_bundle(source: &str, file_path: &str) -> Result<(), Failure> {
+    bundle::write_bundle(source, file_path.as_ref()).map_err(|error| {
+        eprintln!("Could not write `{}`: {}.", file_path, error);
+        Failure::Io
+    })
+}
+
+/// Reports the first line of the script which the formatter would change, if there is one.
+fn check_formatted(source: &str, ast: &[Stmt]) -> Result<(), Failure> {
+    let formatted = source_printer::format(ast, source);
+    // A missing line break at the end of the file counts as a difference, as the formatter always writes one.
+    if formatted == source {
+        return Ok(());
+    }
+    let mut source_lines = source.split_inclusive('\n');
+    let mut formatted_lines = formatted.split_inclusive('\n');
+    let mut line = 1;
+    while source_lines.next() == formatted_lines.next() {
+        line += 1;
+    }
+    eprintln!("The script is not formatted: line {} differs. Run it with `--fmt` to see the formatted script.", line);
+    Err(Failure::Check)
+}
+
+/// Reports the lints found in the script on the standard error stream, failing if any of them is denied.
+/// If `strict` is set, every lint which is not allowed is denied, and finding any of them is a failure of its own.
+fn report_lints(ast: &[Stmt], config: &LintConfig, strict: bool) -> Result<(), Failure> {
+    let mut config = *config;
+    if strict {
+        for lint in Lint::ALL {
+            if config.severity(lint) != Severity::Allow {
+                config.set(lint, Severity::Deny);
+            }
+        }
+    }
+    let warnings = lint::lint(ast, &config);
+    for warning in &warnings {
+        eprintln!("{}", warning);
+    }
+    if !warnings.iter().any(|warning| warning.severity == Severity::Deny) {
+        Ok(())
+    } else if strict {
+        Err(Failure::StrictLint)
+    } else {
+        Err(Failure::Check)
+    }
+}
+
+/// Writes the coverage listing to the file, or to the standard error stream if none is given.
+/// If the file cannot be written, the problem is reported and the exit code to end with is returned.
+fn write_coverage(listing: &str, file_path: Option<&str>) -> Result<(), ExitCode> {
+    match file_path {
+        Some(file_path) => fs::write(file_path, listing).map_err(|error| {
+            eprintln!("Could not write `{}`: {}.", file_path, error);
+            ExitCode::from(exit_code::IO)
+        }),
+        None => {
+            eprint!("{}", listing);
+            Ok(())
+        },
+    }
+}
+
+/// Tokenizes the source code string and prints the token sequence as a table, one token per line, without executing it.
+fn print_tokens(source: &str, error_limit: ErrorLimit, timings: &mut Timings) -> Result<(), Failure> {
+    // As in `run()`, any errors have already been reported by the tokenizer.
+    let start = Instant::now();
+    let tokens = Tokenizer::with_error_limit(source, error_limit).tokenize();
+    timings.tokenize = Some(start.elapsed());
+    let tokens = tokens.map_err(|_| Failure::Tokenize)?;
+    println!("{:<8} {:<13} {:<16} Literal", "Position", "Type", "Lexeme");
+    for token in tokens {
+        println!("{}", token);
+    }
+    Ok(())
+}
+
+/// Executes the source code string with the given interpreter instance, collecting and reporting errors according to `error_limit`.
+/// Any errors are reported as they occur; the returned `Failure` only records the stage at which execution stopped.
+/// On success, returns the value of the last statement if it is an expression (see `Interpreter::interpret()`).
+fn run(source: &str, interpreter: &mut Interpreter, error_limit: ErrorLimit) -> Result<Option<Value>, Failure> {
+    run_timed(source, interpreter, error_limit, &mut Timings::default())
+}
+
+/// Does the same as `run()`, recording how long each stage took in `timings`.
+fn run_timed(source: &str, interpreter: &mut Interpreter, error_limit: ErrorLimit, timings: &mut Timings) -> Result<Option<Value>, Failure> {
+    // A strict interpreter only runs source code which is also strictly formatted.
+    let ast = parse_timed(source, error_limit, interpreter.is_strict(), timings)?;
+
+    // Evaluation and execution.
+    let start = Instant::now();
+    let statements_before = interpreter.statements_executed();
+    // Output which could not be written, e.g., to a pipe whose reader has stopped, is an input or output failure like a report which could not be written.
+    let result = interpreter.interpret(ast).map_err(|error| match error {
+        error::ErrorType::CannotWriteOutput { .. } => Failure::Io,
+        _ => Failure::Runtime,
+    });
+    timings.execute = Some(start.elapsed());
+    timings.statements = Some(interpreter.statements_executed() - statements_before);
+    result
+}
+
+/// Tokenizes and parses the source code string into an abstract syntax tree, collecting and reporting errors according to `error_limit`.
+/// If `strict` is set, each statement has to be on a line of its own. How long each stage took is recorded in `timings`.
+fn parse_timed(source: &str, error_limit: ErrorLimit, strict: bool, timings: &mut Timings) -> Result<Vec<Stmt>, Failure> {
     // Lexical analysis.
-    let mut tokenizer = Tokenizer::new(source);
+    let start = Instant::now();
+    let mut tokenizer = Tokenizer::with_error_limit(source, error_limit);
+    let tokens = tokenizer.tokenize();
+    timings.tokenize = Some(start.elapsed());
     // If the source code was tokenized without errors, assign the token sequence to `tokens`.
-    let Ok(tokens) = tokenizer.tokenize() else {
+    let Ok(tokens) = tokens else {
         // If an error occurred, stop trying to execute the current source code string.
         // If the user is using a REPL interface, this does not then end the session but simply prompts the user for a new source code input, as expected.
-        return;
+        return Err(Failure::Tokenize);
     };
 
     // Syntax analysis.
-    let mut parser = Parser::new(tokens);
-    // Similarly, if the token sequence was parsed without errors, assign the abstract syntax tree to `ast`.
-    let Ok(ast) = parser.parse() else {
-        // If an error occurred, stop trying to execute the current source code string.
-        return;
-    };
-
-    // Evaluation and execution.
-    interpreter.interpret(ast);
+    let start = Instant::now();
+    let mut parser = Parser::with_error_limit(tokens, error_limit);
+    parser.set_strict(strict);
+    // The comments are given to the parser so that the functions keep their doc comments, e.g., for `--doc`.
+    parser.set_comments(tokenizer.comments());
+    let ast = parser.parse();
+    timings.parse = Some(start.elapsed());
+    // Similarly, if the token sequence was parsed without errors, return the abstract syntax tree.
+    // If an error occurred, the caller stops trying to execute the current source code string.
+    ast.map_err(|_| Failure::Parse)
 }
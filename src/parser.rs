@@ -1,40 +1,114 @@
-use crate::error::{ErrorType, self};
+use std::iter::Peekable;
+use std::vec::IntoIter;
+
+use crate::error::{ErrorLimit, ErrorType, self};
 use crate::expr::{Expr, ExprType};
 use crate::hash_table::KeyValue;
 use crate::stmt::{Stmt, StmtType};
-use crate::token::{Token, TokenType, Literal};
+use crate::token::{Comment, Token, TokenType};
+use crate::tokenizer::Tokenizer;
+
+/// The maximum number of blocks, expressions, assignments and unary operators that can be nested inside each other.
+/// Parsing is recursive, so without a limit, adversarial input such as thousands of `(` would overflow the stack.
+const MAX_NESTING_DEPTH: usize = 64;
+
+/// The maximum depth of the AST of an expression. A chain of binary operators, indexes or calls, e.g., `1 + 2 + 3`, is not nested,
+/// but its AST has a level for each of them, which the interpreter and printers recurse through, so it is limited separately.
+const MAX_EXPRESSION_DEPTH: usize = 1024;
 
 /// Performs syntax analysis.
 pub struct Parser {
-    tokens: Vec<Token>,  // The input sequence of tokens.
-    current_index: usize,  // An index pointing to the current token.
+    tokens: Peekable<IntoIter<Token>>,  // The input sequence of tokens. Tokens are moved out as they are consumed, so they never have to be cloned.
     current_line: usize,  // The current line number.
+    current_column: usize,  // The current column number, i.e., the column of the last token consumed.
+    depth: usize,  // The current nesting depth, checked against `MAX_NESTING_DEPTH`.
+    height: usize,  // The depth of the AST being parsed, including chains, checked against `MAX_EXPRESSION_DEPTH`.
+    peak: usize,  // The deepest `height` reached since the current chain started.
+    error_limit: ErrorLimit,  // How many errors `parse()` collects and reports.
+    strict: bool,  // Whether each statement has to be on a line of its own, for `--strict`.
+    doc_comments: Vec<Comment>,  // The `##` comments on lines of their own, which are attached to the function declarations after them.
 }
 
 impl Parser {
     /// Constructs a new `Parser` object given the sequence of tokens.
     pub fn new(tokens: Vec<Token>) -> Self {
+        Self::with_error_limit(tokens, ErrorLimit::default())
+    }
+
+    /// Constructs a new `Parser` object given the sequence of tokens, which collects and reports errors according to `error_limit`.
+    pub fn with_error_limit(tokens: Vec<Token>, error_limit: ErrorLimit) -> Self {
         Self {
-            tokens,
-            current_index: 0,
+            tokens: tokens.into_iter().peekable(),
             current_line: 1,
+            current_column: 1,
+            depth: 0,
+            height: 0,
+            peak: 0,
+            error_limit,
+            strict: false,
+            doc_comments: Vec::new(),
         }
     }
 
+    /// Gives the parser the comments of the source code, e.g., from `Tokenizer::comments()`, so that the `##` doc comments before
+    /// each function declaration are kept in it. Without them, every function's `doc` is `None`.
+    pub fn set_comments(&mut self, comments: &[Comment]) {
+        self.doc_comments = comments.iter().filter(|comment| !comment.trailing && comment.text.starts_with("##")).cloned().collect();
+    }
+
+    /// Returns the text of the doc comments on the lines directly before `line`, without their `##`s, or `None` if there are none.
+    fn doc_before(&self, line: usize) -> Option<String> {
+        let mut lines: Vec<&str> = Vec::new();
+        // The comments are in source order, so the ones directly before the line are found by walking back from the end.
+        for comment in self.doc_comments.iter().rev().skip_while(|comment| comment.line >= line) {
+            if comment.line != line - 1 - lines.len() {
+                break;
+            }
+            let text = &comment.text["##".len()..];
+            // One space after the `##` is part of the comment's layout, not of its text.
+            lines.push(text.strip_prefix(' ').unwrap_or(text));
+        }
+        if lines.is_empty() {
+            return None;
+        }
+        lines.reverse();
+        Some(lines.join("\n"))
+    }
+
+    /// Sets whether each statement has to be followed by a new line, the `}` which ends its block, or the end of the source code.
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
     /// The interface method which returns the abstract syntax tree of the source code as a sequence of statements.
+    /// Any errors are reported to the standard error stream, as well as returned.
     pub fn parse(&mut self) -> Result<Vec<Stmt>, Vec<ErrorType>> {
+        let result = self.parse_unreported();
+        if let Err(errors) = &result {
+            // If errors occurred, report the errors and return an `Err` variant so that the driver code terminates execution.
+            error::report_errors_up_to(&errors[..], self.error_limit.max_reported);
+        }
+        result
+    }
+
+    /// Returns the abstract syntax tree as `parse()` does, but leaves the errors to the caller to report, e.g., as a runtime error.
+    pub fn parse_unreported(&mut self) -> Result<Vec<Stmt>, Vec<ErrorType>> {
         let mut statements: Vec<Stmt> = Vec::new();  // The abstract syntax tree.
 
         // We aim to collect as many errors as possible in one run into a vector and report them all at once.
         let mut errors: Vec<ErrorType> = Vec::new();
 
-        while !self.check_next(&[TokenType::Eof]) {
+        while !self.is_at_end() {
             // While we have not reached the end of the sequence of tokens (EOF), parse the next statement.
-            match self.statement() {
+            match self.statement().and_then(|statement| self.end_of_statement().map(|()| statement)) {
                 Ok(statement) => statements.push(statement),
                 Err(error) => {
                     // If an error occurred during the parse, collect the error, synchronise, and continue.
                     errors.push(error);
+                    if self.error_limit.fail_fast {
+                        // In fail-fast mode, stop at the first error instead.
+                        break;
+                    }
                     self.sync();
                 },
             }
@@ -44,12 +118,21 @@ impl Parser {
             // If no error occurred, return the sequence of statements.
             Ok(statements)
         } else {
-            // If errors occurred, report all the errors and return an `Err` variant so that the driver code terminates execution.
-            error::report_errors(&errors[..]);
             Err(errors)
         }
     }
 
+    /// Returns the abstract syntax tree of source code which is a single expression, e.g., a formula to evaluate.
+    /// Unlike `parse()`, this stops at the first error, and does not report it.
+    pub fn parse_expression(&mut self) -> Result<Expr, ErrorType> {
+        let expr = self.expression()?;
+        match self.tokens.peek() {
+            // Anything after the expression, even another expression, is an error.
+            Some(token) if token.type_ != TokenType::Eof => Err(ErrorType::ExpectedEndOfExpression { line: token.line, column: token.column }),
+            _ => Ok(expr),
+        }
+    }
+
     /// Synchronises the parser to the next possible start of a new statement.
     fn sync(&mut self) {
         while !self.check_next(&[
@@ -59,22 +142,58 @@ impl Parser {
             TokenType::Func,
             TokenType::If,
             TokenType::Print,
+            TokenType::Repeat,
             TokenType::Return,
+            TokenType::Try,
             TokenType::Var,
             TokenType::While,
         ]) {
-            self.current_index += 1;  // Increment `current_index` until a 'safe' token is found.
-            self.current_line = self.tokens[self.current_index].line;  // Update the line number as we iterate.
+            // Discard tokens until a 'safe' token is found.
+            if self.tokens.next().is_none() {
+                // Should be unreachable as the sequence of tokens always ends with EOF, which is 'safe'.
+                break;
+            }
+            if let Some(token) = self.tokens.peek() {
+                // Update the line and column numbers as we iterate.
+                self.current_line = token.line;
+                self.current_column = token.column;
+            }
         }
     }
     
     /// Parses a statement.
-    /// <statement> ::= Break | For <for> | Func <function> | If <if> | Print <print> | Return <return> | Var <var> | While <while> | <expression>
+    /// <statement> ::= Break | For <for> | Func <function> | If <if> | Print <print> | Repeat <repeat> | Return <return> | Try <try>
+    ///               | Var <var> | While <while> | <expression_statement>
     fn statement(&mut self) -> Result<Stmt, ErrorType> {
+        // The statement is positioned at its first token, so that, e.g., traces refer to the line the statement starts on.
+        let (line, column) = match self.tokens.peek() {
+            Some(token) => (token.line, token.column),
+            None => (self.current_line, self.current_column),
+        };
+        let mut stmt = self.statement_body()?;
+        stmt.line = line;
+        stmt.column = column;
+        Ok(stmt)
+    }
+
+    /// In strict mode, checks that the statement just parsed is followed by a new line, a `}` or the end of the source code.
+    /// The statements in the header of a `for` loop are separated by `;` instead, so this is only checked in blocks and at the top level.
+    fn end_of_statement(&mut self) -> Result<(), ErrorType> {
+        match self.tokens.peek() {
+            Some(token) if self.strict && token.line == self.current_line && !matches!(token.type_, TokenType::RightCurly | TokenType::Eof) => {
+                Err(ErrorType::ExpectedNewline { line: token.line, column: token.column })
+            },
+            _ => Ok(()),
+        }
+    }
+
+    /// Parses the rest of a statement for `statement()`.
+    fn statement_body(&mut self) -> Result<Stmt, ErrorType> {
         // If the next token is one of these, consume it and call the relevant function, which will parse the rest of the statement.
         if self.check_and_consume(&[TokenType::Break]).is_some() {
             Ok(Stmt {
                 line: self.current_line,
+                column: self.current_column,
                 stmt_type: StmtType::Break
             })
         } else if self.check_and_consume(&[TokenType::For]).is_some() {
@@ -85,20 +204,66 @@ impl Parser {
             self.if_()
         } else if self.check_and_consume(&[TokenType::Print]).is_some() {
             self.print()
+        } else if self.check_and_consume(&[TokenType::Repeat]).is_some() {
+            self.repeat()
         } else if self.check_and_consume(&[TokenType::Return]).is_some() {
             self.return_()
+        } else if self.check_and_consume(&[TokenType::Try]).is_some() {
+            self.try_()
         } else if self.check_and_consume(&[TokenType::Var]).is_some() {
             self.var()
         } else if self.check_and_consume(&[TokenType::While]).is_some() {
             self.while_()
         } else {
-            Ok(Stmt {
-                line: self.current_line,
-                stmt_type: StmtType::Expression {
-                    expression: self.expression()?
+            self.expression_statement()
+        }
+    }
+
+    /// <expression_statement> ::= <expression> | <or> (Comma <or>)+ (Equal <tuple>)?
+    fn expression_statement(&mut self) -> Result<Stmt, ErrorType> {
+        let mut expression = self.expression()?;
+
+        if matches!(expression.expr_type, ExprType::Assignment { .. }) && self.check_and_consume(&[TokenType::Comma]).is_some() {
+            // Several values can only be assigned by unpacking them into as many targets, e.g., `a, b = 1, 2`, not into one, e.g., `a = 1, 2`.
+            return Err(ErrorType::ExpectedUnpackTargets { line: self.current_line, column: self.current_column });
+        }
+
+        if !matches!(expression.expr_type, ExprType::Assignment { .. }) && self.check_next(&[TokenType::Comma]) {
+            // If a Comma follows, it is a tuple, e.g., `a, b = b, a`, whose elements are parsed without assignments, so that the
+            // Equal after the last one assigns to all of them.
+            let mut elements = vec![expression];
+            while self.check_and_consume(&[TokenType::Comma]).is_some() {
+                elements.push(self.nested(Self::or)?);
+            }
+            if self.check_next(&[TokenType::Equal]) {
+                // A variable can only be assigned one of the values, e.g., not in `a, a = 1, 2`.
+                for (index, element) in elements.iter().enumerate() {
+                    if let ExprType::Variable { name } = &element.expr_type {
+                        if elements[..index].iter().any(|other| matches!(&other.expr_type, ExprType::Variable { name: other } if other == name)) {
+                            return Err(ErrorType::DuplicateUnpackTarget { name: name.clone(), line: element.line, column: element.column });
+                        }
+                    }
                 }
-            })
+            }
+            expression = Expr { line: self.current_line, column: self.current_column, expr_type: ExprType::Tuple { elements } };
+
+            if self.check_and_consume(&[TokenType::Equal]).is_some() {
+                let value = self.tuple()?;
+                expression = Expr {
+                    line: self.current_line,
+                    column: self.current_column,
+                    expr_type: ExprType::Assignment {
+                        target: Box::new(expression),
+                        value: Box::new(value),
+                    }
+                };
+            }
         }
+        Ok(Stmt {
+            line: self.current_line,
+            column: self.current_column,
+            stmt_type: StmtType::Expression { expression }
+        })
     }
 
     /// <block> ::= LeftCurly <statement>* RightCurly
@@ -108,15 +273,26 @@ impl Parser {
         
         // Parse <statement>*.
         let mut statements: Vec<Stmt> = Vec::new();
+        // A missing new line does not stop the rest of the block being parsed. Otherwise, the parser would resynchronise in the middle of the
+        // block, and report its `}` as another error.
+        let mut newline_error = None;
         while !self.check_next(&[TokenType::RightCurly, TokenType::Eof]) {
             // Keep parsing statements until the next token is a RightCurly or we have reached the end of the sequence of tokens.
-            statements.push(self.statement()?);
+            // Blocks can be nested inside blocks, so the statements are parsed one level deeper.
+            statements.push(self.nested(Self::statement)?);
+            if let Err(error) = self.end_of_statement() {
+                newline_error.get_or_insert(error);
+            }
         }
 
         // Consume RightCurly.
         self.expect(TokenType::RightCurly, '}')?;
+        if let Some(error) = newline_error {
+            return Err(error);
+        }
         Ok(Stmt {
             line: self.current_line,
+            column: self.current_column,
             stmt_type: StmtType::Block {
                 body: statements
             }
@@ -138,25 +314,20 @@ impl Parser {
         // Consume Semicolon if it follows.
         if self.check_and_consume(&[TokenType::Semicolon]).is_none() {
             // If there is no Semicolon, raise a specific error to avoid confusion as there are many semicolons in a `for` loop.
-            return Err(ErrorType::ExpectedSemicolonAfterInit { line: self.current_line });
+            return Err(ErrorType::ExpectedSemicolonAfterInit { line: self.current_line, column: self.current_column });
         }
 
         // Parse <expression>? as the condition of the `for` loop. Again, an Option<Expr> is used as it is optional.
-        let mut condition = Expr {
-            line: self.current_line,
-            expr_type: ExprType::Literal {
-                value: Literal::Bool(true)  // If no condition is given, it will be `true` by default.
-            }
-        };
+        let mut condition: Option<Expr> = None;
         if !self.check_next(&[TokenType::Semicolon]) {
             // If the next token is not a Semicolon, we parse it as the <expression>.
-            condition = self.expression()?;
+            condition = Some(self.expression()?);
         }
 
         // Consume Semicolon if it follows.
         if self.check_and_consume(&[TokenType::Semicolon]).is_none() {
             // If there is no Semicolon, again raise a specific error.
-            return Err(ErrorType::ExpectedSemicolonAfterCondition { line: self.current_line });
+            return Err(ErrorType::ExpectedSemicolonAfterCondition { line: self.current_line, column: self.current_column });
         }
 
         // Parse <statement>? as the incrementing statement of the `for` loop.
@@ -167,61 +338,30 @@ impl Parser {
 
         // Consume RightParen if it follows; otherwise, raise a specific error.
         if self.check_and_consume(&[TokenType::RightParen]).is_none() {
-            return Err(ErrorType::ExpectedParenAfterIncrement { line: self.current_line });
+            return Err(ErrorType::ExpectedParenAfterIncrement { line: self.current_line, column: self.current_column });
         }
 
         // Parse <block>, i.e., the body of the `for` loop including the curly brackets.
-        let for_body = self.block()?;
-
-        // Now, we internally convert the `for` loop into a `while` loop:
-        //  {
-        //      `initialiser`
-        //      while (`condition`) {
-        //          {
-        //              `for_body`
-        //          }
-        //          `increment`
-        //      }
-        //  }
-
-        // The body of the `while` loop includes the `for` loop body.
-        let mut while_body_vec = vec![for_body];
-        if let Some(inc) = increment {
-            // If there is an increment, put it at the end of the `while` loop body.
-            while_body_vec.push(inc);
-        }
-
-        // Create the `Block` statement for the `while` loop.
-        let while_body = Stmt {
-            line: self.current_line,
-            stmt_type: StmtType::Block {
-                body: while_body_vec
-            }
-        };
+        let body = self.block()?;
 
-        // The `while` loop has the same condition as the `for` loop.
-        let while_loop = Stmt {
+        // The `for` loop is kept as it is written, rather than being converted into a `while` loop,
+        // so that errors and tools can refer back to the original loop.
+        Ok(Stmt {
             line: self.current_line,
-            stmt_type: StmtType::While {
+            column: self.current_column,
+            stmt_type: StmtType::For {
+                initialiser: initialiser.map(Box::new),
                 condition,
-                body: Box::new(while_body)
+                increment: increment.map(Box::new),
+                body: Box::new(body),
             }
-        };
-        
-        if let Some(init) = initialiser {
-            // If an initialising statement is given, place it before the `while` loop and wrap in a `Block` statement.
-            Ok(Stmt {
-                line: self.current_line,
-                stmt_type: StmtType::Block { body: vec![init, while_loop] }
-            })
-        } else {
-            // Otherwise, just return the `while` loop.
-            Ok(while_loop)
-        }
+        })
     }
 
     /// <function> ::= Identifier LeftParen (Identifier (Comma Identifier)*)? RightParen <block>
     fn function(&mut self) -> Result<Stmt, ErrorType> {
+        // The `func` keyword has just been consumed, so the doc comments are on the lines before the current one.
+        let doc = self.doc_before(self.current_line);
         if let Some(function_name_token) = self.check_and_consume(&[TokenType::Identifier]) {
             // If an Identifier was given (the name of the function), consume it.
 
@@ -238,7 +378,7 @@ impl Parser {
                         parameters.push(parameter.lexeme);
                     } else {
                         // Otherwise, raise a specific error, as a parameter must be given after a comma.
-                        return Err(ErrorType::ExpectedParameterName { line: self.current_line });
+                        return Err(ErrorType::ExpectedParameterName { line: self.current_line, column: self.current_column });
                     }
 
                     // If a Comma does not follow a parameter, then there should be no more parameters.
@@ -257,15 +397,17 @@ impl Parser {
 
             Ok(Stmt {
                 line: self.current_line,
+                column: self.current_column,
                 stmt_type: StmtType::Function {
                     name: function_name_token.lexeme,
                     parameters,
                     body: Box::new(body),
+                    doc,
                 }
             })
         } else {
             // If an Identifier was not given, raise a specific error.
-            Err(ErrorType::ExpectedFunctionName { line: self.current_line })
+            Err(ErrorType::ExpectedFunctionName { line: self.current_line, column: self.current_column })
         }
     }
 
@@ -288,6 +430,7 @@ impl Parser {
             let else_body = self.else_()?;
             Ok(Stmt {
                 line: self.current_line,
+                column: self.current_column,
                 stmt_type: StmtType::If {
                     condition,
                     then_body: Box::new(then_body),
@@ -298,6 +441,7 @@ impl Parser {
             // Otherwise, just return the `if` statement with just the `then` body.
             Ok(Stmt {
                 line: self.current_line,
+                column: self.current_column,
                 stmt_type: StmtType::If {
                     condition,
                     then_body: Box::new(then_body),
@@ -323,45 +467,123 @@ impl Parser {
     fn print(&mut self) -> Result<Stmt, ErrorType> {
         Ok(Stmt {
             line: self.current_line,
+            column: self.current_column,
             stmt_type: StmtType::Print {
                 expression: self.expression()?
             }
         })
     }
 
-    /// <return> ::= <expression>
+    /// <return> ::= <tuple>
     fn return_(&mut self) -> Result<Stmt, ErrorType> {
         Ok(Stmt {
             line: self.current_line,
+            column: self.current_column,
             stmt_type: StmtType::Return {
-                expression: self.expression()?
+                // Several values can be returned at once, e.g., `return q, r`, as a tuple.
+                expression: self.tuple()?
+            }
+        })
+    }
+
+    /// <try> ::= <block> (Catch LeftParen Identifier RightParen <block>)? (Finally <block>)?
+    fn try_(&mut self) -> Result<Stmt, ErrorType> {
+        // Parse <block>, the body which errors are caught from.
+        let body = self.block()?;
+
+        // Parse the `catch` block, with the name the caught error is declared as in brackets before it.
+        let mut catch = None;
+        if self.check_and_consume(&[TokenType::Catch]).is_some() {
+            self.expect(TokenType::LeftParen, '(')?;
+            let Some(name_token) = self.check_and_consume(&[TokenType::Identifier]) else {
+                // The error is declared as a variable, so the same error is raised as for a `var` without a name.
+                return Err(ErrorType::ExpectedVariableName { line: self.current_line, column: self.current_column });
+            };
+            self.expect(TokenType::RightParen, ')')?;
+            catch = Some((name_token.lexeme, Box::new(self.block()?)));
+        }
+
+        // Parse the `finally` block.
+        let mut finally = None;
+        if self.check_and_consume(&[TokenType::Finally]).is_some() {
+            finally = Some(Box::new(self.block()?));
+        }
+
+        // A `try` with neither would do nothing that a block does not, so it is most likely unfinished.
+        if catch.is_none() && finally.is_none() {
+            return Err(ErrorType::ExpectedCatchOrFinally { line: self.current_line, column: self.current_column });
+        }
+        Ok(Stmt {
+            line: self.current_line,
+            column: self.current_column,
+            stmt_type: StmtType::Try {
+                body: Box::new(body),
+                catch,
+                finally,
             }
         })
     }
 
-    /// <var> ::= Identifier Equal <expression>
+    /// <var> ::= Identifier (Comma Identifier)* Equal <tuple>
     fn var(&mut self) -> Result<Stmt, ErrorType> {
         if let Some(target_variable_token) = self.check_and_consume(&[TokenType::Identifier]) {
             // If an Identifier was given (the target variable name), consume it.
 
+            // Collect the names of any other variables declared at the same time, e.g., `var q, r = divide(7, 2)`.
+            let mut names = vec![target_variable_token.lexeme];
+            while self.check_and_consume(&[TokenType::Comma]).is_some() {
+                let Some(name_token) = self.check_and_consume(&[TokenType::Identifier]) else {
+                    return Err(ErrorType::ExpectedVariableName { line: self.current_line, column: self.current_column });
+                };
+                if names.contains(&name_token.lexeme) {
+                    return Err(ErrorType::DuplicateUnpackTarget { name: name_token.lexeme, line: self.current_line, column: self.current_column });
+                }
+                names.push(name_token.lexeme);
+            }
+
             // Consume Equal.
             self.expect(TokenType::Equal, '=')?;
             
-            // Parse <expression>.
-            let value = self.expression()?;
+            let stmt_type = if names.len() == 1 {
+                // A single variable is given a single value, so several values after it are not made into an array, e.g., `var x = 1, 2`.
+                let value = self.expression()?;
+                if self.check_and_consume(&[TokenType::Comma]).is_some() {
+                    return Err(ErrorType::ExpectedUnpackTargets { line: self.current_line, column: self.current_column });
+                }
+                StmtType::VarDecl { name: names.remove(0), value }
+            } else {
+                // Parse <tuple>, which is an <expression> unless it has commas.
+                StmtType::VarUnpack { names, value: self.tuple()? }
+            };
             Ok(Stmt {
                 line: self.current_line,
-                stmt_type: StmtType::VarDecl {
-                    name: target_variable_token.lexeme,
-                    value,
-                }
+                column: self.current_column,
+                stmt_type,
             })
         } else {
             // If an Identifier was not given, raise a specific error.
-            Err(ErrorType::ExpectedVariableName { line: self.current_line })
+            Err(ErrorType::ExpectedVariableName { line: self.current_line, column: self.current_column })
         }
     }
 
+    /// <repeat> ::= <expression> <block>
+    fn repeat(&mut self) -> Result<Stmt, ErrorType> {
+        // Parse <expression>, the number of times to execute the body. Unlike conditions, it does not need brackets around it.
+        let count = self.expression()?;
+
+        // Parse <block>, the body of the loop.
+        let body = self.block()?;
+
+        Ok(Stmt {
+            line: self.current_line,
+            column: self.current_column,
+            stmt_type: StmtType::Repeat {
+                count,
+                body: Box::new(body),
+            }
+        })
+    }
+
     /// <while> ::= LeftParen <expression> RightParen <block>
     fn while_(&mut self) -> Result<Stmt, ErrorType> {
         // Consume LeftParen.
@@ -378,6 +600,7 @@ impl Parser {
 
         Ok(Stmt {
             line: self.current_line,
+            column: self.current_column,
             stmt_type: StmtType::While {
                 condition,
                 body: Box::new(body),
@@ -385,10 +608,29 @@ impl Parser {
         })
     }
 
+    /// <tuple> ::= <expression> (Comma <expression>)*
+    fn tuple(&mut self) -> Result<Expr, ErrorType> {
+        let first = self.expression()?;
+        if !self.check_next(&[TokenType::Comma]) {
+            // A single value is not a tuple, so it is left as it is.
+            return Ok(first);
+        }
+        let mut elements = vec![first];
+        while self.check_and_consume(&[TokenType::Comma]).is_some() {
+            elements.push(self.expression()?);
+        }
+        Ok(Expr {
+            line: self.current_line,
+            column: self.current_column,
+            expr_type: ExprType::Tuple { elements },
+        })
+    }
+
     /// Parses an expression.
     /// <expression> ::= <assignment>
     fn expression(&mut self) -> Result<Expr, ErrorType> {
-        self.assignment()
+        // Every bracketed expression is parsed through here, so it is parsed one level deeper.
+        self.nested(Self::assignment)
     }
 
     /// <assignment> ::= <or> (Equal <assignment>)?
@@ -404,10 +646,12 @@ impl Parser {
             // If an Equal was given, consume it.
 
             // Recursively parse <assignment>.
-            let value = self.assignment()?;
+            // Assignments can be chained without brackets, e.g., `a = b = c`, so this is also one level deeper.
+            let value = self.nested(Self::assignment)?;
             
             Ok(Expr {
                 line: self.current_line,
+                column: self.current_column,
                 expr_type: ExprType::Assignment {
                     target: Box::new(expr),  // Use the <or> as the `target` of the Assignment.
                     value: Box::new(value),
@@ -428,16 +672,23 @@ impl Parser {
     /// In other words, the parse tree should look like `((a or b) or c) or d`, as opposed to
     /// `a or (b or (c or d))`. This also minimises recursion; hence, it is more memory efficient.
     fn or(&mut self) -> Result<Expr, ErrorType> {
+        // Binary operators are chained without brackets, so the chain is not nested, but it is measured towards `MAX_EXPRESSION_DEPTH`.
+        let (height, peak) = self.start_chain();
+
         // Parse <and>.
         let mut expr = self.and()?;
 
         while let Some(operator) = self.check_and_consume(&[TokenType::Or]) {
             // While the following token is an Or, consume it and store the token object (Or) in `operator`.
 
+            // Each operator puts the expression one level deeper than both of its operands.
+            self.deepen()?;
+
             // Parse <and>.
             let right = self.and()?;
             expr = Expr {
                 line: self.current_line,
+                column: self.current_column,
                 expr_type: ExprType::Binary {
                     left: Box::new(expr),  // Use the previous `expr` as the left-hand side to enforce left associativity.
                     operator,  // Store the token object (Or), as this will be used to determine the operation in runtime.
@@ -445,18 +696,22 @@ impl Parser {
                 }
             };
         }
+        self.end_chain(height, peak);
         Ok(expr)
     }
 
     /// <and> ::= <equality> (And <equality>)*
     /// As above.
     fn and(&mut self) -> Result<Expr, ErrorType> {
+        let (height, peak) = self.start_chain();
         let mut expr = self.equality()?;
 
         while let Some(operator) = self.check_and_consume(&[TokenType::And]) {
+            self.deepen()?;
             let right = self.equality()?;
             expr = Expr {
                 line: self.current_line,
+                column: self.current_column,
                 expr_type: ExprType::Binary {
                     left: Box::new(expr),
                     operator,
@@ -464,20 +719,24 @@ impl Parser {
                 }
             };
         }
+        self.end_chain(height, peak);
         Ok(expr)
     }
 
     /// <equality> ::= <comparison> ((EqualEqual | BangEqual) <comparison>)*
     /// As above.
     fn equality(&mut self) -> Result<Expr, ErrorType> {
+        let (height, peak) = self.start_chain();
         let mut expr = self.comparison()?;
 
         while let Some(operator) = self.check_and_consume(&[TokenType::EqualEqual, TokenType::BangEqual]) {
             // This time, allow both EqualEqual and BangEqual tokens as they have equal precedence.
             
+            self.deepen()?;
             let right = self.comparison()?;
             expr = Expr {
                 line: self.current_line,
+                column: self.current_column,
                 expr_type: ExprType::Binary {
                     left: Box::new(expr),
                     operator,
@@ -485,18 +744,22 @@ impl Parser {
                 }
             };
         }
+        self.end_chain(height, peak);
         Ok(expr)
     }
 
     /// <comparison> ::= <plus_minus> ((Greater | Less | GreaterEqual | LessEqual) <plus_minus>)*
     /// As above.
     fn comparison(&mut self) -> Result<Expr, ErrorType> {
+        let (height, peak) = self.start_chain();
         let mut expr = self.plus_minus()?;
 
         while let Some(operator) = self.check_and_consume(&[TokenType::Greater, TokenType::Less, TokenType::GreaterEqual, TokenType::LessEqual]) {
+            self.deepen()?;
             let right = self.plus_minus()?;
             expr = Expr {
                 line: self.current_line,
+                column: self.current_column,
                 expr_type: ExprType::Binary {
                     left: Box::new(expr),
                     operator,
@@ -504,18 +767,22 @@ impl Parser {
                 }
             };
         }
+        self.end_chain(height, peak);
         Ok(expr)
     }
 
     /// <plus_minus> ::= <star_slash_percent> ((Plus | Minus) <star_slash_percent>)*
     /// As above.
     fn plus_minus(&mut self) -> Result<Expr, ErrorType> {
+        let (height, peak) = self.start_chain();
         let mut expr = self.star_slash_percent()?;
 
         while let Some(operator) = self.check_and_consume(&[TokenType::Plus, TokenType::Minus]) {
+            self.deepen()?;
             let right = self.star_slash_percent()?;
             expr = Expr {
                 line: self.current_line,
+                column: self.current_column,
                 expr_type: ExprType::Binary {
                     left: Box::new(expr),
                     operator,
@@ -523,18 +790,22 @@ impl Parser {
                 }
             };
         }
+        self.end_chain(height, peak);
         Ok(expr)
     }
 
     /// <star_slash_percent> ::= <unary> ((Star | Slash | Percent) <unary>)*
     /// As above.
     fn star_slash_percent(&mut self) -> Result<Expr, ErrorType> {
+        let (height, peak) = self.start_chain();
         let mut expr = self.unary()?;
 
         while let Some(operator) = self.check_and_consume(&[TokenType::Star, TokenType::Slash, TokenType::Percent]) {
+            self.deepen()?;
             let right = self.unary()?;
             expr = Expr {
                 line: self.current_line,
+                column: self.current_column,
                 expr_type: ExprType::Binary {
                     left: Box::new(expr),
                     operator,
@@ -542,6 +813,7 @@ impl Parser {
                 }
             };
         }
+        self.end_chain(height, peak);
         Ok(expr)
     }
 
@@ -550,10 +822,11 @@ impl Parser {
         if let Some(operator) = self.check_and_consume(&[TokenType::Bang, TokenType::Minus]) {
             // If the current token is either Bang or Minus, consume it.
 
-            // Recursively parse <unary>.
-            let right = self.unary()?;
+            // Recursively parse <unary>, one level deeper as unary operators can be chained without brackets, e.g., `!!!!true`.
+            let right = self.nested(Self::unary)?;
             Ok(Expr {
                 line: self.current_line,
+                column: self.current_column,
                 expr_type: ExprType::Unary {
                     operator,
                     right: Box::new(right),  // Use the recursion as the right-hand side expression, i.e., !(!(!(!true)))
@@ -565,46 +838,100 @@ impl Parser {
         }
     }
 
-    /// <element> ::= <call> (LeftSquare <expression> RightSquare)*
+    /// <element> ::= <call> (LeftSquare (<expression> | <slice>) RightSquare)*
+    /// <slice> ::= <expression>? Colon <expression>? (Colon <expression>?)?
     fn element(&mut self) -> Result<Expr, ErrorType> {
+        // Indexes are chained like binary operators, e.g., `a[2][3]`, so they are measured the same way.
+        let (height, peak) = self.start_chain();
+
         // Parse <call>, i.e., the 'array' part of an element (`a` in `a[2][3]`).
         let mut expr = self.call()?;
         
         while self.check_and_consume(&[TokenType::LeftSquare]).is_some() {
             // While the following token is LeftSquare, consume it.
-
-            // Parse <expression>, i.e., the 'index' part of an element (`1+2` in `a[1+2]`).
-            let index = self.expression()?;
-            expr = Expr {
-                line: self.current_line,
-                expr_type: ExprType::Element {
-                    array: Box::new(expr),  // Use the previous `expr` as the 'array' part to keep left associativity.
-                    index: Box::new(index),
-                }
-            };
+            self.deepen()?;
+
+            // Parse <expression>, i.e., the 'index' part of an element (`1+2` in `a[1+2]`), or the start of a slice, which can be left out.
+            let index = self.slice_bound()?;
+
+            if self.check_and_consume(&[TokenType::Colon]).is_some() {
+                // If a Colon follows, it is a slice, e.g., `a[1:3]`, and the stop and step can be left out too.
+                let stop = self.slice_bound()?;
+                let step = if self.check_and_consume(&[TokenType::Colon]).is_some() { self.slice_bound()? } else { None };
+                expr = Expr {
+                    line: self.current_line,
+                    column: self.current_column,
+                    expr_type: ExprType::Slice {
+                        array: Box::new(expr),
+                        start: index.map(Box::new),
+                        stop: stop.map(Box::new),
+                        step: step.map(Box::new),
+                    }
+                };
+            } else {
+                // Otherwise, it is an element, which must have an index.
+                let Some(index) = index else {
+                    return Err(ErrorType::ExpectedExpression { line: self.current_line, column: self.current_column });
+                };
+                expr = Expr {
+                    line: self.current_line,
+                    column: self.current_column,
+                    expr_type: ExprType::Element {
+                        array: Box::new(expr),  // Use the previous `expr` as the 'array' part to keep left associativity.
+                        index: Box::new(index),
+                    }
+                };
+            }
 
             // Consume the closing RightSquare of an index.
             self.expect(TokenType::RightSquare, ']')?;
         }
+        self.end_chain(height, peak);
         Ok(expr)
     }
+
+    /// Parses a bound or the step of a slice, or returns `None` if it has been left out, i.e., a Colon or RightSquare follows.
+    fn slice_bound(&mut self) -> Result<Option<Expr>, ErrorType> {
+        if self.check_next(&[TokenType::Colon, TokenType::RightSquare]) {
+            Ok(None)
+        } else {
+            self.expression().map(Some)
+        }
+    }
     
-    /// <call> ::= <primary> (LeftParen (<expression> (Comma <expression>)*)? RightParen)*
+    /// <call> ::= <primary> (LeftParen (<argument> (Comma <argument>)*)? RightParen)*
+    /// <argument> ::= (Identifier Colon)? <expression>
     fn call(&mut self) -> Result<Expr, ErrorType> {
+        // Calls are chained like indexes, e.g., `f(2)(3)`.
+        let (height, peak) = self.start_chain();
+
         // Parse <primary>, i.e., the callee (`f` in `f(2)(3)`).
         let mut expr = self.primary()?;
 
         while self.check_and_consume(&[TokenType::LeftParen]).is_some() {
             // While the following token is LeftParen, consume it.
+            self.deepen()?;
 
-            // Collect the arguments of the function call into an array.
+            // Collect the arguments of the function call into an array, and those given with the name of their parameter into another.
             let mut arguments: Vec<Expr> = Vec::new();
+            let mut named_arguments: Vec<(String, Expr)> = Vec::new();
             
             if !self.check_next(&[TokenType::RightParen]) {
                 // If there are arguments, i.e., not just f()...
                 loop {
                     // keep parsing the argument expressions and pushing them to the array of arguments...
-                    arguments.push(self.expression()?);
+                    let argument = self.expression()?;
+                    match argument.expr_type {
+                        // A variable followed by a Colon is the name of the parameter the argument after it is for, e.g., `color: 'red'`.
+                        ExprType::Variable { name } if self.check_and_consume(&[TokenType::Colon]).is_some() => {
+                            named_arguments.push((name, self.expression()?));
+                        },
+                        // Otherwise, it is given by position, which has to be before any given by name, so that its position is clear.
+                        _ if !named_arguments.is_empty() => {
+                            return Err(ErrorType::ExpectedNamedArgument { line: argument.line, column: argument.column });
+                        },
+                        _ => arguments.push(argument),
+                    }
                     if self.check_and_consume(&[TokenType::Comma]).is_none() {
                         // until the next token is not a Comma, in which case, there are no more arguments.
                         break;
@@ -617,12 +944,15 @@ impl Parser {
 
             expr = Expr {
                 line: self.current_line,
+                column: self.current_column,
                 expr_type: ExprType::Call {
                     callee: Box::new(expr),  // Use the previous `expr` as the 'callee' part to keep left associativity.
                     arguments,
+                    named_arguments,
                 }
             }
         }
+        self.end_chain(height, peak);
         Ok(expr)
     }
 
@@ -645,6 +975,7 @@ impl Parser {
             // which is stored as an attribute in the Token object.
             Ok(Expr {
                 line: self.current_line,
+                column: self.current_column,
                 expr_type: ExprType::Literal {
                     value: token.literal
                 }
@@ -661,6 +992,7 @@ impl Parser {
 
             Ok(Expr {
                 line: self.current_line,
+                column: self.current_column,
                 expr_type: ExprType::Grouping {
                     expression: Box::new(expr)
                 }
@@ -688,6 +1020,7 @@ impl Parser {
             self.expect(TokenType::RightSquare, ']')?;
             Ok(Expr {
                 line: self.current_line,
+                column: self.current_column,
                 expr_type: ExprType::Array { 
                     elements 
                 }
@@ -710,7 +1043,7 @@ impl Parser {
                     // Consume Colon if it follows the key.
                     if self.check_and_consume(&[TokenType::Colon]).is_none() {
                         // Otherwise, raise a specific error.
-                        return Err(ErrorType::ExpectedColonAfterKey { line: self.current_line });
+                        return Err(ErrorType::ExpectedColonAfterKey { line: self.current_line, column: self.current_column });
                     }
 
                     // Parse the value expression.
@@ -730,6 +1063,7 @@ impl Parser {
             self.expect(TokenType::RightCurly, '}')?;
             Ok(Expr {
                 line: self.current_line,
+                column: self.current_column,
                 expr_type: ExprType::Dictionary {
                     elements
                 }
@@ -741,6 +1075,7 @@ impl Parser {
             // Note 'variable' in this case also means function names.
             Ok(Expr {
                 line: self.current_line,
+                column: self.current_column,
                 expr_type: ExprType::Variable {
                     name: identifier.lexeme
                 }
@@ -749,45 +1084,84 @@ impl Parser {
         } else {
             // If no rule matches the token, then we expected an expression but was not given one.
             // So, raise an ExpectedExpression error.
-            Err(ErrorType::ExpectedExpression { line: self.current_line })
+            Err(ErrorType::ExpectedExpression { line: self.current_line, column: self.current_column })
+        }
+    }
+
+    /// Calls the parsing function `rule` one level of nesting deeper.
+    /// Raises a `TooDeeplyNested` error instead if this would exceed `MAX_NESTING_DEPTH` or `MAX_EXPRESSION_DEPTH`.
+    fn nested<T>(&mut self, rule: fn(&mut Self) -> Result<T, ErrorType>) -> Result<T, ErrorType> {
+        if self.depth >= MAX_NESTING_DEPTH || self.height >= MAX_EXPRESSION_DEPTH {
+            return Err(ErrorType::TooDeeplyNested { line: self.current_line, column: self.current_column });
         }
+        // The depths are restored rather than decremented, as a chain which ends in an error does not restore them itself.
+        let (depth, height) = (self.depth, self.height);
+        self.depth += 1;
+        self.height += 1;
+        self.peak = self.peak.max(self.height);
+        let result = rule(self);
+        (self.depth, self.height) = (depth, height);
+        result
+    }
+
+    /// Starts measuring a chain, e.g., `a + b - c` or `a[2][3]`, returning the height and peak to give to `end_chain()`.
+    /// The AST of a chain is as deep as its number of links plus its deepest operand, so `peak` is reset to measure the operands.
+    fn start_chain(&mut self) -> (usize, usize) {
+        let saved = (self.height, self.peak);
+        self.peak = self.height;
+        saved
+    }
+
+    /// Moves one level deeper than everything in the chain so far, for the next link in the chain.
+    /// Raises a `TooDeeplyNested` error instead if this would exceed `MAX_EXPRESSION_DEPTH`.
+    fn deepen(&mut self) -> Result<(), ErrorType> {
+        if self.peak >= MAX_EXPRESSION_DEPTH {
+            return Err(ErrorType::TooDeeplyNested { line: self.current_line, column: self.current_column });
+        }
+        self.height = self.peak + 1;
+        self.peak = self.height;
+        Ok(())
+    }
+
+    /// Ends a chain started by `start_chain()`. The expressions after the chain are parsed at the height it started at,
+    /// but the height it reached still counts towards any chain it is an operand of.
+    fn end_chain(&mut self, height: usize, peak: usize) {
+        self.height = height;
+        self.peak = self.peak.max(peak);
     }
 
     /// Returns `Some(token)` and advances the pointer if the type of the next token is one of the `expected_types`.
     /// Otherwise, or if we are at the end of the sequence of tokens, return `None`.
     fn check_and_consume(&mut self, expected_types: &[TokenType]) -> Option<Token> {
-        if let Some(token) = self.tokens.get(self.current_index) {
-            // If we are not at the end of the sequence of tokens...
-            if expected_types.contains(&token.type_) {
-                // If the type of the next token is one of the `expected_types`,
-                // increment `current_index` and update `current_line`.
-                self.current_index += 1;
-                self.current_line = token.line;
-                Some(token).cloned()
-            } else {
-                // If it the token does not match, then return `None`.
-                None
-            }
-        } else {
-            // If we are at the end, return `None`.
-            None
-        }
+        // If the type of the next token is one of the `expected_types`, move it out of the sequence.
+        // If the token does not match, or we are at the end, `next_if()` leaves the sequence untouched and returns `None`.
+        let token = self.tokens.next_if(|token| expected_types.contains(&token.type_))?;
+
+        // Update `current_line` and `current_column` to the position of the consumed token.
+        self.current_line = token.line;
+        self.current_column = token.column;
+        Some(token)
     }
 
     /// Returns `true` if the type of the next token is one of the `expected_types`.
     /// Otherwise, or if we are at the end of the sequence of tokens, return `false`.
     /// The difference between this and `check_and_consume()` is that this does not advance the pointer if the token matches what is expected.
-    fn check_next(&self, expected_types: &[TokenType]) -> bool {
-        if let Some(token) = self.tokens.get(self.current_index) {
+    fn check_next(&mut self, expected_types: &[TokenType]) -> bool {
+        if let Some(token) = self.tokens.peek() {
             // If we are not at the end of the sequence of tokens, return whether or not
             // the token's type is one of the `expected_types`.
             expected_types.contains(&token.type_)
         } else {
-            // If we are at the end, return `None`.
+            // If we are at the end, return `false`.
             false
         }
     }
 
+    /// Returns `true` if the next token is EOF or there are no tokens left.
+    fn is_at_end(&mut self) -> bool {
+        self.tokens.peek().is_none_or(|token| token.type_ == TokenType::Eof)
+    }
+
     /// Returns `Ok(())` and advances the pointer if the type of the next token is one of the `expected_types`.
     /// Otherwise, return `Err(ErrorType::ExpectedCharacter)`.
     /// The difference between this and `check_and_consume()` is that this does not return the token itself, just an error to be bubbled up.
@@ -797,17 +1171,31 @@ impl Parser {
             return Err(ErrorType::ExpectedCharacter {
                 expected: expected_char,
                 line: self.current_line,
+                column: self.current_column,
             });
         }
         Ok(())
     }
 }
 
+/// Tokenizes and parses `source`, returning the abstract syntax tree or every error found.
+/// This is the entry point used to fuzz the frontend: for any input, it must return rather than panic.
+pub fn parse_no_panic(source: &str) -> Result<Vec<Stmt>, Vec<ErrorType>> {
+    // The tokenizer is used as an iterator so that the errors are not reported, as fuzzing produces a great many of them.
+    let (tokens, errors): (Vec<_>, Vec<_>) = Tokenizer::new(source).partition(Result::is_ok);
+    if !errors.is_empty() {
+        return Err(errors.into_iter().filter_map(Result::err).collect());
+    }
+    Parser::new(tokens.into_iter().filter_map(Result::ok).collect()).parse()
+}
+
 #[cfg(test)]
 mod tests {
     use crate::{token, expr::{Expr, ExprType}, error::ErrorType, tokenizer::Tokenizer, stmt::Stmt, stmt::StmtType};
 
-    use super::Parser;
+    use crate::error::ErrorLimit;
+
+    use super::{parse_no_panic, Parser, MAX_EXPRESSION_DEPTH, MAX_NESTING_DEPTH};
 
     fn parse(source: &str) -> Result<Vec<Stmt>, Vec<ErrorType>> {
         let mut tokenizer = Tokenizer::new(source);
@@ -831,204 +1219,177 @@ mod tests {
     #[test]
     fn for_() {
         let source = "for (var x = 5; x < 10; x = x + 1) {var y = x}";
-        assert_eq!(Ok(vec![Stmt { line: 1, stmt_type: StmtType::Block {
-            body: vec![
-                Stmt { line: 1, stmt_type: StmtType::VarDecl {
-                    name: String::from("x"),
-                    value: Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(5.0) }},
-                }},
-                Stmt { line: 1, stmt_type: StmtType::While {
-                    condition: Expr { line: 1, expr_type: ExprType::Binary {
-                        left: Box::new(Expr { line: 1, expr_type: ExprType::Variable { name: String::from("x") }}),
-                        operator: token::Token { type_: token::TokenType::Less, lexeme: String::from("<"), literal: token::Literal::Null, line: 1 },
-                        right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(10.0) }}),
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::For {
+            initialiser: Some(Box::new(Stmt { line: 1, column: 6, stmt_type: StmtType::VarDecl {
+                name: String::from("x"),
+                value: Expr { line: 1, column: 14, expr_type: ExprType::Literal { value: token::Literal::Number(5.0) }},
+            }})),
+            condition: Some(Expr { line: 1, column: 21, expr_type: ExprType::Binary {
+                left: Box::new(Expr { line: 1, column: 17, expr_type: ExprType::Variable { name: String::from("x") }}),
+                operator: token::Token { type_: token::TokenType::Less, lexeme: String::from("<"), literal: token::Literal::Null, line: 1, column: 19 },
+                right: Box::new(Expr { line: 1, column: 21, expr_type: ExprType::Literal { value: token::Literal::Number(10.0) }}),
+            }}),
+            increment: Some(Box::new(Stmt { line: 1, column: 25, stmt_type: StmtType::Expression { expression: Expr { line: 1, column: 33, expr_type: ExprType::Assignment {
+                target: Box::new(Expr { line: 1, column: 25, expr_type: ExprType::Variable { name: String::from("x") }}),
+                value: Box::new(Expr { line: 1, column: 33, expr_type: ExprType::Binary {
+                    left: Box::new(Expr { line: 1, column: 29, expr_type: ExprType::Variable { name: String::from("x") }}),
+                    operator: token::Token { type_: token::TokenType::Plus, lexeme: String::from("+"), literal: token::Literal::Null, line: 1, column: 31 },
+                    right: Box::new(Expr { line: 1, column: 33, expr_type: ExprType::Literal { value: token::Literal::Number(1.0) }}),
+                }}),
+            }}}})),
+            body: Box::new(Stmt { line: 1, column: 46, stmt_type: StmtType::Block {
+                body: vec![
+                    Stmt { line: 1, column: 37, stmt_type: StmtType::VarDecl {
+                        name: String::from("y"),
+                        value: Expr { line: 1, column: 45, expr_type: ExprType::Variable { name: String::from("x") }},
                     }},
-                    body: Box::new(Stmt { line: 1, stmt_type: StmtType::Block {
-                        body: vec![
-                            Stmt { line: 1, stmt_type: StmtType::Block {
-                                body: vec![
-                                    Stmt { line: 1, stmt_type: StmtType::VarDecl {
-                                        name: String::from("y"),
-                                        value: Expr { line: 1, expr_type: ExprType::Variable { name: String::from("x") }},
-                                    }},
-                                ],
-                            }},
-                            Stmt { line: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, expr_type: ExprType::Assignment {
-                                target: Box::new(Expr { line: 1, expr_type: ExprType::Variable { name: String::from("x") }}),
-                                value: Box::new(Expr { line: 1, expr_type: ExprType::Binary {
-                                    left: Box::new(Expr { line: 1, expr_type: ExprType::Variable { name: String::from("x") }}),
-                                    operator: token::Token { type_: token::TokenType::Plus, lexeme: String::from("+"), literal: token::Literal::Null, line: 1 },
-                                    right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(1.0) }}),
-                                }}),
-                            }}}},
-                        ],
-                    }}),
-                }},
-            ]
+                ],
+            }}),
         }}]), parse(source));
     }
     
     #[test]
     fn for_no_init() {
         let source = "for (; x < 10; x = x + 1) {var y = x}";
-        assert_eq!(Ok(vec![
-            Stmt { line: 1, stmt_type: StmtType::While {
-                condition: Expr { line: 1, expr_type: ExprType::Binary {
-                    left: Box::new(Expr { line: 1, expr_type: ExprType::Variable { name: String::from("x") }}),
-                    operator: token::Token { type_: token::TokenType::Less, lexeme: String::from("<"), literal: token::Literal::Null, line: 1 },
-                    right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(10.0) }}),
-                }},
-                body: Box::new(Stmt { line: 1, stmt_type: StmtType::Block {
-                    body: vec![
-                        Stmt { line: 1, stmt_type: StmtType::Block {
-                            body: vec![
-                                Stmt { line: 1, stmt_type: StmtType::VarDecl {
-                                    name: String::from("y"),
-                                    value: Expr { line: 1, expr_type: ExprType::Variable { name: String::from("x") }},
-                                }},
-                            ],
-                        }},
-                        Stmt { line: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, expr_type: ExprType::Assignment {
-                            target: Box::new(Expr { line: 1, expr_type: ExprType::Variable { name: String::from("x") }}),
-                            value: Box::new(Expr { line: 1, expr_type: ExprType::Binary {
-                                left: Box::new(Expr { line: 1, expr_type: ExprType::Variable { name: String::from("x") }}),
-                                operator: token::Token { type_: token::TokenType::Plus, lexeme: String::from("+"), literal: token::Literal::Null, line: 1 },
-                                right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(1.0) }}),
-                            }}),
-                        }}}},
-                    ],
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::For {
+            initialiser: None,
+            condition: Some(Expr { line: 1, column: 12, expr_type: ExprType::Binary {
+                left: Box::new(Expr { line: 1, column: 8, expr_type: ExprType::Variable { name: String::from("x") }}),
+                operator: token::Token { type_: token::TokenType::Less, lexeme: String::from("<"), literal: token::Literal::Null, line: 1, column: 10 },
+                right: Box::new(Expr { line: 1, column: 12, expr_type: ExprType::Literal { value: token::Literal::Number(10.0) }}),
+            }}),
+            increment: Some(Box::new(Stmt { line: 1, column: 16, stmt_type: StmtType::Expression { expression: Expr { line: 1, column: 24, expr_type: ExprType::Assignment {
+                target: Box::new(Expr { line: 1, column: 16, expr_type: ExprType::Variable { name: String::from("x") }}),
+                value: Box::new(Expr { line: 1, column: 24, expr_type: ExprType::Binary {
+                    left: Box::new(Expr { line: 1, column: 20, expr_type: ExprType::Variable { name: String::from("x") }}),
+                    operator: token::Token { type_: token::TokenType::Plus, lexeme: String::from("+"), literal: token::Literal::Null, line: 1, column: 22 },
+                    right: Box::new(Expr { line: 1, column: 24, expr_type: ExprType::Literal { value: token::Literal::Number(1.0) }}),
                 }}),
-            }},
-        ]), parse(source));
+            }}}})),
+            body: Box::new(Stmt { line: 1, column: 37, stmt_type: StmtType::Block {
+                body: vec![
+                    Stmt { line: 1, column: 28, stmt_type: StmtType::VarDecl {
+                        name: String::from("y"),
+                        value: Expr { line: 1, column: 36, expr_type: ExprType::Variable { name: String::from("x") }},
+                    }},
+                ],
+            }}),
+        }}]), parse(source));
     }
     
     #[test]
     fn for_no_cond() {
         let source = "for (var x = 5;; x = x + 1) {var y = x}";
-        assert_eq!(Ok(vec![Stmt { line: 1, stmt_type: StmtType::Block {
-            body: vec![
-                Stmt { line: 1, stmt_type: StmtType::VarDecl {
-                    name: String::from("x"),
-                    value: Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(5.0) }},
-                }},
-                Stmt { line: 1, stmt_type: StmtType::While {
-                    condition: Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Bool(true) }},
-                    body: Box::new(Stmt { line: 1, stmt_type: StmtType::Block {
-                        body: vec![
-                            Stmt { line: 1, stmt_type: StmtType::Block {
-                                body: vec![
-                                    Stmt { line: 1, stmt_type: StmtType::VarDecl {
-                                        name: String::from("y"),
-                                        value: Expr { line: 1, expr_type: ExprType::Variable { name: String::from("x") }},
-                                    }},
-                                ],
-                            }},
-                            Stmt { line: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, expr_type: ExprType::Assignment {
-                                target: Box::new(Expr { line: 1, expr_type: ExprType::Variable { name: String::from("x") }}),
-                                value: Box::new(Expr { line: 1, expr_type: ExprType::Binary {
-                                    left: Box::new(Expr { line: 1, expr_type: ExprType::Variable { name: String::from("x") }}),
-                                    operator: token::Token { type_: token::TokenType::Plus, lexeme: String::from("+"), literal: token::Literal::Null, line: 1 },
-                                    right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(1.0) }}),
-                                }}),
-                            }}}},
-                        ],
-                    }}),
-                }},
-            ]
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::For {
+            initialiser: Some(Box::new(Stmt { line: 1, column: 6, stmt_type: StmtType::VarDecl {
+                name: String::from("x"),
+                value: Expr { line: 1, column: 14, expr_type: ExprType::Literal { value: token::Literal::Number(5.0) }},
+            }})),
+            condition: None,
+            increment: Some(Box::new(Stmt { line: 1, column: 18, stmt_type: StmtType::Expression { expression: Expr { line: 1, column: 26, expr_type: ExprType::Assignment {
+                target: Box::new(Expr { line: 1, column: 18, expr_type: ExprType::Variable { name: String::from("x") }}),
+                value: Box::new(Expr { line: 1, column: 26, expr_type: ExprType::Binary {
+                    left: Box::new(Expr { line: 1, column: 22, expr_type: ExprType::Variable { name: String::from("x") }}),
+                    operator: token::Token { type_: token::TokenType::Plus, lexeme: String::from("+"), literal: token::Literal::Null, line: 1, column: 24 },
+                    right: Box::new(Expr { line: 1, column: 26, expr_type: ExprType::Literal { value: token::Literal::Number(1.0) }}),
+                }}),
+            }}}})),
+            body: Box::new(Stmt { line: 1, column: 39, stmt_type: StmtType::Block {
+                body: vec![
+                    Stmt { line: 1, column: 30, stmt_type: StmtType::VarDecl {
+                        name: String::from("y"),
+                        value: Expr { line: 1, column: 38, expr_type: ExprType::Variable { name: String::from("x") }},
+                    }},
+                ],
+            }}),
         }}]), parse(source));
     }
     
     #[test]
     fn for_no_inc() {
         let source = "for (var x = 5; x < 10;) {var y = x}";
-        assert_eq!(Ok(vec![Stmt { line: 1, stmt_type: StmtType::Block {
-            body: vec![
-                Stmt { line: 1, stmt_type: StmtType::VarDecl {
-                    name: String::from("x"),
-                    value: Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(5.0) }},
-                }},
-                Stmt { line: 1, stmt_type: StmtType::While {
-                    condition: Expr { line: 1, expr_type: ExprType::Binary {
-                        left: Box::new(Expr { line: 1, expr_type: ExprType::Variable { name: String::from("x") }}),
-                        operator: token::Token { type_: token::TokenType::Less, lexeme: String::from("<"), literal: token::Literal::Null, line: 1 },
-                        right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(10.0) }}),
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::For {
+            initialiser: Some(Box::new(Stmt { line: 1, column: 6, stmt_type: StmtType::VarDecl {
+                name: String::from("x"),
+                value: Expr { line: 1, column: 14, expr_type: ExprType::Literal { value: token::Literal::Number(5.0) }},
+            }})),
+            condition: Some(Expr { line: 1, column: 21, expr_type: ExprType::Binary {
+                left: Box::new(Expr { line: 1, column: 17, expr_type: ExprType::Variable { name: String::from("x") }}),
+                operator: token::Token { type_: token::TokenType::Less, lexeme: String::from("<"), literal: token::Literal::Null, line: 1, column: 19 },
+                right: Box::new(Expr { line: 1, column: 21, expr_type: ExprType::Literal { value: token::Literal::Number(10.0) }}),
+            }}),
+            increment: None,
+            body: Box::new(Stmt { line: 1, column: 36, stmt_type: StmtType::Block {
+                body: vec![
+                    Stmt { line: 1, column: 27, stmt_type: StmtType::VarDecl {
+                        name: String::from("y"),
+                        value: Expr { line: 1, column: 35, expr_type: ExprType::Variable { name: String::from("x") }},
                     }},
-                    body: Box::new(Stmt { line: 1, stmt_type: StmtType::Block {
-                        body: vec![
-                            Stmt { line: 1, stmt_type: StmtType::Block {
-                                body: vec![
-                                    Stmt { line: 1, stmt_type: StmtType::VarDecl {
-                                        name: String::from("y"),
-                                        value: Expr { line: 1, expr_type: ExprType::Variable { name: String::from("x") }},
-                                    }},
-                                ],
-                            }},
-                        ],
-                    }}),
-                }},
-            ]
+                ],
+            }}),
         }}]), parse(source));
     }
     
     #[test]
     fn for_no_init_semicolon() {
         let source = "for (var x = 5 x < 10; x = x + 1) {var y = x}";
-        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedSemicolonAfterInit { line: 1 }]));
+        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedSemicolonAfterInit { line: 1, column: 14 }]));
     }
     
     #[test]
     fn for_no_cond_semicolon() {
         let source = "for (var x = 5; x < 10 x = x + 1) {var y = x}";
-        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedSemicolonAfterCondition { line: 1 }]));
+        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedSemicolonAfterCondition { line: 1, column: 21 }]));
     }
     
     #[test]
     fn unclosed_for() {
         let source = "for (var x = 5; x < 10; x = x + 1 {var y = x}";
-        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedParenAfterIncrement { line: 1 }]));
+        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedParenAfterIncrement { line: 1, column: 33 }]));
     }
 
     #[test]
     fn unopened_block() {
         let source = "for (var x = 5; x < 10; x = x + 1) var y = x}";
-        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedCharacter { expected: '{', line: 1 }]));
+        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedCharacter { expected: '{', line: 1, column: 34 }]));
     }
 
     #[test]
     fn unclosed_block() {
         let source = "for (var x = 5; x < 10; x = x + 1) {var y = x";
-        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedCharacter { expected: '}', line: 1 }]));
+        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedCharacter { expected: '}', line: 1, column: 45 }]));
     }
     
     #[test]
     fn func() {
         let source = "func hello(a, b) {print a print b}";
-        assert_eq!(Ok(vec![Stmt { line: 1, stmt_type: StmtType::Function {
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::Function {
             name: String::from("hello"),
             parameters: vec![String::from("a"), String::from("b")],
-            body: Box::new(Stmt { line: 1, stmt_type: StmtType::Block { body: vec![
-                Stmt { line: 1, stmt_type: StmtType::Print { expression: Expr { line: 1, expr_type: ExprType::Variable { name: String::from("a") }}}},
-                Stmt { line: 1, stmt_type: StmtType::Print { expression: Expr { line: 1, expr_type: ExprType::Variable { name: String::from("b") }}}},
+            body: Box::new(Stmt { line: 1, column: 34, stmt_type: StmtType::Block { body: vec![
+                Stmt { line: 1, column: 19, stmt_type: StmtType::Print { expression: Expr { line: 1, column: 25, expr_type: ExprType::Variable { name: String::from("a") }}}},
+                Stmt { line: 1, column: 27, stmt_type: StmtType::Print { expression: Expr { line: 1, column: 33, expr_type: ExprType::Variable { name: String::from("b") }}}},
             ]}}),
+            doc: None,
         }}]), parse(source));
     }
 
     #[test]
     fn func_keyword_name() {
         let source = "func print(a, b) {print a print b}";
-        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedFunctionName { line: 1 }]));
+        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedFunctionName { line: 1, column: 1 }]));
     }
 
     #[test]
     fn if_() {
         let source = "if (a == 2) {print a}";
-        assert_eq!(Ok(vec![Stmt { line: 1, stmt_type: StmtType::If {
-            condition: Expr { line: 1, expr_type: ExprType::Binary {
-                left: Box::new(Expr { line: 1, expr_type: ExprType::Variable { name: String::from("a") }}),
-                operator: token::Token { type_: token::TokenType::EqualEqual, lexeme: String::from("=="), literal: token::Literal::Null, line: 1 },
-                right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(2.0) }}),
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::If {
+            condition: Expr { line: 1, column: 10, expr_type: ExprType::Binary {
+                left: Box::new(Expr { line: 1, column: 5, expr_type: ExprType::Variable { name: String::from("a") }}),
+                operator: token::Token { type_: token::TokenType::EqualEqual, lexeme: String::from("=="), literal: token::Literal::Null, line: 1, column: 7 },
+                right: Box::new(Expr { line: 1, column: 10, expr_type: ExprType::Literal { value: token::Literal::Number(2.0) }}),
             }},
-            then_body: Box::new(Stmt { line: 1, stmt_type: StmtType::Block { body: vec![Stmt { line: 1, stmt_type: StmtType::Print { expression: Expr { line: 1, expr_type: ExprType::Variable { name: String::from("a") } }}}] }}),
+            then_body: Box::new(Stmt { line: 1, column: 21, stmt_type: StmtType::Block { body: vec![Stmt { line: 1, column: 14, stmt_type: StmtType::Print { expression: Expr { line: 1, column: 20, expr_type: ExprType::Variable { name: String::from("a") } }}}] }}),
             else_body: None,
         }}]), parse(source));
     }
@@ -1036,29 +1397,29 @@ mod tests {
     #[test]
     fn else_if() {
         let source = "if (a == 2) {print a} else if (a == 3) {print b} else if (a == 4) {print c}";
-        assert_eq!(Ok(vec![Stmt { line: 1, stmt_type: StmtType::If {
-            condition: Expr { line: 1, expr_type: ExprType::Binary {
-                left: Box::new(Expr { line: 1, expr_type: ExprType::Variable { name: String::from("a") }}),
-                operator: token::Token { type_: token::TokenType::EqualEqual, lexeme: String::from("=="), literal: token::Literal::Null, line: 1 },
-                right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(2.0) }}),
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::If {
+            condition: Expr { line: 1, column: 10, expr_type: ExprType::Binary {
+                left: Box::new(Expr { line: 1, column: 5, expr_type: ExprType::Variable { name: String::from("a") }}),
+                operator: token::Token { type_: token::TokenType::EqualEqual, lexeme: String::from("=="), literal: token::Literal::Null, line: 1, column: 7 },
+                right: Box::new(Expr { line: 1, column: 10, expr_type: ExprType::Literal { value: token::Literal::Number(2.0) }}),
             }},
-            then_body: Box::new(Stmt { line: 1, stmt_type: StmtType::Block { body: vec![Stmt { line: 1, stmt_type: StmtType::Print { expression: Expr { line: 1, expr_type: ExprType::Variable { name: String::from("a") } }}}] }}),
+            then_body: Box::new(Stmt { line: 1, column: 21, stmt_type: StmtType::Block { body: vec![Stmt { line: 1, column: 14, stmt_type: StmtType::Print { expression: Expr { line: 1, column: 20, expr_type: ExprType::Variable { name: String::from("a") } }}}] }}),
             else_body: Some(Box::new(
-                Stmt { line: 1, stmt_type: StmtType::If {
-                    condition: Expr { line: 1, expr_type: ExprType::Binary {
-                        left: Box::new(Expr { line: 1, expr_type: ExprType::Variable { name: String::from("a") }}),
-                        operator: token::Token { type_: token::TokenType::EqualEqual, lexeme: String::from("=="), literal: token::Literal::Null, line: 1 },
-                        right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(3.0) }}),
+                Stmt { line: 1, column: 75, stmt_type: StmtType::If {
+                    condition: Expr { line: 1, column: 37, expr_type: ExprType::Binary {
+                        left: Box::new(Expr { line: 1, column: 32, expr_type: ExprType::Variable { name: String::from("a") }}),
+                        operator: token::Token { type_: token::TokenType::EqualEqual, lexeme: String::from("=="), literal: token::Literal::Null, line: 1, column: 34 },
+                        right: Box::new(Expr { line: 1, column: 37, expr_type: ExprType::Literal { value: token::Literal::Number(3.0) }}),
                     }},
-                    then_body: Box::new(Stmt { line: 1, stmt_type: StmtType::Block { body: vec![Stmt { line: 1, stmt_type: StmtType::Print { expression: Expr { line: 1, expr_type: ExprType::Variable { name: String::from("b") } }}}]} }),
+                    then_body: Box::new(Stmt { line: 1, column: 48, stmt_type: StmtType::Block { body: vec![Stmt { line: 1, column: 41, stmt_type: StmtType::Print { expression: Expr { line: 1, column: 47, expr_type: ExprType::Variable { name: String::from("b") } }}}]} }),
                     else_body: Some(Box::new(
-                        Stmt { line: 1, stmt_type: StmtType::If {
-                            condition: Expr { line: 1, expr_type: ExprType::Binary {
-                                left: Box::new(Expr { line: 1, expr_type: ExprType::Variable { name: String::from("a") }}),
-                                operator: token::Token { type_: token::TokenType::EqualEqual, lexeme: String::from("=="), literal: token::Literal::Null, line: 1 },
-                                right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(4.0) }}),
+                        Stmt { line: 1, column: 75, stmt_type: StmtType::If {
+                            condition: Expr { line: 1, column: 64, expr_type: ExprType::Binary {
+                                left: Box::new(Expr { line: 1, column: 59, expr_type: ExprType::Variable { name: String::from("a") }}),
+                                operator: token::Token { type_: token::TokenType::EqualEqual, lexeme: String::from("=="), literal: token::Literal::Null, line: 1, column: 61 },
+                                right: Box::new(Expr { line: 1, column: 64, expr_type: ExprType::Literal { value: token::Literal::Number(4.0) }}),
                             }},
-                            then_body: Box::new(Stmt { line: 1, stmt_type: StmtType::Block { body: vec![Stmt { line: 1, stmt_type: StmtType::Print { expression: Expr { line: 1, expr_type: ExprType::Variable { name: String::from("c") } }}}]} }),
+                            then_body: Box::new(Stmt { line: 1, column: 75, stmt_type: StmtType::Block { body: vec![Stmt { line: 1, column: 68, stmt_type: StmtType::Print { expression: Expr { line: 1, column: 74, expr_type: ExprType::Variable { name: String::from("c") } }}}]} }),
                             else_body: None,
                         }}
                     )),
@@ -1070,38 +1431,38 @@ mod tests {
     #[test]
     fn else_() {
         let source = "if (a == 2) {print a} else {print b}";
-        assert_eq!(Ok(vec![Stmt { line: 1, stmt_type: StmtType::If {
-            condition: Expr { line: 1, expr_type: ExprType::Binary {
-                left: Box::new(Expr { line: 1, expr_type: ExprType::Variable { name: String::from("a") }}),
-                operator: token::Token { type_: token::TokenType::EqualEqual, lexeme: String::from("=="), literal: token::Literal::Null, line: 1 },
-                right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(2.0) }}),
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::If {
+            condition: Expr { line: 1, column: 10, expr_type: ExprType::Binary {
+                left: Box::new(Expr { line: 1, column: 5, expr_type: ExprType::Variable { name: String::from("a") }}),
+                operator: token::Token { type_: token::TokenType::EqualEqual, lexeme: String::from("=="), literal: token::Literal::Null, line: 1, column: 7 },
+                right: Box::new(Expr { line: 1, column: 10, expr_type: ExprType::Literal { value: token::Literal::Number(2.0) }}),
             }},
-            then_body: Box::new(Stmt { line: 1, stmt_type: StmtType::Block { body: vec![Stmt { line: 1, stmt_type: StmtType::Print { expression: Expr { line: 1, expr_type: ExprType::Variable { name: String::from("a") } }}}]} }),
-            else_body: Some(Box::new(Stmt { line: 1, stmt_type: StmtType::Block { body: vec![Stmt { line: 1, stmt_type: StmtType::Print { expression: Expr { line: 1, expr_type: ExprType::Variable { name: String::from("b") } }}}]} })),
+            then_body: Box::new(Stmt { line: 1, column: 21, stmt_type: StmtType::Block { body: vec![Stmt { line: 1, column: 14, stmt_type: StmtType::Print { expression: Expr { line: 1, column: 20, expr_type: ExprType::Variable { name: String::from("a") } }}}]} }),
+            else_body: Some(Box::new(Stmt { line: 1, column: 36, stmt_type: StmtType::Block { body: vec![Stmt { line: 1, column: 29, stmt_type: StmtType::Print { expression: Expr { line: 1, column: 35, expr_type: ExprType::Variable { name: String::from("b") } }}}]} })),
         }}]), parse(source));
     }
 
     #[test]
     fn print() {
         let source = "print 5*1+2*(3-4/a)";
-        assert_eq!(Ok(vec![Stmt { line: 1, stmt_type: StmtType::Print { expression: Expr { line: 1, expr_type: ExprType::Binary {
-            left: Box::new(Expr { line: 1, expr_type: ExprType::Binary {
-                left: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(5.0) }}),
-                operator: token::Token { type_: token::TokenType::Star, lexeme: String::from("*"), literal: token::Literal::Null, line: 1 },
-                right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(1.0) }}),
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::Print { expression: Expr { line: 1, column: 19, expr_type: ExprType::Binary {
+            left: Box::new(Expr { line: 1, column: 9, expr_type: ExprType::Binary {
+                left: Box::new(Expr { line: 1, column: 7, expr_type: ExprType::Literal { value: token::Literal::Number(5.0) }}),
+                operator: token::Token { type_: token::TokenType::Star, lexeme: String::from("*"), literal: token::Literal::Null, line: 1, column: 8 },
+                right: Box::new(Expr { line: 1, column: 9, expr_type: ExprType::Literal { value: token::Literal::Number(1.0) }}),
             }}),
-            operator: token::Token { type_: token::TokenType::Plus, lexeme: String::from("+"), literal: token::Literal::Null, line: 1 },
-            right: Box::new(Expr { line: 1, expr_type: ExprType::Binary {
-                left: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(2.0) }}),
-                operator: token::Token { type_: token::TokenType::Star, lexeme: String::from("*"), literal: token::Literal::Null, line: 1 },
-                right: Box::new(Expr { line: 1, expr_type: ExprType::Grouping {
-                    expression: Box::new(Expr { line: 1, expr_type: ExprType::Binary {
-                        left: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(3.0) }}),
-                        operator: token::Token { type_: token::TokenType::Minus, lexeme: String::from("-"), literal: token::Literal::Null, line: 1 },
-                        right: Box::new(Expr { line: 1, expr_type: ExprType::Binary {
-                            left: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(4.0) }}),
-                            operator: token::Token { type_: token::TokenType::Slash, lexeme: String::from("/"), literal: token::Literal::Null, line: 1 },
-                            right: Box::new(Expr { line: 1, expr_type: ExprType::Variable { name: String::from("a") }}),
+            operator: token::Token { type_: token::TokenType::Plus, lexeme: String::from("+"), literal: token::Literal::Null, line: 1, column: 10 },
+            right: Box::new(Expr { line: 1, column: 19, expr_type: ExprType::Binary {
+                left: Box::new(Expr { line: 1, column: 11, expr_type: ExprType::Literal { value: token::Literal::Number(2.0) }}),
+                operator: token::Token { type_: token::TokenType::Star, lexeme: String::from("*"), literal: token::Literal::Null, line: 1, column: 12 },
+                right: Box::new(Expr { line: 1, column: 19, expr_type: ExprType::Grouping {
+                    expression: Box::new(Expr { line: 1, column: 18, expr_type: ExprType::Binary {
+                        left: Box::new(Expr { line: 1, column: 14, expr_type: ExprType::Literal { value: token::Literal::Number(3.0) }}),
+                        operator: token::Token { type_: token::TokenType::Minus, lexeme: String::from("-"), literal: token::Literal::Null, line: 1, column: 15 },
+                        right: Box::new(Expr { line: 1, column: 18, expr_type: ExprType::Binary {
+                            left: Box::new(Expr { line: 1, column: 16, expr_type: ExprType::Literal { value: token::Literal::Number(4.0) }}),
+                            operator: token::Token { type_: token::TokenType::Slash, lexeme: String::from("/"), literal: token::Literal::Null, line: 1, column: 17 },
+                            right: Box::new(Expr { line: 1, column: 18, expr_type: ExprType::Variable { name: String::from("a") }}),
                         }}),
                     }}),
                 }}),
@@ -1112,67 +1473,127 @@ mod tests {
     #[test]
     fn var() {
         let source = "var a = 5";
-        assert_eq!(Ok(vec![Stmt { line: 1, stmt_type: StmtType::VarDecl { name: String::from("a"), value: Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(5.0) } }}}]), parse(source));
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::VarDecl { name: String::from("a"), value: Expr { line: 1, column: 9, expr_type: ExprType::Literal { value: token::Literal::Number(5.0) } }}}]), parse(source));
+    }
+
+    #[test]
+    fn tuples() {
+        let variable = |name: &str, column| Expr { line: 1, column, expr_type: ExprType::Variable { name: String::from(name) } };
+        let tuple = |elements, column| Expr { line: 1, column, expr_type: ExprType::Tuple { elements } };
+        let stmt_type = |source| parse(source).map(|ast| ast[0].stmt_type.clone());
+        assert_eq!(stmt_type("return a, b"), Ok(StmtType::Return { expression: tuple(vec![variable("a", 8), variable("b", 11)], 11) }));
+        assert_eq!(stmt_type("var q, r = f"), Ok(StmtType::VarUnpack { names: vec![String::from("q"), String::from("r")], value: variable("f", 12) }));
+        // The targets of a tuple assignment are not assignments themselves, so the Equal assigns to all of them.
+        assert_eq!(stmt_type("a, b = b, a"), Ok(StmtType::Expression { expression: Expr { line: 1, column: 11, expr_type: ExprType::Assignment {
+            target: Box::new(tuple(vec![variable("a", 1), variable("b", 4)], 4)),
+            value: Box::new(tuple(vec![variable("b", 8), variable("a", 11)], 11)),
+        }}}));
+        assert_eq!(parse("var a, = 1"), Err(vec![ErrorType::ExpectedVariableName { line: 1, column: 6 }]));
+        assert_eq!(parse("a = 1, 2"), Err(vec![ErrorType::ExpectedUnpackTargets { line: 1, column: 6 }]));
+        assert_eq!(parse("a = b = 1, 2\nprint a"), Err(vec![ErrorType::ExpectedUnpackTargets { line: 1, column: 10 }]));
+        assert_eq!(parse("var x = 1, 2"), Err(vec![ErrorType::ExpectedUnpackTargets { line: 1, column: 10 }]));
+        assert_eq!(parse("var a, a = 1, 2"), Err(vec![ErrorType::DuplicateUnpackTarget { name: String::from("a"), line: 1, column: 8 }]));
+        assert_eq!(parse("a, b, a = 1, 2, 3"), Err(vec![ErrorType::DuplicateUnpackTarget { name: String::from("a"), line: 1, column: 7 }]));
+        // Elements are not variables, so the same one can be given twice.
+        assert!(parse("a[0], a[0] = 1, 2").is_ok());
     }
 
     #[test]
     fn invalid_var_name() {
         let source = "var 123 = 5";
-        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedVariableName { line: 1 }]));
+        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedVariableName { line: 1, column: 1 }]));
     }
 
     #[test]
     fn while_() {
         let source = "while (a == 2) {print b}";
-        assert_eq!(Ok(vec![Stmt { line: 1, stmt_type: StmtType::While {
-            condition: Expr { line: 1, expr_type: ExprType::Binary {
-                left: Box::new(Expr { line: 1, expr_type: ExprType::Variable { name: String::from("a") }}),
-                operator: token::Token { type_: token::TokenType::EqualEqual, lexeme: String::from("=="), literal: token::Literal::Null, line: 1 },
-                right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(2.0) }}),
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::While {
+            condition: Expr { line: 1, column: 13, expr_type: ExprType::Binary {
+                left: Box::new(Expr { line: 1, column: 8, expr_type: ExprType::Variable { name: String::from("a") }}),
+                operator: token::Token { type_: token::TokenType::EqualEqual, lexeme: String::from("=="), literal: token::Literal::Null, line: 1, column: 10 },
+                right: Box::new(Expr { line: 1, column: 13, expr_type: ExprType::Literal { value: token::Literal::Number(2.0) }}),
             }},
-            body: Box::new(Stmt { line: 1, stmt_type: StmtType::Block { body: vec![Stmt { line: 1, stmt_type: StmtType::Print { expression: Expr { line: 1, expr_type: ExprType::Variable { name: String::from("b") } }}}]} }),
+            body: Box::new(Stmt { line: 1, column: 24, stmt_type: StmtType::Block { body: vec![Stmt { line: 1, column: 17, stmt_type: StmtType::Print { expression: Expr { line: 1, column: 23, expr_type: ExprType::Variable { name: String::from("b") } }}}]} }),
         }}]), parse(source));
     }
 
+    #[test]
+    fn repeat() {
+        // The count needs no brackets, and the expression stops at the `{` of the body.
+        let source = "repeat n {}";
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::Repeat {
+            count: Expr { line: 1, column: 8, expr_type: ExprType::Variable { name: String::from("n") }},
+            body: Box::new(Stmt { line: 1, column: 11, stmt_type: StmtType::Block { body: vec![] } }),
+        }}]), parse(source));
+        assert!(parse("repeat {}").is_err());
+        assert!(parse("repeat 3 print 1").is_err());
+    }
+
+    #[test]
+    fn parse_expression() {
+        let tokens = |source| Tokenizer::new(source).tokenize().unwrap();
+        assert_eq!(Parser::new(tokens("x")).parse_expression(), Ok(Expr { line: 1, column: 1, expr_type: ExprType::Variable { name: String::from("x") } }));
+        assert_eq!(Parser::new(tokens("1 + 2 3")).parse_expression(), Err(ErrorType::ExpectedEndOfExpression { line: 1, column: 7 }));
+        assert_eq!(Parser::new(tokens("var x = 1")).parse_expression(), Err(ErrorType::ExpectedExpression { line: 1, column: 1 }));
+        assert!(Parser::new(tokens("")).parse_expression().is_err());
+    }
+
+    #[test]
+    fn try_() {
+        let source = "try {} catch (e) {} finally {}";
+        let block = |column| Box::new(Stmt { line: 1, column, stmt_type: StmtType::Block { body: vec![] } });
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::Try {
+            body: block(6),
+            catch: Some((String::from("e"), block(19))),
+            finally: Some(block(30)),
+        }}]), parse(source));
+
+        // Either of `catch` and `finally` can be left out, but not both.
+        assert!(matches!(parse("try {} finally {}").as_deref(), Ok([Stmt { stmt_type: StmtType::Try { catch: None, finally: Some(_), .. }, .. }])));
+        assert_eq!(parse("try {}"), Err(vec![ErrorType::ExpectedCatchOrFinally { line: 1, column: 6 }]));
+        assert_eq!(parse("try {} catch () {}"), Err(vec![ErrorType::ExpectedVariableName { line: 1, column: 14 }]));
+        assert!(parse("try {} finally {} catch (e) {}").is_err());
+    }
+
     #[test]
     fn multiple_statements() {
         let source = "print a if (a == 2) {print a} else {print b} var c = 3";
         assert_eq!(Ok(vec![
-            Stmt { line: 1, stmt_type: StmtType::Print { expression: Expr { line: 1, expr_type: ExprType::Variable { name: String::from("a") } } } },
-            Stmt { line: 1, stmt_type: StmtType::If {
-                condition: Expr { line: 1, expr_type: ExprType::Binary {
-                    left: Box::new(Expr { line: 1, expr_type: ExprType::Variable { name: String::from("a") }}),
-                    operator: token::Token { type_: token::TokenType::EqualEqual, lexeme: String::from("=="), literal: token::Literal::Null, line: 1 },
-                    right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(2.0) }}),
+            Stmt { line: 1, column: 1, stmt_type: StmtType::Print { expression: Expr { line: 1, column: 7, expr_type: ExprType::Variable { name: String::from("a") } } } },
+            Stmt { line: 1, column: 9, stmt_type: StmtType::If {
+                condition: Expr { line: 1, column: 18, expr_type: ExprType::Binary {
+                    left: Box::new(Expr { line: 1, column: 13, expr_type: ExprType::Variable { name: String::from("a") }}),
+                    operator: token::Token { type_: token::TokenType::EqualEqual, lexeme: String::from("=="), literal: token::Literal::Null, line: 1, column: 15 },
+                    right: Box::new(Expr { line: 1, column: 18, expr_type: ExprType::Literal { value: token::Literal::Number(2.0) }}),
                 }},
-                then_body: Box::new(Stmt { line: 1, stmt_type: StmtType::Block { body: vec![Stmt { line: 1, stmt_type: StmtType::Print { expression: Expr { line: 1, expr_type: ExprType::Variable { name: String::from("a") } }}}]} }),
-                else_body: Some(Box::new(Stmt { line: 1, stmt_type: StmtType::Block { body: vec![Stmt { line: 1, stmt_type: StmtType::Print { expression: Expr { line: 1, expr_type: ExprType::Variable { name: String::from("b") } }}}]} })),
+                then_body: Box::new(Stmt { line: 1, column: 29, stmt_type: StmtType::Block { body: vec![Stmt { line: 1, column: 22, stmt_type: StmtType::Print { expression: Expr { line: 1, column: 28, expr_type: ExprType::Variable { name: String::from("a") } }}}]} }),
+                else_body: Some(Box::new(Stmt { line: 1, column: 44, stmt_type: StmtType::Block { body: vec![Stmt { line: 1, column: 37, stmt_type: StmtType::Print { expression: Expr { line: 1, column: 43, expr_type: ExprType::Variable { name: String::from("b") } }}}]} })),
             }},
-            Stmt { line: 1, stmt_type: StmtType::VarDecl { name: String::from("c"), value: Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(3.0) } } } },
+            Stmt { line: 1, column: 46, stmt_type: StmtType::VarDecl { name: String::from("c"), value: Expr { line: 1, column: 54, expr_type: ExprType::Literal { value: token::Literal::Number(3.0) } } } },
         ]), parse(source));
     }
 
     #[test]
     fn bidmas() {
         let source = "5*1+2*(3-4/a)";
-        assert_eq!(Ok(vec![Stmt { line: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, expr_type: ExprType::Binary {
-            left: Box::new(Expr { line: 1, expr_type: ExprType::Binary {
-                left: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(5.0) }}),
-                operator: token::Token { type_: token::TokenType::Star, lexeme: String::from("*"), literal: token::Literal::Null, line: 1 },
-                right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(1.0) }}),
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, column: 13, expr_type: ExprType::Binary {
+            left: Box::new(Expr { line: 1, column: 3, expr_type: ExprType::Binary {
+                left: Box::new(Expr { line: 1, column: 1, expr_type: ExprType::Literal { value: token::Literal::Number(5.0) }}),
+                operator: token::Token { type_: token::TokenType::Star, lexeme: String::from("*"), literal: token::Literal::Null, line: 1, column: 2 },
+                right: Box::new(Expr { line: 1, column: 3, expr_type: ExprType::Literal { value: token::Literal::Number(1.0) }}),
             }}),
-            operator: token::Token { type_: token::TokenType::Plus, lexeme: String::from("+"), literal: token::Literal::Null, line: 1 },
-            right: Box::new(Expr { line: 1, expr_type: ExprType::Binary {
-                left: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(2.0) }}),
-                operator: token::Token { type_: token::TokenType::Star, lexeme: String::from("*"), literal: token::Literal::Null, line: 1 },
-                right: Box::new(Expr { line: 1, expr_type: ExprType::Grouping {
-                    expression: Box::new(Expr { line: 1, expr_type: ExprType::Binary {
-                        left: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(3.0) }}),
-                        operator: token::Token { type_: token::TokenType::Minus, lexeme: String::from("-"), literal: token::Literal::Null, line: 1 },
-                        right: Box::new(Expr { line: 1, expr_type: ExprType::Binary {
-                            left: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(4.0) }}),
-                            operator: token::Token { type_: token::TokenType::Slash, lexeme: String::from("/"), literal: token::Literal::Null, line: 1 },
-                            right: Box::new(Expr { line: 1, expr_type: ExprType::Variable { name: String::from("a") }}),
+            operator: token::Token { type_: token::TokenType::Plus, lexeme: String::from("+"), literal: token::Literal::Null, line: 1, column: 4 },
+            right: Box::new(Expr { line: 1, column: 13, expr_type: ExprType::Binary {
+                left: Box::new(Expr { line: 1, column: 5, expr_type: ExprType::Literal { value: token::Literal::Number(2.0) }}),
+                operator: token::Token { type_: token::TokenType::Star, lexeme: String::from("*"), literal: token::Literal::Null, line: 1, column: 6 },
+                right: Box::new(Expr { line: 1, column: 13, expr_type: ExprType::Grouping {
+                    expression: Box::new(Expr { line: 1, column: 12, expr_type: ExprType::Binary {
+                        left: Box::new(Expr { line: 1, column: 8, expr_type: ExprType::Literal { value: token::Literal::Number(3.0) }}),
+                        operator: token::Token { type_: token::TokenType::Minus, lexeme: String::from("-"), literal: token::Literal::Null, line: 1, column: 9 },
+                        right: Box::new(Expr { line: 1, column: 12, expr_type: ExprType::Binary {
+                            left: Box::new(Expr { line: 1, column: 10, expr_type: ExprType::Literal { value: token::Literal::Number(4.0) }}),
+                            operator: token::Token { type_: token::TokenType::Slash, lexeme: String::from("/"), literal: token::Literal::Null, line: 1, column: 11 },
+                            right: Box::new(Expr { line: 1, column: 12, expr_type: ExprType::Variable { name: String::from("a") }}),
                     }}),
                     }}),
                 }}),
@@ -1183,43 +1604,43 @@ mod tests {
     #[test]
     fn logic() {
         let source = "true and true or false and true or false";
-        assert_eq!(Ok(vec![Stmt { line: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, expr_type: ExprType::Binary {
-            left: Box::new(Expr { line: 1, expr_type: ExprType::Binary {
-                left: Box::new(Expr { line: 1, expr_type: ExprType::Binary {
-                    left: Box::new(Expr { line: 1, expr_type: ExprType::Literal {value: token::Literal::Bool(true) }}),
-                    operator: token::Token { type_: token::TokenType::And, lexeme: String::from("and"), literal: token::Literal::Null, line: 1 },
-                    right: Box::new(Expr { line: 1, expr_type: ExprType::Literal {value: token::Literal::Bool(true) }}),
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, column: 36, expr_type: ExprType::Binary {
+            left: Box::new(Expr { line: 1, column: 28, expr_type: ExprType::Binary {
+                left: Box::new(Expr { line: 1, column: 10, expr_type: ExprType::Binary {
+                    left: Box::new(Expr { line: 1, column: 1, expr_type: ExprType::Literal {value: token::Literal::Bool(true) }}),
+                    operator: token::Token { type_: token::TokenType::And, lexeme: String::from("and"), literal: token::Literal::Null, line: 1, column: 6 },
+                    right: Box::new(Expr { line: 1, column: 10, expr_type: ExprType::Literal {value: token::Literal::Bool(true) }}),
                 }}),
-                operator: token::Token { type_: token::TokenType::Or, lexeme: String::from("or"), literal: token::Literal::Null, line: 1 },
-                right: Box::new(Expr { line: 1, expr_type: ExprType::Binary {
-                    left: Box::new(Expr { line: 1, expr_type: ExprType::Literal {value: token::Literal::Bool(false) }}),
-                    operator: token::Token { type_: token::TokenType::And, lexeme: String::from("and"), literal: token::Literal::Null, line: 1 },
-                    right: Box::new(Expr { line: 1, expr_type: ExprType::Literal {value: token::Literal::Bool(true) }}),
+                operator: token::Token { type_: token::TokenType::Or, lexeme: String::from("or"), literal: token::Literal::Null, line: 1, column: 15 },
+                right: Box::new(Expr { line: 1, column: 28, expr_type: ExprType::Binary {
+                    left: Box::new(Expr { line: 1, column: 18, expr_type: ExprType::Literal {value: token::Literal::Bool(false) }}),
+                    operator: token::Token { type_: token::TokenType::And, lexeme: String::from("and"), literal: token::Literal::Null, line: 1, column: 24 },
+                    right: Box::new(Expr { line: 1, column: 28, expr_type: ExprType::Literal {value: token::Literal::Bool(true) }}),
                 }}),
             }}),
-            operator: token::Token { type_: token::TokenType::Or, lexeme: String::from("or"), literal: token::Literal::Null, line: 1 },
-            right: Box::new(Expr { line: 1, expr_type: ExprType::Literal {value: token::Literal::Bool(false) }}),
+            operator: token::Token { type_: token::TokenType::Or, lexeme: String::from("or"), literal: token::Literal::Null, line: 1, column: 33 },
+            right: Box::new(Expr { line: 1, column: 36, expr_type: ExprType::Literal {value: token::Literal::Bool(false) }}),
         }}}}]), parse(source));
     }
 
     #[test]
     fn array() {
         let source = "[[5, a, b], 3+1, \"g\"]";
-        assert_eq!(Ok(vec![Stmt { line: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, expr_type: ExprType::Array {
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, column: 21, expr_type: ExprType::Array {
             elements: vec![
-                Expr { line: 1, expr_type: ExprType::Array {
+                Expr { line: 1, column: 10, expr_type: ExprType::Array {
                     elements: vec![
-                        Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(5.0) }},
-                        Expr { line: 1, expr_type: ExprType::Variable { name: String::from("a") }},
-                        Expr { line: 1, expr_type: ExprType::Variable { name: String::from("b") }},
+                        Expr { line: 1, column: 3, expr_type: ExprType::Literal { value: token::Literal::Number(5.0) }},
+                        Expr { line: 1, column: 6, expr_type: ExprType::Variable { name: String::from("a") }},
+                        Expr { line: 1, column: 9, expr_type: ExprType::Variable { name: String::from("b") }},
                     ]
                 }},
-                Expr { line: 1, expr_type: ExprType::Binary {
-                    left: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(3.0) }}),
-                    operator: token::Token { type_: token::TokenType::Plus, lexeme: String::from("+"), literal: token::Literal::Null, line: 1 },
-                    right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(1.0) }}),
+                Expr { line: 1, column: 15, expr_type: ExprType::Binary {
+                    left: Box::new(Expr { line: 1, column: 13, expr_type: ExprType::Literal { value: token::Literal::Number(3.0) }}),
+                    operator: token::Token { type_: token::TokenType::Plus, lexeme: String::from("+"), literal: token::Literal::Null, line: 1, column: 14 },
+                    right: Box::new(Expr { line: 1, column: 15, expr_type: ExprType::Literal { value: token::Literal::Number(1.0) }}),
                 }},
-                Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::String_(String::from("g")) }},
+                Expr { line: 1, column: 18, expr_type: ExprType::Literal { value: token::Literal::String_(String::from("g")) }},
             ]
         }}}}]), parse(source));
     }
@@ -1227,133 +1648,171 @@ mod tests {
     #[test]
     fn empty_array() {
         let source = "[]";
-        assert_eq!(Ok(vec![Stmt { line: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, expr_type: ExprType::Array {elements: vec![] }}}}]), parse(source));
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, column: 2, expr_type: ExprType::Array {elements: vec![] }}}}]), parse(source));
     }
 
     #[test]
     fn unclosed_array() {
         let source = "[[5, a, b], 3+1, \"g\"";
-        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedCharacter { expected: ']', line: 1 }]));
+        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedCharacter { expected: ']', line: 1, column: 18 }]));
         let source = "[[5, a, b, 3+1, \"g\"]";
-        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedCharacter { expected: ']', line: 1 }]));
+        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedCharacter { expected: ']', line: 1, column: 20 }]));
     }
     
     #[test]
     fn error_line_numbers() {
         let source = "\n[[5, a, b, 3+1, \"g\"]";
-        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedCharacter { expected: ']', line: 2 }]));
+        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedCharacter { expected: ']', line: 2, column: 20 }]));
         let source = "\n\n[[5, a, b, 3+1, \"g\"]";
-        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedCharacter { expected: ']', line: 3 }]));
+        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedCharacter { expected: ']', line: 3, column: 20 }]));
     }
 
     #[test]
     fn unclosed_grouping() {
         let source = "(5 + 5";
-        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedCharacter { expected: ')', line: 1 }]));
+        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedCharacter { expected: ')', line: 1, column: 6 }]));
     }
 
     #[test]
     fn element() {
         let source = "a[5]";
-        assert_eq!(Ok(vec![Stmt { line: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, expr_type: ExprType::Element {
-            array: Box::new(Expr { line: 1, expr_type: ExprType::Variable { name: String::from("a") }}),
-            index: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(5.0) } }),
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, column: 3, expr_type: ExprType::Element {
+            array: Box::new(Expr { line: 1, column: 1, expr_type: ExprType::Variable { name: String::from("a") }}),
+            index: Box::new(Expr { line: 1, column: 3, expr_type: ExprType::Literal { value: token::Literal::Number(5.0) } }),
         }}}}]), parse(source));
     }
     
     #[test]
     fn element_2d() {
         let source = "a[1][2]";
-        assert_eq!(Ok(vec![Stmt { line: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, expr_type: ExprType::Element {
-            array: Box::new(Expr { line: 1, expr_type: ExprType::Element {
-                array: Box::new(Expr { line: 1, expr_type: ExprType::Variable { name: String::from("a") }}),
-                index: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(1.0) } }),
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, column: 6, expr_type: ExprType::Element {
+            array: Box::new(Expr { line: 1, column: 3, expr_type: ExprType::Element {
+                array: Box::new(Expr { line: 1, column: 1, expr_type: ExprType::Variable { name: String::from("a") }}),
+                index: Box::new(Expr { line: 1, column: 3, expr_type: ExprType::Literal { value: token::Literal::Number(1.0) } }),
             }}),
-            index: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(2.0) } }),
+            index: Box::new(Expr { line: 1, column: 6, expr_type: ExprType::Literal { value: token::Literal::Number(2.0) } }),
         }}}}]), parse(source));
     }
 
+    #[test]
+    fn slice() {
+        let variable = |column| Box::new(Expr { line: 1, column, expr_type: ExprType::Variable { name: String::from("a") } });
+        let number = |value, column| Some(Box::new(Expr { line: 1, column, expr_type: ExprType::Literal { value: token::Literal::Number(value) } }));
+        let expression = |source| match parse(source).as_deref() {
+            Ok([Stmt { stmt_type: StmtType::Expression { expression }, .. }]) => Some(expression.clone()),
+            _ => None,
+        };
+        assert_eq!(expression("a[1:2]"), Some(Expr { line: 1, column: 5, expr_type: ExprType::Slice {
+            array: variable(1), start: number(1.0, 3), stop: number(2.0, 5), step: None,
+        }}));
+        // Each part can be left out, as can the second colon.
+        assert_eq!(expression("a[::3]"), Some(Expr { line: 1, column: 5, expr_type: ExprType::Slice {
+            array: variable(1), start: None, stop: None, step: number(3.0, 5),
+        }}));
+        assert_eq!(expression("a[2:]"), Some(Expr { line: 1, column: 4, expr_type: ExprType::Slice {
+            array: variable(1), start: number(2.0, 3), stop: None, step: None,
+        }}));
+        assert_eq!(parse("a[]"), Err(vec![ErrorType::ExpectedExpression { line: 1, column: 2 }]));
+        assert_eq!(parse("a[1:2:3:4]"), Err(vec![ErrorType::ExpectedCharacter { expected: ']', line: 1, column: 7 }]));
+    }
+
     #[test]
     fn comparison() {
         let source = "1 < 2 == 3 > 4 <= 5 >= 6 != 7";
-        assert_eq!(Ok(vec![Stmt { line: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, expr_type: ExprType::Binary {
-            left: Box::new(Expr { line: 1, expr_type: ExprType::Binary {
-                left: Box::new(Expr { line: 1, expr_type: ExprType::Binary {
-                    left: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(1.0) }}),
-                    operator: token::Token { type_: token::TokenType::Less, lexeme: String::from("<"), literal: token::Literal::Null, line: 1 },
-                    right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(2.0) }}),
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, column: 29, expr_type: ExprType::Binary {
+            left: Box::new(Expr { line: 1, column: 24, expr_type: ExprType::Binary {
+                left: Box::new(Expr { line: 1, column: 5, expr_type: ExprType::Binary {
+                    left: Box::new(Expr { line: 1, column: 1, expr_type: ExprType::Literal { value: token::Literal::Number(1.0) }}),
+                    operator: token::Token { type_: token::TokenType::Less, lexeme: String::from("<"), literal: token::Literal::Null, line: 1, column: 3 },
+                    right: Box::new(Expr { line: 1, column: 5, expr_type: ExprType::Literal { value: token::Literal::Number(2.0) }}),
                 }}),
-                operator: token::Token { type_: token::TokenType::EqualEqual, lexeme: String::from("=="), literal: token::Literal::Null, line: 1 },
-                right: Box::new(Expr { line: 1, expr_type: ExprType::Binary {
-                    left: Box::new(Expr { line: 1, expr_type: ExprType::Binary {
-                        left: Box::new(Expr { line: 1, expr_type: ExprType::Binary {
-                            left: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(3.0) }}),
-                            operator: token::Token { type_: token::TokenType::Greater, lexeme: String::from(">"), literal: token::Literal::Null, line: 1 },
-                            right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(4.0) }}),
+                operator: token::Token { type_: token::TokenType::EqualEqual, lexeme: String::from("=="), literal: token::Literal::Null, line: 1, column: 7 },
+                right: Box::new(Expr { line: 1, column: 24, expr_type: ExprType::Binary {
+                    left: Box::new(Expr { line: 1, column: 19, expr_type: ExprType::Binary {
+                        left: Box::new(Expr { line: 1, column: 14, expr_type: ExprType::Binary {
+                            left: Box::new(Expr { line: 1, column: 10, expr_type: ExprType::Literal { value: token::Literal::Number(3.0) }}),
+                            operator: token::Token { type_: token::TokenType::Greater, lexeme: String::from(">"), literal: token::Literal::Null, line: 1, column: 12 },
+                            right: Box::new(Expr { line: 1, column: 14, expr_type: ExprType::Literal { value: token::Literal::Number(4.0) }}),
                         }}),
-                        operator: token::Token { type_: token::TokenType::LessEqual, lexeme: String::from("<="), literal: token::Literal::Null, line: 1 },
-                        right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(5.0) }}),
+                        operator: token::Token { type_: token::TokenType::LessEqual, lexeme: String::from("<="), literal: token::Literal::Null, line: 1, column: 16 },
+                        right: Box::new(Expr { line: 1, column: 19, expr_type: ExprType::Literal { value: token::Literal::Number(5.0) }}),
                     }}),
-                    operator: token::Token { type_: token::TokenType::GreaterEqual, lexeme: String::from(">="), literal: token::Literal::Null, line: 1 },
-                    right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(6.0) }}),
+                    operator: token::Token { type_: token::TokenType::GreaterEqual, lexeme: String::from(">="), literal: token::Literal::Null, line: 1, column: 21 },
+                    right: Box::new(Expr { line: 1, column: 24, expr_type: ExprType::Literal { value: token::Literal::Number(6.0) }}),
                 }}),
             }}),
-            operator: token::Token { type_: token::TokenType::BangEqual, lexeme: String::from("!="), literal: token::Literal::Null, line: 1 },
-            right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(7.0) }}),
+            operator: token::Token { type_: token::TokenType::BangEqual, lexeme: String::from("!="), literal: token::Literal::Null, line: 1, column: 26 },
+            right: Box::new(Expr { line: 1, column: 29, expr_type: ExprType::Literal { value: token::Literal::Number(7.0) }}),
         }}}}]), parse(source));
     }
 
     #[test]
     fn call() {
         let source = "a(1, \"a\")(bc, 2+3)";
-        assert_eq!(Ok(vec![Stmt { line: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, expr_type: ExprType::Call {
-            callee: Box::new(Expr { line: 1, expr_type: ExprType::Call {
-                callee: Box::new(Expr { line: 1, expr_type: ExprType::Variable { name: String::from("a") }}),
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, column: 18, expr_type: ExprType::Call {
+            callee: Box::new(Expr { line: 1, column: 9, expr_type: ExprType::Call {
+                callee: Box::new(Expr { line: 1, column: 1, expr_type: ExprType::Variable { name: String::from("a") }}),
                 arguments: vec![
-                    Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(1.0) }},
-                    Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::String_(String::from("a")) }}
+                    Expr { line: 1, column: 3, expr_type: ExprType::Literal { value: token::Literal::Number(1.0) }},
+                    Expr { line: 1, column: 6, expr_type: ExprType::Literal { value: token::Literal::String_(String::from("a")) }}
                 ],
+                named_arguments: vec![],
             }}),
             arguments: vec![
-                Expr { line: 1, expr_type: ExprType::Variable { name: String::from("bc") }},
-                Expr { line: 1, expr_type: ExprType::Binary {
-                    left: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(2.0) }}),
-                    operator: token::Token { type_: token::TokenType::Plus, lexeme: String::from("+"), literal: token::Literal::Null, line: 1 },
-                    right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(3.0) }}),
+                Expr { line: 1, column: 11, expr_type: ExprType::Variable { name: String::from("bc") }},
+                Expr { line: 1, column: 17, expr_type: ExprType::Binary {
+                    left: Box::new(Expr { line: 1, column: 15, expr_type: ExprType::Literal { value: token::Literal::Number(2.0) }}),
+                    operator: token::Token { type_: token::TokenType::Plus, lexeme: String::from("+"), literal: token::Literal::Null, line: 1, column: 16 },
+                    right: Box::new(Expr { line: 1, column: 17, expr_type: ExprType::Literal { value: token::Literal::Number(3.0) }}),
                 }}
             ],
+            named_arguments: vec![],
         }}}}]), parse(source));
     }
     
     #[test]
     fn empty_call() {
         let source = "a()";
-        assert_eq!(Ok(vec![Stmt { line: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, expr_type: ExprType::Call {
-            callee: Box::new(Expr { line: 1, expr_type: ExprType::Variable { name: String::from("a") }}),
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, column: 3, expr_type: ExprType::Call {
+            callee: Box::new(Expr { line: 1, column: 1, expr_type: ExprType::Variable { name: String::from("a") }}),
             arguments: vec![],
+            named_arguments: vec![],
         }}}}]), parse(source));
     }
+
+    #[test]
+    fn named_arguments() {
+        let number = |value, column| Expr { line: 1, column, expr_type: ExprType::Literal { value: token::Literal::Number(value) } };
+        assert_eq!(parse("f(1, b: 2, c: 3)"), Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, column: 16, expr_type: ExprType::Call {
+            callee: Box::new(Expr { line: 1, column: 1, expr_type: ExprType::Variable { name: String::from("f") }}),
+            arguments: vec![number(1.0, 3)],
+            named_arguments: vec![(String::from("b"), number(2.0, 9)), (String::from("c"), number(3.0, 15))],
+        }}}}]));
+        // The arguments given by position come first, and only a name can be given before a colon.
+        assert_eq!(parse("f(b: 2, 1)"), Err(vec![ErrorType::ExpectedNamedArgument { line: 1, column: 9 }]));
+        assert_eq!(parse("f((b): 2)"), Err(vec![ErrorType::ExpectedCharacter { expected: ')', line: 1, column: 5 }]));
+    }
     
     #[test]
     fn unclosed_call() {
         let source = "a(1, \"a\"(bc, 2+3)";
-        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedCharacter { expected: ')', line: 1 }]));
+        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedCharacter { expected: ')', line: 1, column: 17 }]));
         let source = "a(1, \"a\")(bc, 2+3";
-        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedCharacter { expected: ')', line: 1 }]));
+        assert!(errors_in_result(parse(source), vec![ErrorType::ExpectedCharacter { expected: ')', line: 1, column: 17 }]));
     }
 
     #[test]
     fn unary() {
         let source = "!!--5";
-        assert_eq!(Ok(vec![Stmt { line: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, expr_type: ExprType::Unary {
-            operator: token::Token { type_: token::TokenType::Bang, lexeme: String::from("!"), literal: token::Literal::Null, line: 1 },
-            right: Box::new(Expr { line: 1, expr_type: ExprType::Unary {
-                operator: token::Token { type_: token::TokenType::Bang, lexeme: String::from("!"), literal: token::Literal::Null, line: 1 },
-                right: Box::new(Expr { line: 1, expr_type: ExprType::Unary {
-                    operator: token::Token { type_: token::TokenType::Minus, lexeme: String::from("-"), literal: token::Literal::Null, line: 1 },
-                    right: Box::new(Expr { line: 1, expr_type: ExprType::Unary {
-                        operator: token::Token { type_: token::TokenType::Minus, lexeme: String::from("-"), literal: token::Literal::Null, line: 1 },
-                        right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(5.0) }}),
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, column: 5, expr_type: ExprType::Unary {
+            operator: token::Token { type_: token::TokenType::Bang, lexeme: String::from("!"), literal: token::Literal::Null, line: 1, column: 1 },
+            right: Box::new(Expr { line: 1, column: 5, expr_type: ExprType::Unary {
+                operator: token::Token { type_: token::TokenType::Bang, lexeme: String::from("!"), literal: token::Literal::Null, line: 1, column: 2 },
+                right: Box::new(Expr { line: 1, column: 5, expr_type: ExprType::Unary {
+                    operator: token::Token { type_: token::TokenType::Minus, lexeme: String::from("-"), literal: token::Literal::Null, line: 1, column: 3 },
+                    right: Box::new(Expr { line: 1, column: 5, expr_type: ExprType::Unary {
+                        operator: token::Token { type_: token::TokenType::Minus, lexeme: String::from("-"), literal: token::Literal::Null, line: 1, column: 4 },
+                        right: Box::new(Expr { line: 1, column: 5, expr_type: ExprType::Literal { value: token::Literal::Number(5.0) }}),
                     }}),
                 }}),
             }}),
@@ -1363,12 +1822,12 @@ mod tests {
     #[test]
     fn etc() {
         let source = "5--4";
-        assert_eq!(Ok(vec![Stmt { line: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, expr_type: ExprType::Binary {
-            left: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(5.0) }}),
-            operator: token::Token { type_: token::TokenType::Minus, lexeme: String::from("-"), literal: token::Literal::Null, line: 1 },
-            right: Box::new(Expr { line: 1, expr_type: ExprType::Unary {
-                operator: token::Token { type_: token::TokenType::Minus, lexeme: String::from("-"), literal: token::Literal::Null, line: 1 },
-                right: Box::new(Expr { line: 1, expr_type: ExprType::Literal { value: token::Literal::Number(4.0) }}),
+        assert_eq!(Ok(vec![Stmt { line: 1, column: 1, stmt_type: StmtType::Expression { expression: Expr { line: 1, column: 4, expr_type: ExprType::Binary {
+            left: Box::new(Expr { line: 1, column: 1, expr_type: ExprType::Literal { value: token::Literal::Number(5.0) }}),
+            operator: token::Token { type_: token::TokenType::Minus, lexeme: String::from("-"), literal: token::Literal::Null, line: 1, column: 2 },
+            right: Box::new(Expr { line: 1, column: 4, expr_type: ExprType::Unary {
+                operator: token::Token { type_: token::TokenType::Minus, lexeme: String::from("-"), literal: token::Literal::Null, line: 1, column: 3 },
+                right: Box::new(Expr { line: 1, column: 4, expr_type: ExprType::Literal { value: token::Literal::Number(4.0) }}),
             }}),
         }}}}]), parse(source));
     }
@@ -1377,8 +1836,147 @@ mod tests {
     fn sync() {
         let source = "print {\nfor (x = 5; x < 2; x = x + 1 {print x}";
         assert!(errors_in_result(parse(source), vec![
-            ErrorType::ExpectedExpression { line: 1 },
-            ErrorType::ExpectedParenAfterIncrement { line: 2 },
+            ErrorType::ExpectedExpression { line: 1, column: 7 },
+            ErrorType::ExpectedParenAfterIncrement { line: 2, column: 28 },
         ]));
     }
+
+    #[test]
+    fn no_tokens() {
+        let mut parser = Parser::new(vec![]);
+        assert_eq!(Ok(vec![]), parser.parse());
+    }
+
+    /// Runs `test` on a thread with a stack the size of a typical main thread's (8 MiB).
+    /// Test threads have smaller stacks, which unoptimised builds can overflow well within `MAX_NESTING_DEPTH`.
+    fn with_main_thread_stack(test: fn()) {
+        std::thread::Builder::new()
+            .stack_size(8 * 1024 * 1024)
+            .spawn(test)
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn nesting_within_limit() {
+        with_main_thread_stack(|| {
+            // The outermost expression takes one level, and each pair of brackets takes another.
+            let depth = MAX_NESTING_DEPTH - 1;
+            let source = format!("print {}1{}", "(".repeat(depth), ")".repeat(depth));
+            assert!(parse(&source).is_ok());
+            let source = format!("print {}1{}", "(".repeat(depth + 1), ")".repeat(depth + 1));
+            assert!(parse(&source).is_err());
+
+            // A chain of operators is not nested, however long it is, so it only has to fit within `MAX_EXPRESSION_DEPTH`. Each
+            // operator in it takes a level, as it does in the AST, and so do the brackets around the operands.
+            let depth = MAX_EXPRESSION_DEPTH - 1;
+            assert!(parse(&format!("print 1{}", " + 1".repeat(depth))).is_ok());
+            assert!(parse(&format!("print 1{}", " + 1".repeat(depth + 1))).is_err());
+            assert!(parse(&format!("print (1{}) + 1", " + 1".repeat(depth - 2))).is_ok());
+            assert!(parse(&format!("print (1{}) + 1", " + 1".repeat(depth - 1))).is_err());
+            assert!(parse(&format!("print 1 * 2{}", " - 1".repeat(depth - 1))).is_ok());
+            assert!(parse(&format!("print 1 * 2{}", " - 1".repeat(depth))).is_err());
+            // The index inside each pair of brackets is nested, so it takes another level.
+            assert!(parse(&format!("print x{}", "[0]".repeat(depth / 2))).is_ok());
+        });
+    }
+
+    #[test]
+    fn too_deeply_nested() {
+        with_main_thread_stack(too_deeply_nested_on_thread);
+    }
+
+    fn too_deeply_nested_on_thread() {
+        let depth = MAX_NESTING_DEPTH * 2;
+        let sources = [
+            format!("print {}1{}", "(".repeat(depth), ")".repeat(depth)),
+            format!("print {}1{}", "[".repeat(depth), "]".repeat(depth)),
+            format!("print {}true", "!".repeat(depth)),
+            format!("{}1", "x = ".repeat(depth)),
+            format!("{}{}", "while (true) {".repeat(depth), "}".repeat(depth)),
+            // Chains of operators, indexes and calls have ASTs as deep as they are long, even without brackets.
+            format!("print 1{}", " + 1".repeat(200_000)),
+            format!("print 1{}", " and 1 * 1".repeat(MAX_EXPRESSION_DEPTH)),
+            format!("print x{}", "[0]".repeat(MAX_EXPRESSION_DEPTH * 2)),
+            format!("print f{}", "()".repeat(MAX_EXPRESSION_DEPTH * 2)),
+            // Chains inside brackets add up, as each is inside the one around it.
+            format!("print {}1{}", "(1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + 1 + ".repeat(60), ")".repeat(60)),
+        ];
+        for source in sources {
+            let Err(errors) = parse(&source) else {
+                panic!("Expected errors for deeply nested source code.");
+            };
+            assert!(errors.iter().any(|error| matches!(error, ErrorType::TooDeeplyNested { .. })));
+        }
+    }
+
+    #[test]
+    fn fuzz() {
+        // Fragments of source code which random programs are built from. These include lone quotes, comment markers and multi-byte characters.
+        const FRAGMENTS: &[&str] = &[
+            "(", ")", "{", "}", "[", "]", ":", ",", "-", "%", "+", ";", "/", "*",
+            "!", "!=", "=", "==", ">", ">=", "<", "<=", "\"", "'", "#", "\n", " ", "\t",
+            "0", "12", "2.5", "5.", "x", "_y1", "and", "or", "if", "else", "for", "while",
+            "func", "var", "print", "return", "break", "null", "true", "false", "repeat", "try", "catch", "finally",
+            "0x1F", "0b", "1e9", "2.5e-3", "e", "_", "1_000",
+            "é", "€", "😀", "\u{0}", "@",
+        ];
+
+        // A xorshift generator with a fixed seed, so that any failure can be reproduced.
+        let mut state: u64 = 0x2545_F491_4F6C_DD1D;
+        let mut next = || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+
+        for _ in 0..2000 {
+            let length = next() % 40;
+            let source: String = (0..length).map(|_| FRAGMENTS[(next() % FRAGMENTS.len() as u64) as usize]).collect();
+            // Only the absence of a panic is being tested, so the result itself does not matter.
+            let _ = parse_no_panic(&source);
+        }
+
+        // Long programs made by repeating a few fragments, e.g., ` + 1` thousands of times, which catch chains built without recursion.
+        let sources: Vec<String> = (0..50).map(|_| {
+            let length = next() % 4 + 1;
+            let run: String = (0..length).map(|_| FRAGMENTS[(next() % FRAGMENTS.len() as u64) as usize]).collect();
+            format!("print 1{}", run.repeat(5000))
+        }).collect();
+        std::thread::Builder::new()
+            .stack_size(8 * 1024 * 1024)
+            .spawn(move || for source in sources {
+                let _ = parse_no_panic(&source);
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn fail_fast() {
+        let source = "var = 1\nvar = 2\nprint (";
+        assert_eq!(parse(source).unwrap_err().len(), 3);
+
+        let tokens = Tokenizer::new(source).tokenize().unwrap();
+        let mut parser = Parser::with_error_limit(tokens, ErrorLimit { max_reported: None, fail_fast: true });
+        assert_eq!(parser.parse(), Err(vec![ErrorType::ExpectedVariableName { line: 1, column: 1 }]));
+    }
+
+    #[test]
+    fn strict() {
+        let strict = |source: &str| {
+            let mut parser = Parser::new(Tokenizer::new(source).tokenize().unwrap());
+            parser.set_strict(true);
+            parser.parse()
+        };
+        assert_eq!(strict("var x = 1 print x"), Err(vec![ErrorType::ExpectedNewline { line: 1, column: 11 }]));
+        assert_eq!(strict("if (true) {\n    print 1 print 2\n}"), Err(vec![ErrorType::ExpectedNewline { line: 2, column: 13 }]));
+        assert_eq!(strict("if (true) { print 1 } print 2"), Err(vec![ErrorType::ExpectedNewline { line: 1, column: 23 }]));
+        // A statement may be followed by the `}` ending its block, and the header of a `for` loop is not affected.
+        assert!(strict("while (false) { print 1 }\nfor (var i = 0; i < 3; i = i + 1) { print i }\nprint (\n    1\n)").is_ok());
+        assert!(parse("var x = 1 print x").is_ok());
+    }
 }
@@ -0,0 +1,137 @@
+//! Running NEAL in a web page, e.g., an interactive playground.
+//!
+//! Nothing here reads from the standard input stream or writes to the standard streams, which do not exist on the
+//! `wasm32-unknown-unknown` target: the output and error messages of each run are collected and returned instead. With the
+//! `wasm` feature, `run()` and `Session` are exported to JavaScript with `wasm-bindgen`:
+//!
+//! ```text
+//! cargo build --lib --release --target wasm32-unknown-unknown --features wasm
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/nea.wasm
+//! ```
+
+use std::io::{self, Cursor, Write};
+use std::sync::{Arc, Mutex};
+
+#[cfg(feature = "wasm")]
+use wasm_bindgen::prelude::wasm_bindgen;
+
+use crate::Interpreter;
+
+/// What running some source code printed.
+#[cfg_attr(feature = "wasm", wasm_bindgen(getter_with_clone))]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RunResult {
+    /// Everything printed by the program, including the prompts of `input()`.
+    pub output: String,
+    /// The error report, in the same format as the `nea` binary's, or an empty string if there were no errors.
+    pub errors: String,
+}
+
+/// Runs the source code in a new session and returns what it printed.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub fn run(source: &str) -> RunResult {
+    Session::new().run(source)
+}
+
+/// An interpreter which keeps its variables and functions between runs, like the REPL.
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+pub struct Session {
+    interpreter: Interpreter,
+    output: SharedBuffer,
+    error_output: SharedBuffer,
+}
+
+#[cfg_attr(feature = "wasm", wasm_bindgen)]
+impl Session {
+    /// Creates a session with no variables, in which `input()` reads nothing until `set_input()` is called.
+    #[cfg_attr(feature = "wasm", wasm_bindgen(constructor))]
+    pub fn new() -> Session {
+        let (output, error_output) = (SharedBuffer::default(), SharedBuffer::default());
+        let mut interpreter = Interpreter::new();
+        interpreter.set_output(Box::new(output.clone()));
+        interpreter.set_error_output(Box::new(error_output.clone()));
+        interpreter.set_input(Box::new(io::empty()));
+        // The output is shown as plain text on the page, where escape codes would be written out as they are.
+        interpreter.set_colour(false);
+        Session { interpreter, output, error_output }
+    }
+
+    /// Sets the text that `input()` reads its lines from, e.g., the contents of a text box on the page.
+    pub fn set_input(&mut self, input: &str) {
+        self.interpreter.set_input(Box::new(Cursor::new(input.to_owned())));
+    }
+
+    /// Runs the source code and returns what it printed. Only the output of this run is returned.
+    pub fn run(&mut self, source: &str) -> RunResult {
+        // Errors from every stage are written to the interpreter's error output, rather than the standard error stream.
+        let _ = crate::run_source(source, &mut self.interpreter);
+        RunResult { output: self.output.take(), errors: self.error_output.take() }
+    }
+
+    /// Returns everything the turtle has drawn in the session as an SVG image, to be shown on the page. There is no file system for
+    /// `save_drawing()` to write to in a browser.
+    pub fn drawing(&self) -> String {
+        self.interpreter.turtle().to_svg()
+    }
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A buffer which the session and its interpreter both hold, so that the session can read what the interpreter wrote.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl SharedBuffer {
+    /// Empties the buffer, returning what it contained.
+    fn take(&self) -> String {
+        String::from_utf8_lossy(&std::mem::take(&mut *self.0.lock().unwrap())).into_owned()
+    }
+}
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{run, Session};
+
+    #[test]
+    fn output_and_errors() {
+        let result = run("print 1 + 2\nprint 1 / 0");
+        assert_eq!(result.output, "3\n");
+        assert_eq!(result.errors, "An error has occurred.\n[E0021] Line 2, column 11: divisor is 0.\n");
+
+        let result = run("print 'a");
+        assert_eq!(result.output, "");
+        assert_eq!(result.errors, "An error has occurred.\n[E0002] Line 1, column 7: this string was never closed by the end of the program.\n");
+    }
+
+    #[test]
+    fn session_keeps_variables() {
+        let mut session = Session::new();
+        session.set_input("Alice\n");
+        assert_eq!(session.run("var name = input('Name: ')").output, "Name: ");
+        let result = session.run("print 'Hello, ' + name");
+        assert_eq!(result.output, "Hello, Alice\n");
+        assert_eq!(result.errors, "");
+    }
+
+    #[test]
+    fn drawing() {
+        let mut session = Session::new();
+        session.run("forward(10)");
+        session.run("turn(90)\nforward(10)");
+        assert_eq!(session.drawing().matches("<line ").count(), 2);
+    }
+}
@@ -0,0 +1,165 @@
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+use crate::hooks::Hooks;
+use crate::stmt::Stmt;
+
+/// How often a line or function ran, and how long it took.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub count: usize,  // The number of times a statement on the line was executed, or the number of calls to the function.
+    pub time: Duration,  // See `Profiler`.
+}
+
+/// Collects the statistics for `--profile`.
+///
+/// The time of a line is the *self* time of its statements: the time spent executing them, less the time spent in the statements
+/// nested in them, e.g., the body of a loop. This way, the times of all the lines add up to the time of the whole program.
+/// The time of a function is the total time spent in calls to it, including the statements in its body and the functions it calls.
+/// Recursive calls are counted, but their time is only added once, by the outermost call.
+#[derive(Debug, Default)]
+pub struct Profiler {
+    lines: HashMap<usize, Stats>,
+    functions: HashMap<String, Stats>,
+    nested_times: Vec<Duration>,  // For each statement currently being executed, innermost last, the time spent in the statements nested in it so far.
+    statement_starts: Vec<Instant>,  // For each statement currently being executed by the interpreter, innermost last, when it started.
+    calls: Vec<(String, Instant)>,  // For each call currently being executed by the interpreter, innermost last, the function and when it started.
+}
+
+impl Profiler {
+    /// Records that a statement has started executing.
+    pub fn enter_statement(&mut self) {
+        self.nested_times.push(Duration::ZERO);
+    }
+
+    /// Records that the statement which started executing last, on the given line, has finished after `elapsed`.
+    pub fn exit_statement(&mut self, line: usize, elapsed: Duration) {
+        let nested_time = self.nested_times.pop().unwrap_or_default();
+        let stats = self.lines.entry(line).or_default();
+        stats.count += 1;
+        stats.time += elapsed.saturating_sub(nested_time);
+
+        // The statement's time is nested in the statement it was executed by, if any.
+        if let Some(parent_nested_time) = self.nested_times.last_mut() {
+            *parent_nested_time += elapsed;
+        }
+    }
+
+    /// Records a call to the function with the given name which took `elapsed`.
+    /// If the function was already being executed, i.e., the call is recursive, the time is left to the outermost call.
+    pub fn record_call(&mut self, function: &str, elapsed: Duration, recursive: bool) {
+        let stats = self.functions.entry(String::from(function)).or_default();
+        stats.count += 1;
+        if !recursive {
+            stats.time += elapsed;
+        }
+    }
+
+    /// Returns the statistics of each line which was executed, with the most time first. Lines with the same time are in order.
+    pub fn lines(&self) -> Vec<(usize, Stats)> {
+        let mut lines: Vec<(usize, Stats)> = self.lines.iter().map(|(line, stats)| (*line, *stats)).collect();
+        lines.sort_by(|a, b| b.1.time.cmp(&a.1.time).then(a.0.cmp(&b.0)));
+        lines
+    }
+
+    /// Returns the statistics of each function which was called, with the most time first. Functions with the same time are in alphabetical order.
+    pub fn functions(&self) -> Vec<(String, Stats)> {
+        let mut functions: Vec<(String, Stats)> = self.functions.iter().map(|(name, stats)| (name.clone(), *stats)).collect();
+        functions.sort_by(|a, b| b.1.time.cmp(&a.1.time).then(a.0.cmp(&b.0)));
+        functions
+    }
+
+    /// Returns the report printed by `--profile`: a table of the lines, then a table of the functions, each with the most time first.
+    pub fn report(&self) -> String {
+        let milliseconds = |time: Duration| time.as_secs_f64() * 1000.0;
+        let mut report = String::from("Lines (self time):\n");
+        report.push_str(&format!("{:>8} {:>10} {:>12}\n", "Line", "Count", "Time (ms)"));
+        for (line, stats) in self.lines() {
+            report.push_str(&format!("{:>8} {:>10} {:>12.3}\n", line, stats.count, milliseconds(stats.time)));
+        }
+
+        let functions = self.functions();
+        if !functions.is_empty() {
+            report.push_str("Functions (total time):\n");
+            report.push_str(&format!("{:<16} {:>10} {:>12}\n", "Function", "Calls", "Time (ms)"));
+            for (function, stats) in functions {
+                report.push_str(&format!("{:<16} {:>10} {:>12.3}\n", function, stats.count, milliseconds(stats.time)));
+            }
+        }
+        report
+    }
+}
+
+/// Times the interpreter's statements and calls. The clock is only read while profiling, as there is none on some targets, e.g., WebAssembly.
+impl Hooks for Profiler {
+    fn before_statement(&mut self, _stmt: &Stmt) {
+        self.enter_statement();
+        self.statement_starts.push(Instant::now());
+    }
+
+    fn after_statement(&mut self, stmt: &Stmt) {
+        if let Some(start) = self.statement_starts.pop() {
+            self.exit_statement(stmt.line, start.elapsed());
+        }
+    }
+
+    fn before_call(&mut self, function: &str) {
+        self.calls.push((String::from(function), Instant::now()));
+    }
+
+    fn after_call(&mut self, _function: &str) {
+        if let Some((function, start)) = self.calls.pop() {
+            // The call is recursive if the function is still being executed by an outer call.
+            let recursive = self.calls.iter().any(|(outer, _)| *outer == function);
+            self.record_call(&function, start.elapsed(), recursive);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Profiler, Stats};
+
+    fn ms(milliseconds: u64) -> Duration {
+        Duration::from_millis(milliseconds)
+    }
+
+    #[test]
+    fn self_time() {
+        let mut profiler = Profiler::default();
+        // A loop on line 1, taking 10ms, whose body on line 2 runs twice, taking 3ms each time.
+        profiler.enter_statement();
+        for _ in 0..2 {
+            profiler.enter_statement();
+            profiler.exit_statement(2, ms(3));
+        }
+        profiler.exit_statement(1, ms(10));
+
+        assert_eq!(profiler.lines(), vec![
+            (2, Stats { count: 2, time: ms(6) }),
+            (1, Stats { count: 1, time: ms(4) }),
+        ]);
+    }
+
+    #[test]
+    fn recursive_calls() {
+        let mut profiler = Profiler::default();
+        profiler.record_call("f", ms(2), true);
+        profiler.record_call("f", ms(5), false);
+        profiler.record_call("g", ms(1), false);
+        assert_eq!(profiler.functions(), vec![
+            (String::from("f"), Stats { count: 2, time: ms(5) }),
+            (String::from("g"), Stats { count: 1, time: ms(1) }),
+        ]);
+    }
+
+    #[test]
+    fn report() {
+        let mut profiler = Profiler::default();
+        profiler.enter_statement();
+        profiler.exit_statement(3, ms(2));
+        assert_eq!(profiler.report(), "Lines (self time):\n    Line      Count    Time (ms)\n       3          1        2.000\n");
+    }
+}
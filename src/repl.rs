@@ -0,0 +1,477 @@
+use std::borrow::Cow;
+use std::env;
+use std::fs;
+use std::io::{self, IsTerminal};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use rustyline::completion::Completer;
+use rustyline::error::ReadlineError;
+use rustyline::highlight::{CmdKind, Highlighter};
+use rustyline::hint::Hinter;
+use rustyline::validate::Validator;
+use rustyline::config::{ColorMode, Config};
+use rustyline::{Context, Editor, Helper};
+use rustyline::history::DefaultHistory;
+
+use crate::cli::Options;
+use nea::error::{report_errors, ErrorLimit, ErrorType};
+use nea::expr::{Expr, ExprType};
+use nea::highlight::{classify, SpanKind};
+use nea::interpreter::Interpreter;
+use nea::parser::Parser;
+use nea::snapshot;
+use nea::token::Token;
+use nea::tokenizer::{Tokenizer, KEYWORDS};
+use nea::value::{BuiltinFunction, Value};
+use nea::visitor::{walk_expr, Visitor};
+
+/// The ANSI escape codes used to highlight the REPL input.
+mod colour {
+    pub const KEYWORD: &str = "\x1b[1;34m";  // Bold blue.
+    pub const CONSTANT: &str = "\x1b[36m";  // Cyan, for `true`, `false` and `null`.
+    pub const NUMBER: &str = "\x1b[33m";  // Yellow.
+    pub const STRING: &str = "\x1b[32m";  // Green.
+    pub const COMMENT: &str = "\x1b[90m";  // Grey.
+    pub const RESET: &str = "\x1b[0m";
+}
+
+/// Provides the line editor with completions and highlighting for the REPL.
+struct ReplHelper {
+    names: Vec<String>,  // The names visible in the interpreter's environment. This is refreshed after every line is run.
+}
+
+impl Completer for ReplHelper {
+    type Candidate = String;
+
+    fn complete(&self, line: &str, pos: usize, _: &Context<'_>) -> rustyline::Result<(usize, Vec<String>)> {
+        Ok(complete_word(line, pos, &self.names))
+    }
+}
+
+impl Highlighter for ReplHelper {
+    fn highlight<'l>(&self, line: &'l str, _: usize) -> Cow<'l, str> {
+        Cow::Owned(highlight(line))
+    }
+
+    fn highlight_char(&self, _: &str, _: usize, _: CmdKind) -> bool {
+        // Any character can change how the rest of the line is tokenized, e.g., an opening quote, so the whole line is highlighted again.
+        true
+    }
+}
+
+// The REPL does not use hints or validation, so the other parts of a helper keep their default behaviour.
+impl Hinter for ReplHelper {
+    type Hint = String;
+}
+impl Validator for ReplHelper {}
+impl Helper for ReplHelper {}
+
+/// Returns `line` with ANSI colour codes around its keywords, literals and comments.
+/// The colours are chosen from the spans the tokenizer classifies, so the highlighting always agrees with how the line will be run.
+fn highlight(line: &str) -> String {
+    // The colour of each character of the line. Spans count characters, not bytes.
+    let chars: Vec<char> = line.chars().collect();
+    let mut colours: Vec<Option<&str>> = vec![None; chars.len()];
+    for span in classify(line) {
+        let colour = match span.kind {
+            SpanKind::Keyword => colour::KEYWORD,
+            SpanKind::Constant => colour::CONSTANT,
+            SpanKind::Number => colour::NUMBER,
+            SpanKind::String => colour::STRING,
+            SpanKind::Comment => colour::COMMENT,
+            SpanKind::Identifier | SpanKind::Operator | SpanKind::Punctuation => continue,
+        };
+        colours[span.start..span.end].fill(Some(colour));
+    }
+
+    // Wrap each run of characters with the same colour in its escape codes.
+    let mut highlighted = String::new();
+    let mut current = None;
+    for (c, colour) in chars.iter().zip(colours) {
+        if colour != current {
+            if current.is_some() {
+                highlighted.push_str(colour::RESET);
+            }
+            if let Some(colour) = colour {
+                highlighted.push_str(colour);
+            }
+            current = colour;
+        }
+        highlighted.push(*c);
+    }
+    if current.is_some() {
+        highlighted.push_str(colour::RESET);
+    }
+    highlighted
+}
+
+/// Returns the start of the word ending at `pos` in `line`, and every keyword and name in `names` which starts with that word, in alphabetical order.
+fn complete_word(line: &str, pos: usize, names: &[String]) -> (usize, Vec<String>) {
+    // Find the start of the word by moving back over the characters which can make up a name.
+    let start = line[..pos]
+        .char_indices()
+        .rev()
+        .take_while(|(_, c)| c.is_ascii_alphanumeric() || *c == '_')
+        .last()
+        .map_or(pos, |(i, _)| i);
+    let word = &line[start..pos];
+
+    let mut candidates: Vec<String> = KEYWORDS.iter()
+        .map(|keyword| String::from(*keyword))
+        .chain(names.iter().cloned())
+        .filter(|candidate| candidate.starts_with(word))
+        .collect();
+    candidates.sort();
+    candidates.dedup();
+    (start, candidates)
+}
+
+/// The commands which can be entered into the REPL instead of source code. Each starts with a colon, e.g., `:quit`.
+#[derive(Debug, PartialEq)]
+enum Command {
+    Help,  // Lists the commands.
+    Env,  // Prints every variable in the session with its value.
+    Load(String),  // Runs the script at the file path in the session, so that its variables and functions can be used afterwards.
+    Type(String),  // Evaluates the expression and prints a summary of its value's type and shape, rather than the whole value.
+    Save(String),  // Writes a snapshot of the session's variables and functions to the file path, which `:load` restores.
+    Reset,  // Starts the session afresh by discarding every variable and function.
+    Quit,  // Ends the session.
+}
+
+/// The help text printed by `:help`.
+const HELP: &str = "\
+:help         Show this list of commands.
+:env          Show every variable and its value.
+:load <file>  Run a script in this session.
+:save <file>  Save every variable and function, to be restored with `:load`.
+:type <expr>  Show the type and size of the expression's value, without printing all of it.
+:reset        Forget every variable and function.
+:quit         End the session.";
+
+/// Parses a line starting with a colon into a REPL command. If the command is unknown or has the wrong arguments, returns a message saying why.
+fn parse_command(line: &str) -> Result<Command, String> {
+    let line = line.trim();
+    // The name of the command is separated from its argument, if any, by whitespace.
+    let (name, argument) = match line.split_once(char::is_whitespace) {
+        Some((name, argument)) => (name, argument.trim()),
+        None => (line, ""),
+    };
+
+    match (name, argument) {
+        (":help", "") => Ok(Command::Help),
+        (":env", "") => Ok(Command::Env),
+        (":load", "") => Err(String::from("`:load` has to be followed by a file path.")),
+        (":load", path) => Ok(Command::Load(String::from(path))),
+        (":save", "") => Err(String::from("`:save` has to be followed by a file path.")),
+        (":save", path) => Ok(Command::Save(String::from(path))),
+        (":type", "") => Err(String::from("`:type` has to be followed by an expression.")),
+        (":type", expression) => Ok(Command::Type(String::from(expression))),
+        (":reset", "") => Ok(Command::Reset),
+        (":quit", "") => Ok(Command::Quit),
+        (":help" | ":env" | ":reset" | ":quit", _) => Err(format!("`{}` does not take an argument.", name)),
+        _ => Err(format!("Unknown command `{}`. Enter `:help` for a list of commands.", name)),
+    }
+}
+
+/// Prints the value of an expression entered into the REPL, in the same way as `print` would, pretty-printed unless `--no-pretty` is given.
+/// `null` is not printed, so that calling a function which returns nothing, e.g., `hanoi(3, 1, 3)`, does not add a line to its output.
+fn echo(value: &Value, pretty: Option<usize>) {
+    match (value, pretty) {
+        (Value::Null, _) => (),
+        (_, Some(max_elements)) => println!("{}", value.pretty(max_elements)),
+        (_, None) => println!("{}", value),
+    }
+}
+
+/// The built-in functions which only return a value, without changing any variable, reading input or writing output, so that
+/// `:type` can call them.
+const PURE_BUILTINS: &[BuiltinFunction] = &[
+    BuiltinFunction::Args, BuiltinFunction::Colored, BuiltinFunction::Defined, BuiltinFunction::Factors, BuiltinFunction::Gcd,
+    BuiltinFunction::Get, BuiltinFunction::GetCell, BuiltinFunction::Globals, BuiltinFunction::Identity, BuiltinFunction::Inspect,
+    BuiltinFunction::IsPrime, BuiltinFunction::Keys, BuiltinFunction::Lcm, BuiltinFunction::Locals, BuiltinFunction::Matrix,
+    BuiltinFunction::Size, BuiltinFunction::Sort, BuiltinFunction::ToBase, BuiltinFunction::ToFixed, BuiltinFunction::ToNumber,
+    BuiltinFunction::ToString, BuiltinFunction::Transpose, BuiltinFunction::Values,
+];
+
+/// Finds whether an expression could change the session when it is evaluated, i.e., whether it has an assignment anywhere in it,
+/// or calls anything other than one of the `PURE_BUILTINS`, e.g., a function declared in the session.
+struct SideEffects<'a> {
+    interpreter: &'a Interpreter,
+    found: bool,
+}
+
+impl Visitor for SideEffects<'_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        match &expr.expr_type {
+            ExprType::Assignment { .. } => self.found = true,
+            ExprType::Call { callee, .. } => {
+                // The name is looked up as it would be when the call is made, as a built-in function can be declared again.
+                let pure = match &callee.expr_type {
+                    ExprType::Variable { name } => matches!(self.interpreter.get_global(name), Some(Value::BuiltinFunction(function)) if PURE_BUILTINS.contains(&function)),
+                    _ => false,
+                };
+                self.found |= !pure;
+            },
+            _ => (),
+        }
+        walk_expr(self, expr);
+    }
+}
+
+/// Returns the summary of the value of `source` for `:type`, as `inspect()` gives it, or `None` if evaluating it could change the
+/// session. `:type` only describes a value, so it is evaluated as a single expression, and only if it changes nothing.
+fn type_of(source: &str, interpreter: &mut Interpreter) -> Result<Option<String>, ErrorType> {
+    let tokens = Tokenizer::new(source).collect::<Result<Vec<Token>, ErrorType>>()?;
+    let expr = Parser::new(tokens).parse_expression()?;
+    let mut side_effects = SideEffects { interpreter, found: false };
+    side_effects.visit_expr(&expr);
+    if side_effects.found {
+        return Ok(None);
+    }
+    interpreter.eval_parsed(&expr).map(|value| Some(value.summary()))
+}
+
+/// The name of the file in the home directory where the REPL history is kept, unless another file is given with `--history`.
+const HISTORY_FILE: &str = ".neal_history";
+
+/// The name of the file in the home directory which is run before the first prompt, if it exists.
+const RC_FILE: &str = ".nealrc";
+
+/// Returns the paths of the files to run before the first prompt: `~/.nealrc`, if it exists, followed by the files given with `--preload`.
+fn preload_paths(options: &Options) -> Vec<PathBuf> {
+    let rc_path = env::home_dir().map(|home| home.join(RC_FILE)).filter(|path| path.is_file());
+    rc_path.into_iter().chain(options.preload.iter().map(PathBuf::from)).collect()
+}
+
+/// Runs the file at the path in the interpreter, for `:load` and preloading.
+/// As with a line of source code, errors in the script are reported but do not end the session.
+fn load(path: &Path, interpreter: &mut Interpreter, error_limit: ErrorLimit) {
+    match fs::read_to_string(path) {
+        Ok(source) => {
+            let _ = crate::run(&source, interpreter, error_limit);
+        },
+        Err(error) => eprintln!("Could not read `{}`: {}.", path.display(), error),
+    }
+}
+
+/// Returns the path of the file the REPL history is kept in, or `None` if there is no home directory to keep it in.
+fn history_path(options: &Options) -> Option<PathBuf> {
+    match &options.history {
+        Some(path) => Some(PathBuf::from(path)),
+        None => env::home_dir().map(|home| home.join(HISTORY_FILE)),
+    }
+}
+
+/// Runs the interactive REPL interface in the console, with the error limit, tracing, colour, history and preload settings in `options`.
+pub fn run_repl(options: &Options) {
+    let error_limit = options.error_limit;
+    let preload_paths = preload_paths(options);
+    // Pressing Ctrl-C while a line is running stops it with an error, rather than ending the session and losing its variables.
+    // At the prompt, the line editor reads Ctrl-C itself, which ends the session as before.
+    let interrupt = Arc::new(AtomicBool::new(false));
+    let handler_interrupt = Arc::clone(&interrupt);
+    if let Err(error) = ctrlc::set_handler(move || handler_interrupt.store(true, Ordering::Relaxed)) {
+        eprintln!("Could not handle Ctrl-C: {}. Pressing it will end the session.", error);
+    }
+    // We need the same `Interpreter` instance across all REPL source code inputs to preserve the variables and functions stored in the environment.
+    // A new interpreter runs the preloaded files straight away, so that their functions are also available after `:reset`.
+    let new_interpreter = || {
+        let mut interpreter = Interpreter::new();
+        interpreter.set_trace(options.trace);
+        interpreter.set_strict(options.strict);
+        interpreter.set_truthy(options.truthy);
+        interpreter.set_colour(options.output_colour());
+        interpreter.set_pretty(options.pretty_elements(true));
+        interpreter.set_interrupt_flag(Arc::clone(&interrupt));
+        for path in &preload_paths {
+            load(path, &mut interpreter, error_limit);
+        }
+        interpreter
+    };
+    let mut interpreter = new_interpreter();
+
+    // The input is highlighted unless colour has been turned off. The line editor also turns it off if the `NO_COLOR` environment variable is set.
+    let colour_mode = if options.colour { ColorMode::Enabled } else { ColorMode::Disabled };
+    let config = Config::builder().color_mode(colour_mode).build();
+    let mut editor: Editor<ReplHelper, DefaultHistory> = match Editor::with_config(config) {
+        Ok(editor) => editor,
+        Err(error) => {
+            eprintln!("Failed to start the REPL: {}", error);
+            return;
+        },
+    };
+    editor.set_helper(Some(ReplHelper { names: interpreter.visible_names() }));
+
+    // Load the lines entered in earlier sessions. There is no history the first time the REPL is run, so a missing file is not a problem.
+    let history_path = history_path(options);
+    if let Some(path) = &history_path {
+        if let Err(error) = editor.load_history(path) {
+            if !matches!(&error, ReadlineError::Io(io_error) if io_error.kind() == io::ErrorKind::NotFound) {
+                eprintln!("Could not load the history from `{}`: {}.", path.display(), error);
+            }
+        }
+    }
+
+    loop {
+        // Read user input into `line`.
+        let line = match editor.readline("> ") {
+            Ok(line) => line,
+            // The input has ended, e.g., when the user presses Ctrl-D, or the user pressed Ctrl-C, so end the session.
+            Err(ReadlineError::Eof | ReadlineError::Interrupted) => {
+                if io::stdin().is_terminal() {
+                    // Move the shell's prompt off the line of the REPL's prompt.
+                    println!();
+                }
+                break;
+            },
+            Err(error) => {
+                eprintln!("Failed to read line: {}", error);
+                break;
+            },
+        };
+
+        if !line.trim().is_empty() {
+            // Remember the line so that it can be recalled with the up arrow key, in this session and later ones.
+            let _ = editor.add_history_entry(line.as_str());
+        }
+
+        if line.trim_start().starts_with(':') {
+            // The line is a command rather than source code.
+            match parse_command(&line) {
+                Ok(Command::Help) => println!("{}", HELP),
+                Ok(Command::Env) => {
+                    for (name, value) in interpreter.variables() {
+                        println!("{} = {}", name, value);
+                    }
+                },
+                Ok(Command::Load(path)) => load(Path::new(&path), &mut interpreter, error_limit),
+                Ok(Command::Save(path)) => {
+                    if let Err(error) = fs::write(&path, snapshot::snapshot(&interpreter)) {
+                        eprintln!("Could not save the session to `{}`: {}.", path, error);
+                    }
+                },
+                Ok(Command::Type(expression)) => match type_of(&expression, &mut interpreter) {
+                    Ok(Some(summary)) => println!("{}", summary),
+                    Ok(None) => eprintln!("`:type` does not run assignments, or calls to functions which could change the session."),
+                    Err(error) => report_errors(&[error]),
+                },
+                Ok(Command::Reset) => interpreter = new_interpreter(),
+                Ok(Command::Quit) => break,
+                Err(message) => eprintln!("{}", message),
+            }
+        } else if let Ok(Some(value)) = crate::run(&line, &mut interpreter, error_limit) {
+            // Errors have already been reported, and they should not end the REPL session, so only a successful result is used.
+            echo(&value, interpreter.pretty());
+            // As in Python's REPL, the last value echoed can be used in the next line as `_`, e.g., `_ * 2`. `null` is not echoed, so it is not kept.
+            if !matches!(value, Value::Null) {
+                interpreter.set_global("_", value);
+            }
+        }
+
+        // The line may have declared new names, so update the completions.
+        if let Some(helper) = editor.helper_mut() {
+            helper.names = interpreter.visible_names();
+        }
+    }
+
+    // Save the history for the next session, however this one ended.
+    if let Some(path) = &history_path {
+        if let Err(error) = editor.save_history(path) {
+            eprintln!("Could not save the history to `{}`: {}.", path.display(), error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use nea::error::ErrorType;
+    use nea::interpreter::Interpreter;
+
+    use super::{colour, complete_word, highlight, parse_command, type_of, Command};
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| String::from(*name)).collect()
+    }
+
+    #[test]
+    fn complete_names_and_keywords() {
+        let names = names(&["printer", "size", "sort"]);
+        assert_eq!(complete_word("pri", 3, &names), (0, vec![String::from("print"), String::from("printer")]));
+        assert_eq!(complete_word("var x = s", 9, &names), (8, vec![String::from("size"), String::from("sort")]));
+        assert_eq!(complete_word("wh", 2, &names), (0, vec![String::from("while")]));
+        assert_eq!(complete_word("xyz", 3, &names), (0, vec![]));
+    }
+
+    #[test]
+    fn complete_in_middle_of_line() {
+        // Only the part of the word before the cursor is completed.
+        let names = names(&["size", "sort"]);
+        assert_eq!(complete_word("so(x) + 1", 2, &names), (0, vec![String::from("sort")]));
+        assert_eq!(complete_word("f(a, si", 7, &names), (5, vec![String::from("size")]));
+    }
+
+    #[test]
+    fn highlighting() {
+        assert_eq!(
+            highlight("var x = 1.5"),
+            format!("{}var{} x = {}1.5{}", colour::KEYWORD, colour::RESET, colour::NUMBER, colour::RESET),
+        );
+        assert_eq!(
+            highlight("print 'a # b' # c"),
+            format!("{}print{} {}'a # b'{} {}# c{}", colour::KEYWORD, colour::RESET, colour::STRING, colour::RESET, colour::COMMENT, colour::RESET),
+        );
+        assert_eq!(
+            highlight("x = true"),
+            format!("x = {}true{}", colour::CONSTANT, colour::RESET),
+        );
+    }
+
+    #[test]
+    fn highlighting_incomplete_lines() {
+        // An unclosed string is highlighted to the end of the line, and characters which cannot be tokenized are left as they are.
+        assert_eq!(highlight("print 'é #"), format!("{}print{} {}'é #{}", colour::KEYWORD, colour::RESET, colour::STRING, colour::RESET));
+        assert_eq!(highlight("x @ 1"), format!("x @ {}1{}", colour::NUMBER, colour::RESET));
+        assert_eq!(highlight(""), "");
+    }
+
+    #[test]
+    fn commands() {
+        assert_eq!(parse_command(":help"), Ok(Command::Help));
+        assert_eq!(parse_command("  :env  "), Ok(Command::Env));
+        assert_eq!(parse_command(":load  scripts/a.nea "), Ok(Command::Load(String::from("scripts/a.nea"))));
+        assert_eq!(parse_command(":save session.neal"), Ok(Command::Save(String::from("session.neal"))));
+        assert_eq!(parse_command(":type  xs[0] "), Ok(Command::Type(String::from("xs[0]"))));
+        assert_eq!(parse_command(":reset"), Ok(Command::Reset));
+        assert_eq!(parse_command(":quit"), Ok(Command::Quit));
+    }
+
+    #[test]
+    fn type_has_no_side_effects() {
+        let mut interpreter = Interpreter::new();
+        assert!(nea::run_source("var xs = [1, 2, 3]", &mut interpreter).is_ok());
+        assert_eq!(type_of("xs[0]", &mut interpreter), Ok(Some(String::from("Number 1"))));
+        // Neither an assignment nor a statement is run, so `xs` is still the array and `f` is not declared.
+        assert_eq!(type_of("xs = 5", &mut interpreter), Ok(None));
+        assert_eq!(type_of("func f() {}", &mut interpreter), Err(ErrorType::ExpectedExpression { line: 1, column: 1 }));
+        // Nor is an assignment inside it, or a call to a function which could change a variable.
+        assert!(nea::run_source("func grow() { xs = [] }", &mut interpreter).is_ok());
+        for source in ["(xs = 5)", "[xs = 7]", "grow()", "size(grow())", "append(xs, 4)", "eval('xs = 1')", "-grow()"] {
+            assert_eq!(type_of(source, &mut interpreter), Ok(None));
+        }
+        assert_eq!(type_of("size(sort(xs))", &mut interpreter), Ok(Some(String::from("Number 3"))));
+        assert_eq!(type_of("xs", &mut interpreter), Ok(Some(String::from("Array of 3 elements (Number)"))));
+        assert!(matches!(type_of("f", &mut interpreter), Err(ErrorType::NameError { .. })));
+    }
+
+    #[test]
+    fn invalid_commands() {
+        assert!(parse_command(":type").is_err());
+        assert!(parse_command(":load").is_err());
+        assert!(parse_command(":save").is_err());
+        assert!(parse_command(":quit now").is_err());
+        assert!(parse_command(":exit").is_err());
+        assert!(parse_command(":").is_err());
+    }
+}
@@ -0,0 +1,150 @@
+//! Saving the variables and functions of a session, so that it can be resumed later, e.g., with `:save` and `:load` in the REPL.
+//!
+//! A snapshot is NEAL source code which declares every global variable and function again, so it is restored by running it, e.g.,
+//! with `run_source()`. Functions are written back as declarations, and other values as literals.
+//!
+//! ```
+//! use nea::{run_source, snapshot, Interpreter, Value};
+//!
+//! let mut interpreter = Interpreter::new();
+//! run_source("var scores = {'Alice': [3, 5]}\nfunc last(x) { return x[size(x) - 1] }", &mut interpreter).unwrap();
+//! let saved = snapshot::snapshot(&interpreter);
+//!
+//! let mut restored = Interpreter::new();
+//! run_source(&saved, &mut restored).unwrap();
+//! run_source("var best = last(scores['Alice'])", &mut restored).unwrap();
+//! assert_eq!(restored.get_global("best"), Some(Value::Number(5.0)));
+//! ```
+
+use crate::source_printer;
+use crate::stmt::{Stmt, StmtType};
+use crate::{Interpreter, Value};
+
+/// The first line of every snapshot.
+const HEADER: &str = "# A snapshot of a NEAL session. Run it, e.g., with `:load` in the REPL, to restore the session.";
+
+/// Returns a snapshot of the interpreter's global variables and functions, in alphabetical order of name.
+///
+/// A function inside an array or dictionary cannot be written as source code, so a variable holding one is left out, with a comment
+/// saying so. Variables holding built-in functions refer to them by name, so they are only restored correctly if the name has not
+/// been declared again.
+pub fn snapshot(interpreter: &Interpreter) -> String {
+    let mut output = format!("{}\n", HEADER);
+    for (name, value) in interpreter.globals() {
+        let line = match value {
+            Value::Function { parameters, body } => {
+                let declaration = Stmt { line: 0, column: 0, stmt_type: StmtType::Function { name, parameters, body: Box::new((*body).clone()), doc: None } };
+                source_printer::print_stmt(&declaration, 0)
+            },
+            value => match value_source(&value, false) {
+                Some(source) => format!("var {} = {}", name, source),
+                None => format!("# `{}` was left out, as it holds a function inside an array or dictionary.", name),
+            },
+        };
+        output.push_str(&line);
+        output.push('\n');
+    }
+    output
+}
+
+/// Returns a literal which evaluates to the value, e.g., for `store_save()`, or `None` if the value contains a user-defined function.
+pub fn value_literal(value: &Value) -> Option<String> {
+    value_source(value, false)
+}
+
+/// Returns an expression which evaluates to the value, or `None` if the value contains a user-defined function.
+/// A frozen array or dictionary is written inside `freeze()`, unless it is `inside_frozen` value, which freezes it already.
+fn value_source(value: &Value, inside_frozen: bool) -> Option<String> {
+    let frozen = value.is_frozen();
+    let source = match value {
+        // Rust writes finite numbers without an exponent, which the tokenizer can read back.
+        Value::Number(x) if x.is_finite() => x.to_string(),
+        // Infinity and NaN have no literal, but `to_number()` accepts the way Rust writes them.
+        Value::Number(x) => format!("to_number('{}')", x),
+        Value::String_(string) => source_printer::print_string(string),
+        Value::Bool(bool) => bool.to_string(),
+        Value::Null => String::from("null"),
+        Value::Array(array) => {
+            let elements = array.iter().map(|element| value_source(element, frozen)).collect::<Option<Vec<String>>>()?;
+            format!("[{}]", elements.join(", "))
+        },
+        Value::Dictionary(dictionary) => {
+            let elements = dictionary.iter()
+                .map(|key_value| Some(format!("{}: {}", value_source(&key_value.key, frozen)?, value_source(&key_value.value, frozen)?)))
+                .collect::<Option<Vec<String>>>()?;
+            format!("{{{}}}", elements.join(", "))
+        },
+        Value::BuiltinFunction(builtin) => String::from(builtin.name()),
+        Value::Function { .. } => return None,
+    };
+    if frozen && !inside_frozen {
+        return Some(format!("freeze({})", source));
+    }
+    Some(source)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{run_source, Interpreter, Value};
+
+    use super::snapshot;
+
+    fn interpreter(source: &str) -> Interpreter {
+        let mut interpreter = Interpreter::new();
+        run_source(source, &mut interpreter).unwrap();
+        interpreter
+    }
+
+    #[test]
+    fn values() {
+        let interpreter = interpreter("var n = -1.5\nvar s = \"it's\"\nvar a = [true, null, {1: 'x'}]\nvar b = 1\nb = to_number('inf')\nvar sz = size");
+        assert_eq!(snapshot(&interpreter), "\
+# A snapshot of a NEAL session. Run it, e.g., with `:load` in the REPL, to restore the session.
+var a = [true, null, {1: 'x'}]
+var b = to_number('inf')
+var n = -1.5
+var s = \"it's\"
+var sz = size
+");
+    }
+
+    #[test]
+    fn frozen_values() {
+        // Only the outermost frozen value needs to be frozen again, as freezing is deep.
+        let interpreter = interpreter("var a = freeze([[1], {2: [3]}])\nvar b = [freeze({'x': 1}), [2]]");
+        let saved = snapshot(&interpreter);
+        assert_eq!(saved.lines().skip(1).collect::<Vec<&str>>(), ["var a = freeze([[1], {2: [3]}])", "var b = [freeze({'x': 1}), [2]]"]);
+        let mut restored = Interpreter::new();
+        assert_eq!(run_source(&saved, &mut restored), Ok(()));
+        assert_eq!(restored.get_global("a").map(|a| a.is_frozen()), Some(true));
+    }
+
+    #[test]
+    fn functions() {
+        let interpreter = interpreter("func f(x) {\n    if (x > 0) { return f(x - 1) }\n    return 'done'\n}\nvar g = f\nvar h = [f]");
+        let saved = snapshot(&interpreter);
+        assert_eq!(saved, "\
+# A snapshot of a NEAL session. Run it, e.g., with `:load` in the REPL, to restore the session.
+func f(x) {
+    if (x > 0) {
+        return f(x - 1)
+    }
+    return 'done'
+}
+func g(x) {
+    if (x > 0) {
+        return f(x - 1)
+    }
+    return 'done'
+}
+# `h` was left out, as it holds a function inside an array or dictionary.
+");
+
+        // The restored functions can be called, and are saved in the same way again.
+        let mut restored = Interpreter::new();
+        assert_eq!(run_source(&saved, &mut restored), Ok(()));
+        assert_eq!(snapshot(&restored), saved.replace("# `h` was left out, as it holds a function inside an array or dictionary.\n", ""));
+        assert_eq!(run_source("var result = g(3)", &mut restored), Ok(()));
+        assert_eq!(restored.get_global("result"), Some(Value::String_(String::from("done"))));
+    }
+}
@@ -0,0 +1,395 @@
+use crate::expr::{Expr, ExprType};
+use crate::stmt::{Stmt, StmtType};
+use crate::token::{Comment, Literal};
+use crate::Tokenizer;
+
+/// The text each level of a block is indented by.
+const INDENT: &str = "    ";
+
+/// Returns source code which parses back into the abstract syntax tree, with one statement per line and blocks indented.
+/// Comments are not part of the tree, so they are not kept; `format()` keeps them. Brackets are only written where the tree has a `Grouping`.
+pub fn print(ast: &[Stmt]) -> String {
+    Printer::new(&[], "").program(ast)
+}
+
+/// Returns the source code, which must have parsed into the abstract syntax tree, laid out in the same way as `print()`, e.g., by `--fmt`.
+/// Unlike `print()`, comments are kept: a comment on a line of its own stays before the statement after it, and a comment after
+/// code stays at the end of that statement's first line. A blank line between two statements is also kept, but several become one.
+pub fn format(ast: &[Stmt], source: &str) -> String {
+    // The parser never sees the comments, so they are collected by scanning the source code again.
+    let mut tokenizer = Tokenizer::new(source);
+    tokenizer.by_ref().for_each(drop);
+    Printer::new(tokenizer.comments(), source).program(ast)
+}
+
+/// Returns the source code of the statement, without a line break at the end.
+/// Any lines after the first are indented to `depth`, so that the statement can be placed at that depth.
+pub fn print_stmt(stmt: &Stmt, depth: usize) -> String {
+    let mut printer = Printer::new(&[], "");
+    printer.stmt(stmt, depth);
+    printer.output + &printer.line
+}
+
+/// Writes statements one line at a time, so that comments can be put before a line or at the end of it.
+struct Printer<'a> {
+    output: String,  // The lines which have been finished.
+    line: String,  // The line being written, without its indentation.
+    indent: usize,  // The depth the line being written is indented to.
+    line_sources: Vec<usize>,  // The source line numbers of the statements and closing braces on the line being written, whose trailing comments go at its end.
+    comments: &'a [Comment],
+    written: Vec<bool>,  // Whether each comment has been written.
+    source_lines: Vec<&'a str>,  // The lines of the source code, to find blank lines in.
+}
+
+impl<'a> Printer<'a> {
+    fn new(comments: &'a [Comment], source: &'a str) -> Self {
+        Printer {
+            output: String::new(),
+            line: String::new(),
+            indent: 0,
+            line_sources: Vec::new(),
+            comments,
+            written: vec![false; comments.len()],
+            source_lines: source.lines().collect(),
+        }
+    }
+
+    /// Writes a whole program, returning the source code.
+    fn program(mut self, ast: &[Stmt]) -> String {
+        for (i, stmt) in ast.iter().enumerate() {
+            self.before_stmt(stmt.line, 0, i == 0);
+            self.stmt(stmt, 0);
+            self.new_line(0);
+        }
+        // Any comments left are after the last statement.
+        self.comments_before(usize::MAX, 0, ast.is_empty());
+        self.output
+    }
+
+    /// Writes the statement onto the current line, starting further lines at `depth`.
+    fn stmt(&mut self, stmt: &Stmt, depth: usize) {
+        if !matches!(stmt.stmt_type, StmtType::Block { .. }) {
+            self.line_sources.push(stmt.line);
+        }
+        match &stmt.stmt_type {
+            StmtType::Block { body } => {
+                // The line of a `Block` is the line of its closing brace.
+                if body.is_empty() && !self.has_comments_before(stmt.line) {
+                    self.line_sources.push(stmt.line);
+                    self.line.push_str("{}");
+                    return;
+                }
+                self.line.push('{');
+                self.new_line(depth + 1);
+                for (i, body_stmt) in body.iter().enumerate() {
+                    self.before_stmt(body_stmt.line, depth + 1, i == 0);
+                    self.stmt(body_stmt, depth + 1);
+                    self.new_line(depth + 1);
+                }
+                // Comments after the last statement stay inside the block.
+                self.comments_before(stmt.line, depth + 1, body.is_empty());
+                self.indent = depth;
+                self.line_sources.push(stmt.line);
+                self.line.push('}');
+            },
+
+            StmtType::Break => self.line.push_str("break"),
+
+            StmtType::Expression { expression } => self.line.push_str(&print_expr(expression)),
+
+            StmtType::For { initialiser, condition, increment, body } => {
+                // Each part of the loop header is optional, and is left empty if it was not given.
+                let initialiser = initialiser.as_ref().map_or(String::new(), |initialiser| print_stmt(initialiser, depth));
+                let condition = condition.as_ref().map_or(String::new(), print_expr);
+                let increment = increment.as_ref().map_or(String::new(), |increment| print_stmt(increment, depth));
+                self.line.push_str(&format!("for ({}; {}; {}) ", initialiser, condition, increment));
+                self.stmt(body, depth);
+            },
+
+            StmtType::Function { name, parameters, body, .. } => {
+                self.line.push_str(&format!("func {}({}) ", name, parameters.join(", ")));
+                self.stmt(body, depth);
+            },
+
+            StmtType::If { condition, then_body, else_body } => {
+                self.line.push_str(&format!("if ({}) ", print_expr(condition)));
+                self.stmt(then_body, depth);
+                // An `else if` is an `if` statement as the `else` body, so it follows the `else` directly.
+                if let Some(else_body) = else_body {
+                    self.line.push_str(" else ");
+                    self.stmt(else_body, depth);
+                }
+            },
+
+            StmtType::Print { expression } => self.line.push_str(&format!("print {}", print_expr(expression))),
+
+            StmtType::Repeat { count, body } => {
+                self.line.push_str(&format!("repeat {} ", print_expr(count)));
+                self.stmt(body, depth);
+            },
+
+            StmtType::Return { expression } => self.line.push_str(&format!("return {}", print_expr(expression))),
+
+            StmtType::Try { body, catch, finally } => {
+                self.line.push_str("try ");
+                self.stmt(body, depth);
+                if let Some((name, catch_body)) = catch {
+                    self.line.push_str(&format!(" catch ({}) ", name));
+                    self.stmt(catch_body, depth);
+                }
+                if let Some(finally) = finally {
+                    self.line.push_str(" finally ");
+                    self.stmt(finally, depth);
+                }
+            },
+
+            StmtType::VarDecl { name, value } => self.line.push_str(&format!("var {} = {}", name, print_expr(value))),
+
+            StmtType::VarUnpack { names, value } => self.line.push_str(&format!("var {} = {}", names.join(", "), print_expr(value))),
+
+            StmtType::While { condition, body } => {
+                self.line.push_str(&format!("while ({}) ", print_expr(condition)));
+                self.stmt(body, depth);
+            },
+        }
+    }
+
+    /// Finishes the current line, with the trailing comments of the statements on it, and starts a new one at `depth`.
+    fn new_line(&mut self, depth: usize) {
+        for (i, comment) in self.comments.iter().enumerate() {
+            if comment.trailing && !self.written[i] && self.line_sources.contains(&comment.line) {
+                self.written[i] = true;
+                self.line.push_str("  ");
+                self.line.push_str(&comment.text);
+            }
+        }
+        // An empty line is not indented, so that it has no trailing whitespace.
+        if !self.line.is_empty() {
+            self.output.push_str(&INDENT.repeat(self.indent));
+            self.output.push_str(&self.line);
+        }
+        self.output.push('\n');
+        self.line.clear();
+        self.line_sources.clear();
+        self.indent = depth;
+    }
+
+    /// Writes what comes before a statement starting on source line `line`: the comments before it, and a blank line if there is
+    /// one before it in the source code. `first` is whether nothing has been written in the block yet, as a block never starts with
+    /// a blank line.
+    fn before_stmt(&mut self, line: usize, depth: usize, first: bool) {
+        let first = self.comments_before(line, depth, first);
+        if !first && self.blank_before(line) {
+            self.output.push('\n');
+        }
+    }
+
+    /// Writes the comments before source line `line` which have not been written yet, each on its own line at `depth`, keeping a
+    /// blank line before each of them as `before_stmt()` does. Returns whether nothing has been written in the block yet.
+    fn comments_before(&mut self, line: usize, depth: usize, mut first: bool) -> bool {
+        for i in 0..self.comments.len() {
+            let comment = &self.comments[i];
+            if self.written[i] || comment.line >= line {
+                continue;
+            }
+            self.written[i] = true;
+            if !first && self.blank_before(comment.line) {
+                self.output.push('\n');
+            }
+            self.line.push_str(&comment.text);
+            self.new_line(depth);
+            first = false;
+        }
+        first
+    }
+
+    /// Returns whether any comment before source line `line` has not been written yet.
+    fn has_comments_before(&self, line: usize) -> bool {
+        self.comments.iter().zip(&self.written).any(|(comment, written)| !written && comment.line < line)
+    }
+
+    /// Returns whether the source line before line `line` is blank.
+    fn blank_before(&self, line: usize) -> bool {
+        line >= 2 && self.source_lines.get(line - 2).is_some_and(|source_line| source_line.trim().is_empty())
+    }
+}
+
+/// Returns the source code of the expression.
+pub fn print_expr(expr: &Expr) -> String {
+    match &expr.expr_type {
+        ExprType::Array { elements } => format!("[{}]", elements.iter().map(print_expr).collect::<Vec<_>>().join(", ")),
+
+        ExprType::Assignment { target, value } => format!("{} = {}", print_expr(target), print_expr(value)),
+
+        ExprType::Binary { left, operator, right } => format!("{} {} {}", print_expr(left), operator.lexeme, print_expr(right)),
+
+        ExprType::Call { callee, arguments, named_arguments } => {
+            let named = named_arguments.iter().map(|(name, argument)| format!("{}: {}", name, print_expr(argument)));
+            format!("{}({})", print_expr(callee), arguments.iter().map(print_expr).chain(named).collect::<Vec<_>>().join(", "))
+        },
+
+        ExprType::Dictionary { elements } => {
+            let elements: Vec<String> = elements.iter()
+                .map(|element| format!("{}: {}", print_expr(&element.key), print_expr(&element.value)))
+                .collect();
+            format!("{{{}}}", elements.join(", "))
+        },
+
+        ExprType::Element { array, index } => format!("{}[{}]", print_expr(array), print_expr(index)),
+
+        ExprType::Grouping { expression } => format!("({})", print_expr(expression)),
+
+        ExprType::Literal { value } => match value {
+            Literal::Number(number) => number.to_string(),
+            Literal::String_(string) => print_string(string),
+            Literal::Bool(bool) => bool.to_string(),
+            Literal::Null => String::from("null"),
+        },
+
+        ExprType::Slice { array, start, stop, step } => {
+            let part = |part: &Option<Box<Expr>>| part.as_deref().map(print_expr).unwrap_or_default();
+            // The second colon is only written if there is a step, e.g., `a[1:]` rather than `a[1::]`.
+            match step {
+                Some(..) => format!("{}[{}:{}:{}]", print_expr(array), part(start), part(stop), part(step)),
+                None => format!("{}[{}:{}]", print_expr(array), part(start), part(stop)),
+            }
+        },
+
+        // A tuple is only parsed where nothing else follows it, e.g., after `return`, so it never needs brackets.
+        ExprType::Tuple { elements } => elements.iter().map(print_expr).collect::<Vec<_>>().join(", "),
+
+        ExprType::Unary { operator, right } => format!("{}{}", operator.lexeme, print_expr(right)),
+
+        ExprType::Variable { name } => name.clone(),
+    }
+}
+
+/// Returns a string literal with the value of the string.
+/// Strings cannot contain escape sequences, so the string is put in double quotes if it contains a single quote. A string which
+/// contains both kinds of quote is split into parts which are joined back together with `+`, and bracketed so that it stays one operand.
+pub fn print_string(string: &str) -> String {
+    if !string.contains('\'') {
+        format!("'{}'", string)
+    } else if !string.contains('"') {
+        format!("\"{}\"", string)
+    } else {
+        let parts: Vec<String> = string.split('\'').map(|part| format!("'{}'", part)).collect();
+        format!("({})", parts.join(" + \"'\" + "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::parse_no_panic;
+    use crate::stmt::StmtType;
+
+    use super::{format, print, print_string};
+
+    /// Asserts that the source code is printed as `expected`, and that the printed source code parses into the same tree.
+    fn assert_round_trip(source: &str, expected: &str) {
+        let ast = parse_no_panic(source).unwrap();
+        let printed = print(&ast);
+        assert_eq!(printed, expected);
+        let reparsed = parse_no_panic(&printed).unwrap();
+        assert_eq!(print(&reparsed), printed);
+    }
+
+    #[test]
+    fn expressions() {
+        assert_round_trip("print -(1+x)*f(2,'a')", "print -(1 + x) * f(2, 'a')\n");
+        assert_round_trip("a[i][0] = {'k' : [true, null], 2:!b}", "a[i][0] = {'k': [true, null], 2: !b}\n");
+        assert_round_trip("x = y = 1.5 or z and --w", "x = y = 1.5 or z and --w\n");
+        assert_round_trip("print [a[ : n+1], a[2:][::-1], a[1:2:]]", "print [a[:n + 1], a[2:][::-1], a[1:2]]\n");
+        assert_round_trip("var q,r = f(1 ,2)\na[0] , b = b,a+1", "var q, r = f(1, 2)\na[0], b = b, a + 1\n");
+        assert_round_trip("plot(x,color :'red' , width:2)", "plot(x, color: 'red', width: 2)\n");
+    }
+
+    #[test]
+    fn statements() {
+        let source = "func f(a,b) {\n  if (a) { return b } else if (b) {print 1} else {}\n}\nfor (var i = 0;; i = i + 1) { while(true){break} }\nrepeat n+1{print n}\ntry{f(1)}catch(e){print e}finally{}\ntry {} finally {print 2}";
+        assert_round_trip(source, "\
+func f(a, b) {
+    if (a) {
+        return b
+    } else if (b) {
+        print 1
+    } else {}
+}
+for (var i = 0; ; i = i + 1) {
+    while (true) {
+        break
+    }
+}
+repeat n + 1 {
+    print n
+}
+try {
+    f(1)
+} catch (e) {
+    print e
+} finally {}
+try {} finally {
+    print 2
+}
+");
+    }
+
+    #[test]
+    fn display() {
+        let ast = parse_no_panic("while (x<3) { x = x+1 }").unwrap();
+        assert_eq!(ast[0].to_string(), "while (x < 3) {\n    x = x + 1\n}");
+        let StmtType::While { condition, .. } = &ast[0].stmt_type else { unreachable!() };
+        assert_eq!(condition.to_string(), "x < 3");
+    }
+
+    #[test]
+    fn strings() {
+        assert_eq!(print_string("a"), "'a'");
+        assert_eq!(print_string("it's"), "\"it's\"");
+        assert_eq!(print_string("it's \"x\""), "('it' + \"'\" + 's \"x\"')");
+        assert_round_trip("print \"it's\" + 'say \"hi\"'", "print \"it's\" + 'say \"hi\"'\n");
+    }
+
+    /// Asserts that the source code is formatted as `expected`, and that formatting it again changes nothing.
+    fn assert_formats(source: &str, expected: &str) {
+        let formatted = format(&parse_no_panic(source).unwrap(), source);
+        assert_eq!(formatted, expected);
+        assert_eq!(format(&parse_no_panic(&formatted).unwrap(), &formatted), formatted);
+    }
+
+    #[test]
+    fn comments() {
+        let source = "\
+# Counts down.
+var n = 3   # start
+while (n > 0) {   # loop
+  # the body
+  print n
+  n = n - 1 # step
+  # after the body
+}   # end
+if (n) {
+} else {  # otherwise
+}
+# the end";
+        assert_formats(source, "\
+# Counts down.
+var n = 3  # start
+while (n > 0) {  # loop
+    # the body
+    print n
+    n = n - 1  # step
+    # after the body
+}  # end
+if (n) {} else {  # otherwise
+}
+# the end
+");
+    }
+
+    #[test]
+    fn blank_lines() {
+        let source = "\n\nvar a = 1\n\n\n\nvar b = 2\nfunc f() {\n\n    print a\n\n    # c\n\n    print b\n\n}\n\n# last\n";
+        assert_formats(source, "var a = 1\n\nvar b = 2\nfunc f() {\n    print a\n\n    # c\n\n    print b\n}\n\n# last\n");
+    }
+}
@@ -1,12 +1,27 @@
+use std::fmt;
+
 use crate::expr::Expr;
+use crate::{ast_printer, source_printer};
 
 /// A statement.
 #[derive(Clone, Debug, PartialEq)]
 pub struct Stmt {
     pub line: usize,  // The line of the source code from which the statement was derived.
+    pub column: usize,  // The column of the source code from which the statement was derived.
     pub stmt_type: StmtType,  // The type of the statement.
 }
 
+/// Shows the statement as source code, with blocks laid out as `--fmt` does, or with `{:#}` as the tree `--ast` shows it.
+impl fmt::Display for Stmt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            f.write_str(&ast_printer::print_stmt(self))
+        } else {
+            f.write_str(&source_printer::print_stmt(self, 0))
+        }
+    }
+}
+
 /// Possible types of statements.
 #[derive(Clone, Debug, PartialEq)]
 pub enum StmtType {
@@ -17,10 +32,18 @@ pub enum StmtType {
     Expression {
         expression: Expr,
     },
+    For {
+        // Each part of the loop header is optional, e.g., `for (;;) {}`.
+        initialiser: Option<Box<Stmt>>,
+        condition: Option<Expr>,
+        increment: Option<Box<Stmt>>,
+        body: Box<Stmt>,
+    },
     Function {
         name: String,
         parameters: Vec<String>,
         body: Box<Stmt>,
+        doc: Option<String>,  // The text of the `##` doc comments on the lines directly before the declaration, if the parser was given the comments.
     },
     If {
         condition: Expr,
@@ -30,15 +53,50 @@ pub enum StmtType {
     Print {
         expression: Expr,
     },
+    Repeat {
+        count: Expr,  // The number of times to execute the body, which is evaluated once, before the first time.
+        body: Box<Stmt>,
+    },
     Return {
         expression: Expr,
     },
+    Try {
+        body: Box<Stmt>,
+        catch: Option<(String, Box<Stmt>)>,  // The name the caught error is declared as, and the block which handles it.
+        finally: Option<Box<Stmt>>,  // The block executed after the others, however they finish. There is a `catch` or a `finally`, or both.
+    },
     VarDecl {
         name: String,
         value: Expr,
     },
+    VarUnpack {
+        // Declares several variables at once, e.g., `var q, r = divide(7, 2)`, from the elements of an array with one for each name.
+        names: Vec<String>,
+        value: Expr,
+    },
     While {
         condition: Expr,
         body: Box<Stmt>,
     },
 }
+
+impl StmtType {
+    /// Returns the name of the type of statement, e.g., `VarDecl`, as used in traces.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Self::Block { .. } => "Block",
+            Self::Break => "Break",
+            Self::Expression { .. } => "Expression",
+            Self::For { .. } => "For",
+            Self::Function { .. } => "Function",
+            Self::If { .. } => "If",
+            Self::Print { .. } => "Print",
+            Self::Repeat { .. } => "Repeat",
+            Self::Return { .. } => "Return",
+            Self::Try { .. } => "Try",
+            Self::VarDecl { .. } => "VarDecl",
+            Self::VarUnpack { .. } => "VarUnpack",
+            Self::While { .. } => "While",
+        }
+    }
+}
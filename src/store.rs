@@ -0,0 +1,134 @@
+//! The data files which `store_save()` and `store_load()` keep values in between runs of a script, e.g., a table of high scores.
+//!
+//! A script's store is the file next to it with the same name and the extension `.store`, e.g., `quiz.store` for `quiz.nea`, or
+//! `neal.store` in the current directory for code which is not in a file, e.g., in the REPL. It holds a dictionary literal of the
+//! names and values saved, written as a snapshot writes values, so it can be read, and even edited, as NEAL source code:
+//!
+//! ```text
+//! # The values saved by `store_save()`. Each is loaded by its name with `store_load()`.
+//! {'best': 12, 'names': ['Alice', 'Bob']}
+//! ```
+//!
+//! Loading a store never runs code from it: anything other than a literal, or a call to `freeze()` or `to_number()` or a string
+//! joined from pieces as a snapshot writes them, is not a valid store.
+
+use std::path::{Path, PathBuf};
+
+use crate::dictionary::Dictionary;
+use crate::expr::{Expr, ExprType};
+use crate::snapshot;
+use crate::token::{Token, TokenType};
+use crate::{Interpreter, Parser, Tokenizer, Value};
+
+/// The first line of every store.
+const HEADER: &str = "# The values saved by `store_save()`. Each is loaded by its name with `store_load()`.";
+
+/// Returns the path of the store of the script at the path, or of code which is not in a file if there is none.
+pub fn path(script: Option<&Path>) -> PathBuf {
+    match script {
+        Some(script) => script.with_extension("store"),
+        None => PathBuf::from("neal.store"),
+    }
+}
+
+/// Returns the entries of the store, or `None` if the text is not a store.
+pub fn parse(text: &str) -> Option<Dictionary> {
+    let tokens = Tokenizer::new(text).collect::<Result<Vec<Token>, _>>().ok()?;
+    let expr = Parser::new(tokens).parse_expression().ok()?;
+    if !matches!(expr.expr_type, ExprType::Dictionary { .. }) || !is_data(&expr) {
+        return None;
+    }
+    // A new interpreter has none of the script's variables, so the literal means the same wherever it is loaded.
+    match Interpreter::new().eval_parsed(&expr) {
+        Ok(Value::Dictionary(entries)) => Some(entries),
+        _ => None,
+    }
+}
+
+/// Returns the text of a store with the entries, or `None` if a value contains a function, which cannot be written to a file.
+pub fn write(entries: &Dictionary) -> Option<String> {
+    let literal = snapshot::value_literal(&Value::Dictionary(entries.clone()))?;
+    // Built-in functions are written by name, which a store does not allow, as the name could mean anything where it is loaded.
+    if entries.iter().any(|key_value| contains_function(&key_value.key) || contains_function(&key_value.value)) {
+        return None;
+    }
+    Some(format!("{}\n{}\n", HEADER, literal))
+}
+
+/// Returns whether the value is a function, or contains one.
+fn contains_function(value: &Value) -> bool {
+    match value {
+        Value::Function { .. } | Value::BuiltinFunction(..) => true,
+        Value::Array(array) => array.iter().any(contains_function),
+        Value::Dictionary(dictionary) => dictionary.iter().any(|key_value| contains_function(&key_value.key) || contains_function(&key_value.value)),
+        _ => false,
+    }
+}
+
+/// Returns whether the expression only writes a value, as `snapshot::value_literal()` writes them, rather than running any code.
+fn is_data(expr: &Expr) -> bool {
+    match &expr.expr_type {
+        ExprType::Literal { .. } => true,
+        ExprType::Grouping { expression } => is_data(expression),
+        // Negative numbers are written with a minus, and strings with both kinds of quote are joined from pieces with a plus.
+        ExprType::Unary { operator, right } => operator.type_ == TokenType::Minus && is_data(right),
+        ExprType::Binary { left, operator, right } => operator.type_ == TokenType::Plus && is_data(left) && is_data(right),
+        ExprType::Array { elements } => elements.iter().all(is_data),
+        ExprType::Dictionary { elements } => elements.iter().all(|key_value| is_data(&key_value.key) && is_data(&key_value.value)),
+        // Frozen values are written inside `freeze()`, and infinities and `NaN` as `to_number()` of a string.
+        ExprType::Call { callee, arguments, named_arguments } => {
+            matches!(&callee.expr_type, ExprType::Variable { name } if name == "freeze" || name == "to_number")
+                && arguments.iter().all(is_data) && named_arguments.is_empty()
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+
+    use crate::dictionary::Dictionary;
+    use crate::{run_source, Interpreter, Value};
+
+    use super::{parse, path, write};
+
+    #[test]
+    fn paths() {
+        assert_eq!(path(Some(Path::new("games/quiz.nea"))), PathBuf::from("games/quiz.store"));
+        assert_eq!(path(None), PathBuf::from("neal.store"));
+    }
+
+    #[test]
+    fn round_trip() {
+        let mut interpreter = Interpreter::new();
+        run_source("var entries = {'best': -12.5, 'names': freeze(['Alice']), 1: {true: null}, 'nan': to_number('NaN')}", &mut interpreter).unwrap();
+        run_source("entries['quote'] = \"it's \" + '\"ok\"'", &mut interpreter).unwrap();
+        let Some(Value::Dictionary(entries)) = interpreter.get_global("entries") else { unreachable!() };
+        let text = write(&entries).unwrap();
+        assert_eq!(
+            text.lines().nth(1),
+            Some("{'best': -12.5, 'names': freeze(['Alice']), 1: {true: null}, 'nan': to_number('NaN'), 'quote': ('it' + \"'\" + 's \"ok\"')}"),
+        );
+        let loaded = parse(&text).unwrap();
+        assert_eq!(loaded.size(), 5);
+        assert_eq!(loaded.get(&Value::String_(String::from("quote")), 0, 0), Ok(&Value::String_(String::from("it's \"ok\""))));
+        assert_eq!(loaded.get(&Value::String_(String::from("best")), 0, 0), Ok(&Value::Number(-12.5)));
+        assert!(loaded.get(&Value::String_(String::from("names")), 0, 0).unwrap().is_frozen());
+        assert_eq!(parse(&write(&Dictionary::new()).unwrap()).map(|entries| entries.size()), Some(0));
+
+        // Functions cannot be written to a file.
+        run_source("var functions = {'f': size}", &mut interpreter).unwrap();
+        let Some(Value::Dictionary(functions)) = interpreter.get_global("functions") else { unreachable!() };
+        assert_eq!(write(&functions), None);
+    }
+
+    #[test]
+    fn only_data() {
+        // Loading a store never runs code, so a store which has been changed to call a function is not a store.
+        for text in ["{'a': input('?')}", "{'a': size}", "{'a': 1 - 2}", "[1, 2]", "{'a': 1", "{'a': 1} {}", "'a"] {
+            assert!(parse(text).is_none(), "{}", text);
+        }
+        assert!(parse("# A comment.\n{'a': (-1), 'b': to_number('inf')}").is_some());
+    }
+}
@@ -0,0 +1,108 @@
+//! The test runner for `--test`, which runs the tests written in NEAL scripts.
+//!
+//! A test is a global function whose name starts with `test_` and which takes no arguments. Each script is run in its own
+//! interpreter, so that its functions are declared, and then each of its tests is called in alphabetical order. A test passes
+//! if it returns without an error, e.g., from `assert_eq()`.
+
+use std::path::{Path, PathBuf};
+use std::{fs, io};
+
+use nea::{run_source, Interpreter, Value};
+
+use crate::cli::{Input, Options};
+
+/// The prefix of the name of every test function.
+const TEST_PREFIX: &str = "test_";
+
+/// The number of tests which passed and failed.
+#[derive(Debug, Default, PartialEq)]
+pub struct Summary {
+    pub passed: usize,
+    pub failed: usize,
+}
+
+/// Runs the tests in the script, or in every `.nea` file in and below the directory if the script is one, and prints a line for
+/// each test and a summary. A script which cannot be run to the end counts as one failed test, and its tests are not run.
+/// If a file or directory cannot be read, the error is returned.
+pub fn run_tests(script: &Input, options: &Options) -> io::Result<Summary> {
+    let mut summary = Summary::default();
+    match script {
+        Input::File(path) if Path::new(path).is_dir() => {
+            for file in nea_files(Path::new(path))? {
+                let source = fs::read_to_string(&file)?;
+                run_file(&file.display().to_string(), Some(&file), &source, options, &mut summary);
+            }
+        },
+        Input::File(path) => run_file(path, Some(Path::new(path)), &fs::read_to_string(path)?, options, &mut summary),
+        Input::Stdin => run_file("-", None, &io::read_to_string(io::stdin())?, options, &mut summary),
+        Input::Eval(source) => run_file("-e", None, source, options, &mut summary),
+    }
+    println!("{} passed, {} failed.", summary.passed, summary.failed);
+    Ok(summary)
+}
+
+/// Runs the script, then its tests, adding the results to `summary`. The path is given if the script is a file, for `include()`.
+fn run_file(name: &str, path: Option<&Path>, source: &str, options: &Options, summary: &mut Summary) {
+    let mut interpreter = Interpreter::with_args(options.args.clone());
+    if let Some(path) = path {
+        interpreter.set_script_path(path);
+    }
+    interpreter.set_strict(options.strict);
+    interpreter.set_truthy(options.truthy);
+    interpreter.set_colour(options.output_colour());
+    // Any errors are reported by the tokenizer, parser or interpreter.
+    if run_source(source, &mut interpreter).is_err() {
+        println!("{} ... FAILED", name);
+        summary.failed += 1;
+        return;
+    }
+
+    for test in test_names(&interpreter) {
+        // The test is called by name, so that a failure's stack trace shows which test it was in.
+        if run_source(&format!("{}()", test), &mut interpreter).is_ok() {
+            println!("{}: {} ... ok", name, test);
+            summary.passed += 1;
+        } else {
+            println!("{}: {} ... FAILED", name, test);
+            summary.failed += 1;
+        }
+    }
+}
+
+/// Returns the names of the tests declared by the script, in alphabetical order.
+fn test_names(interpreter: &Interpreter) -> Vec<String> {
+    interpreter.globals().into_iter()
+        .filter(|(name, value)| name.starts_with(TEST_PREFIX) && matches!(value, Value::Function { parameters, .. } if parameters.is_empty()))
+        .map(|(name, _)| name)
+        .collect()
+}
+
+/// Returns the paths of the `.nea` files in and below the directory, in alphabetical order.
+fn nea_files(directory: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    let mut entries: Vec<PathBuf> = fs::read_dir(directory)?.map(|entry| entry.map(|entry| entry.path())).collect::<io::Result<_>>()?;
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            files.extend(nea_files(&path)?);
+        } else if path.extension().is_some_and(|extension| extension == "nea") {
+            files.push(path);
+        }
+    }
+    Ok(files)
+}
+
+#[cfg(test)]
+mod tests {
+    use nea::{run_source, Interpreter};
+
+    use super::test_names;
+
+    #[test]
+    fn names() {
+        let mut interpreter = Interpreter::new();
+        let source = "func test_b() {}\nfunc test_a() {}\nfunc test_args(x) {}\nfunc helper() {}\nvar test_value = 1";
+        run_source(source, &mut interpreter).unwrap();
+        assert_eq!(test_names(&interpreter), ["test_a", "test_b"]);
+    }
+}
@@ -1,3 +1,5 @@
+use std::fmt;
+
 /// Possible types of tokens.
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum TokenType {
@@ -18,9 +20,9 @@ pub enum TokenType {
     True, False, String_, Number,
 
     // Keywords.
-    And, Break, Else,
+    And, Break, Catch, Else, Finally,
     Func, For, If, Null, Or, Print,
-    Return, Var, While,
+    Repeat, Return, Try, Var, While,
 
     Identifier, Eof
 }
@@ -41,4 +43,48 @@ pub struct Token {
     pub lexeme: String,  // The source code substring from which the token was constructed.
     pub literal: Literal,  // The literal value (number/string/Boolean) the token represents; if the token is not a literal, will be set to the `Null` variant.
     pub line: usize,  // The line number of the source code from which the token was constructed.
+    pub column: usize,  // The column number of the first character of the token within its line, starting from 1.
+}
+
+/// A comment. Comments are not tokens, as the parser ignores them, but the tokenizer keeps them for tools such as the formatter.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Comment {
+    pub text: String,  // The text of the comment, from the `#` to the end of its line, without trailing whitespace.
+    pub line: usize,  // The line number of the comment.
+    pub column: usize,  // The column number of the `#`.
+    pub trailing: bool,  // Whether the comment follows code on the same line, rather than being on a line of its own.
+}
+
+impl fmt::Display for Token {
+    /// Formats the token as a row of the table printed by `--tokens`: its position, type, lexeme and, for literals, its value.
+    /// Lexemes and strings are quoted and escaped so that, e.g., a string spanning lines stays on one row.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let position = format!("{}:{}", self.line, self.column);
+        let type_ = format!("{:?}", self.type_);
+        let lexeme = format!("{:?}", self.lexeme);
+        let literal = match &self.literal {
+            Literal::Number(number) => number.to_string(),
+            Literal::String_(string) => format!("{:?}", string),
+            Literal::Bool(bool) => bool.to_string(),
+            // Tokens which are not literals have a `Null` literal, which is left blank.
+            Literal::Null => String::new(),
+        };
+        let row = format!("{:<8} {:<13} {:<16} {}", position, type_, lexeme, literal);
+        write!(f, "{}", row.trim_end())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Literal, Token, TokenType};
+
+    #[test]
+    fn display() {
+        let number = Token { type_: TokenType::Number, lexeme: String::from("2.50"), literal: Literal::Number(2.5), line: 3, column: 12 };
+        assert_eq!(number.to_string(), "3:12     Number        \"2.50\"           2.5");
+        let string = Token { type_: TokenType::String_, lexeme: String::from("'a\nb'"), literal: Literal::String_(String::from("a\nb")), line: 1, column: 1 };
+        assert_eq!(string.to_string(), "1:1      String_       \"'a\\nb'\"         \"a\\nb\"");
+        let keyword = Token { type_: TokenType::Print, lexeme: String::from("print"), literal: Literal::Null, line: 1, column: 1 };
+        assert_eq!(keyword.to_string(), "1:1      Print         \"print\"");
+    }
 }
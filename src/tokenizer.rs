@@ -1,5 +1,13 @@
-use crate::token::{Token, TokenType, Literal};
-use crate::error::{self, ErrorType};
+use std::iter::FusedIterator;
+
+use crate::token::{Comment, Token, TokenType, Literal};
+use crate::error::{self, ErrorLimit, ErrorType};
+
+/// The keywords of the language, including the literal keywords. These cannot be used as names.
+pub const KEYWORDS: [&str; 18] = [
+    "and", "break", "catch", "else", "false", "finally", "func", "for", "if",
+    "null", "or", "print", "repeat", "return", "true", "try", "var", "while",
+];
 
 /// The states of the DFA.
 #[derive(Debug)]
@@ -31,64 +39,114 @@ enum State {
     InStringDouble,  // Double quote strings.
     InStringSingle,  // Single quote strings.
     GotString,
+    GotZero,  // A `0`, which may start a prefix such as `0x`.
     InNumberBeforeDot,
     InNumberAfterDot,
+    GotExponent,  // The `e` of an exponent, e.g., in `2.5e-3`.
+    GotExponentSign,
+    InExponent,
+    InNumberWithPrefix,  // The digits of a whole number in hexadecimal, octal or binary, after `0x`, `0o` or `0b`.
     InWord,  // Identifiers and keywords.
     NoOp,  // No operation.
 }
 
 /// Performs lexical analysis.
-pub struct Tokenizer<'a> {
-    source: &'a str,  // The source code string.
-    tokens: Vec<Token>,  // The result sequence of tokens.
+/// Tokens can either be collected all at once with `tokenize()`, or pulled one at a time as the `Tokenizer` is also an `Iterator`.
+///
+/// As an iterator, each token is only scanned when it is asked for, so a syntax highlighter or another parser can stop early or
+/// handle each token as it arrives. Errors are produced in place of the tokens they stop, and are not reported to the standard
+/// error stream as `tokenize()` does. Scanning carries on after an error, and the last item is always the `Eof` token.
+///
+/// ```
+/// use nea::token::TokenType;
+/// use nea::Tokenizer;
+///
+/// let types: Vec<_> = Tokenizer::new("print 1 @ x")
+///     .map(|result| result.map(|token| token.type_))
+///     .collect();
+/// assert!(matches!(types[..], [Ok(TokenType::Print), Ok(TokenType::Number), Err(_), Ok(TokenType::Identifier), Ok(TokenType::Eof)]));
+/// ```
+pub struct Tokenizer {
+    source: Vec<char>,  // The characters of the source code string. Indexing by character rather than by byte means multi-byte characters can never split a slice.
     start: usize,  // An index pointing to the start of the current token. This will be used to set the value of lexemes and literals.
     current_index: usize,  // An index pointing to the next character to be scanned.
     current_line: usize,  // The current line number.
+    line_start: usize,  // An index pointing to the first character of the current line. This is used to work out column numbers.
+    start_line: usize,  // The line number of the start of the current token. Strings can span several lines, so this may differ from `current_line`.
+    start_column: usize,  // The column number of the start of the current token.
+    finished: bool,  // Whether the EOF token has been produced, after which no more tokens are produced.
+    error_limit: ErrorLimit,  // How many errors `tokenize()` collects and reports.
+    comments: Vec<Comment>,  // The comments scanned so far, in order. They do not become tokens, but are kept for tools such as the formatter.
 }
 
-impl<'a> Tokenizer<'a> {
+impl Tokenizer {
     /// Constructs a `Tokenizer` instance with the given source code string.
-    pub fn new(source: &'a str) -> Self {
+    pub fn new(source: &str) -> Self {
+        Self::with_error_limit(source, ErrorLimit::default())
+    }
+
+    /// Constructs a `Tokenizer` instance with the given source code string, which collects and reports errors according to `error_limit`.
+    pub fn with_error_limit(source: &str, error_limit: ErrorLimit) -> Self {
         Self {
-            source,  // Equivalent to `souce: source`.
-            tokens: Vec::new(),
+            source: source.chars().collect(),
             start: 0,
             current_index: 0,
             current_line: 1,
+            line_start: 0,
+            start_line: 1,
+            start_column: 1,
+            finished: false,
+            error_limit,
+            comments: Vec::new(),
         }
     }
 
+    /// Returns the comments scanned so far, in the order they appear in the source code.
+    /// All of them are available once the `Eof` token has been produced, e.g., after `tokenize()`.
+    pub fn comments(&self) -> &[Comment] {
+        &self.comments
+    }
+
     /// The interface method which creates and returns an array of tokens.
-    pub fn tokenize(&mut self) -> Result<Vec<Token>, ErrorType> {
-        while self.source.chars().nth(self.current_index).is_some() {
-            // If `current_index` has not reached the end of the source code, scan the next token.
-            match self.scan_token() {
-                Ok(token_opt) => {
-                    // If no error occurred...
-                    if let Some(token) = token_opt {
-                        // and `scan_token()` returned a token, append it to the sequence of tokens.
-                        // It is possible that `scan_token()` returns `Ok(None)` if the DFA lands on the `NoOp` state.
-                        self.tokens.push(token);
+    /// Any errors are reported to the standard error stream, as well as returned.
+    pub fn tokenize(&mut self) -> Result<Vec<Token>, Vec<ErrorType>> {
+        let result = self.tokenize_unreported();
+        if let Err(errors) = &result {
+            // If errors occurred, report the errors and return an `Err` variant so that the driver code knows to end execution.
+            error::report_errors_up_to(&errors[..], self.error_limit.max_reported);
+        }
+        result
+    }
+
+    /// Returns the tokens as `tokenize()` does, but leaves the errors to the caller to report, e.g., to an interpreter's error output.
+    pub fn tokenize_unreported(&mut self) -> Result<Vec<Token>, Vec<ErrorType>> {
+        let mut tokens: Vec<Token> = Vec::new();  // The result sequence of tokens.
+
+        // As with the parser, we aim to collect as many errors as possible in one run and report them all at once.
+        let mut errors: Vec<ErrorType> = Vec::new();
+
+        let error_limit = self.error_limit;
+        for result in self.by_ref() {
+            match result {
+                // If no error occurred, append the token to the sequence of tokens.
+                Ok(token) => tokens.push(token),
+                // If an error has occurred while scanning the token, collect the error; the iterator has already skipped past it.
+                Err(error) => {
+                    errors.push(error);
+                    if error_limit.fail_fast {
+                        // In fail-fast mode, there is no point scanning any further.
+                        break;
                     }
                 },
-                Err(error) => {
-                    // If an error has occurred during the `scan_token()` call, report the error.
-                    error::report_errors(&[error.clone()]);
-                    // Return an `Err` variant so that the driver code knows to end execution.
-                    return Err(error);
-                }
             }
         }
 
-        // Push an EOF token to end the token sequence.
-        self.tokens.push(Token {
-            type_: TokenType::Eof,
-            lexeme: String::from(""),
-            literal: Literal::Null,
-            line: self.current_line
-        });
-
-        Ok(self.tokens.clone())
+        if errors.is_empty() {
+            // The sequence of tokens is moved out rather than copied.
+            Ok(tokens)
+        } else {
+            Err(errors)
+        }
     }
 
     /// Scans the token starting from `current_index` by simulating the DFA.
@@ -98,11 +156,14 @@ impl<'a> Tokenizer<'a> {
         loop {
             // It is possible that the tokenizer reaches the end of the source code before `scan_token()` returns.
             // So, we account for `current_char_opt` being None in all possible current states.
-            let current_char_opt = self.source.chars().nth(self.current_index);
+            let current_char_opt = self.source.get(self.current_index).copied();
 
             match current_state {
                 State::Start => {
-                    self.start = self.current_index;  // The next token starts here.
+                    // The next token starts here.
+                    self.start = self.current_index;
+                    self.start_line = self.current_line;
+                    self.start_column = self.column_of(self.current_index);
                     if let Some(current_char) = current_char_opt {
                         match current_char {
                             '(' => current_state = State::GotLeftParen,
@@ -130,7 +191,8 @@ impl<'a> Tokenizer<'a> {
                             '"' => current_state = State::InStringDouble,
                             '\'' => current_state = State::InStringSingle,
                             
-                            '0'..='9' => current_state = State::InNumberBeforeDot,
+                            '0' => current_state = State::GotZero,
+                            '1'..='9' => current_state = State::InNumberBeforeDot,
                             
                             // Identifiers and keywords.
                             'a'..='z' | 'A'..='Z' | '_' => current_state = State::InWord,
@@ -142,15 +204,18 @@ impl<'a> Tokenizer<'a> {
                             ' ' | '\r' | '\t' => current_state = State::NoOp,
     
                             '\n' => {
-                                self.current_line += 1;
+                                self.new_line();
                                 current_state = State::NoOp;
                             },
     
                             other => {
                                 // If the character does not match any of the above rules, raise an `UnexpectedCharacter` error.
+                                // The character is skipped so that scanning can carry on from the next one.
+                                self.current_index += 1;
                                 return Err(ErrorType::UnexpectedCharacter {
                                     character: other,
-                                    line: self.current_line,
+                                    line: self.start_line,
+                                    column: self.start_column,
                                 });
                             },
                         }
@@ -217,46 +282,60 @@ impl<'a> Tokenizer<'a> {
                 State::InStringDouble => {
                     if current_char_opt == Some('"') {
                         current_state = State::GotString;
+                    } else if current_char_opt == Some('\n') {
+                        // Strings can span several lines.
+                        self.new_line();
                     } else if current_char_opt.is_none() {
                         // We have reached the end and there was no closing `"`.
-                        return Err(ErrorType::UnterminatedString);
+                        // The error points to the opening `"`, and there is nothing left to recover from.
+                        return Err(ErrorType::UnterminatedString { line: self.start_line, column: self.start_column });
                     }
                 },
                 State::InStringSingle => {
                     if current_char_opt == Some('\'') {
                         current_state = State::GotString;
+                    } else if current_char_opt == Some('\n') {
+                        self.new_line();
                     } else if current_char_opt.is_none() {
                         // We have reached the end and there was no closing `'`.
-                        return Err(ErrorType::UnterminatedString);
+                        return Err(ErrorType::UnterminatedString { line: self.start_line, column: self.start_column });
                     }
                 },
                 State::GotString => {
                     return Ok(Some(self.construct_token_with_literal(
                         TokenType::String_,
-                        Literal::String_(self.source[self.start+1..self.current_index-1].to_owned())
+                        Literal::String_(self.source[self.start+1..self.current_index-1].iter().collect())
                     )));
                 },
 
+                State::GotZero => {
+                    match current_char_opt {
+                        // A prefix gives the base of the digits after it.
+                        Some('x' | 'o' | 'b') => current_state = State::InNumberWithPrefix,
+                        Some('.') => current_state = State::InNumberAfterDot,
+                        Some('e' | 'E') => current_state = State::GotExponent,
+                        Some('0'..='9' | '_') => current_state = State::InNumberBeforeDot,
+                        // Otherwise, the number is just `0`.
+                        _ => return self.construct_number_token().map(Some),
+                    }
+                },
                 State::InNumberBeforeDot => {
                     match current_char_opt {
                         Some(current_char) => {
                             if current_char == '.' {
                                 current_state = State::InNumberAfterDot;
-                            } else if !current_char.is_ascii_digit() {
-                                // If it is not '0'-'9' (or a '.'), we have reached the end of the number.
-                                return Ok(Some(self.construct_token_with_literal(
-                                    TokenType::Number,
-                                    Literal::Number(self.source[self.start..self.current_index].parse().unwrap())
-                                )));
+                            } else if current_char == 'e' || current_char == 'E' {
+                                current_state = State::GotExponent;
+                            } else if !(current_char.is_ascii_digit() || current_char == '_') {
+                                // If it is not '0'-'9' (or a '.', an exponent or a digit separator), we have reached the end of the number.
+                                return self.construct_number_token().map(Some);
                             }
-                            // If it is a digit, we stay in this state and keep consuming digits.
+                            // If it is a digit or a separator, we stay in this state and keep consuming digits.
+                            // Where separators can go is checked once the whole number has been scanned.
                         },
                         None => {
                             // If we have reached the end of the source code, then we can return with the number we constructed so far.
-                            return Ok(Some(self.construct_token_with_literal(
-                                TokenType::Number,
-                                Literal::Number(self.source[self.start..self.current_index].parse().unwrap())
-                            )));
+                            return self.construct_number_token().map(Some);
                         }
                     }
                 },
@@ -264,44 +343,71 @@ impl<'a> Tokenizer<'a> {
                     // Similar to above, but do not allow for '.' as we already have one in the number.
                     match current_char_opt {
                         Some(current_char) => {
-                            if !current_char.is_ascii_digit() {
+                            if current_char == 'e' || current_char == 'E' {
+                                current_state = State::GotExponent;
+                            } else if !(current_char.is_ascii_digit() || current_char == '_') {
                                 // We have reached the end of the number.
-                                return Ok(Some(self.construct_token_with_literal(
-                                    TokenType::Number,
-                                    Literal::Number(self.source[self.start..self.current_index].parse().unwrap())
-                                )));
+                                return self.construct_number_token().map(Some);
                             }
-                            // If it is a digit, we stay in this state and keep consuming digits.
+                            // If it is a digit or a separator, we stay in this state and keep consuming digits.
                         },
                         None => {
                             // Again, if we have reached the end of the source code, then we can return with the number we constructed so far.
-                            return Ok(Some(self.construct_token_with_literal(
-                                TokenType::Number,
-                                Literal::Number(self.source[self.start..self.current_index].parse().unwrap())
-                            )));
+                            return self.construct_number_token().map(Some);
                         }
                     }
                 },
+                State::GotExponent => {
+                    // The exponent can have a sign. If it has no digits, e.g., `1e`, the number is reported as invalid.
+                    match current_char_opt {
+                        Some('+' | '-') => current_state = State::GotExponentSign,
+                        Some('0'..='9') => current_state = State::InExponent,
+                        _ => return self.construct_number_token().map(Some),
+                    }
+                },
+                State::GotExponentSign => {
+                    match current_char_opt {
+                        Some('0'..='9') => current_state = State::InExponent,
+                        _ => return self.construct_number_token().map(Some),
+                    }
+                },
+                State::InExponent => {
+                    if current_char_opt.is_none_or(|current_char| !(current_char.is_ascii_digit() || current_char == '_')) {
+                        return self.construct_number_token().map(Some);
+                    }
+                },
+
+                State::InNumberWithPrefix => {
+                    // Every letter and digit is part of the number, even those which are not digits in its base, so that, e.g., `0b12`
+                    // is reported as an invalid number rather than being read as `0b1` followed by `2`.
+                    if current_char_opt.is_none_or(|current_char| !(current_char.is_ascii_alphanumeric() || current_char == '_')) {
+                        return self.construct_number_token().map(Some);
+                    }
+                },
 
                 State::InWord => {
-                    if current_char_opt.map_or(true, |current_char| !(current_char.is_ascii_alphanumeric() || current_char == '_')) {
+                    if current_char_opt.is_none_or(|current_char| !(current_char.is_ascii_alphanumeric() || current_char == '_')) {
                         // Construct the token now if:
                         // we are at the end of the source code, or
                         // if the current character is not alphanumeric or an `_` (i.e., we have now scanned through the complete word).
-                        let lexeme = &self.source[self.start..self.current_index];
-                        return Ok(Some(match lexeme {
+                        let lexeme: String = self.source[self.start..self.current_index].iter().collect();
+                        return Ok(Some(match lexeme.as_str() {
                             "and" => self.construct_token(TokenType::And),
                             "break" => self.construct_token(TokenType::Break),
+                            "catch" => self.construct_token(TokenType::Catch),
                             "else" => self.construct_token(TokenType::Else),
                             "false" => self.construct_token_with_literal(TokenType::False, Literal::Bool(false)),
+                            "finally" => self.construct_token(TokenType::Finally),
                             "func" => self.construct_token(TokenType::Func),
                             "for" => self.construct_token(TokenType::For),
                             "if" => self.construct_token(TokenType::If),
                             "null" => self.construct_token_with_literal(TokenType::Null, Literal::Null),
                             "or" => self.construct_token(TokenType::Or),
                             "print" => self.construct_token(TokenType::Print),
+                            "repeat" => self.construct_token(TokenType::Repeat),
                             "return" => self.construct_token(TokenType::Return),
                             "true" => self.construct_token_with_literal(TokenType::True, Literal::Bool(true)),
+                            "try" => self.construct_token(TokenType::Try),
                             "var" => self.construct_token(TokenType::Var),
                             "while" => self.construct_token(TokenType::While),
                             _ => self.construct_token(TokenType::Identifier)
@@ -312,9 +418,11 @@ impl<'a> Tokenizer<'a> {
                 State::InComment => {
                     // If we have a new line or we have reached the end of the file, the comment has ended.
                     if current_char_opt == Some('\n') {
-                        self.current_line += 1;
+                        self.record_comment();
+                        self.new_line();
                         current_state = State::NoOp;
                     } else if current_char_opt.is_none() {
+                        self.record_comment();
                         current_state = State::NoOp;
                     }
                 },
@@ -327,29 +435,145 @@ impl<'a> Tokenizer<'a> {
         }
     }
 
+    /// A helper function which returns the `Token` object which ends every token sequence.
+    fn construct_eof_token(&self) -> Token {
+        Token {
+            type_: TokenType::Eof,
+            lexeme: String::from(""),
+            literal: Literal::Null,
+            line: self.current_line,
+            column: self.column_of(self.current_index),
+        }
+    }
+
     /// A helper function which returns a fully formed `Token` object.
     fn construct_token_with_literal(&mut self, token_type: TokenType, literal: Literal) -> Token {
         Token {
             type_: token_type,
-            lexeme: String::from(&self.source[self.start..self.current_index]),
+            lexeme: self.source[self.start..self.current_index].iter().collect(),
             literal,
-            line: self.current_line,
+            line: self.start_line,
+            column: self.start_column,
         }
     }
 
+    /// A helper function which returns the `Number` token for the digits between `start` and `current_index`.
+    /// The DFA accepts some lexemes which are not numbers, e.g., `1e` with no exponent, `1__000` with separators which are not
+    /// between two digits, or `0b12`, whose digits are not all digits in its base, so these are reported as errors. So is a number too
+    /// large to be kept, e.g., `1e400`, which would otherwise be read as infinity.
+    fn construct_number_token(&mut self) -> Result<Token, ErrorType> {
+        let lexeme: String = self.source[self.start..self.current_index].iter().collect();
+        let (digits, radix) = match lexeme.get(..2) {
+            Some("0x") => (&lexeme[2..], 16),
+            Some("0o") => (&lexeme[2..], 8),
+            Some("0b") => (&lexeme[2..], 2),
+            _ => (lexeme.as_str(), 10),
+        };
+        let number = match separated_digits(digits, radix) {
+            Some(digits) if radix == 10 => digits.parse().ok(),
+            Some(digits) => parse_whole_number(&digits, radix),
+            None => None,
+        };
+        match number.filter(|number: &f64| number.is_finite()) {
+            Some(number) => Ok(self.construct_token_with_literal(TokenType::Number, Literal::Number(number))),
+            None => Err(ErrorType::InvalidNumber {
+                lexeme,
+                line: self.start_line,
+                column: self.start_column,
+            }),
+        }
+    }
+
+    /// Keeps the comment between `start` and `current_index`, which is the end of its line.
+    fn record_comment(&mut self) {
+        let text: String = self.source[self.start..self.current_index].iter().collect();
+        self.comments.push(Comment {
+            text: text.trim_end().to_owned(),
+            line: self.start_line,
+            column: self.start_column,
+            // A comment is trailing if there is anything other than whitespace before it on its line.
+            trailing: self.source[self.line_start..self.start].iter().any(|c| !c.is_whitespace()),
+        });
+    }
+
+    /// Moves on to the next line. This is called when the character at `current_index` is a new line.
+    fn new_line(&mut self) {
+        self.current_line += 1;
+        self.line_start = self.current_index + 1;
+    }
+
+    /// Returns the column number of the character at `index`, which has to be on the current line.
+    fn column_of(&self, index: usize) -> usize {
+        index - self.line_start + 1
+    }
+
     /// A helper function which returns the `Token` object for a non-literal token.
     fn construct_token(&mut self, token_type: TokenType) -> Token {
         self.construct_token_with_literal(token_type, Literal::Null)
     }
 }
 
+/// Produces the tokens lazily, one `scan_token()` at a time.
+/// Errors are produced in place of the token that could not be scanned, and scanning then carries on past them.
+/// The last item is always the EOF token.
+impl Iterator for Tokenizer {
+    type Item = Result<Token, ErrorType>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.finished {
+            return None;
+        }
+
+        while self.current_index < self.source.len() {
+            // If `current_index` has not reached the end of the source code, scan the next token.
+            match self.scan_token() {
+                // It is possible that `scan_token()` returns `Ok(None)` if the DFA lands on the `NoOp` state.
+                // In that case, keep scanning.
+                Ok(None) => (),
+                Ok(Some(token)) => return Some(Ok(token)),
+                // `scan_token()` has already skipped past the source of the error, so the next call carries on from there.
+                Err(error) => return Some(Err(error)),
+            }
+        }
+
+        // End the token sequence with an EOF token.
+        self.finished = true;
+        Some(Ok(self.construct_eof_token()))
+    }
+}
+
+// Once the EOF token has been produced, `finished` makes every later call return `None`.
+impl FusedIterator for Tokenizer {}
+
+/// Returns the digits without their separators, or `None` if a separator is not between two digits in the base, e.g., in `1__000`,
+/// `1_` or `1_.5`.
+fn separated_digits(digits: &str, radix: u32) -> Option<String> {
+    let chars: Vec<char> = digits.chars().collect();
+    let is_digit = |i: usize| i < chars.len() && chars[i].is_digit(radix);
+    for (i, c) in chars.iter().enumerate() {
+        if *c == '_' && (i == 0 || !is_digit(i - 1) || !is_digit(i + 1)) {
+            return None;
+        }
+    }
+    Some(chars.into_iter().filter(|c| *c != '_').collect())
+}
+
+/// Returns the whole number written with the digits in the base, or `None` if there are no digits or one is not a digit in the base.
+/// The number is built up as an `f64`, so that numbers too large for an integer are rounded, as decimal numbers are.
+fn parse_whole_number(digits: &str, radix: u32) -> Option<f64> {
+    if digits.is_empty() {
+        return None;
+    }
+    digits.chars().try_fold(0.0, |number, digit| Some(number * f64::from(radix) + f64::from(digit.to_digit(radix)?)))
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::{token::{Token, TokenType, Literal}, error::ErrorType};
+    use crate::{token::{Comment, Token, TokenType, Literal}, error::{ErrorLimit, ErrorType}};
 
     use super::Tokenizer;
 
-    fn tokenize(source: &str) -> Result<Vec<Token>, ErrorType> {
+    fn tokenize(source: &str) -> Result<Vec<Token>, Vec<ErrorType>> {
         let mut tokenizer = Tokenizer::new(source);
         tokenizer.tokenize()
     }
@@ -358,21 +582,21 @@ mod tests {
     fn one_char_tokens() {
         let source = "( ) { } [ ] : , - % + ; / *";
         assert_eq!(Ok(vec![
-            Token { type_: TokenType::LeftParen, lexeme: String::from("("), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::RightParen, lexeme: String::from(")"), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::LeftCurly, lexeme: String::from("{"), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::RightCurly, lexeme: String::from("}"), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::LeftSquare, lexeme: String::from("["), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::RightSquare, lexeme: String::from("]"), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::Colon, lexeme: String::from(":"), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::Comma, lexeme: String::from(","), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::Minus, lexeme: String::from("-"), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::Percent, lexeme: String::from("%"), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::Plus, lexeme: String::from("+"), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::Semicolon, lexeme: String::from(";"), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::Slash, lexeme: String::from("/"), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::Star, lexeme: String::from("*"), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::Eof, lexeme: String::from(""), literal: Literal::Null, line: 1 },
+            Token { type_: TokenType::LeftParen, lexeme: String::from("("), literal: Literal::Null, line: 1, column: 1 },
+            Token { type_: TokenType::RightParen, lexeme: String::from(")"), literal: Literal::Null, line: 1, column: 3 },
+            Token { type_: TokenType::LeftCurly, lexeme: String::from("{"), literal: Literal::Null, line: 1, column: 5 },
+            Token { type_: TokenType::RightCurly, lexeme: String::from("}"), literal: Literal::Null, line: 1, column: 7 },
+            Token { type_: TokenType::LeftSquare, lexeme: String::from("["), literal: Literal::Null, line: 1, column: 9 },
+            Token { type_: TokenType::RightSquare, lexeme: String::from("]"), literal: Literal::Null, line: 1, column: 11 },
+            Token { type_: TokenType::Colon, lexeme: String::from(":"), literal: Literal::Null, line: 1, column: 13 },
+            Token { type_: TokenType::Comma, lexeme: String::from(","), literal: Literal::Null, line: 1, column: 15 },
+            Token { type_: TokenType::Minus, lexeme: String::from("-"), literal: Literal::Null, line: 1, column: 17 },
+            Token { type_: TokenType::Percent, lexeme: String::from("%"), literal: Literal::Null, line: 1, column: 19 },
+            Token { type_: TokenType::Plus, lexeme: String::from("+"), literal: Literal::Null, line: 1, column: 21 },
+            Token { type_: TokenType::Semicolon, lexeme: String::from(";"), literal: Literal::Null, line: 1, column: 23 },
+            Token { type_: TokenType::Slash, lexeme: String::from("/"), literal: Literal::Null, line: 1, column: 25 },
+            Token { type_: TokenType::Star, lexeme: String::from("*"), literal: Literal::Null, line: 1, column: 27 },
+            Token { type_: TokenType::Eof, lexeme: String::from(""), literal: Literal::Null, line: 1, column: 28 },
         ]), tokenize(source));
     }
 
@@ -380,15 +604,15 @@ mod tests {
     fn one_two_char_tokens() {
         let source = "! != = == > >= < <=";
         assert_eq!(Ok(vec![
-            Token { type_: TokenType::Bang, lexeme: String::from("!"), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::BangEqual, lexeme: String::from("!="), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::Equal, lexeme: String::from("="), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::EqualEqual, lexeme: String::from("=="), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::Greater, lexeme: String::from(">"), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::GreaterEqual, lexeme: String::from(">="), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::Less, lexeme: String::from("<"), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::LessEqual, lexeme: String::from("<="), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::Eof, lexeme: String::from(""), literal: Literal::Null, line: 1 },
+            Token { type_: TokenType::Bang, lexeme: String::from("!"), literal: Literal::Null, line: 1, column: 1 },
+            Token { type_: TokenType::BangEqual, lexeme: String::from("!="), literal: Literal::Null, line: 1, column: 3 },
+            Token { type_: TokenType::Equal, lexeme: String::from("="), literal: Literal::Null, line: 1, column: 6 },
+            Token { type_: TokenType::EqualEqual, lexeme: String::from("=="), literal: Literal::Null, line: 1, column: 8 },
+            Token { type_: TokenType::Greater, lexeme: String::from(">"), literal: Literal::Null, line: 1, column: 11 },
+            Token { type_: TokenType::GreaterEqual, lexeme: String::from(">="), literal: Literal::Null, line: 1, column: 13 },
+            Token { type_: TokenType::Less, lexeme: String::from("<"), literal: Literal::Null, line: 1, column: 16 },
+            Token { type_: TokenType::LessEqual, lexeme: String::from("<="), literal: Literal::Null, line: 1, column: 18 },
+            Token { type_: TokenType::Eof, lexeme: String::from(""), literal: Literal::Null, line: 1, column: 20 },
         ]), tokenize(source));
     }
 
@@ -396,55 +620,151 @@ mod tests {
     fn literals() {
         let source = "\"abc\" 123 \"abc123\" 123.5 \"\" 123abc 5.5";
         assert_eq!(Ok(vec![
-            Token { type_: TokenType::String_, lexeme: String::from("\"abc\""), literal: Literal::String_(String::from("abc")), line: 1 },
-            Token { type_: TokenType::Number, lexeme: String::from("123"), literal: Literal::Number(123.0), line: 1 },
-            Token { type_: TokenType::String_, lexeme: String::from("\"abc123\""), literal: Literal::String_(String::from("abc123")), line: 1 },
-            Token { type_: TokenType::Number, lexeme: String::from("123.5"), literal: Literal::Number(123.5), line: 1 },
-            Token { type_: TokenType::String_, lexeme: String::from("\"\""), literal: Literal::String_(String::from("")), line: 1 },
-            Token { type_: TokenType::Number, lexeme: String::from("123"), literal: Literal::Number(123.0), line: 1 },
-            Token { type_: TokenType::Identifier, lexeme: String::from("abc"), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::Number, lexeme: String::from("5.5"), literal: Literal::Number(5.5), line: 1 },
-            Token { type_: TokenType::Eof, lexeme: String::from(""), literal: Literal::Null, line: 1 },
+            Token { type_: TokenType::String_, lexeme: String::from("\"abc\""), literal: Literal::String_(String::from("abc")), line: 1, column: 1 },
+            Token { type_: TokenType::Number, lexeme: String::from("123"), literal: Literal::Number(123.0), line: 1, column: 7 },
+            Token { type_: TokenType::String_, lexeme: String::from("\"abc123\""), literal: Literal::String_(String::from("abc123")), line: 1, column: 11 },
+            Token { type_: TokenType::Number, lexeme: String::from("123.5"), literal: Literal::Number(123.5), line: 1, column: 20 },
+            Token { type_: TokenType::String_, lexeme: String::from("\"\""), literal: Literal::String_(String::from("")), line: 1, column: 26 },
+            Token { type_: TokenType::Number, lexeme: String::from("123"), literal: Literal::Number(123.0), line: 1, column: 29 },
+            Token { type_: TokenType::Identifier, lexeme: String::from("abc"), literal: Literal::Null, line: 1, column: 32 },
+            Token { type_: TokenType::Number, lexeme: String::from("5.5"), literal: Literal::Number(5.5), line: 1, column: 36 },
+            Token { type_: TokenType::Eof, lexeme: String::from(""), literal: Literal::Null, line: 1, column: 39 },
         ]), tokenize(source));
     }
 
+    #[test]
+    fn prefixed_numbers() {
+        let numbers: Vec<Literal> = tokenize("0x1F 0xff 0o17 0b1010 0 0.5 07").unwrap().into_iter().map(|token| token.literal).collect();
+        assert_eq!(numbers, [31.0, 255.0, 15.0, 10.0, 0.0, 0.5, 7.0].map(Literal::Number).into_iter().chain([Literal::Null]).collect::<Vec<Literal>>());
+
+        // Letters and digits after a prefix are part of the number, even if they are not digits in its base.
+        for lexeme in ["0b102", "0o8", "0xfg", "0x"] {
+            assert_eq!(tokenize(lexeme), Err(vec![ErrorType::InvalidNumber { lexeme: String::from(lexeme), line: 1, column: 1 }]));
+        }
+        // Only a lone `0` starts a prefix.
+        let types: Vec<TokenType> = tokenize("10x").unwrap().into_iter().map(|token| token.type_).collect();
+        assert_eq!(types, [TokenType::Number, TokenType::Identifier, TokenType::Eof]);
+    }
+
+    #[test]
+    fn exponents_and_separators() {
+        let numbers: Vec<Literal> = tokenize("1e9 2.5e-3 1E+2 0e5 1_000_000 0.000_1 1.5e1_0 0xff_ff").unwrap().into_iter().map(|token| token.literal).collect();
+        let expected = [1e9, 2.5e-3, 100.0, 0.0, 1_000_000.0, 0.0001, 1.5e10, 65535.0];
+        assert_eq!(numbers, expected.map(Literal::Number).into_iter().chain([Literal::Null]).collect::<Vec<Literal>>());
+
+        // An exponent must have digits, a separator must be between two digits, and the number must not be too large to be kept.
+        for lexeme in ["1e", "1e+", "1__000", "1_", "1_.5", "1._5", "0x_f", "0_", "1e400", "2e308"] {
+            assert_eq!(tokenize(lexeme), Err(vec![ErrorType::InvalidNumber { lexeme: String::from(lexeme), line: 1, column: 1 }]), "{}", lexeme);
+        }
+    }
+
     #[test]
     fn line_count() {
         let source = "12\n23";
         assert_eq!(Ok(vec![
-            Token { type_: TokenType::Number, lexeme: String::from("12"), literal: Literal::Number(12.0), line: 1 },
-            Token { type_: TokenType::Number, lexeme: String::from("23"), literal: Literal::Number(23.0), line: 2 },
-            Token { type_: TokenType::Eof, lexeme: String::from(""), literal: Literal::Null, line: 2 },
+            Token { type_: TokenType::Number, lexeme: String::from("12"), literal: Literal::Number(12.0), line: 1, column: 1 },
+            Token { type_: TokenType::Number, lexeme: String::from("23"), literal: Literal::Number(23.0), line: 2, column: 1 },
+            Token { type_: TokenType::Eof, lexeme: String::from(""), literal: Literal::Null, line: 2, column: 3 },
         ]), tokenize(source));
     }
 
     #[test]
     fn unterminated_string() {
         let source = "\"abc\nabc\nabc";
-        assert_eq!(Err(ErrorType::UnterminatedString), tokenize(source));
+        assert_eq!(Err(vec![ErrorType::UnterminatedString { line: 1, column: 1 }]), tokenize(source));
+
+        let source = "12\n  'abc";
+        assert_eq!(Err(vec![ErrorType::UnterminatedString { line: 2, column: 3 }]), tokenize(source));
+    }
+
+    #[test]
+    fn multi_line_string() {
+        let source = "'a\nb' 5";
+        assert_eq!(Ok(vec![
+            Token { type_: TokenType::String_, lexeme: String::from("'a\nb'"), literal: Literal::String_(String::from("a\nb")), line: 1, column: 1 },
+            Token { type_: TokenType::Number, lexeme: String::from("5"), literal: Literal::Number(5.0), line: 2, column: 4 },
+            Token { type_: TokenType::Eof, lexeme: String::from(""), literal: Literal::Null, line: 2, column: 5 },
+        ]), tokenize(source));
+    }
+
+    #[test]
+    fn error_recovery() {
+        // Every error is collected, rather than just the first.
+        let source = "1 @ 2\n$ 'abc";
+        assert_eq!(Err(vec![
+            ErrorType::UnexpectedCharacter { character: '@', line: 1, column: 3 },
+            ErrorType::UnexpectedCharacter { character: '$', line: 2, column: 1 },
+            ErrorType::UnterminatedString { line: 2, column: 3 },
+        ]), tokenize(source));
     }
 
     #[test]
     fn identifiers_and_keywords() {
         let source = "a a2 if and or ifandor";
         assert_eq!(Ok(vec![
-            Token { type_: TokenType::Identifier, lexeme: String::from("a"), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::Identifier, lexeme: String::from("a2"), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::If, lexeme: String::from("if"), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::And, lexeme: String::from("and"), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::Or, lexeme: String::from("or"), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::Identifier, lexeme: String::from("ifandor"), literal: Literal::Null, line: 1 },
-            Token { type_: TokenType::Eof, lexeme: String::from(""), literal: Literal::Null, line: 1 },
+            Token { type_: TokenType::Identifier, lexeme: String::from("a"), literal: Literal::Null, line: 1, column: 1 },
+            Token { type_: TokenType::Identifier, lexeme: String::from("a2"), literal: Literal::Null, line: 1, column: 3 },
+            Token { type_: TokenType::If, lexeme: String::from("if"), literal: Literal::Null, line: 1, column: 6 },
+            Token { type_: TokenType::And, lexeme: String::from("and"), literal: Literal::Null, line: 1, column: 9 },
+            Token { type_: TokenType::Or, lexeme: String::from("or"), literal: Literal::Null, line: 1, column: 13 },
+            Token { type_: TokenType::Identifier, lexeme: String::from("ifandor"), literal: Literal::Null, line: 1, column: 16 },
+            Token { type_: TokenType::Eof, lexeme: String::from(""), literal: Literal::Null, line: 1, column: 23 },
         ]), tokenize(source));
     }
 
+    #[test]
+    fn iterator() {
+        let mut tokenizer = Tokenizer::new("a @ b");
+        assert_eq!(Some(Ok(Token { type_: TokenType::Identifier, lexeme: String::from("a"), literal: Literal::Null, line: 1, column: 1 })), tokenizer.next());
+        assert_eq!(Some(Err(ErrorType::UnexpectedCharacter { character: '@', line: 1, column: 3 })), tokenizer.next());
+        // Scanning carries on after an error, so the tokens after it are still produced.
+        assert_eq!(Some(Ok(Token { type_: TokenType::Identifier, lexeme: String::from("b"), literal: Literal::Null, line: 1, column: 5 })), tokenizer.next());
+        assert_eq!(Some(Ok(Token { type_: TokenType::Eof, lexeme: String::from(""), literal: Literal::Null, line: 1, column: 6 })), tokenizer.next());
+        assert_eq!(None, tokenizer.next());
+        assert_eq!(None, tokenizer.next());
+    }
+
     #[test]
     fn comments() {
         let source = "1\n#abc\n#abc\n1";
         assert_eq!(Ok(vec![
-            Token { type_: TokenType::Number, lexeme: String::from("1"), literal: Literal::Number(1.0), line: 1 },
-            Token { type_: TokenType::Number, lexeme: String::from("1"), literal: Literal::Number(1.0), line: 4 },
-            Token { type_: TokenType::Eof, lexeme: String::from(""), literal: Literal::Null, line: 4 },
+            Token { type_: TokenType::Number, lexeme: String::from("1"), literal: Literal::Number(1.0), line: 1, column: 1 },
+            Token { type_: TokenType::Number, lexeme: String::from("1"), literal: Literal::Number(1.0), line: 4, column: 1 },
+            Token { type_: TokenType::Eof, lexeme: String::from(""), literal: Literal::Null, line: 4, column: 2 },
         ]), tokenize(source));
     }
+
+    #[test]
+    fn kept_comments() {
+        let mut tokenizer = Tokenizer::new("  # first \nvar x = 1  # second\r\n#");
+        assert!(tokenizer.tokenize().is_ok());
+        assert_eq!(tokenizer.comments(), [
+            Comment { text: String::from("# first"), line: 1, column: 3, trailing: false },
+            Comment { text: String::from("# second"), line: 2, column: 12, trailing: true },
+            Comment { text: String::from("#"), line: 3, column: 1, trailing: false },
+        ]);
+    }
+
+    #[test]
+    fn multi_byte_characters() {
+        let source = "'é😀' # ünïcode\n€";
+        let mut tokenizer = Tokenizer::new(source);
+        assert_eq!(Some(Ok(Token { type_: TokenType::String_, lexeme: String::from("'é😀'"), literal: Literal::String_(String::from("é😀")), line: 1, column: 1 })), tokenizer.next());
+        assert_eq!(Some(Err(ErrorType::UnexpectedCharacter { character: '€', line: 2, column: 1 })), tokenizer.next());
+    }
+
+    #[test]
+    fn fail_fast() {
+        let mut tokenizer = Tokenizer::with_error_limit("1 @ 2 $", ErrorLimit { max_reported: None, fail_fast: true });
+        assert_eq!(Err(vec![ErrorType::UnexpectedCharacter { character: '@', line: 1, column: 3 }]), tokenizer.tokenize());
+    }
+
+    #[test]
+    fn keywords() {
+        // Every keyword in `KEYWORDS` is scanned as a keyword rather than an identifier.
+        for keyword in super::KEYWORDS {
+            let token = Tokenizer::new(keyword).next().unwrap().unwrap();
+            assert_ne!(TokenType::Identifier, token.type_);
+        }
+    }
 }
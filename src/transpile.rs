@@ -0,0 +1,1075 @@
+//! Translation of NEAL scripts into Python, as `--transpile py` prints it, so that a program can be compared with the same
+//! program in a mainstream language, or carried on with in one.
+//!
+//! The Python is meant to be read as well as run. Each statement becomes the closest Python statement, e.g., a `for` loop becomes
+//! a `while` loop, and names are kept unless they are Python keywords, which get a `_` after them. Where Python behaves
+//! differently, the program calls a small `_neal_` helper function instead, e.g., `_neal_show()` writes values the way `print`
+//! does. The helpers are defined at the top of the program, and only the ones it uses are included.
+//!
+//! Some differences are not translated:
+//! - Python shares an array or dictionary when it is assigned or passed to a function, where NEAL copies it.
+//! - A variable declared in a block can still be used after the block, as Python only gives functions their own variables.
+//! - Dictionaries are printed in the order their keys were added, `true == 1` is `True`, and `NaN` is not equal to itself.
+//! - Assigning to the element one past the end of an array, e.g., `a[size(a)] = x`, raises an error rather than appending.
+//! - `freeze()` does nothing, so frozen arrays and dictionaries can still be changed.
+//! - An unpacking assignment which fails part of the way through, e.g., `a, b[5] = 1, 2`, leaves the targets before it assigned.
+//! - `eval()` and `include()` raise an error, as the NEAL code they run cannot be translated until the program runs.
+//! - `+` only converts the other side to a string where one side is known to be a string, e.g., a string literal, so adding a
+//!   number to a string held in a variable raises an error.
+//! - Errors are raised as Python exceptions, and only when Python would raise one, e.g., adding a number to an array does not
+//!   raise one until the line runs. Assigning to something which is not a variable or an element is translated as it is, so
+//!   Python rejects the whole program.
+
+use std::collections::BTreeSet;
+
+use crate::expr::{Expr, ExprType};
+use crate::stmt::{Stmt, StmtType};
+use crate::token::{Literal, TokenType};
+use crate::value::BuiltinFunction;
+use crate::visitor::{walk_expr, walk_stmt, Visitor};
+
+/// The text each level of a block is indented by.
+const INDENT: &str = "    ";
+
+/// Python keywords and the Python built-in functions used by the helpers, which cannot be used as names.
+const RESERVED: [&str; 41] = [
+    "False", "None", "True", "as", "assert", "async", "await", "class", "continue", "def", "del", "elif", "except", "finally",
+    "from", "global", "import", "in", "is", "lambda", "nonlocal", "not", "pass", "raise", "try", "with", "yield",
+    "callable", "decimal", "dict", "float", "int", "isinstance", "len", "list", "math", "repr", "sorted", "str", "sys", "type",
+];
+
+/// A Python function written at the top of the program for it to call.
+struct Helper {
+    name: &'static str,  // The name of the helper without the `_neal_` prefix, which is the name of the built-in function it replaces if there is one.
+    imports: &'static [&'static str],  // The modules the helper needs imported.
+    uses: &'static [&'static str],  // The other helpers the helper calls.
+    code: &'static str,  // The definition of the helper.
+}
+
+/// Every helper, in the order they are defined in.
+const HELPERS: [Helper; 62] = [
+    Helper { name: "show", imports: &["decimal", "math"], uses: &[], code: "\
+def _neal_show(value):
+    \"\"\"Returns the value written as NEAL's `print` writes it.\"\"\"
+    if value is None:
+        return 'null'
+    if isinstance(value, bool):
+        return 'true' if value else 'false'
+    if isinstance(value, (int, float)):
+        if math.isnan(value):
+            return 'NaN'
+        if math.isinf(value):
+            return 'inf' if value > 0 else '-inf'
+        if value == 0:
+            return '0'
+        text = repr(float(value))
+        if 1e-6 <= abs(value) < 1e21:
+            return str(int(value)) if value == int(value) else format(decimal.Decimal(text), 'f')
+        mantissa, exponent = text.split('e')
+        return f'{mantissa}e{int(exponent)}'
+    if isinstance(value, list):
+        return '[' + ', '.join(_neal_show(element) for element in value) + ']'
+    if isinstance(value, dict):
+        return '{' + ', '.join(_neal_show(key) + ': ' + _neal_show(element) for key, element in value.items()) + '}'
+    if callable(value):
+        return '<function>'
+    return str(value)" },
+    Helper { name: "type", imports: &[], uses: &[], code: "\
+def _neal_type(value):
+    \"\"\"Returns the name of the value's type in NEAL.\"\"\"
+    if value is None:
+        return 'Null'
+    if isinstance(value, bool):
+        return 'Boolean'
+    if isinstance(value, (int, float)):
+        return 'Number'
+    if isinstance(value, str):
+        return 'String'
+    if isinstance(value, list):
+        return 'Array'
+    if isinstance(value, dict):
+        return 'Dictionary'
+    return 'Function'" },
+    Helper { name: "mod", imports: &["math"], uses: &[], code: "\
+def _neal_mod(left, right):
+    \"\"\"Returns the remainder with the sign of `left`, as NEAL's `%` does, where Python's has the sign of `right`.\"\"\"
+    if right == 0:
+        raise ZeroDivisionError('divisor is 0')
+    result = math.fmod(left, right)
+    return int(result) if isinstance(left, int) and isinstance(right, int) else result" },
+    Helper { name: "key", imports: &[], uses: &[], code: "\
+def _neal_key(key):
+    \"\"\"Returns the index as an `int` if it is a whole number, as Python only indexes lists with `int`s.\"\"\"
+    return int(key) if isinstance(key, float) and key.is_integer() else key" },
+    Helper { name: "repeat", imports: &[], uses: &["show"], code: "\
+def _neal_repeat(count):
+    \"\"\"Returns the range for a `repeat` loop, which has to be a whole number of times.\"\"\"
+    if isinstance(count, bool) or not isinstance(count, (int, float)):
+        raise TypeError(f'expected a number of times to repeat, got `{_neal_show(count)}`')
+    if count < 0 or count != int(count):
+        raise ValueError(f'the number of times to repeat is {_neal_show(count)}, which is not a whole number of at least 0')
+    return range(int(count))" },
+    Helper { name: "set", imports: &[], uses: &["key"], code: "\
+def _neal_set(target, key, value):
+    \"\"\"Assigns to the element and returns the value, for an element assignment inside an expression.\"\"\"
+    target[_neal_key(key)] = value
+    return value" },
+    Helper { name: "bindings", imports: &["sys"], uses: &[], code: "\
+def _neal_bindings(variables):
+    \"\"\"Returns a copy of the variables and functions of the program, in alphabetical order, without the helpers and modules.\"\"\"
+    return {name: value for name, value in sorted(variables.items())
+            if not name.startswith('_neal_') and not name.startswith('__') and not isinstance(value, type(sys))}" },
+    Helper { name: "colour", imports: &["os", "sys"], uses: &[], code: "\
+def _neal_colour(name):
+    \"\"\"Returns the ANSI escape code of the colour or style, or an empty string if the output is not a terminal which shows colours.\"\"\"
+    codes = {'reset': 0, 'bold': 1, 'underline': 4, 'black': 30, 'red': 31, 'green': 32, 'yellow': 33, 'blue': 34, 'magenta': 35,
+             'cyan': 36, 'white': 37, 'grey': 90}
+    if name not in codes:
+        raise ValueError(f'`{name}` is not a colour or style which the terminal can show')
+    return f'\\x1b[{codes[name]}m' if sys.stdout.isatty() and 'NO_COLOR' not in os.environ else ''" },
+    Helper { name: "turtle", imports: &[], uses: &[], code: "\
+# The turtle moved by `_neal_forward()` and `_neal_turn()`, and the lines it has drawn, as `((x1, y1), (x2, y2), colour)`.
+_neal_turtle = {'x': 0.0, 'y': 0.0, 'heading': 90.0, 'down': True, 'colour': 'black', 'lines': []}" },
+    Helper { name: "whole", imports: &[], uses: &["show"], code: "\
+def _neal_whole(n):
+    \"\"\"Returns the number as an `int`, if it is a whole number which NEAL can store exactly.\"\"\"
+    if n != int(n) or abs(n) > 9007199254740992:
+        raise ValueError(f'{_neal_show(n)} is not a whole number which can be stored exactly')
+    return int(n)" },
+    Helper { name: "slice", imports: &[], uses: &["whole"], code: "\
+def _neal_slice(sequence, start, stop, step):
+    \"\"\"Returns the slice as NEAL takes it, where bounds past the ends are clamped, and negative ones are before the start rather than
+    counting from the end.\"\"\"
+    step = 1 if step is None else _neal_whole(step)
+    if step == 0:
+        raise ValueError('the step of a slice cannot be 0')
+    # Counting down, -1 stands for before the first element.
+    low, high = (0, len(sequence)) if step > 0 else (-1, len(sequence) - 1)
+    start = (low if step > 0 else high) if start is None else min(max(_neal_whole(start), low), high)
+    stop = (high if step > 0 else low) if stop is None else min(max(_neal_whole(stop), low), high)
+    elements = [sequence[i] for i in range(start, stop, step)]
+    return ''.join(elements) if isinstance(sequence, str) else elements" },
+    Helper { name: "base", imports: &[], uses: &["show"], code: "\
+def _neal_base(base):
+    \"\"\"Returns the base given to `to_number()` or `to_base()` as an `int`, if it is a whole number from 2 to 36.\"\"\"
+    if base not in range(2, 37):
+        raise ValueError(f'the base is {_neal_show(base)}, which is not a whole number from 2 to 36')
+    return int(base)" },
+    Helper { name: "store", imports: &["decimal", "math", "os", "re", "sys"], uses: &["show"], code: "\
+def _neal_store_path():
+    \"\"\"Returns the path of the file which `store_save()` keeps values in, next to the script, as NEAL keeps it.\"\"\"
+    return os.path.splitext(sys.argv[0])[0] + '.store' if sys.argv[0] else 'neal.store'
+
+
+def _neal_store_literal(value):
+    \"\"\"Returns the value written as a NEAL literal, as NEAL writes it to a store.\"\"\"
+    if value is None:
+        return 'null'
+    if isinstance(value, bool):
+        return 'true' if value else 'false'
+    if isinstance(value, (int, float)):
+        if not math.isfinite(value):
+            return f\"to_number('{_neal_show(value)}')\"
+        # NEAL writes numbers in a store without an exponent, so that they can be read back.
+        return str(int(value)) if value == int(value) else format(decimal.Decimal(repr(float(value))), 'f')
+    if isinstance(value, str):
+        # NEAL's strings have no escapes, so one with both kinds of quote is joined from pieces.
+        if \"'\" not in value:
+            return f\"'{value}'\"
+        if '\"' not in value:
+            return f'\"{value}\"'
+        return '(' + ' + \"\\'\" + '.join(f\"'{part}'\" for part in value.split(\"'\")) + ')'
+    if isinstance(value, list):
+        return '[' + ', '.join(_neal_store_literal(element) for element in value) + ']'
+    if isinstance(value, dict):
+        return '{' + ', '.join(_neal_store_literal(key) + ': ' + _neal_store_literal(element) for key, element in value.items()) + '}'
+    raise TypeError('a function cannot be saved, as it cannot be written to a file')
+
+
+def _neal_store_read():
+    \"\"\"Returns the values in the store, or none if it does not exist yet. Like NEAL, this never runs code from the store.\"\"\"
+    path = _neal_store_path()
+    try:
+        with open(path) as file:
+            text = file.read()
+    except FileNotFoundError:
+        return {}
+    tokens = [token for token in re.findall(r\"#[^\\n]*|'[^']*'|\\\"[^\\\"]*\\\"|-?[0-9.]+|\\w+|\\S\", text) if not token.startswith('#')]
+    position = 0
+
+    def expect(token):
+        nonlocal position
+        if tokens[position] != token:
+            raise ValueError
+        position += 1
+
+    def literal():
+        nonlocal position
+        token = tokens[position]
+        position += 1
+        if token in ('[', '{'):
+            close, elements = ']' if token == '[' else '}', []
+            while tokens[position] != close:
+                element = literal()
+                if token == '{':
+                    expect(':')
+                    element = (element, literal())
+                elements.append(element)
+                if tokens[position] != close:
+                    expect(',')
+            position += 1
+            return elements if token == '[' else dict(elements)
+        if token in ('freeze', 'to_number'):
+            expect('(')
+            value = literal()
+            expect(')')
+            return value if token == 'freeze' else float(value)
+        if token == '(':
+            # A grouping, or a string joined from pieces.
+            value = literal()
+            while tokens[position] == '+':
+                position += 1
+                value += literal()
+            expect(')')
+            return value
+        if token[0] in '\\'\"':
+            return token[1:-1]
+        if token in ('true', 'false', 'null'):
+            return {'true': True, 'false': False, 'null': None}[token]
+        return float(token)
+
+    try:
+        entries = literal()
+        if position != len(tokens) or not isinstance(entries, dict):
+            raise ValueError
+        return entries
+    except (IndexError, TypeError, ValueError):
+        raise ValueError(f'`{path}` is not a store of saved values') from None" },
+    Helper { name: "error", imports: &[], uses: &[], code: "\
+def _neal_error(exception):
+    \"\"\"Returns the exception caught by a `catch` block as NEAL gives errors to them. Python's exceptions have no NEAL error code or position.\"\"\"
+    return {'code': None, 'message': str(exception), 'line': None, 'column': None}" },
+    Helper { name: "append", imports: &[], uses: &[], code: "\
+def _neal_append(array, value):
+    array.append(value)
+    return array" },
+    Helper { name: "args", imports: &["sys"], uses: &[], code: "\
+def _neal_args():
+    return sys.argv[1:]" },
+    Helper { name: "assert_eq", imports: &[], uses: &["show"], code: "\
+def _neal_assert_eq(left, right):
+    if left != right:
+        raise AssertionError(f'`{_neal_show(left)}` is not equal to `{_neal_show(right)}`')" },
+    Helper { name: "clear_screen", imports: &[], uses: &[], code: "\
+def _neal_clear_screen():
+    print('\\x1b[2J\\x1b[H', end='')" },
+    Helper { name: "colored", imports: &[], uses: &["colour", "show"], code: "\
+def _neal_colored(text, name):
+    colour = _neal_colour(name)
+    return f'{colour}{_neal_show(text)}{_neal_colour(\"reset\") if colour else \"\"}'" },
+    Helper { name: "defined", imports: &["sys"], uses: &[], code: "\
+def _neal_defined(name):
+    \"\"\"Returns whether the name is a variable or function where it is called, or one of NEAL's built-in functions.\"\"\"
+    builtins = {
+        'append', 'args', 'assert_eq', 'clear_screen', 'colored', 'defined', 'eval', 'factors', 'forward', 'freeze',
+        'gcd', 'get', 'get_cell', 'get_key', 'globals', 'identity', 'include', 'input', 'input_number', 'inspect',
+        'is_prime', 'keys', 'lcm', 'locals', 'matrix', 'move_cursor', 'pen_color', 'pen_down', 'pen_up', 'read_all',
+        'read_line', 'remove', 'resize', 'save_drawing', 'set_color', 'set_default', 'size', 'sort', 'sort_in_place',
+        'store_load', 'store_save', 'to_base', 'to_fixed', 'to_number', 'to_string', 'transpose', 'turn', 'values'
+    }
+    frame = sys._getframe(1)
+    return name in frame.f_locals or name in frame.f_globals or name in builtins" },
+    Helper { name: "eval", imports: &[], uses: &[], code: "\
+def _neal_eval(source):
+    raise NotImplementedError('eval() runs NEAL code, which cannot be translated into Python while the program runs')" },
+    Helper { name: "factors", imports: &[], uses: &["whole"], code: "\
+def _neal_factors(n):
+    n, factors, divisor = abs(_neal_whole(n)), [], 2
+    while divisor * divisor <= n:
+        while n % divisor == 0:
+            factors.append(divisor)
+            n //= divisor
+        divisor += 1 if divisor == 2 else 2
+    if n >= 2:
+        factors.append(n)
+    return factors" },
+    Helper { name: "forward", imports: &["math"], uses: &["turtle"], code: "\
+def _neal_forward(distance):
+    turtle = _neal_turtle
+    if not math.isfinite(distance):
+        return
+    radians = math.radians(turtle['heading'])
+    x, y = turtle['x'] + distance * math.cos(radians), turtle['y'] + distance * math.sin(radians)
+    if turtle['down']:
+        turtle['lines'].append(((turtle['x'], turtle['y']), (x, y), turtle['colour']))
+    turtle['x'], turtle['y'] = x, y" },
+    Helper { name: "freeze", imports: &[], uses: &[], code: "\
+def _neal_freeze(value):
+    \"\"\"Python has no frozen lists or dictionaries which can still be used as they are, so the value is left as it is.\"\"\"
+    return value" },
+    Helper { name: "gcd", imports: &["math"], uses: &["whole"], code: "\
+def _neal_gcd(a, b):
+    return math.gcd(_neal_whole(a), _neal_whole(b))" },
+    Helper { name: "get", imports: &[], uses: &[], code: "\
+def _neal_get(dictionary, key, default):
+    return dictionary.get(key, default)" },
+    Helper { name: "get_cell", imports: &[], uses: &[], code: "\
+def _neal_get_cell(grid, row, column, default):
+    # Negative indexes count from the end in Python, but are off the grid in NEAL.
+    if row < 0 or column < 0 or row != int(row) or column != int(column) or row >= len(grid) or column >= len(grid[int(row)]):
+        return default
+    return grid[int(row)][int(column)]" },
+    Helper { name: "get_key", imports: &["os", "sys"], uses: &[], code: "\
+def _neal_get_key():
+    \"\"\"Reads one keypress without waiting for Enter, named as NEAL names it, e.g., `a`, `enter` or `up`.\"\"\"
+    names = {'\\n': 'enter', '\\r': 'enter', '\\t': 'tab', '\\x08': 'backspace', '\\x7f': 'backspace'}
+    if not sys.stdin.isatty():
+        key = sys.stdin.read(1)
+        return names.get(key, key) if key else None
+    # The terminal modules only exist on Unix, so they are only imported when they are needed.
+    import termios, tty
+    original = termios.tcgetattr(sys.stdin)
+    try:
+        tty.setraw(sys.stdin)
+        # An arrow key is sent as several bytes at once, which are read together.
+        key = os.read(sys.stdin.fileno(), 8).decode(errors='replace')
+    finally:
+        termios.tcsetattr(sys.stdin, termios.TCSADRAIN, original)
+    if key == '\\x03':
+        raise KeyboardInterrupt
+    if key.startswith('\\x1b'):
+        arrows = {'A': 'up', 'B': 'down', 'C': 'right', 'D': 'left'}
+        return arrows.get(key[2:3], 'escape') if key[1:2] in ('[', 'O') else 'escape'
+    return names.get(key[:1], key[:1]) if key else None" },
+    Helper { name: "globals", imports: &["sys"], uses: &["bindings"], code: "\
+def _neal_globals():
+    return _neal_bindings(sys._getframe(1).f_globals)" },
+    Helper { name: "identity", imports: &[], uses: &[], code: "\
+def _neal_identity(size):
+    return [[1 if row == column else 0 for column in range(int(size))] for row in range(int(size))]" },
+    Helper { name: "include", imports: &[], uses: &[], code: "\
+def _neal_include(path):
+    raise NotImplementedError(f'include({path!r}) runs a NEAL file, which has to be translated into Python and imported instead')" },
+    Helper { name: "input", imports: &["sys"], uses: &["show"], code: "\
+def _neal_input(prompt):
+    print(_neal_show(prompt), end='', flush=True)
+    return sys.stdin.readline().strip()" },
+    Helper { name: "input_number", imports: &["math", "sys"], uses: &["show"], code: "\
+def _neal_input_number(prompt):
+    while True:
+        print(_neal_show(prompt), end='', flush=True)
+        line = sys.stdin.readline()
+        if not line:
+            raise ValueError('no number was typed before the end of the input')
+        try:
+            number = float(line.strip())
+            if math.isfinite(number):
+                return number
+        except ValueError:
+            pass
+        print(f'`{line.strip()}` is not a number. Please try again.')" },
+    Helper { name: "inspect", imports: &[], uses: &["show", "type"], code: "\
+def _neal_inspect(value):
+    \"\"\"Returns a one-line description of the value's type and shape, as NEAL's `inspect()` does.\"\"\"
+    def plural(count, noun):
+        if count == 1:
+            return '1 ' + noun
+        return f'{count} {noun[:-1]}ies' if noun.endswith('y') else f'{count} {noun}s'
+
+    def types(values):
+        return '/'.join(dict.fromkeys(_neal_type(element) for element in values))
+
+    if value is None:
+        return 'Null'
+    if isinstance(value, bool):
+        return 'Boolean ' + _neal_show(value)
+    if isinstance(value, (int, float)):
+        return 'Number ' + _neal_show(value)
+    if isinstance(value, str):
+        return 'String of ' + plural(len(value), 'character')
+    if isinstance(value, list):
+        return f'Array of {plural(len(value), \"element\")} ({types(value)})' if value else 'Array of 0 elements'
+    if isinstance(value, dict):
+        if not value:
+            return 'Dictionary of 0 entries'
+        return f'Dictionary of {plural(len(value), \"entry\")} ({types(value.keys())} keys, {types(value.values())} values)'
+    if value.__name__.startswith('_neal_'):
+        return f'Built-in function `{value.__name__[6:]}`'
+    parameters = value.__code__.co_varnames[:value.__code__.co_argcount]
+    if not parameters:
+        return 'Function with no parameters'
+    return f'Function with {plural(len(parameters), \"parameter\")} ({\", \".join(parameters)})'" },
+    Helper { name: "is_prime", imports: &[], uses: &["factors", "whole"], code: "\
+def _neal_is_prime(n):
+    n = _neal_whole(n)
+    return n >= 2 and _neal_factors(n) == [n]" },
+    Helper { name: "keys", imports: &[], uses: &[], code: "\
+def _neal_keys(dictionary):
+    return list(dictionary.keys())" },
+    Helper { name: "lcm", imports: &["math"], uses: &["whole"], code: "\
+def _neal_lcm(a, b):
+    return math.lcm(_neal_whole(a), _neal_whole(b))" },
+    Helper { name: "locals", imports: &["sys"], uses: &["bindings"], code: "\
+def _neal_locals():
+    # Outside any function, Python's local variables are its global variables, as NEAL's are.
+    return _neal_bindings(sys._getframe(1).f_locals)" },
+    Helper { name: "matrix", imports: &[], uses: &[], code: "\
+def _neal_matrix(rows, columns, fill):
+    # Each row is a list of its own, so that changing one cell does not change the cell below it.
+    return [[fill] * int(columns) for _ in range(int(rows))]" },
+    Helper { name: "move_cursor", imports: &[], uses: &[], code: "\
+def _neal_move_cursor(row, column):
+    print(f'\\x1b[{int(row)};{int(column)}H', end='')" },
+    Helper { name: "pen_color", imports: &[], uses: &["turtle"], code: "\
+def _neal_pen_color(name):
+    if name not in ('reset', 'black', 'red', 'green', 'yellow', 'blue', 'magenta', 'cyan', 'white', 'grey'):
+        raise ValueError(f'`{name}` is not a colour or style which the terminal can show')
+    _neal_turtle['colour'] = 'black' if name == 'reset' else name" },
+    Helper { name: "pen_down", imports: &[], uses: &["turtle"], code: "\
+def _neal_pen_down():
+    _neal_turtle['down'] = True" },
+    Helper { name: "pen_up", imports: &[], uses: &["turtle"], code: "\
+def _neal_pen_up():
+    _neal_turtle['down'] = False" },
+    Helper { name: "read_all", imports: &["sys"], uses: &[], code: "\
+def _neal_read_all():
+    return sys.stdin.read()" },
+    Helper { name: "read_line", imports: &["sys"], uses: &[], code: "\
+def _neal_read_line():
+    line = sys.stdin.readline()
+    if not line:
+        return None
+    # Python reads `\\r\\n` line endings as `\\n`.
+    return line.removesuffix('\\n')" },
+    Helper { name: "remove", imports: &[], uses: &["key"], code: "\
+def _neal_remove(target, key):
+    del target[_neal_key(key)]
+    return target" },
+    Helper { name: "resize", imports: &[], uses: &[], code: "\
+def _neal_resize(array, size, fill):
+    del array[int(size):]
+    array.extend([fill] * (int(size) - len(array)))
+    return array" },
+    Helper { name: "save_drawing", imports: &["math"], uses: &["turtle"], code: "\
+def _neal_save_drawing(path):
+    \"\"\"Writes the drawing as an SVG image, in the same way as NEAL's `save_drawing()`.\"\"\"
+    def point(x, y):
+        # SVG's `y` axis points down the page, and coordinates are rounded half away from zero, as Rust rounds them.
+        return tuple(math.copysign(math.floor(abs(n) * 100 + 0.5), n) / 100 + 0.0 for n in (x, -y))
+    ends = [(point(*start), point(*end), colour) for start, end, colour in _neal_turtle['lines']]
+    points = [point for start, end, _ in ends for point in (start, end)] or [(0.0, 0.0)]
+    left, top = min(x for x, _ in points), min(y for _, y in points)
+    width, height = max(x for x, _ in points) - left + 20, max(y for _, y in points) - top + 20
+    with open(path, 'w') as file:
+        file.write(f'<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width:.2f}\" height=\"{height:.2f}\" '
+                   f'viewBox=\"{left - 10:.2f} {top - 10:.2f} {width:.2f} {height:.2f}\">\\n')
+        for (x1, y1), (x2, y2), colour in ends:
+            file.write(f'  <line x1=\"{x1:.2f}\" y1=\"{y1:.2f}\" x2=\"{x2:.2f}\" y2=\"{y2:.2f}\" stroke=\"{colour}\" '
+                       f'stroke-width=\"2\" stroke-linecap=\"round\"/>\\n')
+        file.write('</svg>\\n')" },
+    Helper { name: "set_color", imports: &[], uses: &["colour"], code: "\
+def _neal_set_color(name):
+    print(_neal_colour(name), end='')" },
+    Helper { name: "set_default", imports: &[], uses: &[], code: "\
+def _neal_set_default(dictionary, key, default):
+    return dictionary.setdefault(key, default)" },
+    Helper { name: "size", imports: &[], uses: &[], code: "\
+def _neal_size(value):
+    return len(value)" },
+    Helper { name: "sort", imports: &[], uses: &[], code: "\
+def _neal_sort(array):
+    return sorted(array)" },
+    Helper { name: "sort_in_place", imports: &[], uses: &[], code: "\
+def _neal_sort_in_place(array):
+    array.sort()" },
+    Helper { name: "store_load", imports: &[], uses: &["store"], code: "\
+def _neal_store_load(name):
+    return _neal_store_read().get(name)" },
+    Helper { name: "store_save", imports: &[], uses: &["store"], code: "\
+def _neal_store_save(name, value):
+    entries = _neal_store_read()
+    entries[name] = value
+    text = _neal_store_literal(entries)
+    with open(_neal_store_path(), 'w') as file:
+        file.write(f'# The values saved by `store_save()`. Each is loaded by its name with `store_load()`.\\n{text}\\n')" },
+    Helper { name: "to_base", imports: &[], uses: &["base", "whole"], code: "\
+def _neal_to_base(number, base):
+    number, base = _neal_whole(number), _neal_base(base)
+    rest, digits = abs(number), ''
+    while True:
+        digits = '0123456789abcdefghijklmnopqrstuvwxyz'[rest % base] + digits
+        rest //= base
+        if rest == 0:
+            return '-' + digits if number < 0 else digits" },
+    Helper { name: "to_fixed", imports: &["math"], uses: &["show"], code: "\
+def _neal_to_fixed(number, places):
+    return format(number, f'.{int(places)}f') if math.isfinite(number) else _neal_show(number)" },
+    Helper { name: "to_number", imports: &[], uses: &["base"], code: "\
+def _neal_to_number(value, base=None):
+    if base is None:
+        return float(value)
+    # Python's `int()` also allows spaces, underscores and prefixes such as `0x`, which NEAL does not.
+    base = _neal_base(base)
+    digits = value[1:] if value.startswith(('-', '+')) else value
+    if not digits or any(c not in '0123456789abcdefghijklmnopqrstuvwxyz'[:base] for c in digits.lower()):
+        raise ValueError(f'{value!r} is not a number in base {base}')
+    return -int(digits, base) if value.startswith('-') else int(digits, base)" },
+    Helper { name: "to_string", imports: &[], uses: &["show"], code: "\
+def _neal_to_string(value):
+    return _neal_show(value)" },
+    Helper { name: "transpose", imports: &[], uses: &[], code: "\
+def _neal_transpose(grid):
+    for i, row in enumerate(grid):
+        if len(row) != len(grid[0]):
+            raise ValueError(f'row {i} of the grid has {len(row)} element(s), but the first row has {len(grid[0])}')
+    return [list(column) for column in zip(*grid)]" },
+    Helper { name: "turn", imports: &["math"], uses: &["turtle"], code: "\
+def _neal_turn(degrees):
+    if math.isfinite(degrees):
+        _neal_turtle['heading'] = (_neal_turtle['heading'] - degrees) % 360" },
+    Helper { name: "values", imports: &[], uses: &[], code: "\
+def _neal_values(dictionary):
+    return list(dictionary.values())" },
+];
+
+/// Returns the abstract syntax tree translated into a Python program. See the module documentation for what is translated and how.
+pub fn to_python(ast: &[Stmt]) -> String {
+    let mut declarations = Declarations::default();
+    declarations.visit_stmts(ast);
+    let mut transpiler = Transpiler { lines: Vec::new(), helpers: BTreeSet::new(), declared: declarations.0, scopes: Vec::new() };
+    for stmt in ast {
+        transpiler.stmt(stmt, 0);
+    }
+
+    // Add the helpers which the used helpers call, until there are no more to add.
+    let mut helpers = transpiler.helpers;
+    loop {
+        let uses: BTreeSet<&str> = HELPERS.iter().filter(|helper| helpers.contains(helper.name)).flat_map(|helper| helper.uses.iter().copied()).collect();
+        if uses.is_subset(&helpers) {
+            break;
+        }
+        helpers.extend(uses);
+    }
+    let used: Vec<&Helper> = HELPERS.iter().filter(|helper| helpers.contains(helper.name)).collect();
+    let imports: BTreeSet<&str> = used.iter().flat_map(|helper| helper.imports.iter().copied()).collect();
+
+    let mut output = String::from("# Translated from NEAL into Python by `nea --transpile py`.\n");
+    for import in &imports {
+        output.push_str(&format!("import {}\n", import));
+    }
+    // Definitions are separated by two blank lines, as most Python is.
+    for helper in used {
+        output.push_str(&format!("\n\n{}\n", helper.code));
+    }
+    if !helpers.is_empty() {
+        output.push('\n');
+    }
+    output.push('\n');
+    for line in transpiler.lines {
+        output.push_str(&line);
+        output.push('\n');
+    }
+    output
+}
+
+/// Writes Python one line at a time, recording which helpers it calls.
+struct Transpiler {
+    lines: Vec<String>,  // The lines written so far, each indented.
+    helpers: BTreeSet<&'static str>,  // The names of the helpers the lines call.
+    declared: BTreeSet<String>,  // The names declared anywhere in the program, which are not the built-in functions even if they have their names.
+    scopes: Vec<BTreeSet<String>>,  // The names declared by each function around the statement being written, from the outermost.
+}
+
+impl Transpiler {
+    /// Writes a line at the depth.
+    fn line(&mut self, depth: usize, line: String) {
+        self.lines.push(INDENT.repeat(depth) + &line);
+    }
+
+    /// Writes the statement as the body of a Python block, which needs `pass` if the statement writes nothing, e.g., `{}`.
+    fn body(&mut self, stmt: &Stmt, depth: usize) {
+        let start = self.lines.len();
+        self.stmt(stmt, depth);
+        if self.lines.len() == start {
+            self.line(depth, String::from("pass"));
+        }
+    }
+
+    /// Writes the statement at the depth.
+    fn stmt(&mut self, stmt: &Stmt, depth: usize) {
+        match &stmt.stmt_type {
+            // Python has no blocks of their own, so their statements are written where the block is.
+            StmtType::Block { body } => {
+                for stmt in body {
+                    self.stmt(stmt, depth);
+                }
+            },
+            StmtType::Break => self.line(depth, String::from("break")),
+            StmtType::Expression { expression } => {
+                let line = self.expression_stmt(expression);
+                self.line(depth, line);
+            },
+            StmtType::For { initialiser, condition, increment, body } => {
+                // Python's `for` loops go over a sequence, so a NEAL `for` loop becomes a `while` loop with the increment at the end of the
+                // body. NEAL has no `continue` which could skip the increment.
+                if let Some(initialiser) = initialiser {
+                    self.stmt(initialiser, depth);
+                }
+                let condition = condition.as_ref().map_or_else(|| String::from("True"), |condition| self.expr(condition));
+                self.line(depth, format!("while {}:", condition));
+                let start = self.lines.len();
+                self.stmt(body, depth + 1);
+                if let Some(increment) = increment {
+                    self.stmt(increment, depth + 1);
+                }
+                if self.lines.len() == start {
+                    self.line(depth + 1, String::from("pass"));
+                }
+            },
+            StmtType::Function { name, parameters, body, .. } => {
+                let mut scope = Scope::default();
+                scope.declared.extend(parameters.iter().cloned());
+                scope.visit_stmt(body);
+
+                let parameters: Vec<String> = parameters.iter().map(|parameter| self.name(parameter)).collect();
+                let name = self.name(name);
+                self.line(depth, format!("def {}({}):", name, parameters.join(", ")));
+                // A function can assign to the variables of the functions around it and to global variables, which Python has to be told of.
+                let (mut nonlocals, mut globals) = (Vec::new(), Vec::new());
+                for outer in scope.assigned.difference(&scope.declared) {
+                    if self.scopes.iter().any(|declared| declared.contains(outer)) {
+                        nonlocals.push(self.name(outer));
+                    } else {
+                        globals.push(self.name(outer));
+                    }
+                }
+                if !globals.is_empty() {
+                    self.line(depth + 1, format!("global {}", globals.join(", ")));
+                }
+                if !nonlocals.is_empty() {
+                    self.line(depth + 1, format!("nonlocal {}", nonlocals.join(", ")));
+                }
+
+                self.scopes.push(scope.declared);
+                self.body(body, depth + 1);
+                self.scopes.pop();
+            },
+            StmtType::If { condition, then_body, else_body } => {
+                let condition = self.expr(condition);
+                self.line(depth, format!("if {}:", condition));
+                self.body(then_body, depth + 1);
+                // An `else if` chain becomes `elif`s rather than nested `if` statements.
+                let mut else_body = else_body.as_deref();
+                while let Some(stmt) = else_body {
+                    if let StmtType::If { condition, then_body, else_body: next } = &stmt.stmt_type {
+                        let condition = self.expr(condition);
+                        self.line(depth, format!("elif {}:", condition));
+                        self.body(then_body, depth + 1);
+                        else_body = next.as_deref();
+                    } else {
+                        self.line(depth, String::from("else:"));
+                        self.body(stmt, depth + 1);
+                        break;
+                    }
+                }
+            },
+            StmtType::Print { expression } => {
+                self.helpers.insert("show");
+                let expression = self.expr(expression);
+                self.line(depth, format!("print(_neal_show({}))", expression));
+            },
+            StmtType::Repeat { count, body } => {
+                // The loop variable is never used, so it is called `_`, as is usual in Python.
+                self.helpers.insert("repeat");
+                let count = self.expr(count);
+                self.line(depth, format!("for _ in _neal_repeat({}):", count));
+                self.body(body, depth + 1);
+            },
+            StmtType::Return { expression } => {
+                let expression = self.expr(expression);
+                self.line(depth, format!("return {}", expression));
+            },
+            StmtType::Try { body, catch, finally } => {
+                self.line(depth, String::from("try:"));
+                self.body(body, depth + 1);
+                if let Some((name, catch_body)) = catch {
+                    // Python deletes the name given to `as` at the end of the `except` block, so the error is given the NEAL name separately.
+                    // `Exception` leaves out `KeyboardInterrupt`, so that Ctrl-C still stops the program, as it does in NEAL.
+                    self.helpers.insert("error");
+                    self.line(depth, String::from("except Exception as _neal_exception:"));
+                    let line = format!("{} = _neal_error(_neal_exception)", self.name(name));
+                    self.line(depth + 1, line);
+                    self.stmt(catch_body, depth + 1);
+                }
+                if let Some(finally) = finally {
+                    self.line(depth, String::from("finally:"));
+                    self.body(finally, depth + 1);
+                }
+            },
+            StmtType::VarDecl { name, value } => {
+                let line = format!("{} = {}", self.name(name), self.expr(value));
+                self.line(depth, line);
+            },
+            StmtType::VarUnpack { names, value } => {
+                let names: Vec<String> = names.iter().map(|name| self.name(name)).collect();
+                let line = format!("{} = {}", names.join(", "), self.expr(value));
+                self.line(depth, line);
+            },
+            StmtType::While { condition, body } => {
+                let condition = self.expr(condition);
+                self.line(depth, format!("while {}:", condition));
+                self.body(body, depth + 1);
+            },
+        }
+    }
+
+    /// Returns the expression written as a statement.
+    fn expression_stmt(&mut self, expression: &Expr) -> String {
+        // An assignment, or a chain of them such as `a = b = 1`, is written as a Python assignment statement, which can be chained in the same way.
+        let mut targets = Vec::new();
+        let mut value = expression;
+        while let ExprType::Assignment { target, value: next } = &value.expr_type {
+            targets.push(self.target(target));
+            value = next;
+        }
+        targets.push(self.expr(value));
+        targets.join(" = ")
+    }
+
+    /// Returns the target of an assignment.
+    fn target(&mut self, target: &Expr) -> String {
+        match &target.expr_type {
+            ExprType::Variable { name } => self.name(name),
+            ExprType::Element { array, index } => format!("{}[{}]", self.operand(array, &TokenType::LeftSquare), self.index(index)),
+            // Python unpacks a list into several targets in the same way.
+            ExprType::Tuple { elements } => {
+                let targets: Vec<String> = elements.iter().map(|element| self.target(element)).collect();
+                targets.join(", ")
+            },
+            _ => self.expr(target),
+        }
+    }
+
+    /// Returns the expression as Python.
+    fn expr(&mut self, expr: &Expr) -> String {
+        match &expr.expr_type {
+            ExprType::Array { elements } => format!("[{}]", self.list(elements)),
+            // An assignment inside another expression needs `:=`, which can only assign to variables, so elements are assigned by a helper.
+            ExprType::Assignment { target, value } => match &target.expr_type {
+                ExprType::Element { array, index } => {
+                    self.helpers.insert("set");
+                    format!("_neal_set({}, {}, {})", self.expr(array), self.expr(index), self.expr(value))
+                },
+                _ => format!("({} := {})", self.target(target), self.expr(value)),
+            },
+            ExprType::Binary { left, operator, right } => {
+                let operator_type = &operator.type_;
+                let operator = match operator.type_ {
+                    TokenType::Percent => {
+                        self.helpers.insert("mod");
+                        return format!("_neal_mod({}, {})", self.expr(left), self.expr(right));
+                    },
+                    // Python does not convert the other side of `+` to a string, so it is converted where one side is known to be one.
+                    TokenType::Plus if is_string(left) || is_string(right) => {
+                        let (left, right) = (self.concatenated(left), self.concatenated(right));
+                        return format!("{} + {}", left, right);
+                    },
+                    TokenType::And => "and",
+                    TokenType::Or => "or",
+                    // The other operators are written the same way in Python.
+                    _ => &operator.lexeme,
+                }.to_owned();
+                format!("{} {} {}", self.operand(left, operator_type), operator, self.operand(right, operator_type))
+            },
+            ExprType::Call { callee, arguments, named_arguments } => {
+                let mut arguments: Vec<String> = arguments.iter().map(|argument| self.expr(argument)).collect();
+                // Python's keyword arguments are written with `=`, and the parameters they name are renamed in the same way as they are.
+                for (name, argument) in named_arguments {
+                    let name = if RESERVED.contains(&name.as_str()) { format!("{}_", name) } else { name.clone() };
+                    arguments.push(format!("{}={}", name, self.expr(argument)));
+                }
+                format!("{}({})", self.operand(callee, &TokenType::LeftParen), arguments.join(", "))
+            },
+            ExprType::Dictionary { elements } => {
+                let elements: Vec<String> = elements.iter().map(|key_value| format!("{}: {}", self.expr(&key_value.key), self.expr(&key_value.value))).collect();
+                format!("{{{}}}", elements.join(", "))
+            },
+            ExprType::Element { array, index } => format!("{}[{}]", self.operand(array, &TokenType::LeftSquare), self.index(index)),
+            ExprType::Grouping { expression } => format!("({})", self.expr(expression)),
+            ExprType::Slice { array, start, stop, step } => {
+                self.helpers.insert("slice");
+                let mut part = |part: &Option<Box<Expr>>| part.as_deref().map_or(String::from("None"), |part| self.expr(part));
+                let (start, stop, step) = (part(start), part(stop), part(step));
+                format!("_neal_slice({}, {}, {}, {})", self.expr(array), start, stop, step)
+            },
+            ExprType::Literal { value } => match value {
+                // Whole numbers are written as Python `int`s, e.g., `3` rather than `3.0`, which behave the same except for `/`, which gives a `float` anyway.
+                Literal::Number(x) => x.to_string(),
+                Literal::String_(string) => python_string(string),
+                Literal::Bool(true) => String::from("True"),
+                Literal::Bool(false) => String::from("False"),
+                Literal::Null => String::from("None"),
+            },
+            // A tuple is a list, as NEAL's arrays are, rather than a Python tuple, so that it can be used in the same ways as one.
+            ExprType::Tuple { elements } => format!("[{}]", self.list(elements)),
+            ExprType::Unary { operator, right } => match operator.type_ {
+                TokenType::Bang => format!("not {}", self.operand(right, &TokenType::Bang)),
+                _ => format!("-{}", self.operand(right, &TokenType::Minus)),
+            },
+            ExprType::Variable { name } => self.name(name),
+        }
+    }
+
+    /// Returns the expressions separated by commas.
+    fn list(&mut self, exprs: &[Expr]) -> String {
+        let exprs: Vec<String> = exprs.iter().map(|expr| self.expr(expr)).collect();
+        exprs.join(", ")
+    }
+
+    /// Returns an operand of the operator, which is a `LeftParen` for a callee and a `LeftSquare` for an array being indexed.
+    /// The tree only has brackets where the source code did, so the operand is bracketed where Python would otherwise read it
+    /// differently from NEAL.
+    fn operand(&mut self, expr: &Expr, operator: &TokenType) -> String {
+        let bracketed = match &expr.expr_type {
+            // Python's `not` comes after every operator except `and` and `or`, e.g., `not a == b` is `not (a == b)`.
+            ExprType::Unary { operator: inner, .. } => inner.type_ == TokenType::Bang && !matches!(operator, TokenType::And | TokenType::Or | TokenType::Bang),
+            // Python chains comparisons, e.g., `a < b == c` is `a < b and b == c`.
+            ExprType::Binary { operator: inner, .. } => is_comparison(&inner.type_) && is_comparison(operator),
+            _ => false,
+        };
+        if bracketed {
+            format!("({})", self.expr(expr))
+        } else {
+            self.expr(expr)
+        }
+    }
+
+    /// Returns an operand of a `+` which joins strings, converting it to a string with a helper unless it is known to be one.
+    fn concatenated(&mut self, operand: &Expr) -> String {
+        if is_string(operand) {
+            self.operand(operand, &TokenType::Plus)
+        } else {
+            self.helpers.insert("show");
+            format!("_neal_show({})", self.expr(operand))
+        }
+    }
+
+    /// Returns the index of an element. Any index which might be a `float` is converted by a helper.
+    fn index(&mut self, index: &Expr) -> String {
+        match index.expr_type {
+            ExprType::Literal { value: Literal::Number(..) | Literal::String_(..) } => self.expr(index),
+            _ => {
+                self.helpers.insert("key");
+                format!("_neal_key({})", self.expr(index))
+            },
+        }
+    }
+
+    /// Returns the Python name for the NEAL name: the helper for a built-in function, or the name with a `_` after it if Python reserves it.
+    fn name(&mut self, name: &str) -> String {
+        if !self.declared.contains(name) {
+            if let Some(builtin) = BuiltinFunction::ALL.iter().find(|builtin| builtin.name() == name) {
+                self.helpers.insert(builtin.name());
+                return format!("_neal_{}", name);
+            }
+        }
+        if RESERVED.contains(&name) {
+            format!("{}_", name)
+        } else {
+            name.to_owned()
+        }
+    }
+}
+
+/// Returns whether the expression is known to evaluate to a string without running it: a string literal, or a `+` with one.
+fn is_string(expr: &Expr) -> bool {
+    match &expr.expr_type {
+        ExprType::Literal { value: Literal::String_(..) } => true,
+        ExprType::Grouping { expression } => is_string(expression),
+        ExprType::Binary { left, operator, right } => operator.type_ == TokenType::Plus && (is_string(left) || is_string(right)),
+        _ => false,
+    }
+}
+
+/// Returns whether the operator compares its operands, which Python chains.
+fn is_comparison(operator: &TokenType) -> bool {
+    matches!(operator, TokenType::BangEqual | TokenType::EqualEqual | TokenType::Greater | TokenType::GreaterEqual | TokenType::Less | TokenType::LessEqual)
+}
+
+/// Returns the string as a Python string literal.
+fn python_string(string: &str) -> String {
+    let mut literal = String::from("'");
+    for c in string.chars() {
+        match c {
+            '\\' => literal.push_str("\\\\"),
+            '\'' => literal.push_str("\\'"),
+            '\n' => literal.push_str("\\n"),
+            '\t' => literal.push_str("\\t"),
+            '\r' => literal.push_str("\\r"),
+            c if c.is_control() => literal.push_str(&format!("\\x{:02x}", c as u32)),
+            c => literal.push(c),
+        }
+    }
+    literal.push('\'');
+    literal
+}
+
+/// Collects every name declared in the program, as a variable, a function or a parameter.
+#[derive(Default)]
+struct Declarations(BTreeSet<String>);
+
+impl Visitor for Declarations {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        match &stmt.stmt_type {
+            StmtType::VarDecl { name, .. } | StmtType::Try { catch: Some((name, _)), .. } => {
+                self.0.insert(name.clone());
+            },
+            StmtType::VarUnpack { names, .. } => self.0.extend(names.iter().cloned()),
+            StmtType::Function { name, parameters, .. } => {
+                self.0.insert(name.clone());
+                self.0.extend(parameters.iter().cloned());
+            },
+            _ => {},
+        }
+        walk_stmt(self, stmt);
+    }
+}
+
+/// Collects the names a function declares and the variables it assigns to, leaving out the functions declared inside it, which have their own.
+#[derive(Default)]
+struct Scope {
+    declared: BTreeSet<String>,
+    assigned: BTreeSet<String>,
+}
+
+impl Visitor for Scope {
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        match &stmt.stmt_type {
+            StmtType::VarDecl { name, .. } | StmtType::Try { catch: Some((name, _)), .. } => {
+                self.declared.insert(name.clone());
+            },
+            StmtType::VarUnpack { names, .. } => self.declared.extend(names.iter().cloned()),
+            StmtType::Function { name, .. } => {
+                self.declared.insert(name.clone());
+                return;
+            },
+            _ => {},
+        }
+        walk_stmt(self, stmt);
+    }
+
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let ExprType::Assignment { target, .. } = &expr.expr_type {
+            // Each target of a tuple is assigned to, e.g., both `a` and `b` in `a, b = b, a`.
+            let targets = match &target.expr_type {
+                ExprType::Tuple { elements } => elements.iter().collect(),
+                _ => vec![target.as_ref()],
+            };
+            for target in targets {
+                if let ExprType::Variable { name } = &target.expr_type {
+                    self.assigned.insert(name.clone());
+                }
+            }
+        }
+        walk_expr(self, expr);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::parser::parse_no_panic;
+    use crate::value::BuiltinFunction;
+
+    use super::{to_python, HELPERS};
+
+    /// Returns the program translated into Python, without the header and helpers, which end with a blank line.
+    fn python(source: &str) -> String {
+        let python = to_python(&parse_no_panic(source).unwrap());
+        python.rsplit_once("\n\n").unwrap().1.to_owned()
+    }
+
+    #[test]
+    fn statements() {
+        let source = "var x = 1\nif (x < 2) {\n    print x\n} else if (x == 2) {} else {\n    x = 3\n}\nfor (var i = 0; i < 3; i = i + 1) {\n    while (true) { break }\n}";
+        assert_eq!(python(source), "\
+x = 1
+if x < 2:
+    print(_neal_show(x))
+elif x == 2:
+    pass
+else:
+    x = 3
+i = 0
+while i < 3:
+    while True:
+        break
+    i = i + 1
+");
+    }
+
+    #[test]
+    fn string_concatenation() {
+        // Only the sides not known to be strings are converted, and adding two unknown values is left alone.
+        assert_eq!(python("print 'Score: ' + 10"), "print(_neal_show('Score: ' + _neal_show(10)))\n");
+        assert_eq!(python("x = 1 + 2 + 'a' + b"), "x = _neal_show(1 + 2) + 'a' + _neal_show(b)\n");
+        assert_eq!(python("x = a + ('b' + c) + d"), "x = _neal_show(a) + ('b' + _neal_show(c)) + _neal_show(d)\n");
+        assert_eq!(python("x = a + b"), "x = a + b\n");
+    }
+
+    #[test]
+    fn repeat() {
+        let python = to_python(&parse_no_panic("repeat 3 {\n    repeat 0 {}\n}").unwrap());
+        assert!(python.contains("\n\n\ndef _neal_repeat(count):"));
+        assert!(python.ends_with("\n\n\nfor _ in _neal_repeat(3):\n    for _ in _neal_repeat(0):\n        pass\n"));
+    }
+
+    #[test]
+    fn functions() {
+        let source = "var count = 0\nfunc f(a, b) {\n    var total = 0\n    func g() {\n        total = total + 1\n        count = count + 1\n    }\n    g()\n    return total\n}";
+        assert_eq!(python(source), "\
+count = 0
+def f(a, b):
+    total = 0
+    def g():
+        global count
+        nonlocal total
+        total = total + 1
+        count = count + 1
+    g()
+    return total
+");
+    }
+
+    #[test]
+    fn expressions() {
+        assert_eq!(python("a = b = !x == (y or z) and !(1 < 2 == -1.5 * c)"), "a = b = (not x) == (y or z) and not ((1 < 2) == -1.5 * c)\n");
+        assert_eq!(python("x = a[i] = d['k'][0]"), "x = a[_neal_key(i)] = d['k'][0]\n");
+        assert_eq!(python("f(x = 1, [a[0] = 2], {'a\\b': null, 1: false})"), "f((x := 1), [_neal_set(a, 0, 2)], {'a\\\\b': None, 1: False})\n");
+        // Python counts negative indexes from the end, so slices are taken by a helper.
+        assert_eq!(python("x = a[1:][::-1]"), "x = _neal_slice(_neal_slice(a, 1, None, None), None, None, -1)\n");
+        // Named arguments are Python's keyword arguments.
+        assert_eq!(python("f(1, color: 'red', lambda: 2)"), "f(1, color='red', lambda_=2)\n");
+        // A tuple is a list, which Python unpacks in the same way.
+        assert_eq!(python("var q, r = 1, 2\na[0], b = b, a"), "q, r = [1, 2]\na[0], b = [b, a]\n");
+        // Python keywords are renamed, and a declared name is not a built-in function.
+        assert_eq!(python("var lambda = 1\nvar size = 2\nprint size + lambda"), "lambda_ = 1\nsize = 2\nprint(_neal_show(size + lambda_))\n");
+    }
+
+    #[test]
+    fn helpers() {
+        // Only the helpers which are used are written, with the helpers they use and the modules they import.
+        let python = to_python(&parse_no_panic("var x = to_string(7 % 2)").unwrap());
+        assert!(python.starts_with("# Translated from NEAL into Python by `nea --transpile py`.\nimport decimal\nimport math\n\n\ndef _neal_show(value):"));
+        assert!(python.contains("\n\n\ndef _neal_mod(left, right):"));
+        assert!(python.contains("\n\n\ndef _neal_to_string(value):"));
+        assert!(!python.contains("_neal_input"));
+        assert!(python.ends_with("\n\n\nx = _neal_to_string(_neal_mod(7, 2))\n"));
+        assert_eq!(to_python(&[]), "# Translated from NEAL into Python by `nea --transpile py`.\n\n");
+
+        // `defined()` knows the name of every built-in function, as they are not Python names.
+        let defined = HELPERS.iter().find(|helper| helper.name == "defined").unwrap();
+        for builtin in BuiltinFunction::ALL {
+            assert!(defined.code.contains(&format!("'{}'", builtin.name())), "{} is missing", builtin.name());
+        }
+    }
+}
@@ -0,0 +1,189 @@
+//! Turtle graphics: a pen which is moved around by `forward()` and `turn()`, drawing lines behind it, saved as SVG by `save_drawing()`.
+//!
+//! The turtle starts at the origin, facing up the page, with its pen down and drawing in black. Distances are in pixels, and angles
+//! in degrees, turning clockwise, as a compass does:
+//!
+//! ```
+//! use nea::turtle::Turtle;
+//!
+//! let mut turtle = Turtle::new();
+//! turtle.forward(10.0);
+//! turtle.turn(90.0);
+//! turtle.forward(10.0);
+//! assert_eq!(turtle.lines().len(), 2);
+//! assert!(turtle.to_svg().contains(r#"<line x1="0.00" y1="-10.00" x2="10.00" y2="-10.00" stroke="black""#));
+//! ```
+
+use std::fmt::Write;
+
+/// The space left around the lines of a drawing, in pixels.
+const MARGIN: f64 = 10.0;
+
+/// The width of every line, in pixels.
+const LINE_WIDTH: f64 = 2.0;
+
+/// Returns the SVG colour of the pen colour, or `None` if the pen cannot draw in it. These are the colours of `set_color()`, with
+/// `reset` going back to black.
+pub fn pen_colour(name: &str) -> Option<&'static str> {
+    Some(match name {
+        "reset" | "black" => "black",
+        "red" => "red",
+        "green" => "green",
+        "yellow" => "yellow",
+        "blue" => "blue",
+        "magenta" => "magenta",
+        "cyan" => "cyan",
+        "white" => "white",
+        "grey" => "grey",
+        _ => return None,
+    })
+}
+
+/// A line drawn by the turtle, in the coordinates of the drawing, where `y` increases up the page.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Line {
+    pub from: (f64, f64),
+    pub to: (f64, f64),
+    pub colour: &'static str,  // The SVG colour of the line, as given by `pen_colour()`.
+}
+
+/// The turtle, and the lines it has drawn so far.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Turtle {
+    position: (f64, f64),
+    heading: f64,  // The direction the turtle faces, in degrees anticlockwise from the positive `x` axis.
+    pen_down: bool,  // Whether moving draws a line.
+    colour: &'static str,  // The SVG colour lines are drawn in.
+    lines: Vec<Line>,
+}
+
+impl Default for Turtle {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Turtle {
+    /// Initialises a new instance of `Turtle` at the origin, facing up the page, with its pen down, and nothing drawn.
+    pub fn new() -> Self {
+        Self { position: (0.0, 0.0), heading: 90.0, pen_down: true, colour: "black", lines: Vec::new() }
+    }
+
+    /// Moves the turtle forward by the distance, or backward if it is negative, drawing a line if the pen is down.
+    /// A distance which is not finite is ignored, as the turtle would leave the drawing for good.
+    pub fn forward(&mut self, distance: f64) {
+        if !distance.is_finite() {
+            return;
+        }
+        let (x, y) = self.position;
+        let radians = self.heading.to_radians();
+        let to = (x + distance * radians.cos(), y + distance * radians.sin());
+        if self.pen_down {
+            self.lines.push(Line { from: self.position, to, colour: self.colour });
+        }
+        self.position = to;
+    }
+
+    /// Turns the turtle clockwise by the angle in degrees, or anticlockwise if it is negative.
+    pub fn turn(&mut self, degrees: f64) {
+        if degrees.is_finite() {
+            // The heading is kept between 0 and 360, so that it does not lose precision after many turns.
+            self.heading = (self.heading - degrees).rem_euclid(360.0);
+        }
+    }
+
+    /// Sets whether moving the turtle draws a line.
+    pub fn set_pen_down(&mut self, down: bool) {
+        self.pen_down = down;
+    }
+
+    /// Sets the SVG colour that the lines drawn from now on are drawn in (see `pen_colour()`).
+    pub fn set_colour(&mut self, colour: &'static str) {
+        self.colour = colour;
+    }
+
+    /// Returns the lines drawn so far, in the order they were drawn.
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    /// Returns the drawing as an SVG image, just big enough for its lines and a margin around them.
+    pub fn to_svg(&self) -> String {
+        let ends: Vec<[(f64, f64); 2]> = self.lines.iter().map(|line| [svg_point(line.from), svg_point(line.to)]).collect();
+        let mut points = ends.iter().flatten();
+        let (mut left, mut top) = points.next().copied().unwrap_or((0.0, 0.0));
+        let (mut right, mut bottom) = (left, top);
+        for &(x, y) in points {
+            (left, top, right, bottom) = (left.min(x), top.min(y), right.max(x), bottom.max(y));
+        }
+        let (width, height) = (right - left + 2.0 * MARGIN, bottom - top + 2.0 * MARGIN);
+
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{:.2}\" height=\"{:.2}\" viewBox=\"{:.2} {:.2} {:.2} {:.2}\">\n",
+            width, height, left - MARGIN, top - MARGIN, width, height,
+        );
+        for (line, [(x1, y1), (x2, y2)]) in self.lines.iter().zip(ends) {
+            writeln!(
+                svg, "  <line x1=\"{:.2}\" y1=\"{:.2}\" x2=\"{:.2}\" y2=\"{:.2}\" stroke=\"{}\" stroke-width=\"{}\" stroke-linecap=\"round\"/>",
+                x1, y1, x2, y2, line.colour, LINE_WIDTH,
+            ).expect("Error: failed to write to a string");
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// Returns the point in SVG's coordinates, where `y` increases down the page, rounded to two decimal places.
+/// The rounding means that a turtle which returns to where it started is drawn there, and that no coordinate is written as `-0.00`.
+fn svg_point((x, y): (f64, f64)) -> (f64, f64) {
+    let round = |n: f64| (n * 100.0).round() / 100.0 + 0.0;
+    (round(x), round(-y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{pen_colour, Line, Turtle};
+
+    /// Returns the ends of the line, rounded to two decimal places.
+    fn rounded(line: &Line) -> [(f64, f64); 2] {
+        let round = |(x, y): (f64, f64)| ((x * 100.0).round() / 100.0 + 0.0, (y * 100.0).round() / 100.0 + 0.0);
+        [round(line.from), round(line.to)]
+    }
+
+    #[test]
+    fn moving() {
+        let mut turtle = Turtle::new();
+        turtle.forward(10.0);
+        turtle.turn(90.0);
+        turtle.set_pen_down(false);
+        turtle.forward(5.0);
+        turtle.set_pen_down(true);
+        turtle.set_colour(pen_colour("red").unwrap());
+        turtle.turn(-450.0);
+        turtle.forward(-2.0);
+        turtle.forward(f64::NAN);
+
+        let lines: Vec<[(f64, f64); 2]> = turtle.lines().iter().map(rounded).collect();
+        assert_eq!(lines, [[(0.0, 0.0), (0.0, 10.0)], [(5.0, 10.0), (5.0, 8.0)]]);
+        assert_eq!(turtle.lines()[1].colour, "red");
+        assert_eq!(pen_colour("reset"), Some("black"));
+        assert_eq!(pen_colour("bold"), None);
+    }
+
+    #[test]
+    fn svg() {
+        let mut turtle = Turtle::new();
+        turtle.turn(90.0);
+        turtle.forward(30.0);
+        turtle.turn(90.0);
+        turtle.forward(20.0);
+        assert_eq!(turtle.to_svg(), "\
+<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"50.00\" height=\"40.00\" viewBox=\"-10.00 -10.00 50.00 40.00\">
+  <line x1=\"0.00\" y1=\"0.00\" x2=\"30.00\" y2=\"0.00\" stroke=\"black\" stroke-width=\"2\" stroke-linecap=\"round\"/>
+  <line x1=\"30.00\" y1=\"0.00\" x2=\"30.00\" y2=\"20.00\" stroke=\"black\" stroke-width=\"2\" stroke-linecap=\"round\"/>
+</svg>
+");
+        // An empty drawing is only its margin.
+        assert_eq!(Turtle::new().to_svg(), "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"20.00\" height=\"20.00\" viewBox=\"-10.00 -10.00 20.00 20.00\">\n</svg>\n");
+    }
+}
@@ -1,19 +1,21 @@
 use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
 
 use crate::stmt::Stmt;
-use crate::hash_table::HashTable;
+use crate::dictionary::Dictionary;
 
 /// Represents evaluated/stored values within the interpreter.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug)]
 pub enum Value {
     Number(f64),
     String_(String),
     Bool(bool),
-    Array(Vec<Value>),
-    Dictionary(HashTable),
+    Array(Array),
+    Dictionary(Dictionary),
     Function {
         parameters: Vec<String>,
-        body: Stmt,
+        body: Arc<Stmt>,  // Shared by every copy of the function, which is how copies are told apart from other functions, and makes copying them cheap.
     },
     BuiltinFunction(BuiltinFunction),
     Null,
@@ -34,11 +36,317 @@ impl Value {
     }
 }
 
+impl Value {
+    /// Returns whether the value is an array or dictionary which has been frozen with `freeze()`, so that it cannot be changed.
+    pub fn is_frozen(&self) -> bool {
+        match self {
+            Self::Array(array) => array.frozen,
+            Self::Dictionary(dict) => dict.is_frozen(),
+            _ => false,
+        }
+    }
+
+    /// Freezes the value and every array and dictionary inside it, as `freeze()` does. Other values cannot be changed anyway.
+    pub fn freeze(&mut self) {
+        match self {
+            Self::Array(array) => {
+                array.frozen = true;
+                array.elements.iter_mut().for_each(Value::freeze);
+            },
+            Self::Dictionary(dict) => dict.freeze(),
+            _ => (),
+        }
+    }
+}
+
+/// The elements of an array, and whether it has been frozen with `freeze()`.
+/// It can be used as the `Vec` of its elements, e.g., `array.len()`, so code which changes an array has to check `is_frozen()` first.
+/// Freezing does not change what the array is equal to.
+#[derive(Clone, Debug, Default)]
+pub struct Array {
+    elements: Vec<Value>,
+    frozen: bool,
+}
+
+impl Array {
+    /// Returns whether the array has been frozen, so that it cannot be changed.
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+}
+
+impl Deref for Array {
+    type Target = Vec<Value>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.elements
+    }
+}
+
+impl DerefMut for Array {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.elements
+    }
+}
+
+impl From<Vec<Value>> for Array {
+    fn from(elements: Vec<Value>) -> Self {
+        Self { elements, frozen: false }
+    }
+}
+
+impl FromIterator<Value> for Array {
+    fn from_iter<I: IntoIterator<Item = Value>>(iter: I) -> Self {
+        Self::from(iter.into_iter().collect::<Vec<Value>>())
+    }
+}
+
+impl IntoIterator for Array {
+    type Item = Value;
+    type IntoIter = std::vec::IntoIter<Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a Array {
+    type Item = &'a Value;
+    type IntoIter = std::slice::Iter<'a, Value>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.elements.iter()
+    }
+}
+
+impl PartialEq for Array {
+    fn eq(&self, other: &Self) -> bool {
+        self.elements == other.elements
+    }
+}
+
+impl Value {
+    /// Returns whether the value counts as `true` as a condition, which only values other than Booleans need with `--truthy`:
+    /// `null`, `0`, `''` and empty arrays and dictionaries count as `false`, and every other value, including `NaN`, as `true`.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Self::Bool(x) => *x,
+            Self::Null => false,
+            Self::Number(x) => *x != 0.0,
+            Self::String_(x) => !x.is_empty(),
+            Self::Array(array) => !array.is_empty(),
+            Self::Dictionary(dict) => dict.size() != 0,
+            Self::Function { .. } | Self::BuiltinFunction(..) => true,
+        }
+    }
+}
+
+impl Value {
+    /// Returns a one-line description of the value's type and shape, e.g., `Array of 3 elements (Number, String)`, as `inspect()`
+    /// and `:type` show it. Only numbers and Booleans are shown in full, so the summary of a huge value stays short.
+    pub fn summary(&self) -> String {
+        match self {
+            Self::Number(x) => format!("Number {}", format_number(*x)),
+            Self::String_(x) => format!("String of {}", plural(x.chars().count(), "character")),
+            Self::Bool(x) => format!("Boolean {}", x),
+            Self::Array(array) if array.is_empty() => String::from("Array of 0 elements"),
+            Self::Array(array) => format!("Array of {} ({})", plural(array.len(), "element"), type_list(array.iter())),
+            Self::Dictionary(dict) if dict.size() == 0 => String::from("Dictionary of 0 entries"),
+            Self::Dictionary(dict) => format!(
+                "Dictionary of {} ({} keys, {} values)",
+                plural(dict.size(), "entry"),
+                type_list(dict.iter().map(|key_value| &key_value.key)),
+                type_list(dict.iter().map(|key_value| &key_value.value)),
+            ),
+            Self::Function { parameters, .. } => match parameters.len() {
+                0 => String::from("Function with no parameters"),
+                _ => format!("Function with {} ({})", plural(parameters.len(), "parameter"), parameters.join(", ")),
+            },
+            Self::BuiltinFunction(builtin) => format!("Built-in function `{}`", builtin.name()),
+            Self::Null => String::from("Null"),
+        }
+    }
+}
+
+/// The number of columns a pretty-printed array or dictionary has to fit in to be written on one line.
+const PRETTY_WIDTH: usize = 80;
+
+impl Value {
+    /// Returns the value written as `print` writes it with `--pretty`, which the REPL does by default. Arrays and dictionaries which
+    /// do not fit on one line are written with each element on its own line, indented by its depth. Only the first `max_elements`
+    /// elements of each are written, followed by how many were left out, e.g., `… 49,900 more`, so a huge value stays readable.
+    pub fn pretty(&self, max_elements: usize) -> String {
+        let mut output = String::new();
+        self.write_pretty(max_elements, 0, 0, &mut output);
+        output
+    }
+
+    /// Writes the value pretty-printed to `output`, starting `indent` levels in, after `used` characters of its line, e.g., for a key.
+    fn write_pretty(&self, max_elements: usize, indent: usize, used: usize, output: &mut String) {
+        let budget = PRETTY_WIDTH.saturating_sub(indent * 4 + used);
+        let (open, close, elements) = match self {
+            Self::Array(array) if !array.is_empty() => ('[', ']', array.len()),
+            Self::Dictionary(dict) if dict.size() != 0 => ('{', '}', dict.size()),
+            // Everything else is written as `print` writes it.
+            _ => return output.push_str(&self.to_string()),
+        };
+        if let Some(line) = self.compact(max_elements, budget) {
+            return output.push_str(&line);
+        }
+
+        // Each element goes on its own line, with a comma after every one except the last.
+        let inner = "    ".repeat(indent + 1);
+        output.push(open);
+        output.push('\n');
+        let mut write_element = |i: usize, key: Option<&Value>, value: &Value| {
+            output.push_str(&inner);
+            if let Some(key) = key {
+                key.write_pretty(max_elements, indent + 1, 0, output);
+                output.push_str(": ");
+            }
+            // A key written over several lines leaves its value at the end of its last line.
+            let used = output.rsplit('\n').next().map_or(0, |line| line.chars().count()).saturating_sub(inner.len());
+            value.write_pretty(max_elements, indent + 1, used, output);
+            if i + 1 < elements.min(max_elements) || elements > max_elements {
+                output.push(',');
+            }
+            output.push('\n');
+        };
+        match self {
+            Self::Array(array) => array.iter().take(max_elements).enumerate().for_each(|(i, value)| write_element(i, None, value)),
+            Self::Dictionary(dict) => dict.iter().take(max_elements).enumerate()
+                .for_each(|(i, key_value)| write_element(i, Some(&key_value.key), &key_value.value)),
+            _ => unreachable!(),
+        }
+        if elements > max_elements {
+            output.push_str(&format!("{}… {} more\n", inner, group_digits(elements - max_elements)));
+        }
+        output.push_str(&"    ".repeat(indent));
+        output.push(close);
+    }
+
+    /// Returns the value written on one line, truncating each array and dictionary after `max_elements` elements, or `None` if it
+    /// would take more than `budget` characters or more than one line. As the writing stops once it is over budget, this is quick
+    /// however big the value is.
+    fn compact(&self, max_elements: usize, budget: usize) -> Option<String> {
+        let mut line = String::new();
+        let push = |line: &mut String, text: &str| {
+            line.push_str(text);
+            (line.chars().count() <= budget && !text.contains('\n')).then_some(())
+        };
+        match self {
+            Self::Array(array) => {
+                push(&mut line, "[")?;
+                for (i, value) in array.iter().take(max_elements).enumerate() {
+                    if i > 0 {
+                        push(&mut line, ", ")?;
+                    }
+                    push(&mut line, &value.compact(max_elements, budget)?)?;
+                }
+                if array.len() > max_elements {
+                    push(&mut line, &format!("{}… {} more", if max_elements > 0 { ", " } else { "" }, group_digits(array.len() - max_elements)))?;
+                }
+                push(&mut line, "]")?;
+            },
+            Self::Dictionary(dict) => {
+                push(&mut line, "{")?;
+                for (i, key_value) in dict.iter().take(max_elements).enumerate() {
+                    if i > 0 {
+                        push(&mut line, ", ")?;
+                    }
+                    push(&mut line, &key_value.key.compact(max_elements, budget)?)?;
+                    push(&mut line, ": ")?;
+                    push(&mut line, &key_value.value.compact(max_elements, budget)?)?;
+                }
+                if dict.size() > max_elements {
+                    push(&mut line, &format!("{}… {} more", if max_elements > 0 { ", " } else { "" }, group_digits(dict.size() - max_elements)))?;
+                }
+                push(&mut line, "}")?;
+            },
+            _ => push(&mut line, &self.to_string())?,
+        }
+        Some(line)
+    }
+}
+
+/// Returns the count with its digits in groups of three separated by commas, e.g., `49,000`.
+fn group_digits(count: usize) -> String {
+    let digits = count.to_string();
+    let mut grouped = String::new();
+    for (i, digit) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    grouped
+}
+
+/// Returns the number written as `print` writes it: in the fewest digits which read back as the same number, with no decimal point
+/// if it is whole, e.g., `3` and `0.1`. Numbers from `1e21` up and below `0.000001` are written with an exponent, e.g., `1.5e-7`,
+/// rather than with dozens of zeros, and `-0` is written as `0`. `to_fixed()` writes a number to a set number of decimal places instead.
+pub fn format_number(x: f64) -> String {
+    let magnitude = x.abs();
+    if x == 0.0 {
+        String::from("0")
+    } else if magnitude.is_finite() && !(1e-6..1e21).contains(&magnitude) {
+        format!("{:e}", x)
+    } else {
+        x.to_string()
+    }
+}
+
+/// Returns the count with the noun, in the plural unless the count is 1, e.g., `3 entries`.
+fn plural(count: usize, noun: &str) -> String {
+    match (count, noun.strip_suffix('y')) {
+        (1, _) => format!("1 {}", noun),
+        (_, Some(stem)) => format!("{} {}ies", count, stem),
+        (_, None) => format!("{} {}s", count, noun),
+    }
+}
+
+/// Returns the distinct types of the values, in the order they first appear, separated by `/`, e.g., `Number/String`.
+fn type_list<'a>(values: impl Iterator<Item = &'a Value>) -> String {
+    let mut types: Vec<String> = Vec::new();
+    for value in values {
+        let type_ = value.type_to_string();
+        if !types.contains(&type_) {
+            types.push(type_);
+        }
+    }
+    types.join("/")
+}
+
+/// The equality of `==`, `!=` and `assert_eq()`, and of dictionary keys:
+/// - Values of different types are never equal, e.g., `1 != '1'` and `1 != true`.
+/// - Numbers are equal if they have the same value, so `0 == -0`. Unlike in most languages, `NaN` is equal to itself, so that every
+///   value is equal to a copy of it, e.g., `[x] == [x]` for any `x`, and `NaN` can be a dictionary key. `<` and `>` are still `false`.
+/// - Arrays are equal if they have the same length and their elements are equal in order. Dictionaries are equal if they have the
+///   same keys with equal values, in any order.
+/// - A user-defined function is only equal to copies of itself, made by assigning it or passing it around. Declaring a function
+///   again, even with the same code, makes a new one, so `func f() {}` run twice gives two different functions.
+/// - A built-in function is equal to itself.
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Number(left), Self::Number(right)) => left == right || (left.is_nan() && right.is_nan()),
+            (Self::String_(left), Self::String_(right)) => left == right,
+            (Self::Bool(left), Self::Bool(right)) => left == right,
+            (Self::Array(left), Self::Array(right)) => left == right,
+            (Self::Dictionary(left), Self::Dictionary(right)) => left == right,
+            (Self::Function { body: left, .. }, Self::Function { body: right, .. }) => Arc::ptr_eq(left, right),
+            (Self::BuiltinFunction(left), Self::BuiltinFunction(right)) => left == right,
+            (Self::Null, Self::Null) => true,
+            _ => false,
+        }
+    }
+}
+
 /// Used when printing `Value`s.
 impl fmt::Display for Value {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Number(x) => write!(f, "{}", x),
+            Self::Number(x) => write!(f, "{}", format_number(*x)),
             Self::String_(x) => write!(f, "{}", x),
             Self::Bool(x) => write!(f, "{}", x),
             Self::Array(array) => {
@@ -53,9 +361,8 @@ impl fmt::Display for Value {
                 write!(f, "]")
             },
             Self::Dictionary(dict) => {
-                let flattened = dict.flatten();
                 write!(f, "{{")?;
-                let mut it = flattened.iter().peekable();
+                let mut it = dict.iter().peekable();
                 while let Some(key_value) = it.next() {
                     key_value.key.fmt(f)?;
                     write!(f, ": ")?;
@@ -76,10 +383,118 @@ impl fmt::Display for Value {
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum BuiltinFunction {
     Append,
+    Args,
+    AssertEq,
+    ClearScreen,
+    Colored,
+    Defined,
+    Eval,
+    Factors,
+    Forward,
+    Freeze,
+    Gcd,
+    Get,
+    GetCell,
+    GetKey,
+    Globals,
+    Identity,
+    Include,
     Input,
+    InputNumber,
+    Inspect,
+    IsPrime,
+    Keys,
+    Lcm,
+    Locals,
+    Matrix,
+    MoveCursor,
+    PenColor,
+    PenDown,
+    PenUp,
+    ReadAll,
+    ReadLine,
     Remove,
+    Resize,
+    SaveDrawing,
+    SetColor,
+    SetDefault,
     Size,
     Sort,
+    SortInPlace,
+    StoreLoad,
+    StoreSave,
+    ToBase,
+    ToFixed,
     ToNumber,
     ToString,
+    Transpose,
+    Turn,
+    Values,
+}
+
+impl BuiltinFunction {
+    /// Every built-in function, in alphabetical order of name.
+    pub const ALL: [BuiltinFunction; 48] = [
+        Self::Append, Self::Args, Self::AssertEq, Self::ClearScreen, Self::Colored, Self::Defined, Self::Eval, Self::Factors,
+        Self::Forward, Self::Freeze, Self::Gcd, Self::Get, Self::GetCell, Self::GetKey, Self::Globals, Self::Identity,
+        Self::Include, Self::Input, Self::InputNumber, Self::Inspect, Self::IsPrime, Self::Keys, Self::Lcm, Self::Locals,
+        Self::Matrix, Self::MoveCursor, Self::PenColor, Self::PenDown, Self::PenUp, Self::ReadAll, Self::ReadLine, Self::Remove,
+        Self::Resize, Self::SaveDrawing, Self::SetColor, Self::SetDefault, Self::Size, Self::Sort, Self::SortInPlace,
+        Self::StoreLoad, Self::StoreSave, Self::ToBase, Self::ToFixed, Self::ToNumber, Self::ToString, Self::Transpose,
+        Self::Turn, Self::Values,
+    ];
+
+    /// Returns the name the built-in function is declared with in the base scope, e.g., `sort_in_place`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Append => "append",
+            Self::Args => "args",
+            Self::AssertEq => "assert_eq",
+            Self::ClearScreen => "clear_screen",
+            Self::Colored => "colored",
+            Self::Defined => "defined",
+            Self::Eval => "eval",
+            Self::Factors => "factors",
+            Self::Forward => "forward",
+            Self::Freeze => "freeze",
+            Self::Gcd => "gcd",
+            Self::Get => "get",
+            Self::GetCell => "get_cell",
+            Self::GetKey => "get_key",
+            Self::Globals => "globals",
+            Self::Identity => "identity",
+            Self::Include => "include",
+            Self::Input => "input",
+            Self::InputNumber => "input_number",
+            Self::Inspect => "inspect",
+            Self::IsPrime => "is_prime",
+            Self::Keys => "keys",
+            Self::Lcm => "lcm",
+            Self::Locals => "locals",
+            Self::Matrix => "matrix",
+            Self::MoveCursor => "move_cursor",
+            Self::PenColor => "pen_color",
+            Self::PenDown => "pen_down",
+            Self::PenUp => "pen_up",
+            Self::ReadAll => "read_all",
+            Self::ReadLine => "read_line",
+            Self::Remove => "remove",
+            Self::Resize => "resize",
+            Self::SaveDrawing => "save_drawing",
+            Self::SetColor => "set_color",
+            Self::SetDefault => "set_default",
+            Self::Size => "size",
+            Self::Sort => "sort",
+            Self::SortInPlace => "sort_in_place",
+            Self::StoreLoad => "store_load",
+            Self::StoreSave => "store_save",
+            Self::ToBase => "to_base",
+            Self::ToFixed => "to_fixed",
+            Self::ToNumber => "to_number",
+            Self::ToString => "to_string",
+            Self::Transpose => "transpose",
+            Self::Turn => "turn",
+            Self::Values => "values",
+        }
+    }
 }
@@ -0,0 +1,210 @@
+//! Traversal of the abstract syntax tree, so that tools such as linters and metrics collectors only have to handle the nodes they are interested in.
+//!
+//! A tool implements `Visitor`, overriding `visit_stmt()` and `visit_expr()` for the nodes it wants to look at. The default
+//! implementations call `walk_stmt()` and `walk_expr()`, which visit every child of the node in source order. An overriding
+//! method calls them itself to carry on into the children, or leaves them out to skip the children.
+//!
+//! ```
+//! use nea::expr::{Expr, ExprType};
+//! use nea::visitor::{walk_expr, Visitor};
+//!
+//! /// Collects the name of every variable used.
+//! struct Names(Vec<String>);
+//!
+//! impl Visitor for Names {
+//!     fn visit_expr(&mut self, expr: &Expr) {
+//!         if let ExprType::Variable { name } = &expr.expr_type {
+//!             self.0.push(name.clone());
+//!         }
+//!         walk_expr(self, expr);
+//!     }
+//! }
+//!
+//! let ast = nea::parser::parse_no_panic("print a + f(b)").unwrap();
+//! let mut names = Names(Vec::new());
+//! names.visit_stmts(&ast);
+//! assert_eq!(names.0, ["a", "f", "b"]);
+//! ```
+
+use crate::expr::{Expr, ExprType};
+use crate::stmt::{Stmt, StmtType};
+
+/// Visits the nodes of an abstract syntax tree. See the module documentation for how to use it.
+pub trait Visitor {
+    /// Visits a statement. By default, visits each of its children.
+    fn visit_stmt(&mut self, stmt: &Stmt) {
+        walk_stmt(self, stmt);
+    }
+
+    /// Visits an expression. By default, visits each of its children.
+    fn visit_expr(&mut self, expr: &Expr) {
+        walk_expr(self, expr);
+    }
+
+    /// Visits each of the statements in order, e.g., a whole program.
+    fn visit_stmts(&mut self, stmts: &[Stmt]) {
+        for stmt in stmts {
+            self.visit_stmt(stmt);
+        }
+    }
+}
+
+/// Visits each statement and expression directly inside the statement, in source order.
+pub fn walk_stmt<V: Visitor + ?Sized>(visitor: &mut V, stmt: &Stmt) {
+    match &stmt.stmt_type {
+        StmtType::Block { body } => visitor.visit_stmts(body),
+        StmtType::Break => (),
+        StmtType::Expression { expression }
+        | StmtType::Print { expression }
+        | StmtType::Return { expression }
+        | StmtType::VarDecl { value: expression, .. }
+        | StmtType::VarUnpack { value: expression, .. } => visitor.visit_expr(expression),
+        StmtType::For { initialiser, condition, increment, body } => {
+            if let Some(initialiser) = initialiser {
+                visitor.visit_stmt(initialiser);
+            }
+            if let Some(condition) = condition {
+                visitor.visit_expr(condition);
+            }
+            if let Some(increment) = increment {
+                visitor.visit_stmt(increment);
+            }
+            visitor.visit_stmt(body);
+        },
+        StmtType::Function { body, .. } => visitor.visit_stmt(body),
+        StmtType::If { condition, then_body, else_body } => {
+            visitor.visit_expr(condition);
+            visitor.visit_stmt(then_body);
+            if let Some(else_body) = else_body {
+                visitor.visit_stmt(else_body);
+            }
+        },
+        StmtType::Try { body, catch, finally } => {
+            visitor.visit_stmt(body);
+            if let Some((_, catch_body)) = catch {
+                visitor.visit_stmt(catch_body);
+            }
+            if let Some(finally) = finally {
+                visitor.visit_stmt(finally);
+            }
+        },
+        StmtType::Repeat { count: condition, body } | StmtType::While { condition, body } => {
+            visitor.visit_expr(condition);
+            visitor.visit_stmt(body);
+        },
+    }
+}
+
+/// Visits each expression directly inside the expression, in source order.
+pub fn walk_expr<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expr) {
+    match &expr.expr_type {
+        ExprType::Array { elements } | ExprType::Tuple { elements } => {
+            for element in elements {
+                visitor.visit_expr(element);
+            }
+        },
+        ExprType::Assignment { target, value } => {
+            visitor.visit_expr(target);
+            visitor.visit_expr(value);
+        },
+        ExprType::Binary { left, right, .. } => {
+            visitor.visit_expr(left);
+            visitor.visit_expr(right);
+        },
+        ExprType::Call { callee, arguments, named_arguments } => {
+            visitor.visit_expr(callee);
+            for argument in arguments.iter().chain(named_arguments.iter().map(|(_, argument)| argument)) {
+                visitor.visit_expr(argument);
+            }
+        },
+        ExprType::Dictionary { elements } => {
+            for element in elements {
+                visitor.visit_expr(&element.key);
+                visitor.visit_expr(&element.value);
+            }
+        },
+        ExprType::Element { array, index } => {
+            visitor.visit_expr(array);
+            visitor.visit_expr(index);
+        },
+        ExprType::Slice { array, start, stop, step } => {
+            visitor.visit_expr(array);
+            for part in [start, stop, step].into_iter().flatten() {
+                visitor.visit_expr(part);
+            }
+        },
+        ExprType::Grouping { expression } | ExprType::Unary { right: expression, .. } => visitor.visit_expr(expression),
+        ExprType::Literal { .. } | ExprType::Variable { .. } => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::expr::{Expr, ExprType};
+    use crate::parser::parse_no_panic;
+    use crate::stmt::Stmt;
+
+    use super::{walk_expr, walk_stmt, Visitor};
+
+    /// Records the kind of every node visited, in order.
+    #[derive(Default)]
+    struct Kinds(Vec<String>);
+
+    impl Visitor for Kinds {
+        fn visit_stmt(&mut self, stmt: &Stmt) {
+            self.0.push(String::from(stmt.stmt_type.kind()));
+            walk_stmt(self, stmt);
+        }
+
+        fn visit_expr(&mut self, expr: &Expr) {
+            let kind = match &expr.expr_type {
+                ExprType::Variable { name } => name.clone(),
+                ExprType::Literal { .. } => String::from("Literal"),
+                _ => String::from("Expr"),
+            };
+            self.0.push(kind);
+            walk_expr(self, expr);
+        }
+    }
+
+    #[test]
+    fn source_order() {
+        let ast = parse_no_panic("for (var i = 0; i < n; i = i + 1) {\n    d[i] = {k: [f(v)]}\n}").unwrap();
+        let mut kinds = Kinds::default();
+        kinds.visit_stmts(&ast);
+        assert_eq!(kinds.0, [
+            "For",
+            "VarDecl", "Literal",
+            "Expr", "i", "n",
+            "Expression", "Expr", "i", "Expr", "i", "Literal",
+            "Block", "Expression", "Expr", "Expr", "d", "i", "Expr", "k", "Expr", "Expr", "f", "v",
+        ]);
+    }
+
+    /// Counts the calls, without looking inside function declarations.
+    #[derive(Default)]
+    struct TopLevelCalls(usize);
+
+    impl Visitor for TopLevelCalls {
+        fn visit_stmt(&mut self, stmt: &Stmt) {
+            if !matches!(stmt.stmt_type, crate::stmt::StmtType::Function { .. }) {
+                walk_stmt(self, stmt);
+            }
+        }
+
+        fn visit_expr(&mut self, expr: &Expr) {
+            if matches!(expr.expr_type, ExprType::Call { .. }) {
+                self.0 += 1;
+            }
+            walk_expr(self, expr);
+        }
+    }
+
+    #[test]
+    fn skipping_children() {
+        let ast = parse_no_panic("func f() {\n    g()\n}\nif (true) { f(h()) } else { print -f() }").unwrap();
+        let mut calls = TopLevelCalls::default();
+        calls.visit_stmts(&ast);
+        assert_eq!(calls.0, 3);
+    }
+}
@@ -0,0 +1,909 @@
+use std::{fs, path::PathBuf};
+
+use assert_cmd::Command;
+use predicates::prelude::*;
+
+/// Writes `source` to a uniquely named file in the temporary directory and returns its path.
+fn script(name: &str, source: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("neal_cli_{}_{}.nea", name, std::process::id()));
+    fs::write(&path, source).expect("Failed to write script.");
+    path
+}
+
+/// Returns the path of a uniquely named history file in the temporary directory, which does not exist yet.
+fn history_file(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("neal_cli_{}_{}.history", name, std::process::id()));
+    let _ = fs::remove_file(&path);
+    path
+}
+
+/// Returns a command which runs the REPL with its own history file, so that tests do not write to the user's history.
+/// Returns a uniquely named, empty directory in the temporary directory, to use as the home directory.
+fn home_directory(name: &str) -> PathBuf {
+    let path = std::env::temp_dir().join(format!("neal_cli_{}_{}_home", name, std::process::id()));
+    let _ = fs::remove_dir_all(&path);
+    fs::create_dir(&path).expect("Failed to create the home directory.");
+    path
+}
+
+/// Returns a command which runs the REPL with its own history file and home directory, so that tests do not use the real ones.
+fn repl(name: &str) -> Command {
+    let mut command = Command::cargo_bin("nea").unwrap();
+    command.arg("--history").arg(history_file(name)).env("HOME", home_directory(name));
+    command
+}
+
+#[test]
+fn success() {
+    let path = script("success", "print 1 + 2");
+    Command::cargo_bin("nea").unwrap()
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout("3\n")
+        .stderr("");
+}
+
+#[test]
+fn tokenize_error() {
+    let path = script("tokenize_error", "print 1 @ 2");
+    Command::cargo_bin("nea").unwrap()
+        .arg(&path)
+        .assert()
+        .code(65)
+        .stdout("")
+        .stderr(predicate::str::contains("unexpected character `@`"));
+}
+
+#[test]
+fn parse_error() {
+    let path = script("parse_error", "var = 5");
+    Command::cargo_bin("nea").unwrap()
+        .arg(&path)
+        .assert()
+        .code(66)
+        .stdout("")
+        .stderr(predicate::str::contains("expected variable name"));
+}
+
+#[test]
+fn nesting_limit() {
+    // Code nested as deeply as the parser allows has to run without overflowing the stack, even in a debug build, and a long chain
+    // of operators is not nested at all.
+    let path = script("nesting_limit", &format!("print 1{}\nprint {}1{}", " + 1".repeat(1023), "(".repeat(62), ")".repeat(62)));
+    for mode in [None, Some("--fmt"), Some("--ast-json"), Some("--lint")] {
+        Command::cargo_bin("nea").unwrap()
+            .args(mode)
+            .arg(&path)
+            .assert()
+            .success();
+    }
+    Command::cargo_bin("nea").unwrap()
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout("1024\n1\n");
+
+    let path = script("nesting_limit_chain", &format!("print 1{}", " + 1".repeat(200_000)));
+    Command::cargo_bin("nea").unwrap()
+        .arg("--check")
+        .arg(&path)
+        .assert()
+        .code(66)
+        .stderr(predicate::str::contains("E0032"));
+}
+
+#[test]
+fn runtime_error() {
+    let path = script("runtime_error", "print 1\nprint 1 / 0");
+    Command::cargo_bin("nea").unwrap()
+        .arg(&path)
+        .assert()
+        .code(70)
+        .stdout("1\n")
+        .stderr(predicate::str::contains("[E0021]").and(predicate::str::contains("divisor is 0")));
+}
+
+#[test]
+fn usage_error() {
+    Command::cargo_bin("nea").unwrap()
+        .args(["--max-errors", "many", "a"])
+        .assert()
+        .code(64)
+        .stderr(predicate::str::contains("Usage"));
+}
+
+#[test]
+fn help_and_version() {
+    Command::cargo_bin("nea").unwrap()
+        .arg("--help")
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("Usage:").and(predicate::str::contains("--max-errors <n>")));
+
+    Command::cargo_bin("nea").unwrap()
+        .arg("--version")
+        .assert()
+        .success()
+        .stdout(format!("nea {}\n", env!("CARGO_PKG_VERSION")));
+}
+
+#[test]
+fn unknown_flag() {
+    Command::cargo_bin("nea").unwrap()
+        .args(["--tokns", "a.nea"])
+        .assert()
+        .code(64)
+        .stderr(predicate::str::contains("Did you mean `--tokens`?"));
+}
+
+#[test]
+fn explain() {
+    Command::cargo_bin("nea").unwrap()
+        .args(["--explain", "E0021"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("divided by 0"));
+
+    Command::cargo_bin("nea").unwrap()
+        .args(["--explain", "l0003"])
+        .assert()
+        .success()
+        .stdout(predicate::str::contains("never used"));
+}
+
+#[test]
+fn explain_unknown_code() {
+    Command::cargo_bin("nea").unwrap()
+        .args(["--explain", "E9999"])
+        .assert()
+        .code(64)
+        .stderr(predicate::str::contains("not a valid error code"));
+}
+
+#[test]
+fn repl_ends_at_end_of_input() {
+    repl("repl_ends_at_end_of_input")
+        .write_stdin("var x = 2\nprint x * 3\n")
+        .assert()
+        .success()
+        .stdout("6\n");
+}
+
+#[test]
+fn repl_echoes_expressions() {
+    // Assignments and calls to functions which return nothing are not echoed.
+    repl("repl_echoes_expressions")
+        .write_stdin("var x = 2\nx = x + 1\nx * 3\nfunc f() { print 'f' }\nf()\n'a' + 'b'\n")
+        .assert()
+        .success()
+        .stdout("9\nf\nab\n");
+}
+
+#[test]
+fn repl_last_value() {
+    // `_` is the last value echoed, and lines which echo nothing leave it as it was.
+    repl("repl_last_value")
+        .write_stdin("1 + 2\n_ * 10\nvar x = 5\nfunc f() {}\nf()\n_ + 1\n")
+        .assert()
+        .success()
+        .stdout("3\n30\n31\n");
+}
+
+#[test]
+fn repl_commands() {
+    let library = script("repl_commands", "func double(x) { return x * 2 }\nvar loaded = true\n");
+    repl("repl_commands")
+        .write_stdin(format!("var x = [1, 2]\n:load {}\n:env\ndouble(4)\n:reset\n:env\n:quit\nprint 'not run'\n", library.display()))
+        .assert()
+        .success()
+        .stdout("double = <function>\nloaded = true\nx = [1, 2]\n8\n");
+}
+
+#[test]
+fn repl_save() {
+    let path = std::env::temp_dir().join(format!("neal_cli_repl_save_{}.nea", std::process::id()));
+    repl("repl_save")
+        .write_stdin(format!("var x = [1, 'a']\nfunc double(n) {{ return n * 2 }}\n:save {}\n", path.display()))
+        .assert()
+        .success()
+        .stderr("");
+
+    // A new session restores the variables and functions with `:load`.
+    repl("repl_save")
+        .write_stdin(format!(":load {}\n:env\ndouble(x[0])\n", path.display()))
+        .assert()
+        .success()
+        .stdout("double = <function>\nx = [1, a]\n2\n");
+}
+
+#[test]
+fn repl_type() {
+    repl("repl_type")
+        .write_stdin("var xs = [1, 2, 3]\nfunc grow() { xs = [] }\n:type xs\n:type xs[0]\n:type xs = 1\n:type [xs = 7]\n:type grow()\n:type xs\n")
+        .assert()
+        .success()
+        // Neither the assignments nor the call are run, so `xs` is unchanged.
+        .stdout("Array of 3 elements (Number)\nNumber 1\nArray of 3 elements (Number)\n")
+        .stderr("`:type` does not run assignments, or calls to functions which could change the session.\n".repeat(3));
+}
+
+#[test]
+fn repl_unknown_command() {
+    repl("repl_unknown_command")
+        .write_stdin(":exit\nprint 1\n")
+        .assert()
+        .success()
+        .stdout("1\n")
+        .stderr(predicate::str::contains("Unknown command `:exit`"));
+}
+
+#[test]
+fn repl_history() {
+    let path = history_file("repl_history");
+    for line in ["var x = 1\n", "print 2\n\n"] {
+        Command::cargo_bin("nea").unwrap()
+            .arg("--history")
+            .arg(&path)
+            .env("HOME", home_directory("repl_history"))
+            .write_stdin(line)
+            .assert()
+            .success();
+    }
+
+    // The lines from both sessions are kept, but blank lines are not.
+    let history = fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = history.lines().filter(|line| !line.starts_with('#')).collect();
+    assert_eq!(lines, ["var x = 1", "print 2"]);
+}
+
+#[test]
+#[cfg(unix)]
+fn repl_interrupt() {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let mut child = std::process::Command::new(assert_cmd::cargo::cargo_bin("nea"))
+        .arg("--history")
+        .arg(history_file("repl_interrupt"))
+        .env("HOME", home_directory("repl_interrupt"))
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut stdin = child.stdin.take().unwrap();
+    stdin.write_all(b"var x = 5\nwhile (true) {}\n").unwrap();
+
+    // Interrupt the loop once it has had time to start, the same as pressing Ctrl-C.
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    let status = std::process::Command::new("kill").arg("-INT").arg(child.id().to_string()).status().unwrap();
+    assert!(status.success());
+
+    // The session carries on, with the variables declared before the loop.
+    stdin.write_all(b"print x\n").unwrap();
+    drop(stdin);
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(String::from_utf8_lossy(&output.stdout), "5\n");
+    assert!(String::from_utf8_lossy(&output.stderr).contains("[E0034]"));
+}
+
+#[test]
+fn repl_preload() {
+    let mut command = repl("repl_preload");
+    let home = home_directory("repl_preload");
+    fs::write(home.join(".nealrc"), "var greeting = 'hi'").unwrap();
+    let utils = script("repl_preload_utils", "func double(x) {\n    return x * 2\n}");
+    // `~/.nealrc` is run first, then the preloaded files in order, and they are run again after `:reset`.
+    command
+        .env("HOME", &home)
+        .arg("--preload")
+        .arg(&utils)
+        .write_stdin("print greeting\ndouble(3)\n:reset\ndouble(4)\n")
+        .assert()
+        .success()
+        .stdout("hi\n6\n8\n")
+        .stderr("");
+
+    repl("repl_preload_missing")
+        .arg("--preload")
+        .arg("neal_cli_does_not_exist.nea")
+        .write_stdin("print 1\n")
+        .assert()
+        .success()
+        .stdout("1\n")
+        .stderr(predicate::str::contains("Could not read `neal_cli_does_not_exist.nea`"));
+}
+
+#[test]
+fn tokens() {
+    let path = script("tokens", "print x + 2.5");
+    Command::cargo_bin("nea").unwrap()
+        .arg("--tokens")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(concat!(
+            "Position Type          Lexeme           Literal\n",
+            "1:1      Print         \"print\"\n",
+            "1:7      Identifier    \"x\"\n",
+            "1:9      Plus          \"+\"\n",
+            "1:11     Number        \"2.5\"            2.5\n",
+            "1:14     Eof           \"\"\n",
+        ));
+}
+
+#[test]
+fn tokens_error() {
+    // The script is only tokenized, so the undefined name is not reported.
+    let path = script("tokens_error", "print x @ 2");
+    Command::cargo_bin("nea").unwrap()
+        .arg("--tokens")
+        .arg(&path)
+        .assert()
+        .code(65)
+        .stdout("")
+        .stderr(predicate::str::contains("[E0001]"));
+}
+
+#[test]
+fn ast() {
+    let path = script("ast", "var x = 1\nprint x + 2");
+    Command::cargo_bin("nea").unwrap()
+        .arg("--ast")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout("Var x\n  Literal 1\nPrint\n  Binary +\n    Variable x\n    Literal 2\n");
+}
+
+#[test]
+fn ast_json() {
+    let path = script("ast_json", "x");
+    Command::cargo_bin("nea").unwrap()
+        .arg("--ast-json")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(concat!(
+            "{\n",
+            "  \"version\": 1,\n",
+            "  \"statements\": [\n",
+            "    {\n",
+            "      \"kind\": \"Expression\",\n",
+            "      \"line\": 1,\n",
+            "      \"column\": 1,\n",
+            "      \"expression\": {\n",
+            "        \"kind\": \"Variable\",\n",
+            "        \"line\": 1,\n",
+            "        \"column\": 1,\n",
+            "        \"name\": \"x\"\n",
+            "      }\n",
+            "    }\n",
+            "  ]\n",
+            "}\n",
+        ));
+}
+
+#[test]
+fn ast_error() {
+    let path = script("ast_error", "print (1 + 2");
+    Command::cargo_bin("nea").unwrap()
+        .arg("--ast")
+        .arg(&path)
+        .assert()
+        .code(66)
+        .stdout("");
+}
+
+#[test]
+fn check() {
+    // The script is not executed, so neither its output nor the runtime error is produced.
+    let path = script("check", "print 1\nprint 1 / 0");
+    Command::cargo_bin("nea").unwrap()
+        .arg("--check")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+}
+
+#[test]
+fn check_errors() {
+    // Every parse error is reported, not just the first.
+    let path = script("check_errors", "print (1\nvar = 2\nprint 3");
+    Command::cargo_bin("nea").unwrap()
+        .arg("--check")
+        .arg(&path)
+        .assert()
+        .code(66)
+        .stdout("")
+        .stderr(predicate::str::contains("[E0003]").and(predicate::str::contains("[E0007]")));
+
+    let path = script("check_tokenize_errors", "print 1 @ 2");
+    Command::cargo_bin("nea").unwrap()
+        .arg("--check")
+        .arg(&path)
+        .assert()
+        .code(65);
+}
+
+#[test]
+fn fmt() {
+    let path = script("fmt", "# Greets.\nfunc greet(name){print 'Hi '+name}   # once\n\n\ngreet('Bo')");
+    Command::cargo_bin("nea").unwrap()
+        .arg("--fmt")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout("# Greets.\nfunc greet(name) {  # once\n    print 'Hi ' + name\n}\n\ngreet('Bo')\n")
+        .stderr("");
+}
+
+#[test]
+fn fmt_check() {
+    let path = script("fmt_check", "var x = 1\nif (x) {\n  print x\n}\n");
+    Command::cargo_bin("nea").unwrap()
+        .args(["--fmt", "--check"])
+        .arg(&path)
+        .assert()
+        .code(1)
+        .stdout("")
+        .stderr("The script is not formatted: line 3 differs. Run it with `--fmt` to see the formatted script.\n");
+
+    let path = script("fmt_check_formatted", "var x = 1  # one\nif (x) {\n    print x\n}\n");
+    Command::cargo_bin("nea").unwrap()
+        .args(["--check", "--fmt"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("");
+
+    // A script which cannot be parsed cannot be formatted.
+    let path = script("fmt_check_errors", "print (1");
+    Command::cargo_bin("nea").unwrap()
+        .args(["--fmt", "--check"])
+        .arg(&path)
+        .assert()
+        .code(66);
+}
+
+#[test]
+fn test_runner() {
+    let path = script("test_runner", "func double(x) { return x * 2 }\nfunc test_double() { assert_eq(double(2), 4) }\nfunc test_wrong() {\n    assert_eq(double(2), 5)\n}");
+    let name = path.display().to_string();
+    Command::cargo_bin("nea").unwrap()
+        .arg("--test")
+        .arg(&path)
+        .assert()
+        .code(1)
+        .stdout(format!("{name}: test_double ... ok\n{name}: test_wrong ... FAILED\n1 passed, 1 failed.\n"))
+        // The test is called by the runner, so the stack trace gives the position of the call `test_wrong()` which it made.
+        .stderr(predicate::str::contains("[E0038] Line 4, column 27: assertion failed: `4` is not equal to `5`.")
+            .and(predicate::str::contains("in `test_wrong`, called at line 1, column 12\n")));
+
+    Command::cargo_bin("nea").unwrap()
+        .args(["--test", "-e", "func test_sum() { assert_eq(1 + 2, 3) }"])
+        .assert()
+        .success()
+        .stdout("-e: test_sum ... ok\n1 passed, 0 failed.\n");
+}
+
+#[test]
+fn doc() {
+    let path = script("doc", "## Doubles a number.\nfunc double(x) {\n    return x * 2\n}");
+    Command::cargo_bin("nea").unwrap()
+        .arg("--doc")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout("# Functions\n\n## `double(x)`\n\nDoubles a number.\n\nParameters: `x`.\n\nDeclared on line 2.\n");
+}
+
+#[test]
+fn transpile() {
+    let path = script("transpile", "var x = 7\nprint x % 2 == 1");
+    Command::cargo_bin("nea").unwrap()
+        .args(["--transpile", "py"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout(predicate::str::starts_with("# Translated from NEAL into Python by `nea --transpile py`.\nimport decimal\nimport math\n"))
+        .stdout(predicate::str::ends_with("\n\n\nx = 7\nprint(_neal_show(_neal_mod(x, 2) == 1))\n"));
+    Command::cargo_bin("nea").unwrap()
+        .args(["--transpile", "js"])
+        .arg(&path)
+        .assert()
+        .code(64)
+        .stderr(predicate::str::contains("the only language is `py`"));
+}
+
+#[test]
+fn bundle() {
+    let path = script("bundle", "print 'Hello, ' + args()[0]");
+    let program = std::env::temp_dir().join(format!("neal_cli_bundle_{}", std::process::id()));
+    Command::cargo_bin("nea").unwrap()
+        .arg("--bundle")
+        .arg(&program)
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout("");
+    // The bundle runs the script without it, and gives it every argument, even ones which are flags of `nea`.
+    fs::remove_file(&path).unwrap();
+    Command::new(&program)
+        .args(["World", "--help"])
+        .assert()
+        .success()
+        .stdout("Hello, World\n");
+    fs::remove_file(&program).unwrap();
+
+    // A script with errors is not bundled.
+    let path = script("bundle_error", "print (");
+    Command::cargo_bin("nea").unwrap()
+        .arg("--bundle")
+        .arg(&program)
+        .arg(&path)
+        .assert()
+        .code(66);
+    assert!(!program.exists());
+}
+
+#[test]
+fn lint() {
+    let path = script("lint", "func f(a, b) {\n    return a\n}\nwhile (false) {}\nprint 1 / 0");
+    Command::cargo_bin("nea").unwrap()
+        .arg("--lint")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout("")
+        .stderr("\
+warning [L0003] Line 1, column 1: the parameter `b` of `f` is never used.
+warning [L0002] Line 4, column 8: the loop never runs, as its condition is always `false`.
+");
+
+    // A denied lint makes the run fail, and an allowed one is not reported.
+    Command::cargo_bin("nea").unwrap()
+        .args(["--lint", "--deny", "constant-condition", "--allow", "L0003"])
+        .arg(&path)
+        .assert()
+        .code(1)
+        .stderr("error [L0002] Line 4, column 8: the loop never runs, as its condition is always `false`.\n");
+
+    // With `--strict`, every lint which is not allowed is denied, and finding one is a failure of its own.
+    Command::cargo_bin("nea").unwrap()
+        .args(["--lint", "--strict", "--allow", "L0003"])
+        .arg(&path)
+        .assert()
+        .code(65)
+        .stderr("error [L0002] Line 4, column 8: the loop never runs, as its condition is always `false`.\n");
+    let path = script("lint_strict", "func f(a) {\n    return a\n}\nprint f(1)");
+    Command::cargo_bin("nea").unwrap()
+        .args(["--lint", "--strict"])
+        .arg(&path)
+        .assert()
+        .success()
+        .stderr("");
+}
+
+#[test]
+fn eval() {
+    Command::cargo_bin("nea").unwrap()
+        .args(["-e", "print 1 + 2"])
+        .assert()
+        .success()
+        .stdout("3\n");
+
+    // Inline code exits with the same codes as a script.
+    Command::cargo_bin("nea").unwrap()
+        .args(["--eval", "print 1 / 0"])
+        .assert()
+        .code(70)
+        .stderr(predicate::str::contains("[E0021]"));
+}
+
+#[test]
+fn stdin() {
+    // Unlike in the REPL, the whole program is read before it is run, so statements can span lines.
+    Command::cargo_bin("nea").unwrap()
+        .arg("-")
+        .write_stdin("func f(x) {\n    return x * 2\n}\nprint f(3)\n")
+        .assert()
+        .success()
+        .stdout("6\n");
+
+    Command::cargo_bin("nea").unwrap()
+        .arg("-")
+        .write_stdin("print (1")
+        .assert()
+        .code(66);
+}
+
+#[test]
+fn script_args() {
+    let path = script("script_args", "print args()");
+    Command::cargo_bin("nea").unwrap()
+        .arg(&path)
+        .args(["input.csv", "10", "--ast"])
+        .assert()
+        .success()
+        .stdout("[input.csv, 10, --ast]\n");
+}
+
+#[test]
+fn time() {
+    let path = script("time", "var total = 0\nfor (var i = 0; i < 3; i = i + 1) { total = total + i }\nprint total");
+    Command::cargo_bin("nea").unwrap()
+        .arg("--time")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout("3\n")
+        .stderr(predicate::str::contains("Tokenizing:")
+            .and(predicate::str::contains("Parsing:"))
+            .and(predicate::str::contains("Executing:"))
+            .and(predicate::str::contains("Statements executed: 13")));
+
+    // Only the stages which were reached are reported.
+    let path = script("time_error", "print (1");
+    Command::cargo_bin("nea").unwrap()
+        .args(["--time"])
+        .arg(&path)
+        .assert()
+        .code(66)
+        .stderr(predicate::str::contains("Parsing:").and(predicate::str::contains("Executing:").not()));
+}
+
+#[test]
+fn trace() {
+    let path = script("trace", "func f(a) {\n    a[0] = 2\n    return a\n}\nvar x = [1]\nif (true) {\n    x = f(x)\n}\nprint x");
+    Command::cargo_bin("nea").unwrap()
+        .arg("--trace")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout("[2]\n")
+        .stderr(concat!(
+            "line 1: Function\n",
+            "line 5: VarDecl\n",
+            "  x = [1]\n",
+            "line 6: If\n",
+            "  line 7: Expression\n",
+            "    line 2: Expression\n",
+            "      a[0] = 2\n",
+            "    line 3: Return\n",
+            "    x = [2]\n",
+            "line 9: Print\n",
+        ));
+}
+
+#[test]
+fn profile() {
+    let path = script("profile", "func f() {\n    return 1\n}\nfor (var i = 0; i < 3; i = i + 1) { f() }");
+    Command::cargo_bin("nea").unwrap()
+        .arg("--profile")
+        .arg(&path)
+        .assert()
+        .success()
+        .stderr(predicate::str::contains("Lines (self time):")
+            .and(predicate::str::is_match(r"\n +2 +3 ").unwrap())
+            .and(predicate::str::is_match(r"\nf +3 ").unwrap()));
+}
+
+#[test]
+fn coverage() {
+    let path = script("coverage", "func f(x) {\n    if (x) {\n        print 1\n    } else {\n        print 2\n    }\n}\nf(true)");
+    let listing = "       1:    1: func f(x) {
+       1:    2:     if (x) {
+       1:    3:         print 1
+       -:    4:     } else {
+   #####:    5:         print 2
+       -:    6:     }
+       -:    7: }
+       1:    8: f(true)
+Executed 4 of 5 lines (80.0%).
+";
+    Command::cargo_bin("nea").unwrap()
+        .arg("--coverage")
+        .arg(&path)
+        .assert()
+        .success()
+        .stdout("1\n")
+        .stderr(listing);
+
+    let output = std::env::temp_dir().join(format!("neal_cli_coverage_{}.txt", std::process::id()));
+    Command::cargo_bin("nea").unwrap()
+        .arg("--coverage-file")
+        .arg(&output)
+        .arg(&path)
+        .assert()
+        .success()
+        .stderr("");
+    assert_eq!(fs::read_to_string(&output).unwrap(), listing);
+    let _ = fs::remove_file(output);
+}
+
+#[test]
+fn strict() {
+    let path = script("strict", "var total = 0\nfunc add(x) {\n    total = total + x\n}\nadd(1) print total");
+    Command::cargo_bin("nea").unwrap()
+        .arg("--strict")
+        .arg("--check")
+        .arg(&path)
+        .assert()
+        .code(66)
+        .stderr(predicate::str::contains("[E0035] Line 5, column 8"));
+
+    let path = script("strict_runtime", "var total = 0\nfunc add(x) {\n    total = total + x\n}\nadd(1)");
+    Command::cargo_bin("nea").unwrap()
+        .arg("--strict")
+        .arg(&path)
+        .assert()
+        .code(70)
+        .stderr(predicate::str::contains("[E0037] Line 3, column 5"));
+    // Without `--strict`, the same script is fine.
+    Command::cargo_bin("nea").unwrap()
+        .arg(&path)
+        .assert()
+        .success();
+}
+
+#[test]
+fn input_from_stdin() {
+    let path = script("input_from_stdin", "print 'Hello, ' + input('Name: ')");
+    Command::cargo_bin("nea").unwrap()
+        .arg(&path)
+        .write_stdin("Bob\n")
+        .assert()
+        .success()
+        .stdout("Name: Hello, Bob\n");
+}
+
+#[test]
+fn no_colour_when_piped() {
+    // Colours are only written to a terminal, so the output of a pipeline or a file is plain text.
+    Command::cargo_bin("nea").unwrap()
+        .args(["-e", "set_color('red')\nprint colored('warning', 'yellow')\nset_color('reset')"])
+        .assert()
+        .success()
+        .stdout("warning\n");
+    Command::cargo_bin("nea").unwrap()
+        .args(["-e", "set_color('orange')"])
+        .assert()
+        .code(70)
+        .stderr(predicate::str::contains("[E0046]"));
+}
+
+#[test]
+fn filter_from_stdin() {
+    // A script can read its input line by line, without prompts, to be used in a pipeline.
+    let path = script("filter_from_stdin", "var line = read_line()\nwhile (line != null) {\n    print '> ' + line\n    line = read_line()\n}");
+    Command::cargo_bin("nea").unwrap()
+        .arg(&path)
+        .write_stdin("a\n\nb")
+        .assert()
+        .success()
+        .stdout("> a\n> \n> b\n");
+}
+
+#[test]
+fn unreadable_file() {
+    let path = std::env::temp_dir().join("neal_cli_does_not_exist.nea");
+    Command::cargo_bin("nea").unwrap()
+        .arg(&path)
+        .assert()
+        .code(74)
+        .stdout("")
+        .stderr(predicate::str::contains(path.to_str().unwrap()).and(predicate::str::contains("panicked").not()));
+
+    // A directory cannot be read as a script either.
+    Command::cargo_bin("nea").unwrap()
+        .arg(std::env::temp_dir())
+        .assert()
+        .code(74);
+}
+
+#[test]
+fn max_errors() {
+    let path = script("max_errors", "var = 1\nvar = 2\nvar = 3\nvar = 4");
+    Command::cargo_bin("nea").unwrap()
+        .args(["--max-errors", "2"])
+        .arg(&path)
+        .assert()
+        .code(66)
+        .stderr(predicate::str::contains("Line 2").and(predicate::str::contains("Line 3").not()).and(predicate::str::contains("... and 2 more error(s).")));
+}
+
+#[test]
+fn fail_fast() {
+    let path = script("fail_fast", "var = 1\nvar = 2");
+    Command::cargo_bin("nea").unwrap()
+        .arg("--fail-fast")
+        .arg(&path)
+        .assert()
+        .code(66)
+        .stderr(predicate::str::contains("Line 1").and(predicate::str::contains("Line 2").not()));
+}
+
+#[test]
+fn translated_messages() {
+    let catalogue = script("catalogue", "# French\nheader = Une erreur s'est produite.\nposition = Ligne {line}, colonne {column} :\nE0021 = le diviseur est 0.\n");
+    let path = script("translated_messages", "print 1 / 0");
+    Command::cargo_bin("nea").unwrap()
+        .arg("--messages")
+        .arg(&catalogue)
+        .arg(&path)
+        .assert()
+        .code(70)
+        .stderr("Une erreur s'est produite.\n[E0021] Ligne 1, colonne 11 : le diviseur est 0.\n");
+
+    let catalogue = script("invalid_catalogue", "E0021");
+    Command::cargo_bin("nea").unwrap()
+        .arg("--messages")
+        .arg(&catalogue)
+        .arg(&path)
+        .assert()
+        .code(64)
+        .stderr(predicate::str::contains("line 1 of the catalogue"));
+}
+
+#[test]
+fn include() {
+    // The included files are in a directory of their own, so that relative paths are found from the including file.
+    let directory = std::env::temp_dir().join(format!("neal_cli_include_{}", std::process::id()));
+    fs::create_dir_all(directory.join("lib")).expect("Failed to create directory.");
+    fs::write(directory.join("lib/shapes.nea"), "include('maths.nea')\nfunc area(w, h) { return times(w, h) }").unwrap();
+    fs::write(directory.join("lib/maths.nea"), "func times(a, b) { return a * b }").unwrap();
+    fs::write(directory.join("main.nea"), "include('lib/shapes.nea')\nprint area(2, 3)").unwrap();
+    Command::cargo_bin("nea").unwrap()
+        .arg(directory.join("main.nea"))
+        .assert()
+        .success()
+        .stdout("6\n");
+
+    // A file which includes itself, even through another file, is reported rather than included forever.
+    fs::write(directory.join("lib/maths.nea"), "include('shapes.nea')").unwrap();
+    Command::cargo_bin("nea").unwrap()
+        .arg(directory.join("main.nea"))
+        .assert()
+        .code(70)
+        .stderr(predicate::str::contains("[E0043]")
+            .and(predicate::str::contains("in `include('maths.nea')`, called at line 1"))
+            .and(predicate::str::contains("in `include('lib/shapes.nea')`, called at line 1")));
+
+    Command::cargo_bin("nea").unwrap()
+        .args(["-e", "include('neal_missing_file.nea')"])
+        .assert()
+        .code(70)
+        .stderr(predicate::str::contains("[E0044]").and(predicate::str::contains("could not read `neal_missing_file.nea`")));
+    let _ = fs::remove_dir_all(&directory);
+}
+
+#[test]
+fn store_between_runs() {
+    // The store is kept next to the script, so each run sees the values saved by the last one.
+    let path = script("store", "var runs = store_load('runs')\nif (runs == null) { runs = 0 }\nstore_save('runs', runs + 1)\nprint runs + 1");
+    let store = path.with_extension("store");
+    let _ = fs::remove_file(&store);
+    for runs in ["1\n", "2\n"] {
+        Command::cargo_bin("nea").unwrap()
+            .arg(&path)
+            .assert()
+            .success()
+            .stdout(runs);
+    }
+    assert!(fs::read_to_string(&store).unwrap().ends_with("\n{'runs': 2}\n"));
+
+    // A store which has been edited to run code is not loaded.
+    fs::write(&store, "{'runs': input('?')}").unwrap();
+    Command::cargo_bin("nea").unwrap()
+        .arg(&path)
+        .assert()
+        .code(70)
+        .stderr(predicate::str::contains("[E0052]"));
+    let _ = fs::remove_file(&store);
+}